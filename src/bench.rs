@@ -0,0 +1,65 @@
+//! Allocation benchmarks, run with `cargo test bench -- --nocapture`. A counting
+//! allocator tallies allocations made on the current thread, so tests running in
+//! parallel don't disturb each other's counts.
+
+use crate::game::{share_lines, GameState, Line};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+    
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Allocations made while running `f`
+fn allocations<T>(f: impl FnOnce() -> T) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    let result = f();
+    let count = ALLOCATIONS.with(Cell::get) - before;
+    drop(result);
+    count
+}
+
+/// A turn of a verbose game: a long computer listing ending at the command prompt
+fn verbose_turn(lines: usize) -> Vec<String> {
+    (0..lines)
+        .map(|i| format!("   {} , {}    ***   ***   ***   ***   ***   ***   ***", i / 8 + 1, i % 8 + 1))
+        .chain(std::iter::once("COMMAND?".to_string()))
+        .collect()
+}
+
+#[test]
+fn bench_turn_allocations() {
+    let output = share_lines(verbose_turn(400));
+    let copies: Vec<String> = output.iter().map(|line| line.to_string()).collect();
+    
+    // What a turn's output costs each holder: GameState::last_output, PromptContext::block
+    // and any strategy keeping the lines around
+    let shared = allocations(|| output.to_vec());
+    let copied = allocations(|| copies.to_vec());
+    println!("Keeping a {}-line turn: {} allocations shared, {} copied", output.len(), shared, copied);
+    assert_eq!(shared, 1);
+    assert_eq!(copied, output.len() + 1);
+    
+    let mut state = GameState::new();
+    let per_turn = allocations(|| state.update_with(&output, |line| line == "COMMAND?"));
+    println!("GameState::update_with for the turn: {} allocations", per_turn);
+    let context = state.prompt_context.as_ref().unwrap();
+    assert!(Line::ptr_eq(&context.block[0], &output[0]));
+    assert!(Line::ptr_eq(&state.last_output[0], &output[0]));
+}
@@ -0,0 +1,137 @@
+use crate::interpreter::Tolerance;
+use crate::turn::TurnLog;
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+
+/// Where a game first differed from the golden transcript
+#[derive(Debug, Clone, PartialEq)]
+pub struct Divergence {
+    /// Counted from 1, with 0 for the opening before the first command
+    pub turn: usize,
+    /// The golden line and the line this build printed; None past the end of the output
+    pub expected: Option<String>,
+    pub actual: Option<String>,
+}
+
+/// Compare a game's output with the golden game's, the opening and then the reply to
+/// each command, line by line. Lines match within `tolerance`, which ignores spacing
+/// since interpreters pad lines and numbers differently. Anything printed after the
+/// golden game ends is ignored.
+pub fn first_divergence(golden: &TurnLog, actual: &TurnLog, tolerance: &Tolerance) -> Option<Divergence> {
+    let actual = replies(actual);
+    for (turn, expected) in replies(golden).into_iter().enumerate() {
+        let printed = actual.get(turn).copied().unwrap_or_default();
+        for line in 0..expected.len().max(printed.len()) {
+            let expected = expected.get(line).map(|line| line.trim_end());
+            let printed = printed.get(line).map(|line| line.trim_end());
+            let same = match (expected, printed) {
+                (Some(expected), Some(printed)) => tolerance.lines_match(expected, printed),
+                _ => false,
+            };
+            if !same {
+                return Some(Divergence {
+                    turn,
+                    expected: expected.map(str::to_string),
+                    actual: printed.map(str::to_string),
+                });
+            }
+        }
+    }
+    None
+}
+
+/// The output of a game in the order it was printed: the opening, then each reply
+fn replies(log: &TurnLog) -> Vec<&[String]> {
+    std::iter::once(&log.opening[..]).chain(log.turns.iter().map(|turn| &turn.events[..])).collect()
+}
+
+/// Interpreter builds to bisect, in order (oldest first by name, e.g. numbered builds or
+/// dated directories)
+pub fn builds(dir: &str) -> Result<Vec<PathBuf>> {
+    let mut builds: Vec<PathBuf> = std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read build directory {}", dir))?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<_>>()?;
+    builds.sort();
+    Ok(builds)
+}
+
+/// Binary search for the first build that diverges, assuming the builds before it match
+/// and those after it diverge too
+#[derive(Debug, Clone)]
+pub struct Bisection {
+    low: usize,
+    high: usize,
+    first_bad: Option<usize>,
+}
+
+impl Bisection {
+    pub fn new(count: usize) -> Self {
+        Self { low: 0, high: count, first_bad: None }
+    }
+    
+    /// The build to test next; None once the search is over
+    pub fn next(&self) -> Option<usize> {
+        (self.low < self.high).then(|| (self.low + self.high) / 2)
+    }
+    
+    /// Record whether the build from next() diverged
+    pub fn record(&mut self, diverges: bool) {
+        let Some(middle) = self.next() else { return };
+        if diverges {
+            self.first_bad = Some(middle);
+            self.high = middle;
+        } else {
+            self.low = middle + 1;
+        }
+    }
+    
+    /// The first diverging build; None if the last build still matches
+    pub fn first_bad(&self) -> Option<usize> {
+        self.first_bad
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcript::Transcript;
+    
+    /// A game printing each batch of output in turn, answered with SRS
+    fn game(outputs: &[&[&str]]) -> TurnLog {
+        let mut transcript = Transcript::new();
+        for (index, output) in outputs.iter().enumerate() {
+            if index > 0 {
+                transcript.record_input("SRS");
+            }
+            transcript.record_output(&output.iter().map(|line| line.to_string()).collect::<Vec<_>>());
+        }
+        TurnLog::from_transcript(&transcript).unwrap()
+    }
+    
+    #[test]
+    fn test_first_divergence() {
+        let golden = game(&[&["COMMAND?"], &["ENERGY 3000 ", "COMMAND?"]]);
+        let exact = Tolerance::default();
+        assert_eq!(first_divergence(&golden, &game(&[&["COMMAND?"], &["ENERGY 3000", "COMMAND?"]]), &exact), None);
+        assert_eq!(
+            first_divergence(&golden, &game(&[&["COMMAND?"], &["ENERGY 2999", "COMMAND?"]]), &exact),
+            Some(Divergence { turn: 1, expected: Some("ENERGY 3000".to_string()), actual: Some("ENERGY 2999".to_string()) })
+        );
+        assert_eq!(first_divergence(&golden, &game(&[&["COMMAND?"]]), &exact).map(|divergence| divergence.turn), Some(1));
+        assert_eq!(first_divergence(&golden, &game(&[&["ORDERS", "COMMAND?"]]), &exact).map(|divergence| divergence.turn), Some(0));
+        assert_eq!(first_divergence(&golden, &game(&[&["COMMAND?"], &["ENERGY 2999", "COMMAND?"]]), &Tolerance::new(1.0)), None);
+    }
+    
+    #[test]
+    fn test_bisection() {
+        let mut bisection = Bisection::new(7);
+        let mut tested = Vec::new();
+        while let Some(build) = bisection.next() {
+            tested.push(build);
+            bisection.record(build >= 5);
+        }
+        assert_eq!(tested, [3, 5, 4]);
+        assert_eq!(bisection.first_bad(), Some(5));
+    }
+}
@@ -0,0 +1,110 @@
+use crate::turn::Turn;
+use std::collections::BTreeMap;
+
+/// Who shot at whom in one game and how it went, read from the game's output
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CombatSummary {
+    /// Phaser commands that fired, each hitting every Klingon in the quadrant
+    pub phaser_volleys: usize,
+    pub phaser_hits: usize,
+    /// Klingons the phasers did no damage to
+    pub phaser_misses: usize,
+    pub phaser_damage: f64,
+    pub torpedoes: usize,
+    /// Torpedoes that destroyed a Klingon
+    pub torpedo_hits: usize,
+    pub hits_taken: usize,
+    pub damage_taken: f64,
+    /// Klingons destroyed in each quadrant
+    pub kills: BTreeMap<(i32, i32), usize>,
+}
+
+impl CombatSummary {
+    /// Summarize a recorded game from its turns
+    pub fn from_turns(turns: &[Turn]) -> Self {
+        let mut summary = Self::default();
+        for turn in turns {
+            summary.add_output(&turn.events, turn.after.current_quadrant);
+        }
+        summary
+    }
+    
+    /// Count the combat in one turn's output, which happened in `quadrant`
+    pub fn add_output(&mut self, output: &[String], quadrant: Option<(i32, i32)>) {
+        let mut torpedo_in_flight = false;
+        let mut phasers_fired = false;
+        for line in output {
+            if line.contains("TORPEDO TRACK:") {
+                self.torpedoes += 1;
+                torpedo_in_flight = true;
+            } else if line.contains("KLINGON DESTROYED") {
+                if std::mem::take(&mut torpedo_in_flight) {
+                    self.torpedo_hits += 1;
+                }
+                *self.kills.entry(quadrant.unwrap_or((0, 0))).or_insert(0) += 1;
+            } else if line.contains("TORPEDO MISSED") || line.contains("ABSORBED TORPEDO ENERGY") || line.contains("STARBASE DESTROYED") {
+                torpedo_in_flight = false;
+            } else if let Some((damage, _)) = line.split_once("UNIT HIT ON KLINGON") {
+                phasers_fired = true;
+                self.phaser_hits += 1;
+                self.phaser_damage += damage.trim().parse::<f64>().unwrap_or(0.0);
+            } else if line.contains("SENSORS SHOW NO DAMAGE TO ENEMY") {
+                phasers_fired = true;
+                self.phaser_misses += 1;
+            } else if let Some((damage, _)) = line.split_once("UNIT HIT ON ENTERPRISE") {
+                self.hits_taken += 1;
+                self.damage_taken += damage.trim().parse::<f64>().unwrap_or(0.0);
+            }
+        }
+        if phasers_fired {
+            self.phaser_volleys += 1;
+        }
+    }
+    
+    pub fn klingons_destroyed(&self) -> usize {
+        self.kills.values().sum()
+    }
+    
+    /// The summary as report lines
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![
+            format!("Phasers: {} volleys, {} hits, {} without damage, {:.0} damage dealt",
+                    self.phaser_volleys, self.phaser_hits, self.phaser_misses, self.phaser_damage),
+            format!("Torpedoes: {} fired, {} hits, {} misses",
+                    self.torpedoes, self.torpedo_hits, self.torpedoes - self.torpedo_hits),
+            format!("Hits taken: {} for {:.0} damage", self.hits_taken, self.damage_taken),
+            format!("Klingons destroyed: {}", self.klingons_destroyed()),
+        ];
+        for ((row, column), kills) in &self.kills {
+            lines.push(format!("  quadrant {},{}: {}", row, column, kills));
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_add_output() {
+        let mut summary = CombatSummary::default();
+        let output: Vec<String> = [
+            " 150 UNIT HIT ON KLINGON AT SECTOR 3 , 4",
+            "*** KLINGON DESTROYED ***",
+            "SENSORS SHOW NO DAMAGE TO ENEMY AT 5 , 5",
+            " 40 UNIT HIT ON ENTERPRISE FROM SECTOR 5 , 5",
+        ].iter().map(|line| line.to_string()).collect();
+        summary.add_output(&output, Some((2, 7)));
+        
+        let output: Vec<String> = ["TORPEDO TRACK:", "  4 , 5", "TORPEDO MISSED", "TORPEDO TRACK:", "  5 , 5", "*** KLINGON DESTROYED ***"]
+            .iter().map(|line| line.to_string()).collect();
+        summary.add_output(&output, Some((2, 7)));
+        
+        assert_eq!((summary.phaser_volleys, summary.phaser_hits, summary.phaser_misses), (1, 1, 1));
+        assert_eq!(summary.phaser_damage, 150.0);
+        assert_eq!((summary.torpedoes, summary.torpedo_hits), (2, 1));
+        assert_eq!((summary.hits_taken, summary.damage_taken), (1, 40.0));
+        assert_eq!(summary.kills.get(&(2, 7)), Some(&2));
+    }
+}
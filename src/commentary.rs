@@ -0,0 +1,97 @@
+use crate::game::{GameState, Line};
+
+/// Play-by-play commentary: what happened each turn in a sentence, e.g. "Turn 34:
+/// Entered DENEB quadrant, 2 Klingons detected, shields raised to 600". Much easier to
+/// follow in a demo than the raw BASIC output.
+#[derive(Debug, Clone, Default)]
+pub struct Commentary {
+    quadrant: Option<(i32, i32)>,
+    shields: Option<i32>,
+}
+
+impl Commentary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Describe a turn from its output and the state it left the game in; None when
+    /// nothing worth mentioning happened
+    pub fn turn(&mut self, turn: usize, output: &[Line], state: &GameState) -> Option<String> {
+        let mut events = Vec::new();
+        let mut hits = (0, 0.0);
+        let mut kills = 0;
+        
+        for line in output {
+            if let Some(name) = line.split_once("NOW ENTERING ").and_then(|(_, rest)| rest.split_once(" QUADRANT")).map(|(name, _)| name) {
+                events.push(format!("entered {} quadrant", name.trim()));
+            } else if line.contains("COMBAT AREA") {
+                let klingons = state.sector_map.iter().flatten().flatten().filter(|sector| *sector == "+K+").count();
+                events.push(match klingons {
+                    0 => "Klingons detected".to_string(),
+                    1 => "1 Klingon detected".to_string(),
+                    count => format!("{} Klingons detected", count),
+                });
+            } else if line.contains("SHIELDS NOW AT") {
+                if let Some(shields) = state.shields {
+                    let direction = if self.shields.is_some_and(|before| shields < before) { "lowered" } else { "raised" };
+                    events.push(format!("shields {} to {}", direction, shields));
+                }
+            } else if line.contains("TORPEDO MISSED") {
+                events.push("torpedo missed".to_string());
+            } else if line.contains("KLINGON DESTROYED") {
+                kills += 1;
+            } else if let Some((damage, _)) = line.split_once("UNIT HIT ON ENTERPRISE") {
+                hits.0 += 1;
+                hits.1 += damage.trim().parse::<f64>().unwrap_or(0.0);
+            } else if line.contains("SHIELDS DROPPED FOR DOCKING") {
+                events.push("docked at a starbase".to_string());
+            } else if line.contains("STARBASE DESTROYED") {
+                events.push("destroyed a starbase".to_string());
+            }
+        }
+        
+        if kills > 0 {
+            events.push(if kills == 1 { "Klingon destroyed".to_string() } else { format!("{} Klingons destroyed", kills) });
+        }
+        if hits.0 > 0 {
+            events.push(format!("hit {} time{} for {:.0} damage", hits.0, if hits.0 == 1 { "" } else { "s" }, hits.1));
+        }
+        if state.current_quadrant != self.quadrant && !events.iter().any(|event| event.starts_with("entered")) {
+            if let (Some(_), Some((row, column))) = (self.quadrant, state.current_quadrant) {
+                events.insert(0, format!("moved to quadrant {},{}", row, column));
+            }
+        }
+        self.quadrant = state.current_quadrant;
+        self.shields = state.shields;
+        
+        let mut events = events.into_iter();
+        let first = events.next()?;
+        let mut sentence = first[..1].to_uppercase() + &first[1..];
+        for event in events {
+            sentence.push_str(", ");
+            sentence.push_str(&event);
+        }
+        Some(format!("Turn {}: {}", turn, sentence))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_turn() {
+        let mut commentary = Commentary::new();
+        let mut state = GameState::new();
+        state.current_quadrant = Some((2, 3));
+        state.sector_map = Some(vec![vec!["+K+".to_string(), "".to_string(), "+K+".to_string()]]);
+        let output: Vec<Line> = ["NOW ENTERING DENEB II QUADRANT . . .", "COMBAT AREA      CONDITION RED"]
+            .into_iter().map(Line::from).collect();
+        assert_eq!(commentary.turn(34, &output, &state).as_deref(), Some("Turn 34: Entered DENEB II quadrant, 2 Klingons detected"));
+        
+        state.shields = Some(600);
+        let output: Vec<Line> = ["  'SHIELDS NOW AT 600 UNITS PER YOUR COMMAND.'"].into_iter().map(Line::from).collect();
+        assert_eq!(commentary.turn(35, &output, &state).as_deref(), Some("Turn 35: Shields raised to 600"));
+        assert_eq!(commentary.turn(36, &[], &state), None);
+    }
+}
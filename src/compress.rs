@@ -0,0 +1,104 @@
+use anyhow::{Context, Result};
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use std::io::{Read, Write};
+use std::path::Path;
+
+/// zstd's default level
+const ZSTD_LEVEL: i32 = 3;
+
+/// Compression used for a file, picked from its extension
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Compression {
+    None,
+    /// `.gz`
+    Gzip,
+    /// `.zst` or `.zstd`
+    Zstd,
+}
+
+impl Compression {
+    pub fn for_path(path: &str) -> Self {
+        match Path::new(path).extension().and_then(|ext| ext.to_str()) {
+            Some("gz") => Compression::Gzip,
+            Some("zst") | Some("zstd") => Compression::Zstd,
+            _ => Compression::None,
+        }
+    }
+    
+    fn compress(&self, data: &[u8]) -> Result<Vec<u8>> {
+        Ok(match self {
+            Compression::None => data.to_vec(),
+            Compression::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(data)?;
+                encoder.finish()?
+            }
+            Compression::Zstd => zstd::encode_all(data, ZSTD_LEVEL)?,
+        })
+    }
+    
+    fn decompress(&self, data: &[u8]) -> Result<Vec<u8>> {
+        Ok(match self {
+            Compression::None => data.to_vec(),
+            Compression::Gzip => {
+                let mut decompressed = Vec::new();
+                MultiGzDecoder::new(data).read_to_end(&mut decompressed)?;
+                decompressed
+            }
+            Compression::Zstd => zstd::decode_all(data)?,
+        })
+    }
+}
+
+/// Write a text file, compressing it if the extension asks for it. The file is only
+/// created once the contents are compressed, so a failure leaves nothing behind.
+pub fn write_file(path: &str, contents: &str) -> Result<()> {
+    let compression = Compression::for_path(path);
+    let data = compression.compress(contents.as_bytes())
+        .with_context(|| format!("Failed to compress {} ({:?})", path, compression))?;
+    std::fs::write(path, data).with_context(|| format!("Failed to write {}", path))
+}
+
+/// Read a text file written by write_file, decompressing it transparently
+pub fn read_file(path: &str) -> Result<String> {
+    let compression = Compression::for_path(path);
+    let data = std::fs::read(path).with_context(|| format!("Failed to read {}", path))?;
+    let data = compression.decompress(&data)
+        .with_context(|| format!("Failed to decompress {} ({:?})", path, compression))?;
+    Ok(String::from_utf8_lossy(&data).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_compression_for_path() {
+        assert_eq!(Compression::for_path("game.jsonl"), Compression::None);
+        assert_eq!(Compression::for_path("game.jsonl.gz"), Compression::Gzip);
+        assert_eq!(Compression::for_path("game.jsonl.zst"), Compression::Zstd);
+        assert_eq!(Compression::for_path("logs/game.zstd"), Compression::Zstd);
+    }
+    
+    #[test]
+    fn test_round_trip() {
+        let dir = std::env::temp_dir().join(format!("trekbot-compress-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let contents = "{\"t\":0.5,\"dir\":\"out\",\"text\":\"COMMAND?\"}\n".repeat(100);
+        for name in ["game.jsonl", "game.jsonl.gz", "game.jsonl.zst"] {
+            let path = dir.join(name).to_string_lossy().to_string();
+            write_file(&path, &contents).unwrap();
+            assert_eq!(read_file(&path).unwrap(), contents, "{}", name);
+            if name != "game.jsonl" {
+                assert!(std::fs::metadata(&path).unwrap().len() < contents.len() as u64, "{} isn't compressed", name);
+            }
+        }
+        
+        // Not what the extension says: an error, not garbage
+        let path = dir.join("plain.gz").to_string_lossy().to_string();
+        std::fs::write(&path, &contents).unwrap();
+        assert!(read_file(&path).is_err());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
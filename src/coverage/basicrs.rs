@@ -0,0 +1,101 @@
+use super::{CoverageData, CoverageLaunch, CoverageProvider};
+use crate::json::Json;
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+
+/// BASIC line coverage written by BasicRS with `--coverage-file`
+pub struct BasicRSCoverage {
+    coverage_file: String,
+    program: String,
+}
+
+impl BasicRSCoverage {
+    pub fn new(coverage_file: String, program: &str) -> Self {
+        Self {
+            coverage_file,
+            program: program.to_string(),
+        }
+    }
+}
+
+impl CoverageProvider for BasicRSCoverage {
+    fn name(&self) -> &'static str {
+        "basicrs"
+    }
+    
+    fn configure(&self, reset: bool) -> CoverageLaunch {
+        let mut program_args = vec!["--coverage-file".to_string(), self.coverage_file.clone()];
+        if reset {
+            program_args.push("--reset-coverage".to_string());
+        }
+        CoverageLaunch {
+            runtime_args: Vec::new(),
+            program_args,
+        }
+    }
+    
+    fn collect(&self) -> Result<CoverageData> {
+        let text = std::fs::read_to_string(&self.coverage_file)
+            .with_context(|| format!("Failed to read coverage file {}", self.coverage_file))?;
+        let json = Json::parse(&text)?;
+        
+        let mut lines = BTreeMap::new();
+        collect_line_hits(&json, false, &mut lines);
+        
+        let mut data = CoverageData::new();
+        for (line, hits) in lines {
+            data.add_line(&self.program, line, hits);
+        }
+        Ok(data)
+    }
+}
+
+/// Find BASIC line numbers anywhere in the coverage JSON: objects keyed by line number
+/// (`{"100": 3}` or `{"100": {"count": 3}}`) and arrays of line numbers under a key
+/// mentioning lines. This keeps working as the BasicRS format grows extra fields.
+fn collect_line_hits(json: &Json, in_line_list: bool, lines: &mut BTreeMap<u32, u64>) {
+    match json {
+        Json::Object(fields) => {
+            for (key, value) in fields {
+                if let Ok(line) = key.parse::<u32>() {
+                    let hits = match value {
+                        Json::Number(hits) => Some(*hits as u64),
+                        Json::Object(_) => ["count", "hits", "executions"].iter()
+                            .find_map(|name| value.get(name).and_then(Json::as_f64))
+                            .map(|hits| hits as u64),
+                        _ => Some(1),
+                    };
+                    if let Some(hits) = hits.filter(|hits| *hits > 0) {
+                        *lines.entry(line).or_insert(0) += hits;
+                    }
+                } else {
+                    collect_line_hits(value, key.to_lowercase().contains("line"), lines);
+                }
+            }
+        }
+        Json::Array(values) => {
+            for value in values {
+                match value {
+                    Json::Number(line) if in_line_list => {
+                        *lines.entry(*line as u32).or_insert(0) += 1;
+                    }
+                    _ => collect_line_hits(value, in_line_list, lines),
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_collect_line_hits() {
+        let json = Json::parse(r#"{"program":"x.bas","lines":{"100":3,"110":0,"120":{"count":1}},"covered_lines":[130]}"#).unwrap();
+        let mut lines = BTreeMap::new();
+        collect_line_hits(&json, false, &mut lines);
+        assert_eq!(lines.into_iter().collect::<Vec<_>>(), vec![(100, 3), (120, 1), (130, 1)]);
+    }
+}
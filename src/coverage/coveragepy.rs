@@ -0,0 +1,84 @@
+use super::{CoverageData, CoverageLaunch, CoverageProvider};
+use crate::json::Json;
+use anyhow::{Context, Result};
+use std::process::Command;
+
+/// Python line coverage of TrekBasic, recorded by coverage.py
+/// (`python -m coverage run`) and read back through `coverage json`
+pub struct CoveragePyCoverage {
+    python_path: String,
+    data_file: String,
+}
+
+impl CoveragePyCoverage {
+    pub fn new(python_path: String, data_file: String) -> Self {
+        Self { python_path, data_file }
+    }
+}
+
+impl CoverageProvider for CoveragePyCoverage {
+    fn name(&self) -> &'static str {
+        "coverage.py"
+    }
+    
+    fn configure(&self, reset: bool) -> CoverageLaunch {
+        let mut runtime_args = vec![
+            "-m".to_string(),
+            "coverage".to_string(),
+            "run".to_string(),
+            format!("--data-file={}", self.data_file),
+        ];
+        if !reset {
+            runtime_args.push("--append".to_string());
+        }
+        CoverageLaunch {
+            runtime_args,
+            program_args: Vec::new(),
+        }
+    }
+    
+    fn collect(&self) -> Result<CoverageData> {
+        let json_file = format!("{}.json", self.data_file);
+        let output = Command::new(&self.python_path)
+            .args(["-m", "coverage", "json", "-q"])
+            .arg(format!("--data-file={}", self.data_file))
+            .args(["-o", &json_file])
+            .output()
+            .context("Failed to run coverage.py")?;
+        if !output.status.success() {
+            anyhow::bail!("coverage json failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+        }
+        
+        let text = std::fs::read_to_string(&json_file)?;
+        parse_coverage_json(&Json::parse(&text)?)
+    }
+}
+
+/// Read the `files.<name>.executed_lines` lists of a `coverage json` report
+fn parse_coverage_json(json: &Json) -> Result<CoverageData> {
+    let files = match json.get("files") {
+        Some(Json::Object(files)) => files,
+        _ => anyhow::bail!("coverage.py report has no 'files' section"),
+    };
+    
+    let mut data = CoverageData::new();
+    for (file, report) in files {
+        let executed = report.get("executed_lines").and_then(Json::as_array).unwrap_or(&[]);
+        for line in executed.iter().filter_map(Json::as_usize) {
+            data.add_line(file, line as u32, 1);
+        }
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_parse_coverage_json() {
+        let json = Json::parse(r#"{"meta":{},"files":{"basic.py":{"executed_lines":[1,2,5],"missing_lines":[3]}}}"#).unwrap();
+        let data = parse_coverage_json(&json).unwrap();
+        assert_eq!(data.lines("basic.py"), vec![1, 2, 5]);
+    }
+}
@@ -0,0 +1,88 @@
+use super::{CoverageData, CoverageLaunch, CoverageProvider};
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::process::Command;
+
+/// Java line coverage of TrekBasicJ, recorded by the JaCoCo agent and
+/// read back from the XML report produced by the JaCoCo CLI
+pub struct JacocoCoverage {
+    java_path: String,
+    agent_jar: String,
+    cli_jar: String,
+    class_files: String,
+    exec_file: String,
+}
+
+impl JacocoCoverage {
+    pub fn new(java_path: String, agent_jar: String, cli_jar: String, class_files: String, exec_file: String) -> Self {
+        Self {
+            java_path,
+            agent_jar,
+            cli_jar,
+            class_files,
+            exec_file,
+        }
+    }
+}
+
+impl CoverageProvider for JacocoCoverage {
+    fn name(&self) -> &'static str {
+        "jacoco"
+    }
+    
+    fn configure(&self, reset: bool) -> CoverageLaunch {
+        CoverageLaunch {
+            runtime_args: vec![format!(
+                "-javaagent:{}=destfile={},append={}",
+                self.agent_jar, self.exec_file, !reset
+            )],
+            program_args: Vec::new(),
+        }
+    }
+    
+    fn collect(&self) -> Result<CoverageData> {
+        let xml_file = format!("{}.xml", self.exec_file);
+        let output = Command::new(&self.java_path)
+            .args(["-jar", &self.cli_jar, "report", &self.exec_file])
+            .args(["--classfiles", &self.class_files, "--xml", &xml_file])
+            .output()
+            .context("Failed to run the JaCoCo CLI")?;
+        if !output.status.success() {
+            anyhow::bail!("JaCoCo report failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+        }
+        
+        let xml = std::fs::read_to_string(&xml_file)?;
+        parse_jacoco_xml(&xml)
+    }
+}
+
+/// Read covered lines (`ci` > 0) per source file from a JaCoCo XML report
+fn parse_jacoco_xml(xml: &str) -> Result<CoverageData> {
+    let tag_regex = Regex::new(r"<(package|sourcefile|line)\s([^>]*)>")?;
+    let attribute_regex = Regex::new(r#"(\w+)="([^"]*)""#)?;
+    
+    let mut data = CoverageData::new();
+    let mut package = String::new();
+    let mut source_file = String::new();
+    
+    for tag in tag_regex.captures_iter(xml) {
+        let attribute = |name: &str| {
+            attribute_regex.captures_iter(&tag[2])
+                .find(|caps| &caps[1] == name)
+                .map(|caps| caps[2].to_string())
+        };
+        match &tag[1] {
+            "package" => package = attribute("name").unwrap_or_default(),
+            "sourcefile" => source_file = attribute("name").unwrap_or_default(),
+            _ => {
+                let line = attribute("nr").and_then(|nr| nr.parse::<u32>().ok());
+                let covered = attribute("ci").and_then(|ci| ci.parse::<u64>().ok()).unwrap_or(0);
+                if let (Some(line), true) = (line, covered > 0) {
+                    let file = if package.is_empty() { source_file.clone() } else { format!("{}/{}", package, source_file) };
+                    data.add_line(&file, line, covered);
+                }
+            }
+        }
+    }
+    Ok(data)
+}
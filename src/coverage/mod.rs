@@ -0,0 +1,136 @@
+use crate::json::Json;
+use anyhow::Result;
+use std::collections::BTreeMap;
+
+pub mod basicrs;
+pub mod coveragepy;
+pub mod jacoco;
+pub mod reachability;
+pub mod target;
+
+pub use basicrs::BasicRSCoverage;
+pub use coveragepy::CoveragePyCoverage;
+pub use jacoco::JacocoCoverage;
+pub use reachability::{parse_program, ReachabilityReport};
+pub use target::{LineRange, SearchAttempt, TargetScenario};
+
+/// How an interpreter launch has to change so the game records coverage
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CoverageLaunch {
+    /// Arguments for the runtime, before the interpreter script or JAR
+    /// (e.g. `-m coverage run ...` for Python, `-javaagent:...` for Java)
+    pub runtime_args: Vec<String>,
+    /// Arguments after the BASIC program (e.g. `--coverage-file` for BasicRS)
+    pub program_args: Vec<String>,
+}
+
+/// Covered lines per source file, with hit counts where the format has them
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CoverageData {
+    files: BTreeMap<String, BTreeMap<u32, u64>>,
+}
+
+impl CoverageData {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Record hits for a line; repeated calls add up
+    pub fn add_line(&mut self, file: &str, line: u32, hits: u64) {
+        *self.files.entry(file.to_string()).or_default().entry(line).or_insert(0) += hits;
+    }
+    
+    pub fn merge(&mut self, other: &CoverageData) {
+        for (file, lines) in &other.files {
+            for (line, hits) in lines {
+                self.add_line(file, *line, *hits);
+            }
+        }
+    }
+    
+    /// Covered lines of one file, in order
+    pub fn lines(&self, file: &str) -> Vec<u32> {
+        self.files.get(file).map_or(Vec::new(), |lines| lines.keys().copied().collect())
+    }
+    
+    /// Hits per line of each file, `{"files": {"x.bas": {"lines": {"100": 3}}}}`, which
+    /// BasicRSCoverage reads back
+    pub fn to_json(&self) -> Json {
+        let files = self.files.iter().map(|(file, lines)| {
+            let lines = lines.iter().map(|(line, hits)| (line.to_string(), Json::from(*hits))).collect();
+            (file.clone(), Json::object().with("lines", Json::Object(lines)))
+        });
+        Json::object().with("files", Json::Object(files.collect()))
+    }
+    
+    pub fn summary(&self) -> CoverageSummary {
+        CoverageSummary {
+            files: self.files.iter().map(|(file, lines)| (file.clone(), lines.len())).collect(),
+        }
+    }
+}
+
+/// Number of covered lines per file
+#[derive(Debug, Clone)]
+pub struct CoverageSummary {
+    pub files: Vec<(String, usize)>,
+}
+
+impl CoverageSummary {
+    pub fn total_lines(&self) -> usize {
+        self.files.iter().map(|(_, lines)| lines).sum()
+    }
+    
+    pub fn print(&self, provider: &str) {
+        println!("=== Coverage ({}) ===", provider);
+        for (file, lines) in &self.files {
+            println!("{:>6} lines  {}", lines, file);
+        }
+        println!("{:>6} lines covered in {} files", self.total_lines(), self.files.len());
+    }
+}
+
+/// A source of coverage data for one interpreter. Each provider knows how to make
+/// its interpreter record coverage and how to read the result back, so BasicRS,
+/// coverage.py and JaCoCo data all end up in the same CoverageData.
+pub trait CoverageProvider {
+    /// Short name for reports, e.g. "basicrs"
+    fn name(&self) -> &'static str;
+    
+    /// Launch changes that make the next game record coverage. With `reset`
+    /// earlier data is discarded, otherwise the game adds to it.
+    fn configure(&self, reset: bool) -> CoverageLaunch;
+    
+    /// Read the coverage recorded so far
+    fn collect(&self) -> Result<CoverageData>;
+    
+    /// Combine coverage from separate games or runs
+    fn merge(&self, into: &mut CoverageData, other: &CoverageData) {
+        into.merge(other);
+    }
+    
+    fn summarize(&self, data: &CoverageData) -> CoverageSummary {
+        data.summary()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_merge_and_summary() {
+        let mut a = CoverageData::new();
+        a.add_line("superstartrek.bas", 10, 1);
+        a.add_line("superstartrek.bas", 20, 2);
+        let mut b = CoverageData::new();
+        b.add_line("superstartrek.bas", 20, 1);
+        b.add_line("superstartrek.bas", 30, 1);
+        b.add_line("basic.py", 5, 1);
+        
+        a.merge(&b);
+        assert_eq!(a.lines("superstartrek.bas"), vec![10, 20, 30]);
+        assert_eq!(a.summary().total_lines(), 4);
+        assert_eq!(a.to_json().to_string(), r#"{"files":{"basic.py":{"lines":{"5":1}},"superstartrek.bas":{"lines":{"10":1,"20":3,"30":1}}}}"#);
+    }
+}
@@ -0,0 +1,323 @@
+use super::CoverageData;
+use std::collections::BTreeSet;
+
+/// Text printed by the program that tells which game behavior a stretch of code handles.
+/// Matched against the source of unreached lines to suggest what strategies never trigger.
+const BEHAVIOR_MARKERS: &[(&str, &str)] = &[
+    ("SHIELDS DROPPED FOR DOCKING", "docking at a starbase"),
+    ("C$=\"DOCKED\"", "docking at a starbase"),
+    ("STARBASE SHIELDS PROTECT", "being attacked while docked"),
+    ("ABSORBED TORPEDO ENERGY", "torpedo hitting a star"),
+    ("STARBASE DESTROYED", "destroying a starbase"),
+    ("COURT MARTIAL", "destroying a starbase"),
+    ("TORPEDO MISSED", "torpedo missing"),
+    ("KLINGON DESTROYED", "destroying a Klingon"),
+    ("STRANDED YOUR SHIP", "running out of energy"),
+    ("INSUFFICIENT MANEUVERING ENERGY", "running out of energy"),
+    ("SHIELD CONTROL SUPPLIES ENERGY", "moving on shield energy"),
+    ("CROSSING OF GALACTIC PERIMETER", "leaving the galaxy"),
+    ("BAD NAVAGATION", "running into an object while moving"),
+    ("STATE OF REPAIR IMPROVED", "random repairs during travel"),
+    ("REPAIR COMPLETED", "device repairs completing"),
+    ("WILL YOU AUTHORIZE THE REPAIR ORDER", "starbase repairs"),
+    ("SHIELDS DANGEROUSLY LOW", "low shields in combat"),
+    ("THE ENTERPRISE HAS BEEN DESTROYED", "the Enterprise being destroyed"),
+    ("IT IS STARDATE", "running out of time"),
+    ("CONGRULATION", "winning the game"),
+    ("LET HIM STEP FORWARD", "starting a new game"),
+];
+
+/// One numbered line of a BASIC program
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceLine {
+    pub number: u32,
+    pub text: String,
+}
+
+impl SourceLine {
+    /// Lines holding only a comment; interpreters don't all record these, so they are
+    /// left out of the report
+    fn is_comment(&self) -> bool {
+        self.text.trim_start().to_uppercase().starts_with("REM")
+    }
+}
+
+/// Split a BASIC program into numbered lines, skipping anything without a line number
+pub fn parse_program(source: &str) -> Vec<SourceLine> {
+    source.lines()
+        .filter_map(|line| {
+            let line = line.trim_start();
+            let digits = line.find(|c: char| !c.is_ascii_digit()).unwrap_or(line.len());
+            let number = line[..digits].parse().ok()?;
+            Some(SourceLine {
+                number,
+                text: line[digits..].trim().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Line numbers called with GOSUB anywhere in the program
+fn gosub_targets(program: &[SourceLine]) -> BTreeSet<u32> {
+    let mut targets = BTreeSet::new();
+    for line in program {
+        let code = strip_strings(&line.text).to_uppercase();
+        let mut rest = code.as_str();
+        while let Some(pos) = rest.find("GOSUB") {
+            rest = rest[pos + "GOSUB".len()..].trim_start();
+            let digits = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+            if let Ok(target) = rest[..digits].parse() {
+                targets.insert(target);
+            }
+        }
+    }
+    targets
+}
+
+/// Blank out string literals so keywords inside PRINT text aren't mistaken for code
+fn strip_strings(text: &str) -> String {
+    let mut in_string = false;
+    text.chars()
+        .map(|c| {
+            if c == '"' {
+                in_string = !in_string;
+                c
+            } else if in_string {
+                ' '
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// A stretch of the program that starts at a REM header or a GOSUB target
+#[derive(Debug, Clone)]
+pub struct Routine {
+    pub name: String,
+    pub lines: Vec<SourceLine>,
+}
+
+/// Split the program into routines. The original marks each section with a REM header
+/// and each subroutine is entered with GOSUB, so a routine starts at a comment that
+/// follows code, or at a GOSUB target that isn't already the start of a routine.
+pub fn routines(program: &[SourceLine]) -> Vec<Routine> {
+    let targets = gosub_targets(program);
+    let mut routines: Vec<Routine> = Vec::new();
+    
+    for line in program {
+        let current_has_code = routines.last()
+            .is_none_or(|routine| routine.lines.iter().any(|l| !l.is_comment()));
+        let previous_is_comment = routines.last()
+            .and_then(|routine| routine.lines.last())
+            .is_some_and(SourceLine::is_comment);
+        
+        let starts_routine = if line.is_comment() {
+            !previous_is_comment
+        } else {
+            targets.contains(&line.number) && current_has_code
+        };
+        
+        if starts_routine || routines.is_empty() {
+            let name = if line.is_comment() {
+                comment_text(&line.text)
+            } else {
+                format!("SUBROUTINE {}", line.number)
+            };
+            routines.push(Routine { name, lines: Vec::new() });
+        }
+        routines.last_mut().unwrap().lines.push(line.clone());
+    }
+    
+    routines
+}
+
+/// Text of a REM line without the keyword and decoration, e.g. "LIBRARY COMPUTER CODE"
+fn comment_text(text: &str) -> String {
+    let text = text.trim_start()[3..].trim();
+    let text = text.trim_matches(|c: char| c == '*' || c == '.' || c.is_whitespace());
+    if text.is_empty() {
+        "(UNNAMED)".to_string()
+    } else {
+        text.to_string()
+    }
+}
+
+/// Consecutive program lines that never ran
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnreachedRange {
+    pub first: u32,
+    pub last: u32,
+    pub lines: usize,
+    /// Game behaviors handled by these lines
+    pub behaviors: Vec<&'static str>,
+}
+
+/// Coverage of one routine
+#[derive(Debug, Clone)]
+pub struct RoutineReport {
+    pub name: String,
+    pub start: u32,
+    pub total_lines: usize,
+    pub covered_lines: usize,
+    pub unreached: Vec<UnreachedRange>,
+}
+
+/// Unreached code of a program, grouped by routine
+#[derive(Debug, Clone)]
+pub struct ReachabilityReport {
+    pub routines: Vec<RoutineReport>,
+}
+
+impl ReachabilityReport {
+    /// Compare the program against the BASIC lines that were executed
+    pub fn build(program: &[SourceLine], covered: &BTreeSet<u32>) -> Self {
+        let routines = routines(program)
+            .into_iter()
+            .filter_map(|routine| {
+                let start = routine.lines.first()?.number;
+                let code: Vec<&SourceLine> = routine.lines.iter().filter(|l| !l.is_comment()).collect();
+                if code.is_empty() {
+                    return None;
+                }
+                
+                let mut unreached: Vec<Vec<&SourceLine>> = Vec::new();
+                let mut previous_reached = true;
+                for line in &code {
+                    if covered.contains(&line.number) {
+                        previous_reached = true;
+                        continue;
+                    }
+                    if previous_reached {
+                        unreached.push(Vec::new());
+                    }
+                    unreached.last_mut().unwrap().push(line);
+                    previous_reached = false;
+                }
+                
+                let covered_lines = code.iter().filter(|l| covered.contains(&l.number)).count();
+                Some(RoutineReport {
+                    name: routine.name,
+                    start,
+                    total_lines: code.len(),
+                    covered_lines,
+                    unreached: unreached.into_iter().map(|lines| unreached_range(&lines)).collect(),
+                })
+            })
+            .collect();
+        
+        Self { routines }
+    }
+    
+    /// Build the report from merged coverage data recorded for `program_file`
+    pub fn from_coverage(program: &[SourceLine], coverage: &CoverageData, program_file: &str) -> Self {
+        let covered = coverage.lines(program_file).into_iter().collect();
+        Self::build(program, &covered)
+    }
+    
+    /// Behaviors that some unreached code handles, each listed once
+    pub fn untriggered_behaviors(&self) -> Vec<&'static str> {
+        let mut behaviors = Vec::new();
+        for range in self.routines.iter().flat_map(|routine| &routine.unreached) {
+            for behavior in &range.behaviors {
+                if !behaviors.contains(behavior) {
+                    behaviors.push(*behavior);
+                }
+            }
+        }
+        behaviors
+    }
+    
+    pub fn print(&self) {
+        let total: usize = self.routines.iter().map(|r| r.total_lines).sum();
+        let covered: usize = self.routines.iter().map(|r| r.covered_lines).sum();
+        
+        println!("=== Reachability ===");
+        println!("{} of {} program lines reached", covered, total);
+        println!();
+        
+        for routine in self.routines.iter().filter(|r| !r.unreached.is_empty()) {
+            println!("{:>5} {} ({}/{} lines reached)",
+                     routine.start, routine.name, routine.covered_lines, routine.total_lines);
+            for range in &routine.unreached {
+                let lines = if range.first == range.last {
+                    format!("{}", range.first)
+                } else {
+                    format!("{}-{}", range.first, range.last)
+                };
+                if range.behaviors.is_empty() {
+                    println!("        never reached: {}", lines);
+                } else {
+                    println!("        never reached: {} ({})", lines, range.behaviors.join(", "));
+                }
+            }
+        }
+        
+        let behaviors = self.untriggered_behaviors();
+        if !behaviors.is_empty() {
+            println!();
+            println!("Behaviors never triggered:");
+            for behavior in behaviors {
+                println!("  - {}", behavior);
+            }
+        }
+    }
+}
+
+fn unreached_range(lines: &[&SourceLine]) -> UnreachedRange {
+    let mut behaviors = Vec::new();
+    for line in lines {
+        let text = line.text.to_uppercase();
+        for (marker, behavior) in BEHAVIOR_MARKERS {
+            if text.contains(marker) && !behaviors.contains(behavior) {
+                behaviors.push(*behavior);
+            }
+        }
+    }
+    UnreachedRange {
+        first: lines[0].number,
+        last: lines[lines.len() - 1].number,
+        lines: lines.len(),
+        behaviors,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    const PROGRAM: &str = "10 REM SETUP\n\
+        20 X=1:GOSUB 100\n\
+        30 IF X>1 THEN PRINT\"TORPEDO MISSED\"\n\
+        40 END\n\
+        90 REM HELPER\n\
+        100 PRINT\"GOSUB 999\"\n\
+        110 RETURN\n";
+    
+    #[test]
+    fn test_routines() {
+        let program = parse_program(PROGRAM);
+        assert_eq!(program.len(), 7);
+        assert_eq!(gosub_targets(&program).into_iter().collect::<Vec<_>>(), vec![100]);
+        
+        let names: Vec<String> = routines(&program).into_iter().map(|r| r.name).collect();
+        assert_eq!(names, vec!["SETUP", "HELPER"]);
+    }
+    
+    #[test]
+    fn test_unreached_ranges() {
+        let program = parse_program(PROGRAM);
+        let covered = [20, 40, 100, 110].into_iter().collect();
+        let report = ReachabilityReport::build(&program, &covered);
+        
+        assert_eq!(report.routines[0].covered_lines, 2);
+        assert_eq!(report.routines[0].unreached, vec![UnreachedRange {
+            first: 30,
+            last: 30,
+            lines: 1,
+            behaviors: vec!["torpedo missing"],
+        }]);
+        assert!(report.routines[1].unreached.is_empty());
+        assert_eq!(report.untriggered_behaviors(), vec!["torpedo missing"]);
+    }
+}
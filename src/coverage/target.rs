@@ -0,0 +1,130 @@
+use crate::json::Json;
+use crate::metadata::RunMetadata;
+use anyhow::{Context, Result};
+use std::fmt;
+use std::str::FromStr;
+
+/// BASIC lines a coverage search has to reach, written as "4670-4700" or "4670"
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LineRange {
+    pub first: u32,
+    pub last: u32,
+}
+
+impl LineRange {
+    pub fn contains(&self, line: u32) -> bool {
+        (self.first..=self.last).contains(&line)
+    }
+    
+    /// Covered lines that fall inside the range
+    pub fn reached(&self, covered: &[u32]) -> Vec<u32> {
+        covered.iter().copied().filter(|line| self.contains(*line)).collect()
+    }
+}
+
+impl FromStr for LineRange {
+    type Err = anyhow::Error;
+    
+    fn from_str(text: &str) -> Result<Self> {
+        let (first, last) = match text.split_once('-') {
+            Some((first, last)) => (first.trim(), last.trim()),
+            None => (text.trim(), text.trim()),
+        };
+        let first = first.parse::<u32>().with_context(|| format!("Invalid line range '{}'", text))?;
+        let last = last.parse::<u32>().with_context(|| format!("Invalid line range '{}'", text))?;
+        if last < first {
+            anyhow::bail!("Invalid line range '{}': {} comes after {}", text, first, last);
+        }
+        Ok(Self { first, last })
+    }
+}
+
+impl fmt::Display for LineRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.first == self.last {
+            write!(f, "{}", self.first)
+        } else {
+            write!(f, "{}-{}", self.first, self.last)
+        }
+    }
+}
+
+/// Strategy settings for one game of a coverage search
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchAttempt {
+    pub seed: u64,
+    pub dock: bool,
+    pub max_turns: usize,
+}
+
+impl SearchAttempt {
+    /// Settings for the attempt with the given index. Each attempt uses a new seed for
+    /// the random strategy; docking alternates, and longer games are tried as the
+    /// search goes on since rare code is often only reached late in a game.
+    pub fn nth(index: usize, base_seed: u64, max_turns: usize) -> Self {
+        let length_factor = 1 << ((index / 2) % 3);
+        Self {
+            seed: base_seed.wrapping_add(index as u64),
+            dock: index % 2 == 1,
+            max_turns: max_turns * length_factor,
+        }
+    }
+}
+
+/// A game found to reach target lines, saved so it can be replayed as a regression check
+#[derive(Debug, Clone)]
+pub struct TargetScenario {
+    pub program: String,
+    pub interpreter: String,
+    pub lines: LineRange,
+    pub reached: Vec<u32>,
+    pub attempt: SearchAttempt,
+    pub allow_known_bugs: bool,
+    pub transcript: String,
+    pub metadata: RunMetadata,
+}
+
+impl TargetScenario {
+    pub fn to_json(&self) -> Json {
+        Json::object()
+            .with("program", self.program.as_str())
+            .with("interpreter", self.interpreter.as_str())
+            .with("lines", self.lines.to_string())
+            .with("reached", Json::Array(self.reached.iter().map(|line| Json::from(*line as usize)).collect()))
+            .with("strategy", "random")
+            .with("seed", self.attempt.seed)
+            .with("dock", self.attempt.dock)
+            .with("max_turns", self.attempt.max_turns)
+            .with("allow_known_bugs", self.allow_known_bugs)
+            .with("transcript", self.transcript.as_str())
+            .with("metadata", self.metadata.to_json())
+    }
+    
+    pub fn save(&self, path: &str) -> Result<()> {
+        std::fs::write(path, format!("{}\n", self.to_json()))
+            .with_context(|| format!("Failed to write scenario {}", path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_line_range() {
+        let range: LineRange = "4670-4700".parse().unwrap();
+        assert_eq!(range, LineRange { first: 4670, last: 4700 });
+        assert_eq!(range.reached(&[4600, 4670, 4690, 4710]), vec![4670, 4690]);
+        assert_eq!("5260".parse::<LineRange>().unwrap().to_string(), "5260");
+        assert!("4700-4670".parse::<LineRange>().is_err());
+        assert!("abc".parse::<LineRange>().is_err());
+    }
+    
+    #[test]
+    fn test_search_attempts() {
+        assert_eq!(SearchAttempt::nth(0, 10, 100), SearchAttempt { seed: 10, dock: false, max_turns: 100 });
+        assert_eq!(SearchAttempt::nth(1, 10, 100), SearchAttempt { seed: 11, dock: true, max_turns: 100 });
+        assert_eq!(SearchAttempt::nth(4, 10, 100).max_turns, 400);
+        assert_eq!(SearchAttempt::nth(6, 10, 100).max_turns, 100);
+    }
+}
@@ -0,0 +1,361 @@
+use crate::game::{is_menu_line, Advisory, Line, StateChange};
+use anyhow::Result;
+use std::io::Write;
+use std::str::FromStr;
+
+/// Kinds of lines --display can show or hide
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisplayCategory {
+    /// The line the game waits at
+    Prompts,
+    /// What TrekBot sends
+    Commands,
+    /// Hits, kills, torpedo tracks and condition red
+    Combat,
+    /// The command and computer menus printed after an invalid choice
+    Menus,
+    /// Short and long range scans and the computer's galaxy maps
+    Scans,
+    /// TrekBot's status line after each turn
+    Status,
+    /// Everything else the game prints
+    Text,
+}
+
+impl DisplayCategory {
+    const ALL: &'static [DisplayCategory] = &[
+        DisplayCategory::Prompts,
+        DisplayCategory::Commands,
+        DisplayCategory::Combat,
+        DisplayCategory::Menus,
+        DisplayCategory::Scans,
+        DisplayCategory::Status,
+        DisplayCategory::Text,
+    ];
+}
+
+impl FromStr for DisplayCategory {
+    type Err = anyhow::Error;
+    
+    fn from_str(text: &str) -> Result<Self> {
+        match text.trim() {
+            "prompts" => Ok(DisplayCategory::Prompts),
+            "commands" => Ok(DisplayCategory::Commands),
+            "combat" => Ok(DisplayCategory::Combat),
+            "menus" => Ok(DisplayCategory::Menus),
+            "scans" => Ok(DisplayCategory::Scans),
+            "status" => Ok(DisplayCategory::Status),
+            "text" => Ok(DisplayCategory::Text),
+            _ => anyhow::bail!(
+                "Unknown display category '{}', expected prompts, commands, combat, menus, scans, status or text",
+                text
+            ),
+        }
+    }
+}
+
+/// Text that marks a line as part of a fight
+const COMBAT_MARKERS: &[&str] = &[
+    "UNIT HIT",
+    "KLINGON DESTROYED",
+    "TORPEDO TRACK",
+    "TORPEDO MISSED",
+    "ABSORBED TORPEDO ENERGY",
+    "PHASERS LOCKED",
+    "SENSORS SHOW",
+    "COMBAT AREA",
+    "CONDITION RED",
+    "SHIELDS DOWN TO",
+    "ENTERPRISE HAS BEEN DESTROYED",
+];
+
+/// Headers of scans and maps that run until the next blank line
+const SCAN_HEADERS: &[&str] = &["LONG RANGE SCAN", "COMPUTER RECORD OF GALAXY", "THE GALAXY"];
+
+/// Which kinds of lines --display prints
+#[derive(Debug, Clone, PartialEq)]
+pub struct DisplayFilter {
+    shown: Vec<DisplayCategory>,
+}
+
+impl Default for DisplayFilter {
+    fn default() -> Self {
+        Self { shown: DisplayCategory::ALL.to_vec() }
+    }
+}
+
+impl DisplayFilter {
+    /// Show only the `show` categories (all if empty), minus the `hide` ones
+    pub fn new(show: &[String], hide: &[String]) -> Result<Self> {
+        let show: Vec<DisplayCategory> = show.iter().map(|name| name.parse()).collect::<Result<_>>()?;
+        let hide: Vec<DisplayCategory> = hide.iter().map(|name| name.parse()).collect::<Result<_>>()?;
+        let shown = DisplayCategory::ALL
+            .iter()
+            .copied()
+            .filter(|category| show.is_empty() || show.contains(category))
+            .filter(|category| !hide.contains(category))
+            .collect();
+        Ok(Self { shown })
+    }
+    
+    pub fn shows(&self, category: DisplayCategory) -> bool {
+        self.shown.contains(&category)
+    }
+    
+    /// The lines of a block of output that should be displayed, with their categories
+    pub fn visible<'a>(&self, output: &'a [Line], at_prompt: bool) -> Vec<(DisplayCategory, &'a str)> {
+        classify(output, at_prompt)
+            .into_iter()
+            .filter(|(category, _)| self.shows(*category))
+            .collect()
+    }
+}
+
+const RESET: &str = "\x1b[0m";
+
+/// How displayed lines look: ANSI colors by category and a turn number in front
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DisplayStyle {
+    pub color: bool,
+    pub turn_numbers: bool,
+}
+
+impl DisplayStyle {
+    /// Colors when stdout is a terminal and NO_COLOR isn't set, turn numbers always
+    pub fn for_terminal() -> Self {
+        use std::io::IsTerminal;
+        Self {
+            color: std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none(),
+            turn_numbers: true,
+        }
+    }
+    
+    fn paint(&self, code: &str, text: &str) -> String {
+        if self.color && !code.is_empty() {
+            format!("\x1b[{}m{}{}", code, text, RESET)
+        } else {
+            text.to_string()
+        }
+    }
+    
+    fn prefix(&self, turn: usize) -> String {
+        if self.turn_numbers {
+            self.paint("2", &format!("[{:>4}] ", turn))
+        } else {
+            String::new()
+        }
+    }
+    
+    /// A line of game output
+    pub fn line(&self, turn: usize, category: DisplayCategory, line: &str) -> String {
+        let code = match category {
+            DisplayCategory::Prompts => "1;36",
+            DisplayCategory::Commands => "1;33",
+            DisplayCategory::Combat => "31",
+            DisplayCategory::Menus | DisplayCategory::Scans => "2",
+            DisplayCategory::Status => "34",
+            DisplayCategory::Text => "",
+        };
+        format!("{}{}", self.prefix(turn), self.paint(code, line))
+    }
+    
+    /// The command TrekBot sends
+    pub fn command(&self, turn: usize, command: &str) -> String {
+        self.line(turn, DisplayCategory::Commands, &format!("🤖 TrekBot sends: {}", command))
+    }
+    
+    /// Why the strategy chose the command shown before it
+    pub fn decision(&self, turn: usize, reason: &str) -> String {
+        format!("{}{}", self.prefix(turn), self.paint("2", &format!("   ↳ {}", reason)))
+    }
+    
+    /// A warning from the advisory engine
+    pub fn advisory(&self, turn: usize, advisory: &Advisory) -> String {
+        format!("{}{}", self.prefix(turn), self.paint("1;35", &format!("⚠ {}: {}", advisory.kind.label(), advisory.message)))
+    }
+    
+    /// A note that the ship's condition changed, e.g. GREEN to RED
+    pub fn condition_change(&self, turn: usize, from: &str, to: &str) -> String {
+        let code = match to {
+            "RED" => "1;41",
+            "YELLOW" => "1;33",
+            "GREEN" => "1;32",
+            _ => "1;35",
+        };
+        format!("{}{}", self.prefix(turn), self.paint(code, &format!("⚠ CONDITION {} → {}", from, to)))
+    }
+    
+    /// What changed since the previous turn, rises in green and drops in red
+    pub fn state_changes(&self, turn: usize, changes: &[StateChange]) -> String {
+        if changes.is_empty() {
+            return format!("{}{}", self.prefix(turn), self.paint("2", "Δ no change"));
+        }
+        let changes: Vec<String> = changes
+            .iter()
+            .map(|change| {
+                let code = match change {
+                    StateChange::Amount { delta, .. } if *delta > 0 => "32",
+                    StateChange::Amount { .. } => "31",
+                    StateChange::Value { .. } => "1",
+                };
+                self.paint(code, &change.to_string())
+            })
+            .collect();
+        format!("{}Δ {}", self.prefix(turn), changes.join(", "))
+    }
+}
+
+/// Sort each line of a block of output into a category. Scans and menus span several
+/// lines, so this walks the block rather than looking at lines one at a time.
+pub fn classify(output: &[Line], at_prompt: bool) -> Vec<(DisplayCategory, &str)> {
+    let frame = "-".repeat(33);
+    let mut in_sector_scan = false;
+    let mut in_map = false;
+    let mut lines = Vec::new();
+    
+    for (index, line) in output.iter().enumerate() {
+        let trimmed = line.trim();
+        let category = if at_prompt && index + 1 == output.len() {
+            DisplayCategory::Prompts
+        } else if trimmed.starts_with(&frame) {
+            in_sector_scan = !in_sector_scan;
+            DisplayCategory::Scans
+        } else if in_sector_scan {
+            DisplayCategory::Scans
+        } else if SCAN_HEADERS.iter().any(|header| trimmed.contains(header)) {
+            in_map = true;
+            DisplayCategory::Scans
+        } else if in_map && !trimmed.is_empty() {
+            DisplayCategory::Scans
+        } else if is_menu_line(line) {
+            DisplayCategory::Menus
+        } else if COMBAT_MARKERS.iter().any(|marker| trimmed.contains(marker)) {
+            DisplayCategory::Combat
+        } else {
+            DisplayCategory::Text
+        };
+        if trimmed.is_empty() {
+            in_map = false;
+        }
+        lines.push((category, &**line));
+    }
+    lines
+}
+
+/// How the status line is shown after each turn
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum StatusLineMode {
+    #[default]
+    Off,
+    /// One line per turn
+    Full,
+    /// A single line rewritten in place with a carriage return
+    Compact,
+}
+
+/// Prints the status line after each turn in the chosen mode
+#[derive(Debug, Default)]
+pub struct StatusLine {
+    mode: StatusLineMode,
+    /// A compact line is on screen without a newline after it
+    pending: bool,
+}
+
+impl StatusLine {
+    pub fn new(mode: StatusLineMode) -> Self {
+        Self { mode, pending: false }
+    }
+    
+    pub fn is_enabled(&self) -> bool {
+        self.mode != StatusLineMode::Off
+    }
+    
+    pub fn show(&mut self, line: &str) {
+        match self.mode {
+            StatusLineMode::Off => {}
+            StatusLineMode::Full => println!("{}", line),
+            StatusLineMode::Compact => {
+                print!("\r\x1b[2K{}", line);
+                let _ = std::io::stdout().flush();
+                self.pending = true;
+            }
+        }
+    }
+    
+    /// Wipe a compact line so other output can take its place
+    pub fn clear(&mut self) {
+        if self.pending {
+            print!("\r\x1b[2K");
+            self.pending = false;
+        }
+    }
+    
+    /// Leave the last compact line on screen and move past it
+    pub fn finish(&mut self) {
+        if self.pending {
+            println!();
+            self.pending = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::GameState;
+    
+    #[test]
+    fn test_filter_hides_scans() {
+        let frame = "-".repeat(33);
+        let output: Vec<Line> = [
+            frame.as_str(),
+            "        *       <*>     STARDATE           2300",
+            frame.as_str(),
+            "LONG RANGE SCAN FOR QUADRANT 1 , 1",
+            ": *** : 103 : 002 :",
+            "",
+            "  247 UNIT HIT ON ENTERPRISE FROM SECTOR 3 , 5",
+            "ENTER ONE OF THE FOLLOWING:",
+            "COMMAND?",
+        ]
+        .iter()
+        .map(|line| Line::from(*line))
+        .collect();
+        
+        let categories: Vec<DisplayCategory> = classify(&output, true).into_iter().map(|(category, _)| category).collect();
+        assert_eq!(categories[..5], [DisplayCategory::Scans; 5]);
+        assert_eq!(categories[5..], [DisplayCategory::Text, DisplayCategory::Combat, DisplayCategory::Menus, DisplayCategory::Prompts]);
+        
+        let filter = DisplayFilter::new(&[], &["scans".to_string(), "menus".to_string()]).unwrap();
+        let visible: Vec<&str> = filter.visible(&output, true).into_iter().map(|(_, line)| line).collect();
+        assert_eq!(visible, ["", "  247 UNIT HIT ON ENTERPRISE FROM SECTOR 3 , 5", "COMMAND?"]);
+        let combat = DisplayFilter::new(&["combat".to_string()], &[]).unwrap();
+        assert!(combat.shows(DisplayCategory::Combat) && !combat.shows(DisplayCategory::Commands));
+        assert!(DisplayFilter::new(&["maps".to_string()], &[]).is_err());
+    }
+    
+    #[test]
+    fn test_style() {
+        let plain = DisplayStyle::default();
+        assert_eq!(plain.line(3, DisplayCategory::Prompts, "COMMAND?"), "COMMAND?");
+        
+        let annotated = DisplayStyle { color: true, turn_numbers: true };
+        assert_eq!(annotated.command(12, "SRS"), "\x1b[2m[  12] \x1b[0m\x1b[1;33m🤖 TrekBot sends: SRS\x1b[0m");
+        assert_eq!(plain.decision(12, "no Klingons known, exploring"), "   ↳ no Klingons known, exploring");
+        assert!(annotated.condition_change(12, "GREEN", "RED").contains("\x1b[1;41m⚠ CONDITION GREEN → RED"));
+        
+        let mut previous = GameState::new();
+        previous.energy = Some(3000);
+        previous.shields = Some(0);
+        previous.condition = Some("GREEN".to_string());
+        let mut state = previous.clone();
+        state.energy = Some(2280);
+        state.shields = Some(500);
+        state.condition = Some("RED".to_string());
+        state.torpedoes = Some(10);
+        let changes = state.changes_since(&previous);
+        assert_eq!(plain.state_changes(3, &changes), "Δ energy -720, shields +500, torpedoes ?→10, condition GREEN→RED");
+        assert!(annotated.state_changes(3, &changes).contains("\x1b[31menergy -720\x1b[0m, \x1b[32mshields +500"));
+        assert_eq!(plain.state_changes(4, &state.changes_since(&state)), "Δ no change");
+    }
+}
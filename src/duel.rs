@@ -0,0 +1,106 @@
+use crate::player::GameResult;
+
+/// One seed played by both strategies
+#[derive(Debug, Clone)]
+pub struct DuelGame {
+    pub seed: u64,
+    pub first: (GameResult, usize),
+    pub second: (GameResult, usize),
+    /// Turn (from 1) at which the two sent different commands, None if they never did
+    pub divergence: Option<usize>,
+}
+
+impl DuelGame {
+    pub fn new(seed: u64, first: (GameResult, usize, &[String]), second: (GameResult, usize, &[String])) -> Self {
+        Self {
+            seed,
+            divergence: first_divergence(first.2, second.2),
+            first: (first.0, first.1),
+            second: (second.0, second.1),
+        }
+    }
+}
+
+/// First turn (from 1) at which two command sequences differ, including one game ending
+/// before the other
+pub fn first_divergence(first: &[String], second: &[String]) -> Option<usize> {
+    match first.iter().zip(second).position(|(a, b)| a != b) {
+        Some(index) => Some(index + 1),
+        None if first.len() != second.len() => Some(first.len().min(second.len()) + 1),
+        None => None,
+    }
+}
+
+/// Two strategies played on the same seeds, compared game by game
+pub struct DuelReport {
+    pub first: String,
+    pub second: String,
+    pub games: Vec<DuelGame>,
+}
+
+impl DuelReport {
+    /// Games counted by outcome: [[both won, only first won], [only second won, neither won]]
+    pub fn matrix(&self) -> [[usize; 2]; 2] {
+        let mut matrix = [[0; 2]; 2];
+        for game in &self.games {
+            let row = usize::from(game.first.0 != GameResult::Victory);
+            let column = usize::from(game.second.0 != GameResult::Victory);
+            // Rows are the first strategy's outcome, columns the second's
+            matrix[row][column] += 1;
+        }
+        matrix
+    }
+    
+    pub fn print(&self) {
+        println!("=== Duel: {} vs {} ===", self.first, self.second);
+        println!("{:>8}  {:<24} {:<24} {:>9}", "Seed", self.first, self.second, "Diverged");
+        for game in &self.games {
+            let outcome = |(result, turns): &(GameResult, usize)| format!("{} ({})", result.description(), turns);
+            let divergence = game.divergence.map_or("never".to_string(), |turn| format!("turn {}", turn));
+            println!("{:>8}  {:<24} {:<24} {:>9}", game.seed, outcome(&game.first), outcome(&game.second), divergence);
+        }
+        
+        let matrix = self.matrix();
+        println!();
+        println!("{:<16} {:>12} {:>12}", "", format!("{} won", self.second), format!("{} lost", self.second));
+        println!("{:<16} {:>12} {:>12}", format!("{} won", self.first), matrix[0][0], matrix[0][1]);
+        println!("{:<16} {:>12} {:>12}", format!("{} lost", self.first), matrix[1][0], matrix[1][1]);
+        
+        let turns: Vec<usize> = self.games.iter().filter_map(|game| game.divergence).collect();
+        if !turns.is_empty() {
+            println!("Commands first differed at turn {:.1} on average ({} of {} games, earliest turn {})",
+                     turns.iter().sum::<usize>() as f64 / turns.len() as f64, turns.len(), self.games.len(),
+                     turns.iter().min().unwrap());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn commands(list: &[&str]) -> Vec<String> {
+        list.iter().map(|command| command.to_string()).collect()
+    }
+    
+    #[test]
+    fn test_divergence_and_matrix() {
+        assert_eq!(first_divergence(&commands(&["SRS", "NAV"]), &commands(&["SRS", "TOR"])), Some(2));
+        assert_eq!(first_divergence(&commands(&["SRS"]), &commands(&["SRS", "LRS"])), Some(2));
+        assert_eq!(first_divergence(&commands(&["SRS"]), &commands(&["SRS"])), None);
+        
+        let won = GameResult::Victory;
+        let lost = GameResult::Destroyed;
+        let report = DuelReport {
+            first: "speedrun".to_string(),
+            second: "random".to_string(),
+            games: vec![
+                DuelGame::new(1, (won.clone(), 40, &commands(&["SRS"])), (lost.clone(), 90, &commands(&["NAV"]))),
+                DuelGame::new(2, (won.clone(), 35, &[]), (won, 80, &[])),
+                DuelGame::new(3, (lost.clone(), 20, &[]), (lost, 10, &[])),
+            ],
+        };
+        assert_eq!(report.matrix(), [[1, 1], [0, 1]]);
+        assert_eq!(report.games[0].divergence, Some(1));
+    }
+}
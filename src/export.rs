@@ -0,0 +1,396 @@
+use crate::combat::CombatSummary;
+use crate::json::Json;
+use crate::game::GameState;
+use crate::transcript::{Direction, Transcript};
+use crate::turn::TurnLog;
+#[cfg(feature = "parquet")]
+use crate::turn::Turn;
+use anyhow::Result;
+#[cfg(feature = "parquet")]
+use arrow_array::{ArrayRef, Float64Array, Int32Array, RecordBatch, StringArray, UInt64Array};
+#[cfg(feature = "parquet")]
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+#[cfg(feature = "parquet")]
+use std::sync::Arc;
+
+/// A recorded game as one standalone HTML page: a timeline scrubber, the opening and
+/// each turn's command, the output that answered it, the state after it, and the sector
+/// and long range scans rendered as grids, and a summary of the game's combat.
+/// Everything is inline so the file can be attached to a bug report as is.
+pub fn to_html(transcript: &Transcript) -> Result<String> {
+    let log = TurnLog::from_transcript(transcript)?;
+    
+    let step = |elapsed: f64, command: Option<&str>, reason: Option<&str>, output: &[String], state: &GameState| {
+        Json::object()
+            .with("t", (elapsed * 1000.0).round() / 1000.0)
+            .with("command", command)
+            .with("reason", reason)
+            .with("output", output.to_vec())
+            .with("status", state.status_line())
+            .with("sector", state.sector_map.clone())
+            .with("scan", state.galaxy_map.clone())
+    };
+    let mut data = vec![step(log.opening_elapsed, None, None, &log.opening, &log.opening_state)];
+    data.extend(log.turns.iter().map(|turn| {
+        step(turn.elapsed, Some(turn.command.as_str()), turn.reason.as_deref(), &turn.events, &turn.after)
+    }));
+    
+    let title = match transcript.metadata() {
+        Some(metadata) => format!("TrekBot game recorded {}", metadata.timestamp),
+        None => "TrekBot game".to_string(),
+    };
+    // A "</script>" inside the output must not end the data block early
+    let data = Json::Array(data).to_string().replace("</", "<\\/");
+    let combat = CombatSummary::from_turns(&log.turns).lines().join("\n")
+        .replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+    
+    Ok(HTML_TEMPLATE.replace("{{title}}", &title).replace("{{combat}}", &combat).replace("{{turns}}", &data))
+}
+
+/// Terminal size recorded in cast headers; the games are written for 80 columns
+const CAST_WIDTH: usize = 80;
+const CAST_HEIGHT: usize = 24;
+
+/// A recorded game as an asciinema v2 .cast file: the interpreter's output with its
+/// original timing, and each command both as an input event and echoed on screen the
+/// way a terminal would show it, since players only draw output events.
+pub fn to_cast(transcript: &Transcript) -> String {
+    let mut header = Json::object()
+        .with("version", 2usize)
+        .with("width", CAST_WIDTH)
+        .with("height", CAST_HEIGHT);
+    if let Some(metadata) = transcript.metadata() {
+        header = header.with("title", format!("TrekBot game recorded {}", metadata.timestamp));
+    }
+    
+    let mut cast = format!("{}\n", header);
+    let event = |time: f64, kind: &str, data: &str| {
+        format!("{}\n", Json::Array(vec![Json::from((time * 1e6).round() / 1e6), Json::from(kind), Json::from(data)]))
+    };
+    let entries = transcript.entries();
+    for (index, entry) in entries.iter().enumerate() {
+        match entry.direction {
+            Direction::Output => {
+                // The command is typed on the prompt's line
+                let answered = entries.get(index + 1).is_some_and(|next| next.direction == Direction::Input);
+                let end = if answered { " " } else { "\r\n" };
+                cast.push_str(&event(entry.elapsed, "o", &format!("{}{}", entry.text, end)));
+            }
+            Direction::Input => {
+                cast.push_str(&event(entry.elapsed, "i", &format!("{}\r", entry.text)));
+                cast.push_str(&event(entry.elapsed, "o", &format!("{}\r\n", entry.text)));
+            }
+        }
+    }
+    cast
+}
+
+/// Columns of the turn table, in order, before the state columns
+const TURN_COLUMNS: &[&str] = &[
+    "game", "turn", "elapsed", "think_time", "response_time", "prompt", "command", "reason",
+];
+
+/// State columns of the turn table, each once for the state before the turn
+/// (`energy_before`) and once for after it (`energy_after`)
+const STATE_COLUMNS: &[&str] = &[
+    "stardate", "energy", "shields", "torpedoes", "klingons", "quadrant", "sector", "condition",
+];
+
+/// Quote a CSV field if it holds a comma, quote or line break, e.g. coordinates "1,2"
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// The STATE_COLUMNS of one state; unknown values are empty
+fn state_fields(state: &GameState) -> [String; 8] {
+    let number = |value: Option<i32>| value.map_or(String::new(), |value| value.to_string());
+    let position = |value: Option<(i32, i32)>| value.map_or(String::new(), |(row, column)| format!("{},{}", row, column));
+    [
+        number(state.stardate),
+        number(state.energy),
+        number(state.shields),
+        number(state.torpedoes),
+        number(state.klingons_remaining),
+        position(state.current_quadrant),
+        position(state.current_sector),
+        state.condition.clone().unwrap_or_default(),
+    ]
+}
+
+/// Every turn of the games as a CSV table for analysis tools, one row per Turn: the
+/// game it belongs to, its timing, the prompt and the command that answered it, and
+/// the state before and after. Unknown values are empty.
+pub fn to_turn_csv(games: &[(String, Transcript)]) -> Result<String> {
+    let mut header: Vec<String> = TURN_COLUMNS.iter().map(|column| column.to_string()).collect();
+    for side in ["before", "after"] {
+        header.extend(STATE_COLUMNS.iter().map(|column| format!("{}_{}", column, side)));
+    }
+    let mut csv = header.join(",") + "\n";
+    for (game, transcript) in games {
+        for turn in TurnLog::from_transcript(transcript)?.turns {
+            let mut row = vec![
+                game.clone(),
+                turn.index.to_string(),
+                format!("{:.3}", turn.elapsed),
+                format!("{:.3}", turn.think_time),
+                format!("{:.3}", turn.response_time),
+                format!("{:?}", turn.prompt.kind),
+                turn.command,
+                turn.reason.unwrap_or_default(),
+            ];
+            row.extend(state_fields(&turn.before));
+            row.extend(state_fields(&turn.after));
+            let row: Vec<String> = row.iter().map(|field| csv_field(field)).collect();
+            csv.push_str(&(row.join(",") + "\n"));
+        }
+    }
+    Ok(csv)
+}
+
+/// The turn table's columns with their types: numbers stay numbers, and unknown values
+/// and missing reasons are null
+#[cfg(feature = "parquet")]
+fn turn_schema() -> Schema {
+    let mut fields = vec![
+        Field::new("game", DataType::Utf8, false),
+        Field::new("turn", DataType::UInt64, false),
+        Field::new("elapsed", DataType::Float64, false),
+        Field::new("think_time", DataType::Float64, false),
+        Field::new("response_time", DataType::Float64, false),
+        Field::new("prompt", DataType::Utf8, false),
+        Field::new("command", DataType::Utf8, false),
+        Field::new("reason", DataType::Utf8, true),
+    ];
+    for side in ["before", "after"] {
+        fields.extend(STATE_COLUMNS.iter().map(|column| {
+            let kind = match *column {
+                "quadrant" | "sector" | "condition" => DataType::Utf8,
+                _ => DataType::Int32,
+            };
+            Field::new(format!("{}_{}", column, side), kind, true)
+        }));
+    }
+    Schema::new(fields)
+}
+
+/// One game's turns as rows of the turn table
+#[cfg(feature = "parquet")]
+fn turn_batch(schema: &SchemaRef, game: &str, turns: &[Turn]) -> Result<RecordBatch> {
+    let mut columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from(vec![game; turns.len()])),
+        Arc::new(UInt64Array::from_iter_values(turns.iter().map(|turn| turn.index as u64))),
+        Arc::new(Float64Array::from_iter_values(turns.iter().map(|turn| turn.elapsed))),
+        Arc::new(Float64Array::from_iter_values(turns.iter().map(|turn| turn.think_time))),
+        Arc::new(Float64Array::from_iter_values(turns.iter().map(|turn| turn.response_time))),
+        Arc::new(StringArray::from_iter_values(turns.iter().map(|turn| format!("{:?}", turn.prompt.kind)))),
+        Arc::new(StringArray::from_iter_values(turns.iter().map(|turn| turn.command.as_str()))),
+        Arc::new(turns.iter().map(|turn| turn.reason.as_deref()).collect::<StringArray>()),
+    ];
+    let sides: [fn(&Turn) -> &GameState; 2] = [|turn| &turn.before, |turn| &turn.after];
+    for side in sides {
+        let number = |value: fn(&GameState) -> Option<i32>| -> ArrayRef {
+            Arc::new(turns.iter().map(|turn| value(side(turn))).collect::<Int32Array>())
+        };
+        let position = |value: fn(&GameState) -> Option<(i32, i32)>| -> ArrayRef {
+            let text = |(row, column): (i32, i32)| format!("{},{}", row, column);
+            Arc::new(turns.iter().map(|turn| value(side(turn)).map(text)).collect::<StringArray>())
+        };
+        columns.extend([
+            number(|state| state.stardate),
+            number(|state| state.energy),
+            number(|state| state.shields),
+            number(|state| state.torpedoes),
+            number(|state| state.klingons_remaining),
+            position(|state| state.current_quadrant),
+            position(|state| state.current_sector),
+            Arc::new(turns.iter().map(|turn| side(turn).condition.as_deref()).collect::<StringArray>()),
+        ]);
+    }
+    Ok(RecordBatch::try_new(schema.clone(), columns)?)
+}
+
+/// Write the turn table of the games as a Parquet file, which DuckDB, Polars and Spark
+/// read far faster than CSV or JSON lines. Each game is written as its own row group
+/// as soon as it is loaded, so an export of many games never holds them all in memory.
+#[cfg(feature = "parquet")]
+pub fn write_parquet(games: impl Iterator<Item = Result<(String, Transcript)>>, path: &str) -> Result<()> {
+    use anyhow::Context;
+    use parquet::arrow::ArrowWriter;
+    use parquet::basic::Compression;
+    use parquet::file::properties::WriterProperties;
+    
+    let schema = Arc::new(turn_schema());
+    let file = std::fs::File::create(path).with_context(|| format!("Failed to create {}", path))?;
+    let properties = WriterProperties::builder().set_compression(Compression::SNAPPY).build();
+    let mut writer = ArrowWriter::try_new(file, schema.clone(), Some(properties))?;
+    for game in games {
+        let (game, transcript) = game?;
+        let turns = TurnLog::from_transcript(&transcript)?.turns;
+        writer.write(&turn_batch(&schema, &game, &turns)?)?;
+        writer.flush()?;
+    }
+    writer.close().with_context(|| format!("Failed to write {}", path))?;
+    Ok(())
+}
+
+/// Parquet needs the `parquet` feature; without it, exporting to Parquet fails up front
+#[cfg(not(feature = "parquet"))]
+pub fn write_parquet(_games: impl Iterator<Item = Result<(String, Transcript)>>, path: &str) -> Result<()> {
+    anyhow::bail!("Can't write {}: this TrekBot was built without Parquet support (build it with --features parquet)", path)
+}
+
+const HTML_TEMPLATE: &str = r##"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{{title}}</title>
+<style>
+body { font-family: sans-serif; margin: 1em 2em; background: #111; color: #ddd; }
+pre { background: #000; color: #9f9; padding: 0.5em; white-space: pre-wrap; }
+#timeline { width: 100%; }
+.command { color: #ff0; font-weight: bold; }
+.maps { display: flex; gap: 2em; }
+table.grid { border-collapse: collapse; font-family: monospace; }
+table.grid td { border: 1px solid #444; width: 2.2em; height: 1.4em; text-align: center; }
+td.ship { background: #046; } td.klingon { background: #600; } td.base { background: #060; }
+#log { max-height: 20em; overflow-y: auto; font-family: monospace; font-size: 0.9em; }
+#log div { cursor: pointer; } #log div.current { background: #333; }
+</style>
+</head>
+<body>
+<h1>{{title}}</h1>
+<input type="range" id="timeline" min="0" value="0">
+<p><button id="prev">&larr;</button> Turn <span id="turn"></span> of <span id="count"></span>
+at <span id="time"></span>s <button id="next">&rarr;</button></p>
+<p id="status"></p>
+<div class="maps"><div><h3>Sector</h3><div id="sector">no scan yet</div></div>
+<div><h3>Long range scan</h3><div id="scan">no scan yet</div></div></div>
+<p>Sent: <span class="command" id="command"></span> <span id="reason"></span></p>
+<h3>Output</h3>
+<pre id="output"></pre>
+<h3>Combat</h3>
+<pre>{{combat}}</pre>
+<h3>Transcript</h3>
+<div id="log"></div>
+<script>
+const turns = {{turns}};
+const $ = id => document.getElementById(id);
+function grid(rows) {
+  if (!rows) return "no scan yet";
+  const cls = c => c === "<*>" ? "ship" : c === "+K+" ? "klingon" : c === ">!<" ? "base" : "";
+  return "<table class='grid'>" + rows.map(r => "<tr>" + r.map(c =>
+    "<td class='" + cls(c) + "'>" + c.replace(/</g, "&lt;").replace(/>/g, "&gt;") + "</td>").join("") + "</tr>").join("") + "</table>";
+}
+function show(i) {
+  const turn = turns[i];
+  $("timeline").value = i;
+  $("turn").textContent = i;
+  $("time").textContent = turn.t.toFixed(2);
+  $("status").textContent = turn.status;
+  $("output").textContent = turn.output.join("\n");
+  $("command").textContent = turn.command === null ? "(opening)" : turn.command === "" ? "[ENTER]" : turn.command;
+  $("reason").textContent = turn.reason === null ? "" : "(" + turn.reason + ")";
+  $("sector").innerHTML = grid(turn.sector);
+  $("scan").innerHTML = grid(turn.scan);
+  document.querySelectorAll("#log div").forEach((d, j) => d.className = j === i ? "current" : "");
+}
+$("count").textContent = turns.length - 1;
+$("timeline").max = Math.max(turns.length - 1, 0);
+turns.forEach((turn, i) => {
+  const line = document.createElement("div");
+  line.textContent = i + ": " + (turn.command ?? "(opening)") + "  ->  " + (turn.output[turn.output.length - 1] || "");
+  line.onclick = () => show(i);
+  $("log").appendChild(line);
+});
+$("timeline").oninput = e => show(+e.target.value);
+$("prev").onclick = () => show(Math.max(+$("timeline").value - 1, 0));
+$("next").onclick = () => show(Math.min(+$("timeline").value + 1, turns.length - 1));
+document.onkeydown = e => { if (e.key === "ArrowLeft") $("prev").click(); if (e.key === "ArrowRight") $("next").click(); };
+if (turns.length) show(0);
+</script>
+</body>
+</html>
+"##;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_html_is_self_contained() {
+        let mut transcript = Transcript::new();
+        transcript.record_output(&["</script><b>".to_string(), "COMMAND?".to_string()]);
+        transcript.record_input("SRS");
+        
+        let html = to_html(&transcript).unwrap();
+        assert!(html.contains("const turns = [{"));
+        assert!(html.contains("<\\/script>"));
+        assert_eq!(html.matches("</script>").count(), 1);
+        assert!(!html.contains("{{"));
+    }
+    
+    #[test]
+    fn test_cast_events() {
+        let mut transcript = Transcript::new();
+        transcript.record_output(&["ENERGY 3000".to_string(), "COMMAND?".to_string()]);
+        transcript.record_input("SRS");
+        
+        let cast = to_cast(&transcript);
+        let lines: Vec<&str> = cast.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(Json::parse(lines[0]).unwrap().get("version").and_then(Json::as_usize), Some(2));
+        let event = |line: &str| {
+            let event = Json::parse(line).unwrap();
+            let fields = event.as_array().unwrap();
+            (fields[1].as_str().unwrap().to_string(), fields[2].as_str().unwrap().to_string())
+        };
+        assert_eq!(event(lines[1]), ("o".to_string(), "ENERGY 3000\r\n".to_string()));
+        assert_eq!(event(lines[2]), ("o".to_string(), "COMMAND? ".to_string()));
+        assert_eq!(event(lines[3]), ("i".to_string(), "SRS\r".to_string()));
+        assert_eq!(event(lines[4]), ("o".to_string(), "SRS\r\n".to_string()));
+    }
+    
+    #[test]
+    fn test_turn_table() {
+        let mut transcript = Transcript::new();
+        transcript.record_output(&["COMMAND?".to_string()]);
+        transcript.record_input("NAV");
+        transcript.record_output(&["COORDINATES?".to_string()]);
+        transcript.record_input("1,2");
+        
+        let csv = to_turn_csv(&[("game-1".to_string(), transcript)]).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].split(',').count(), TURN_COLUMNS.len() + 2 * STATE_COLUMNS.len());
+        assert!(lines[1].starts_with("game-1,1,"));
+        assert!(lines[1].contains(",Command,NAV,"));
+        assert!(lines[2].contains(",\"1,2\","));
+        assert_eq!(csv_field("say \"aye\""), "\"say \"\"aye\"\"\"");
+    }
+    
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn test_parquet_row_groups() {
+        use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+        
+        let mut transcript = Transcript::new();
+        transcript.record_output(&["COMMAND?".to_string()]);
+        transcript.record_input("NAV");
+        transcript.record_output(&["COORDINATES?".to_string()]);
+        transcript.record_input("1,2");
+        let games = ["game-1", "game-2"].map(|game| Ok((game.to_string(), transcript.clone())));
+        
+        let path = std::env::temp_dir().join(format!("trekbot-turns-{}.parquet", std::process::id()));
+        write_parquet(games.into_iter(), &path.to_string_lossy()).unwrap();
+        let reader = ParquetRecordBatchReaderBuilder::try_new(std::fs::File::open(&path).unwrap()).unwrap();
+        assert_eq!(reader.metadata().num_row_groups(), 2);
+        assert_eq!(reader.metadata().file_metadata().num_rows(), 4);
+        assert_eq!(reader.schema().fields().len(), TURN_COLUMNS.len() + 2 * STATE_COLUMNS.len());
+        assert_eq!(reader.schema().field_with_name("energy_after").unwrap().data_type(), &DataType::Int32);
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,102 @@
+use crate::game::PromptKind;
+use crate::json::Json;
+use crate::player::GameResult;
+use std::collections::BTreeMap;
+
+/// Commands listed under each prompt kind
+const TOP_COMMANDS: usize = 5;
+
+/// The last command sent before a game ended, and the kind of prompt it answered
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrecedingInput {
+    pub prompt: PromptKind,
+    pub command: String,
+}
+
+impl PrecedingInput {
+    pub fn to_json(&self) -> Json {
+        Json::object()
+            .with("prompt", format!("{:?}", self.prompt))
+            .with("command", self.command.as_str())
+    }
+}
+
+/// Whether a game ended with the interpreter failing rather than the game being lost
+pub fn is_failure(result: &GameResult) -> bool {
+    matches!(result, GameResult::BasicError | GameResult::InterpreterCrashed | GameResult::Hung)
+}
+
+/// Interpreter failures counted by the input that preceded them. Failures piling up after
+/// one kind of prompt point at the BASIC feature an interpreter gets wrong, e.g. INPUT of
+/// a number after the course prompt.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FailureStats {
+    /// Count of failures after each command, by prompt kind
+    failures: BTreeMap<String, BTreeMap<String, usize>>,
+    /// Failures before any command was sent
+    before_input: usize,
+}
+
+impl FailureStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Count the game if it ended in an interpreter failure
+    pub fn record(&mut self, result: &GameResult, preceding: Option<&PrecedingInput>) {
+        if !is_failure(result) {
+            return;
+        }
+        match preceding {
+            Some(input) => {
+                let commands = self.failures.entry(format!("{:?}", input.prompt)).or_default();
+                *commands.entry(input.command.trim().to_string()).or_insert(0) += 1;
+            }
+            None => self.before_input += 1,
+        }
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.failures.is_empty() && self.before_input == 0
+    }
+    
+    pub fn print_summary(&self) {
+        println!("=== Failures by preceding input ===");
+        let mut kinds: Vec<(&String, usize)> = self.failures.iter()
+            .map(|(kind, commands)| (kind, commands.values().sum()))
+            .collect();
+        kinds.sort_by_key(|&(_, total)| std::cmp::Reverse(total));
+        for (kind, total) in kinds {
+            println!("{} ({} failures)", kind, total);
+            let mut ranked: Vec<(&String, &usize)> = self.failures[kind].iter().collect();
+            ranked.sort_by(|a, b| b.1.cmp(a.1));
+            for (command, count) in ranked.into_iter().take(TOP_COMMANDS) {
+                let command = if command.is_empty() { "[ENTER]" } else { command.as_str() };
+                println!("  {:<17} {:>5}", command, count);
+            }
+        }
+        if self.before_input > 0 {
+            println!("Before any input ({} failures)", self.before_input);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_failures_by_input() {
+        let course = |command: &str| PrecedingInput { prompt: PromptKind::Course, command: command.to_string() };
+        let mut failures = FailureStats::new();
+        failures.record(&GameResult::Victory, Some(&course("1")));
+        assert!(failures.is_empty());
+        
+        failures.record(&GameResult::BasicError, Some(&course("9.5")));
+        failures.record(&GameResult::Hung, Some(&course("9.5 ")));
+        failures.record(&GameResult::InterpreterCrashed, None);
+        assert_eq!(failures.failures["Course"]["9.5"], 2);
+        assert_eq!(failures.before_input, 1);
+        assert_eq!(course("9.5").to_json().to_string(), r#"{"prompt":"Course","command":"9.5"}"#);
+    }
+}
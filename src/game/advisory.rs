@@ -0,0 +1,161 @@
+use super::{GameState, Line, PromptContext, PromptKind};
+
+/// What an advisory is warning about
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdvisoryKind {
+    /// The ship is in trouble, e.g. under attack with its shields down
+    Danger,
+    /// The strategy sent input the game should refuse
+    InvalidInput,
+    /// The game went along with input it should have refused, a hint that the
+    /// interpreter doesn't run the program's validation as written
+    AcceptedInvalidInput,
+}
+
+/// A warning about the game as TrekBot sees it, for debugging strategies and spotting
+/// interpreter bugs
+#[derive(Debug, Clone, PartialEq)]
+pub struct Advisory {
+    pub kind: AdvisoryKind,
+    pub message: String,
+}
+
+impl AdvisoryKind {
+    /// How the kind is named in warnings
+    pub fn label(&self) -> &'static str {
+        match self {
+            AdvisoryKind::Danger => "danger",
+            AdvisoryKind::InvalidInput => "invalid input",
+            AdvisoryKind::AcceptedInvalidInput => "possible interpreter validation bug",
+        }
+    }
+}
+
+impl Advisory {
+    fn new(kind: AdvisoryKind, message: String) -> Self {
+        Self { kind, message }
+    }
+}
+
+/// Told about every advisory during play, e.g. to count the games with interpreter
+/// validation bugs. Any `FnMut(turn, &Advisory)` closure is an observer.
+pub trait AdvisoryObserver {
+    fn advise(&mut self, turn: usize, advisory: &Advisory);
+}
+
+impl<F: FnMut(usize, &Advisory)> AdvisoryObserver for F {
+    fn advise(&mut self, turn: usize, advisory: &Advisory) {
+        self(turn, advisory)
+    }
+}
+
+/// Input the game should have refused, checked against its reply
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Refusable {
+    /// More shield units than the energy available
+    Shields { requested: i32, available: i32 },
+    /// PHA with phaser control damaged
+    Phasers,
+}
+
+/// Watches the answers sent and the state parsed from the game for danger and for
+/// input the game accepted when it shouldn't have
+#[derive(Debug, Clone, Default)]
+pub struct AdvisoryEngine {
+    /// The ship was in RED condition with no shields at the last check, so that is
+    /// reported once each time it happens rather than every turn
+    unshielded: bool,
+    /// Invalid input sent with the last command, whose reply is still to be checked
+    refusable: Option<Refusable>,
+}
+
+impl AdvisoryEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Check an answer before it is sent to the game
+    pub fn answered(&mut self, prompt: &PromptContext, state: &GameState, command: &str) -> Vec<Advisory> {
+        let command = command.trim();
+        self.refusable = match prompt.kind {
+            PromptKind::Command if command.to_uppercase().starts_with("PHA") && state.is_system_damaged("PHASER CONTROL") => {
+                Some(Refusable::Phasers)
+            }
+            PromptKind::ShieldUnits => match (command.parse::<f64>(), prompt.energy_available) {
+                (Ok(requested), Some(available)) if requested > available as f64 => {
+                    Some(Refusable::Shields { requested: requested as i32, available })
+                }
+                _ => None,
+            },
+            _ => None,
+        };
+        let advisory = match self.refusable {
+            Some(Refusable::Phasers) => format!("firing phasers ({}) with phaser control damaged", command),
+            Some(Refusable::Shields { requested, available }) => {
+                format!("setting shields to {} with only {} energy available", requested, available)
+            }
+            None => return Vec::new(),
+        };
+        vec![Advisory::new(AdvisoryKind::InvalidInput, advisory)]
+    }
+    
+    /// Check a batch of output and the state it left the game in
+    pub fn check(&mut self, output: &[Line], state: &GameState) -> Vec<Advisory> {
+        let mut advisories = Vec::new();
+        let printed = |text: &str| output.iter().any(|line| line.contains(text));
+        match self.refusable.take() {
+            // The program prints "PHASERS INOPERATIVE" and goes back to the command prompt
+            Some(Refusable::Phasers) if state.prompt_context.as_ref().is_some_and(|prompt| prompt.kind == PromptKind::PhaserUnits) => {
+                advisories.push(Advisory::new(AdvisoryKind::AcceptedInvalidInput,
+                    "the game asked how many units to fire with phaser control damaged".to_string()));
+            }
+            // ... and "THIS IS NOT THE FEDERATION TREASURY" with the shields unchanged
+            Some(Refusable::Shields { requested, available }) if printed("SHIELDS NOW AT") => {
+                advisories.push(Advisory::new(AdvisoryKind::AcceptedInvalidInput,
+                    format!("the game raised the shields to {} with only {} energy available", requested, available)));
+            }
+            _ => {}
+        }
+        
+        let unshielded = state.is_in_combat() && state.shields == Some(0);
+        if unshielded && !self.unshielded {
+            advisories.push(Advisory::new(AdvisoryKind::Danger, "condition RED with the shields down".to_string()));
+        }
+        self.unshielded = unshielded;
+        advisories
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::share_lines;
+    
+    fn prompt(kind: PromptKind) -> PromptContext {
+        PromptContext { kind, energy_available: Some(1000), ..PromptContext::default() }
+    }
+    
+    #[test]
+    fn test_advisories() {
+        let mut engine = AdvisoryEngine::new();
+        let mut state = GameState::new();
+        state.update(&["CONDITION           *RED*".to_string(), "SHIELDS             0".to_string()]).unwrap();
+        let kinds = |advisories: Vec<Advisory>| advisories.iter().map(|advisory| advisory.kind).collect::<Vec<_>>();
+        assert_eq!(kinds(engine.check(&[], &state)), [AdvisoryKind::Danger]);
+        assert_eq!(kinds(engine.check(&[], &state)), []);
+        
+        assert_eq!(kinds(engine.answered(&prompt(PromptKind::ShieldUnits), &state, "500")), []);
+        let too_many = engine.answered(&prompt(PromptKind::ShieldUnits), &state, "2000");
+        assert_eq!(too_many[0].message, "setting shields to 2000 with only 1000 energy available");
+        let refused = share_lines(vec!["SHIELD CONTROL REPORTS  'THIS IS NOT THE FEDERATION TREASURY.'".to_string()]);
+        assert_eq!(kinds(engine.check(&refused, &state)), []);
+        engine.answered(&prompt(PromptKind::ShieldUnits), &state, "2000");
+        let accepted = share_lines(vec!["  'SHIELDS NOW AT 2000 UNITS PER YOUR COMMAND.'".to_string()]);
+        assert_eq!(kinds(engine.check(&accepted, &state)), [AdvisoryKind::AcceptedInvalidInput]);
+        
+        state.damage_report.insert("PHASER CONTROL".to_string(), -1.0);
+        assert_eq!(kinds(engine.answered(&prompt(PromptKind::Command), &state, "PHA")), [AdvisoryKind::InvalidInput]);
+        state.prompt_context = Some(prompt(PromptKind::PhaserUnits));
+        assert_eq!(kinds(engine.check(&[], &state)), [AdvisoryKind::AcceptedInvalidInput]);
+    }
+}
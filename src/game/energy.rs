@@ -0,0 +1,112 @@
+use super::{Line, PromptKind};
+
+/// Energy (ship and shields together) after docking, E0 in the program
+const DOCKED_ENERGY: f64 = 3000.0;
+
+/// Expected total energy (energy plus shields), kept from what the bot answered and what
+/// happened in the output, following the program's arithmetic:
+/// - shield transfers move energy without changing the total
+/// - phasers cost the units fired, torpedoes 2, warp moves INT(warp * 8 + .5) + 10
+/// - every hit on the Enterprise comes off the shields
+/// - docking restores 3000
+///
+/// Each "TOTAL ENERGY" reading of a short range scan is checked against it.
+#[derive(Debug, Clone, Default)]
+pub struct EnergyModel {
+    /// Unknown until the first reading
+    expected: Option<f64>,
+    /// The last answer, if it was to a prompt that spends energy
+    answer: Option<(PromptKind, f64)>,
+}
+
+impl EnergyModel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Note an answer sent to the game; its cost is applied with the output that follows
+    pub fn answered(&mut self, kind: PromptKind, answer: &str) {
+        self.answer = match kind {
+            PromptKind::PhaserUnits | PromptKind::WarpFactor => answer.trim().parse().ok().map(|value| (kind, value)),
+            _ => None,
+        };
+    }
+    
+    /// Follow a turn's output, describing each reading that differs from the expected
+    /// energy by more than rounding. The model continues from the game's reading.
+    pub fn check(&mut self, output: &[Line]) -> Vec<String> {
+        let mut mismatches = Vec::new();
+        if let (Some(expected), Some(cost)) = (&mut self.expected, self.answer.take().and_then(|answer| answer_cost(answer, output))) {
+            *expected = (*expected - cost).max(0.0);
+        }
+        
+        for line in output {
+            if line.contains("SHIELDS DROPPED FOR DOCKING PURPOSES") {
+                self.expected = Some(DOCKED_ENERGY);
+            } else if line.contains("TORPEDO TRACK:") {
+                self.spend(2.0);
+            } else if let Some(hit) = line.split_once("UNIT HIT ON ENTERPRISE").and_then(|(hit, _)| hit.trim().parse::<f64>().ok()) {
+                self.spend(hit);
+            } else if let Some(reported) = line.split_once("TOTAL ENERGY").and_then(|(_, value)| value.trim().parse::<f64>().ok()) {
+                if let Some(expected) = self.expected {
+                    // The scan shows INT(E+S)
+                    if (expected.floor() - reported).abs() > 1.0 {
+                        mismatches.push(format!("total energy is {}, expected {}", reported, expected));
+                    }
+                }
+                self.expected = Some(reported);
+            }
+        }
+        mismatches
+    }
+    
+    fn spend(&mut self, energy: f64) {
+        if let Some(expected) = &mut self.expected {
+            *expected = (*expected - energy).max(0.0);
+        }
+    }
+}
+
+/// Energy an answer cost, given the output it produced; nothing if the game refused it
+fn answer_cost((kind, value): (PromptKind, f64), output: &[Line]) -> Option<f64> {
+    let refused = |messages: &[&str]| output.iter().any(|line| messages.iter().any(|message| line.contains(message)));
+    match kind {
+        // Too many units and the game asks again
+        PromptKind::PhaserUnits if value > 0.0 && !refused(&["ENERGY AVAILABLE ="]) => Some(value),
+        PromptKind::WarpFactor if value > 0.0 && value <= 8.0
+            && !refused(&["INSUFFICIENT ENERGY", "WARP ENGINES ARE DAMAGED", "ENGINES WON'T TAKE"]) => {
+            Some((value * 8.0 + 0.5).floor() + 10.0)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn lines(lines: &[&str]) -> Vec<Line> {
+        lines.iter().map(|line| Line::from(*line)).collect()
+    }
+    
+    #[test]
+    fn test_energy_model() {
+        let mut model = EnergyModel::new();
+        assert!(model.check(&lines(&["        TOTAL ENERGY       3000"])).is_empty());
+        
+        // Warp 1 costs 18, and a Klingon hits for 120
+        model.answered(PromptKind::WarpFactor, "1");
+        let output = lines(&[" 120 UNIT HIT ON ENTERPRISE FROM SECTOR 3 , 4", "        TOTAL ENERGY       2862"]);
+        assert!(model.check(&output).is_empty());
+        
+        // Refused phaser fire costs nothing, the torpedo costs 2
+        model.answered(PromptKind::PhaserUnits, "9000");
+        assert!(model.check(&lines(&["ENERGY AVAILABLE = 2000 UNITS"])).is_empty());
+        model.answered(PromptKind::TorpedoCourse, "3");
+        assert!(model.check(&lines(&["TORPEDO TRACK:", "        TOTAL ENERGY       2860"])).is_empty());
+        
+        model.answered(PromptKind::PhaserUnits, "100");
+        assert_eq!(model.check(&lines(&["        TOTAL ENERGY       2860"])), ["total energy is 2860, expected 2760"]);
+        assert!(model.check(&lines(&["SHIELDS DROPPED FOR DOCKING PURPOSES", "        TOTAL ENERGY       3000"])).is_empty());
+    }
+}
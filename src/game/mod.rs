@@ -1,5 +1,22 @@
 pub mod state;
+pub mod advisory;
 pub mod parser;
+pub mod prompt;
+pub mod energy;
+pub mod profile;
 
 pub use state::*;
-pub use parser::*; 
\ No newline at end of file
+pub use advisory::*;
+pub use parser::*;
+pub use prompt::*;
+pub use energy::*;
+pub use profile::*;
+
+/// One line of interpreter output. A turn's lines are read once and then shared, not
+/// copied, by the Player, GameState, PromptContext and strategies.
+pub type Line = std::sync::Arc<str>;
+
+/// Turn freshly read output into shared lines
+pub fn share_lines(output: Vec<String>) -> Vec<Line> {
+    output.into_iter().map(Line::from).collect()
+}
\ No newline at end of file
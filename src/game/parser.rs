@@ -1,3 +1,4 @@
+use super::Line;
 use anyhow::Result;
 use regex::Regex;
 
@@ -42,7 +43,7 @@ pub fn parse_coordinates_prompt(line: &str) -> bool {
 }
 
 /// Parse damage control report sections
-pub fn parse_damage_control_report(lines: &[String]) -> Vec<(String, f32)> {
+pub fn parse_damage_control_report(lines: &[Line]) -> Vec<(String, f32)> {
     let mut damage_reports = Vec::new();
     let system_regex = Regex::new(r"([A-Z\s]+)\s+([\d\.-]+)").unwrap();
     
@@ -64,118 +65,292 @@ pub fn parse_damage_control_report(lines: &[String]) -> Vec<(String, f32)> {
     damage_reports
 }
 
-/// Parse short range sensor scan to extract sector map
-pub fn parse_short_range_scan(lines: &[String]) -> Option<Vec<Vec<String>>> {
-    let mut sector_map = Vec::new();
-    let mut in_scan = false;
+/// A line of only dashes, at least `min` of them: the frame around the scans.
+/// Interpreters pad it differently, so the exact length isn't checked.
+fn is_frame(line: &str, min: usize) -> bool {
+    let line = line.trim();
+    line.len() >= min && line.chars().all(|c| c == '-')
+}
+
+/// Parse long range sensor scan to extract galaxy map: three rows of three quadrants,
+/// each "***" (outside the galaxy) or three digits. Only the tokens between the colons
+/// count, however the interpreter spaces them.
+pub fn parse_long_range_scan(lines: &[Line]) -> Option<Vec<Vec<String>>> {
+    let start = lines.iter().position(|line| line.contains("LONG RANGE SCAN"))?;
+    let mut galaxy_map = Vec::new();
     
-    for line in lines {
-        if line.contains("SHORT RANGE SENSORS") {
-            in_scan = true;
+    for line in &lines[start + 1..] {
+        if is_frame(line, 3) {
             continue;
         }
-        
-        if in_scan {
-            // Look for grid lines (containing sector positions)
-            if line.contains("<*>") || line.contains("+K+") || line.contains(">!<") || line.contains(" * ") {
-                let mut row = Vec::new();
-                // Split the line into 3-character sectors
-                let mut chars = line.chars().collect::<Vec<_>>();
-                
-                // Pad to ensure we have complete sectors
-                while chars.len() % 3 != 0 {
-                    chars.push(' ');
-                }
-                
-                for chunk in chars.chunks(3) {
-                    let sector: String = chunk.iter().collect();
-                    row.push(sector);
-                }
-                
-                if !row.is_empty() {
-                    sector_map.push(row);
-                }
-            } else if line.trim().is_empty() && !sector_map.is_empty() {
-                // End of scan
-                break;
-            }
+        let quadrants: Vec<String> = line
+            .split(':')
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string())
+            .collect();
+        let is_row = line.contains(':') && quadrants.len() == 3
+            && quadrants.iter().all(|q| q == "***" || (q.len() == 3 && q.chars().all(|c| c.is_ascii_digit())));
+        if !is_row {
+            break;
         }
+        galaxy_map.push(quadrants);
     }
     
-    if sector_map.is_empty() {
+    if galaxy_map.is_empty() {
         None
     } else {
-        Some(sector_map)
+        Some(galaxy_map)
     }
 }
 
-/// Parse long range sensor scan to extract galaxy map
-pub fn parse_long_range_scan(lines: &[String]) -> Option<Vec<Vec<String>>> {
-    let mut galaxy_map = Vec::new();
-    let mut in_scan = false;
+/// What can occupy a sector in a short range scan
+const SECTOR_GLYPHS: &[&str] = &["<*>", "+K+", ">!<", "*"];
+
+/// The glyphs at the start of a short range scan row with the character offset of each
+/// one's middle, stopping at the status field ("STARDATE", "CONDITION", ...)
+fn sector_glyphs(row: &str) -> Vec<(usize, &'static str)> {
+    let mut glyphs = Vec::new();
+    let mut offset = 0;
+    while offset < row.len() {
+        let rest = &row[offset..];
+        if rest.starts_with(char::is_whitespace) {
+            offset += rest.chars().next().map_or(1, char::len_utf8);
+        } else if let Some(glyph) = SECTOR_GLYPHS.iter().find(|glyph| rest.starts_with(**glyph)) {
+            glyphs.push((offset + glyph.len() / 2, *glyph));
+            offset += glyph.len();
+        } else {
+            break;
+        }
+    }
+    glyphs
+}
+
+/// Sector of each glyph from its position: BASIC prints every sector as a space and
+/// three characters, but some interpreters drop the separating spaces (three-character
+/// sectors). When neither layout fits, e.g. because runs of spaces were collapsed, the
+/// glyphs keep their order and are placed as close to their position as possible.
+fn sector_columns(glyphs: &[(usize, &str)]) -> Vec<usize> {
+    for (width, middle) in [(4, 2), (3, 1)] {
+        let columns: Vec<usize> = glyphs.iter().map(|(offset, _)| offset / width).collect();
+        let aligned = glyphs.iter().all(|(offset, _)| offset % width == middle);
+        if aligned && columns.iter().all(|column| *column < 8) && columns.windows(2).all(|pair| pair[0] < pair[1]) {
+            return columns;
+        }
+    }
     
-    for line in lines {
-        if line.contains("LONG RANGE SCAN") {
-            in_scan = true;
+    let mut columns: Vec<usize> = Vec::new();
+    for (index, (offset, _)) in glyphs.iter().enumerate() {
+        let earliest = columns.last().map_or(0, |column| column + 1);
+        let latest = 8usize.saturating_sub(glyphs.len() - index);
+        columns.push((offset / 4).max(earliest).min(latest));
+    }
+    columns
+}
+
+/// Parse a short range scan into 8 rows of 8 sectors ("<*>", "+K+", ">!<", "*" or "").
+/// The scan is framed by lines of dashes, and each row is the sectors followed by a
+/// status field. Sectors are read as glyphs rather than fixed-width cells, since
+/// interpreters pad the rows differently.
+pub fn parse_short_range_scan(lines: &[Line]) -> Option<Vec<Vec<String>>> {
+    let start = lines.iter().position(|line| is_frame(line, 24))?;
+    let rows = lines.get(start + 1..start + 9)?;
+    if !lines.get(start + 9).is_some_and(|line| is_frame(line, 24)) {
+        return None;
+    }
+    
+    let mut map = Vec::new();
+    for row in rows {
+        let glyphs = sector_glyphs(row);
+        if glyphs.len() > 8 {
+            return None;
+        }
+        let mut sectors = vec![String::new(); 8];
+        for ((_, glyph), column) in glyphs.iter().zip(sector_columns(&glyphs)) {
+            sectors[column] = glyph.to_string();
+        }
+        map.push(sectors);
+    }
+    Some(map)
+}
+
+/// The library computer's table of the whole galaxy, one entry per row of quadrants
+#[derive(Debug, Clone, PartialEq)]
+pub enum GalacticRecord {
+    /// COM 0, the cumulative galactic record: each quadrant's three-digit code (Klingons,
+    /// starbases, stars), None where it was never scanned ("***")
+    Quadrants(Vec<Vec<Option<String>>>),
+    /// COM 5, the galactic region names: the left (quadrants 1-4) and right (5-8) region
+    Regions(Vec<(String, String)>),
+}
+
+/// Parse the library computer's galactic record ("COMPUTER RECORD OF GALAXY FOR
+/// QUADRANT 4 , 5") or region name map ("THE GALAXY"): a header of column numbers, then
+/// eight numbered rows between dashed lines. Spacing inside a row doesn't matter.
+pub fn parse_galactic_record(lines: &[Line]) -> Option<GalacticRecord> {
+    let header = lines.iter().position(|line| line.contains("COMPUTER RECORD OF GALAXY") || line.trim() == "THE GALAXY")?;
+    // The rows start after the column numbers and the first dashed line
+    let start = header + lines[header..].iter().position(|line| line.contains("-----"))?;
+    let mut quadrants = Vec::new();
+    let mut regions = Vec::new();
+    
+    for line in &lines[start + 1..] {
+        let (row, rest) = line.trim().split_once(char::is_whitespace).unwrap_or((line.trim(), ""));
+        if row.parse::<usize>().ok() != Some(quadrants.len() + regions.len() + 1) {
             continue;
         }
         
-        if in_scan {
-            if line.contains(":") && line.contains("***") || line.len() > 10 {
-                // Parse galaxy quadrant line
-                let parts: Vec<&str> = line.split(':').collect();
-                if parts.len() >= 2 {
-                    let quadrant_data = parts[1].trim();
-                    let quadrants: Vec<String> = quadrant_data
-                        .split_whitespace()
-                        .map(|s| s.to_string())
-                        .collect();
-                    if !quadrants.is_empty() {
-                        galaxy_map.push(quadrants);
-                    }
-                }
-            } else if line.contains("---") {
-                // Skip separator lines
-                continue;
-            } else if line.trim().is_empty() && !galaxy_map.is_empty() {
-                // End of scan
-                break;
+        let names: Vec<&str> = rest.split_whitespace().collect();
+        let codes: String = rest.chars().filter(|c| !c.is_whitespace()).collect();
+        if names.len() == 2 && names.iter().all(|name| name.chars().all(|c| c.is_ascii_alphabetic())) {
+            regions.push((names[0].to_string(), names[1].to_string()));
+        } else if codes.len() == 24 && codes.chars().all(|c| c.is_ascii_digit() || c == '*') {
+            let row: Vec<Option<String>> = (0..8)
+                .map(|column| &codes[column * 3..column * 3 + 3])
+                .map(|code| if code == "***" { None } else { Some(code.to_string()) })
+                .collect();
+            if row.iter().flatten().any(|code| code.contains('*')) {
+                return None;
             }
+            quadrants.push(row);
+        } else {
+            return None;
+        }
+        if quadrants.len() + regions.len() == 8 {
+            break;
         }
     }
     
-    if galaxy_map.is_empty() {
-        None
-    } else {
-        Some(galaxy_map)
+    match (quadrants.len(), regions.len()) {
+        (8, 0) => Some(GalacticRecord::Quadrants(quadrants)),
+        (0, 8) => Some(GalacticRecord::Regions(regions)),
+        _ => None,
     }
 }
 
-/// Parse computer command output for galactic record
-pub fn parse_galactic_record(lines: &[String]) -> Option<Vec<(i32, i32, String)>> {
-    let mut records = Vec::new();
-    let record_regex = Regex::new(r"(\d+),(\d+)\s+(.+)").unwrap();
+/// What a library computer course reading points at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CourseTarget {
+    /// COM 2, photon torpedo data: one reading per Klingon in the quadrant
+    Klingon,
+    /// COM 3, starbase nav data
+    Starbase,
+    /// COM 4, the direction/distance calculator between two entered sectors
+    Calculator,
+}
+
+/// A course and distance reported by the library computer, ready to use as the course
+/// of a NAV or TOR command
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CourseReading {
+    pub target: CourseTarget,
+    /// Course, 1 to 9
+    pub direction: f32,
+    /// In sectors
+    pub distance: f32,
+}
+
+/// The number after "NAME =", however the interpreter formats it (" 3.25 ", "3.2500001",
+/// ".5", "2.5E-01")
+fn labelled_number(line: &str, name: &str) -> Option<f32> {
+    let (label, value) = line.split_once('=')?;
+    if label.trim() != name {
+        return None;
+    }
+    value.trim().parse().ok()
+}
+
+/// Parse the library computer's course readings: "DIRECTION = 3.25" followed by
+/// "DISTANCE = 2.82", after the heading naming what they point at
+pub fn parse_course_readings(lines: &[Line]) -> Vec<CourseReading> {
+    let mut readings = Vec::new();
+    let mut target = None;
+    let mut direction = None;
     
     for line in lines {
-        if line.contains("GALACTIC RECORD") {
-            continue;
-        }
-        
-        if let Some(caps) = record_regex.captures(line) {
-            if let (Some(q1), Some(q2), Some(data)) = (caps.get(1), caps.get(2), caps.get(3)) {
-                let q1: i32 = q1.as_str().parse().unwrap_or(0);
-                let q2: i32 = q2.as_str().parse().unwrap_or(0);
-                let data = data.as_str().trim().to_string();
-                records.push((q1, q2, data));
+        if line.contains("FROM ENTERPRISE TO KLINGON") {
+            target = Some(CourseTarget::Klingon);
+        } else if line.contains("FROM ENTERPRISE TO STARBASE") {
+            target = Some(CourseTarget::Starbase);
+        } else if line.contains("DIRECTION/DISTANCE CALCULATOR") {
+            target = Some(CourseTarget::Calculator);
+        } else if let Some(value) = labelled_number(line, "DIRECTION") {
+            direction = Some(value);
+        } else if let Some(distance) = labelled_number(line, "DISTANCE") {
+            if let (Some(target), Some(direction)) = (target, direction.take()) {
+                readings.push(CourseReading { target, direction, distance });
             }
         }
     }
     
-    if records.is_empty() {
-        None
-    } else {
-        Some(records)
+    readings
+}
+
+/// Parse the sectors a photon torpedo passed through, in order: "TORPEDO TRACK:" followed
+/// by one "4 , 5" line per sector
+pub fn parse_torpedo_track(lines: &[Line]) -> Option<Vec<(i32, i32)>> {
+    let start = lines.iter().position(|line| line.contains("TORPEDO TRACK:"))?;
+    let track = lines[start + 1..].iter()
+        .map_while(|line| {
+            let (row, column) = line.split_once(',')?;
+            Some((row.trim().parse().ok()?, column.trim().parse().ok()?))
+        })
+        .collect();
+    Some(track)
+}
+
+/// How the mission stands, from the library computer's status report (COM 1)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MissionInfo {
+    pub klingons: i32,
+    /// Stardates left to complete the mission
+    pub stardates: f32,
+    pub starbases: i32,
+}
+
+/// The number between `before` and `after` (or the end of the line) in a line, e.g. 23.4
+/// in "MISSION MUST BE COMPLETED IN 23.4 STARDATES"
+fn number_between(line: &str, before: &str, after: Option<&str>) -> Option<f32> {
+    let (_, rest) = line.split_once(before)?;
+    let value = match after {
+        Some(after) => rest.split_once(after)?.0,
+        None => rest,
+    };
+    value.trim().parse().ok()
+}
+
+/// The stardate of the attack on Federation headquarters, from the orders at the start of
+/// the game: "ON STARDATE 2330   THIS GIVES YOU 30 DAYS."
+pub fn parse_deadline(line: &str) -> Option<i32> {
+    let (_, rest) = line.split_once("ON STARDATE")?;
+    rest.split_whitespace().next()?.parse().ok()
+}
+
+/// Parse the status report: "KLINGONS LEFT: 5", "MISSION MUST BE COMPLETED IN 23.4
+/// STARDATES", then "THE FEDERATION IS MAINTAINING 2 STARBASES IN THE GALAXY" or the
+/// complaint that no starbases are left
+pub fn parse_status_report(lines: &[Line]) -> Option<MissionInfo> {
+    let start = lines.iter().position(|line| line.contains("STATUS REPORT:"))?;
+    let mut klingons = None;
+    let mut stardates = None;
+    let mut starbases = None;
+    
+    for line in &lines[start + 1..] {
+        if let Some(value) = number_between(line, "LEFT:", None).filter(|_| line.contains("KLINGON")) {
+            klingons = Some(value as i32);
+        } else if let Some(value) = number_between(line, "MISSION MUST BE COMPLETED IN", Some("STARDATE")) {
+            stardates = Some(value);
+        } else if let Some(value) = number_between(line, "FEDERATION IS MAINTAINING", Some("STARBASE")) {
+            starbases = Some(value as i32);
+        } else if line.contains("NO STARBASES LEFT") {
+            starbases = Some(0);
+        }
+        if starbases.is_some() {
+            break;
+        }
     }
+    
+    Some(MissionInfo { klingons: klingons?, stardates: stardates?, starbases: starbases? })
 }
 
 #[cfg(test)]
@@ -202,4 +377,177 @@ mod tests {
         assert_eq!(parse_quadrant_name("LOCATED IN RIGEL QUADRANT"), Some("RIGEL".to_string()));
         assert_eq!(parse_quadrant_name("NO QUADRANT INFO"), None);
     }
-} 
\ No newline at end of file
+    
+    #[test]
+    fn test_parse_long_range_scan() {
+        let lines: Vec<Line> = vec![
+            "LONG RANGE SCAN FOR QUADRANT 1 , 4",
+            "-------------------",
+            ": *** : *** : *** :",
+            "-------------------",
+            ": 103 : 005 : 017 :",
+            "-------------------",
+            ": 002 : 211 : 004 :",
+            "-------------------",
+        ].into_iter().map(Line::from).collect();
+        let map = parse_long_range_scan(&lines).unwrap();
+        assert_eq!(map.len(), 3);
+        assert_eq!(map[1], vec!["103", "005", "017"]);
+    }
+    
+    #[test]
+    fn test_parse_long_range_scan_collapsed() {
+        let lines: Vec<Line> = vec![
+            "LONG RANGE SCAN FOR QUADRANT 8 , 4",
+            "-------------------",
+            ":103:005:017:",
+            "-------------------",
+            ": *** : *** : *** :",
+            "-------------------",
+            "COMMAND?",
+        ].into_iter().map(Line::from).collect();
+        assert_eq!(parse_long_range_scan(&lines).unwrap(), vec![vec!["103", "005", "017"], vec!["***", "***", "***"]]);
+    }
+    
+    /// The same scan as each backend prints it
+    fn short_range_scan(rows: [&str; 2], frame: &str) -> Vec<Line> {
+        let mut lines = vec![frame.to_string(), rows[0].to_string()];
+        lines.extend((0..6).map(|_| String::new()));
+        lines.push(rows[1].to_string());
+        lines.push(frame.to_string());
+        crate::game::share_lines(lines)
+    }
+    
+    #[test]
+    fn test_parse_short_range_scan() {
+        let backends = [
+            // BasicRS: every sector is a space and three characters, as the BASIC prints it
+            short_range_scan([
+                "      *      <*>                        STARDATE           2300",
+                " +K+                         >!<        SHIELDS            0",
+            ], &"-".repeat(33)),
+            // TrekBasicJ: no space between sectors, and a wider frame
+            short_range_scan([
+                "    *    <*>                    STARDATE           2300",
+                "+K+                  >!<        SHIELDS            0",
+            ], &"-".repeat(35)),
+        ];
+        for lines in backends {
+            let map = parse_short_range_scan(&lines).unwrap();
+            assert_eq!(map.len(), 8);
+            assert_eq!(map[0][1], "*");
+            assert_eq!(map[0][2], "");
+            assert_eq!(map[0][3], "<*>");
+            assert_eq!(map[7][0], "+K+");
+            assert_eq!(map[7][7], ">!<");
+            assert_eq!(map[3], vec![""; 8]);
+            assert_eq!(parse_short_range_scan(&lines[..5]), None);
+        }
+        
+        // Collapsed spaces lose the positions, but not the glyphs or their order
+        let lines = short_range_scan([" * <*> +K+ STARDATE 2300", " +K+ >!< SHIELDS 0"], &"-".repeat(33));
+        let map = parse_short_range_scan(&lines).unwrap();
+        assert_eq!(map[0].iter().filter(|s| !s.is_empty()).collect::<Vec<_>>(), ["*", "<*>", "+K+"]);
+        assert_eq!(map[7].iter().filter(|s| !s.is_empty()).collect::<Vec<_>>(), ["+K+", ">!<"]);
+    }
+    
+    #[test]
+    fn test_parse_galactic_record() {
+        let mut lines = vec![
+            "        COMPUTER RECORD OF GALAXY FOR QUADRANT 4 , 5".to_string(),
+            "".to_string(),
+            "       1     2     3     4     5     6     7     8".to_string(),
+            "     ----- ----- ----- ----- ----- ----- ----- -----".to_string(),
+        ];
+        for row in 1..=8 {
+            let codes = if row == 4 { "   ***   ***   ***   104   007   ***   ***   ***" } else { "   ***   ***   ***   ***   ***   ***   ***   ***" };
+            lines.push(format!(" {} {}", row, codes));
+            lines.push("     ----- ----- ----- ----- ----- ----- ----- -----".to_string());
+        }
+        let Some(GalacticRecord::Quadrants(quadrants)) = parse_galactic_record(&crate::game::share_lines(lines.clone())) else { panic!() };
+        assert_eq!(quadrants.len(), 8);
+        assert_eq!(quadrants[3][3], Some("104".to_string()));
+        assert_eq!(quadrants[3][4], Some("007".to_string()));
+        assert_eq!(quadrants[0][0], None);
+        
+        // Cut off before the last row
+        assert_eq!(parse_galactic_record(&crate::game::share_lines(lines[..10].to_vec())), None);
+        
+        let mut lines = vec![
+            "                        THE GALAXY".to_string(),
+            "       1     2     3     4     5     6     7     8".to_string(),
+            "     ----- ----- ----- ----- ----- ----- ----- -----".to_string(),
+        ];
+        for (row, (left, right)) in [("ANTARES", "SIRIUS"), ("RIGEL", "DENEB"), ("PROCYON", "CAPELLA"), ("VEGA", "BETELGEUSE"),
+                                     ("CANOPUS", "ALDEBARAN"), ("ALTAIR", "REGULUS"), ("SAGITTARIUS", "ARCTURUS"), ("POLLUX", "SPICA")].iter().enumerate() {
+            lines.push(format!(" {}        {}               {}", row + 1, left, right));
+        }
+        let Some(GalacticRecord::Regions(regions)) = parse_galactic_record(&crate::game::share_lines(lines)) else { panic!() };
+        assert_eq!(regions[6], ("SAGITTARIUS".to_string(), "ARCTURUS".to_string()));
+    }
+    
+    #[test]
+    fn test_parse_course_readings() {
+        // The same readings as BasicRS, TrekBasic and TrekBasicJ print them
+        let backends = [
+            ["DIRECTION = 3.25 ", "DISTANCE = 2.82843 ", "DIRECTION = 7 ", "DISTANCE = 1 "],
+            ["DIRECTION = 3.25", "DISTANCE = 2.8284271247461903", "DIRECTION = 7", "DISTANCE = 1"],
+            ["DIRECTION = 3.25", "DISTANCE = 2.828427", "DIRECTION = 7.0", "DISTANCE = 1.0"],
+        ];
+        for [direction, distance, second_direction, second_distance] in backends {
+            let lines: Vec<Line> = ["FROM ENTERPRISE TO KLINGON BATTLE CRUSERS", direction, distance, second_direction, second_distance]
+                .into_iter().map(Line::from).collect();
+            let readings = parse_course_readings(&lines);
+            assert_eq!(readings.len(), 2);
+            assert_eq!(readings[0].target, CourseTarget::Klingon);
+            assert_eq!(readings[0].direction, 3.25);
+            assert!((readings[0].distance - 2.828427).abs() < 1e-5);
+            assert_eq!((readings[1].direction, readings[1].distance), (7.0, 1.0));
+        }
+        
+        let lines: Vec<Line> = ["FROM ENTERPRISE TO STARBASE:", "DIRECTION = .5", "DISTANCE = 2.5E+00"]
+            .into_iter().map(Line::from).collect();
+        assert_eq!(parse_course_readings(&lines), vec![CourseReading { target: CourseTarget::Starbase, direction: 0.5, distance: 2.5 }]);
+        assert_eq!(parse_course_readings(&lines[..2]), vec![]);
+    }
+    
+    #[test]
+    fn test_parse_status_report() {
+        let lines: Vec<Line> = [
+            "   STATUS REPORT:",
+            "KLINGONS LEFT:  5 ",
+            "MISSION MUST BE COMPLETED IN 23.4 STARDATES",
+            "THE FEDERATION IS MAINTAINING 2 STARBASES IN THE GALAXY",
+        ].into_iter().map(Line::from).collect();
+        assert_eq!(parse_status_report(&lines), Some(MissionInfo { klingons: 5, stardates: 23.4, starbases: 2 }));
+        assert_eq!(parse_status_report(&lines[..3]), None);
+        
+        let lines: Vec<Line> = [
+            "   STATUS REPORT:",
+            "KLINGON LEFT: 1",
+            "MISSION MUST BE COMPLETED IN 3 STARDATES",
+            "YOUR STUPIDITY HAS LEFT YOU ON YOUR ON IN",
+            "  THE GALAXY -- YOU HAVE NO STARBASES LEFT!",
+        ].into_iter().map(Line::from).collect();
+        assert_eq!(parse_status_report(&lines), Some(MissionInfo { klingons: 1, stardates: 3.0, starbases: 0 }));
+    }
+    
+    #[test]
+    fn test_parse_deadline() {
+        assert_eq!(parse_deadline("  ON STARDATE 2330   THIS GIVES YOU 30 DAYS.  THERE ARE"), Some(2330));
+        assert_eq!(parse_deadline("THE GALAXY BEFORE THEY CAN ATTACK FEDERATION HEADQUARTERS ON STARDATE 3125"), Some(3125));
+        assert_eq!(parse_deadline("STARDATE           2300"), None);
+    }
+    
+    #[test]
+    fn test_parse_torpedo_track() {
+        let lines: Vec<Line> = [
+            "TORPEDO TRACK:",
+            "                4 , 5 ",
+            "                3 , 6 ",
+            "*** KLINGON DESTROYED ***",
+        ].into_iter().map(Line::from).collect();
+        assert_eq!(parse_torpedo_track(&lines), Some(vec![(4, 5), (3, 6)]));
+        assert_eq!(parse_torpedo_track(&lines[1..]), None);
+    }
+}
@@ -0,0 +1,214 @@
+use crate::game::Line;
+use crate::json::Json;
+use crate::metadata::digest;
+use crate::player::GameResult;
+use anyhow::{Context, Result};
+use std::path::Path;
+
+/// The game profile used when --game-profile isn't given
+pub const DEFAULT_GAME_PROFILE: &str = "superstartrek";
+
+/// Text that ends a game and the result it means, matched anywhere in a line regardless
+/// of case
+#[derive(Debug, Clone, PartialEq)]
+pub struct EndingRule {
+    pub pattern: String,
+    pub result: GameResult,
+}
+
+/// What TrekBot needs to know about a BASIC game beyond the interpreter, built in for
+/// Super Star Trek or defined in the config file for variants and other games:
+///
+/// ```json
+/// {"games": [
+///   {"name": "sst-1978", "programs": ["sst-1978*.bas", "digest:9f2c0d41b7e6a853"],
+///    "endings": [{"pattern": "YOU WIN", "result": "victory"},
+///                {"pattern": "THE ENTERPRISE HAS BEEN DESTROYED", "result": "destroyed"}]}
+/// ]}
+/// ```
+///
+/// The first rule matching a line decides the result; "unknown" ends the game without
+/// saying how it went. Patterns are plain text, not regular expressions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameProfile {
+    pub name: String,
+    pub endings: Vec<EndingRule>,
+    /// Programs the profile is picked for without --game-profile: file names, with `*`
+    /// and `?` wildcards, or `digest:` and the digest of the program's text
+    pub programs: Vec<String>,
+}
+
+impl GameProfile {
+    /// The endings Super Star Trek prints
+    pub fn super_star_trek() -> Self {
+        let rule = |pattern: &str, result| EndingRule { pattern: pattern.to_string(), result };
+        Self {
+            name: DEFAULT_GAME_PROFILE.to_string(),
+            endings: vec![
+                rule("MISSION ACCOMPLISHED", GameResult::Victory),
+                rule("YOU HAVE BEEN KILLED", GameResult::Destroyed),
+                rule("TIME HAS RUN OUT", GameResult::TimeUp),
+                rule("FEDERATION DESTROYED", GameResult::FederationDestroyed),
+                rule("GAME OVER", GameResult::Unknown),
+            ],
+            programs: Vec::new(),
+        }
+    }
+    
+    /// The profile called `name` in the config file at `path`, or the built-in one
+    pub fn find(path: &str, name: &str) -> Result<Self> {
+        if Path::new(path).exists() {
+            if let Some(profile) = load_game_profiles(path)?.into_iter().find(|profile| profile.name == name) {
+                return Ok(profile);
+            }
+        }
+        if name == DEFAULT_GAME_PROFILE {
+            return Ok(Self::super_star_trek());
+        }
+        anyhow::bail!("Unknown game profile '{}': not built in or defined in {}", name, path)
+    }
+    
+    /// The profile called `name`, or without a name the first profile in the config file
+    /// listing `program` in its "programs", falling back on the built-in one
+    pub fn select(path: &str, name: Option<&str>, program: &str) -> Result<Self> {
+        if let Some(name) = name {
+            return Self::find(path, name);
+        }
+        if Path::new(path).exists() {
+            let text = std::fs::read_to_string(program).ok();
+            let program_digest = text.as_deref().map(digest);
+            let file_name = Path::new(program).file_name().map_or_else(|| program.to_string(), |name| name.to_string_lossy().to_string());
+            if let Some(profile) = load_game_profiles(path)?.into_iter().find(|profile| profile.matches(&file_name, program_digest.as_deref())) {
+                log::info!("Using game profile '{}' for {}", profile.name, program);
+                return Ok(profile);
+            }
+        }
+        Ok(Self::super_star_trek())
+    }
+    
+    /// Whether one of the profile's "programs" names this file or its digest
+    fn matches(&self, file_name: &str, program_digest: Option<&str>) -> bool {
+        self.programs.iter().any(|pattern| match pattern.strip_prefix("digest:") {
+            Some(expected) => program_digest == Some(expected.trim()),
+            None => wildcard_match(&pattern.to_lowercase(), &file_name.to_lowercase()),
+        })
+    }
+    
+    /// How the game ended, if any line of the output is one of its endings. An "unknown"
+    /// ending gives way to any other ending in the same output.
+    pub fn ending(&self, output: &[Line]) -> Option<GameResult> {
+        let mut endings = output.iter().filter_map(|line| {
+            let line = line.to_uppercase();
+            self.endings.iter()
+                .find(|rule| line.contains(&rule.pattern.to_uppercase()))
+                .map(|rule| rule.result.clone())
+        });
+        let first = endings.next()?;
+        if first != GameResult::Unknown {
+            return Some(first);
+        }
+        Some(endings.find(|result| *result != GameResult::Unknown).unwrap_or(first))
+    }
+    
+    fn from_json(json: &Json) -> Result<Self> {
+        let name = json.get("name").and_then(Json::as_str).context("game profile without a \"name\"")?;
+        let endings = json.get("endings").and_then(Json::as_array)
+            .with_context(|| format!("Game profile '{}' needs a list of \"endings\"", name))?;
+        let endings = endings.iter().map(|rule| {
+            let pattern = rule.get("pattern").and_then(Json::as_str).context("ending without a \"pattern\"")?;
+            let result = rule.get("result").and_then(Json::as_str).context("ending without a \"result\"")?;
+            Ok(EndingRule {
+                pattern: pattern.to_string(),
+                result: GameResult::from_slug(result).with_context(|| format!("unknown result '{}'", result))?,
+            })
+        }).collect::<Result<_>>().with_context(|| format!("Invalid ending in game profile '{}'", name))?;
+        let programs = match json.get("programs") {
+            Some(programs) => programs.as_array()
+                .and_then(|programs| programs.iter().map(|program| program.as_str().map(str::to_string)).collect())
+                .with_context(|| format!("\"programs\" of game profile '{}' must be a list of strings", name))?,
+            None => Vec::new(),
+        };
+        Ok(Self { name: name.to_string(), endings, programs })
+    }
+}
+
+/// Whether `text` matches `pattern`, where `*` stands for any run of characters and `?`
+/// for any one
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let (pattern, text): (Vec<char>, Vec<char>) = (pattern.chars().collect(), text.chars().collect());
+    // Where the last `*` was, and the text position it is currently stretched to
+    let (mut p, mut t, mut star) = (0, 0, None);
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    star = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Every game profile in a config file
+pub fn load_game_profiles(path: &str) -> Result<Vec<GameProfile>> {
+    let text = std::fs::read_to_string(path).with_context(|| format!("Failed to read config file {}", path))?;
+    let json = Json::parse(&text).with_context(|| format!("Invalid config file {}", path))?;
+    let Some(profiles) = json.get("games") else {
+        return Ok(Vec::new());
+    };
+    profiles.as_array().with_context(|| format!("\"games\" in {} must be a list", path))?
+        .iter()
+        .map(GameProfile::from_json)
+        .collect::<Result<_>>()
+        .with_context(|| format!("Invalid game profile in {}", path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_ending() {
+        let lines = |lines: &[&str]| crate::game::share_lines(lines.iter().map(|line| line.to_string()).collect());
+        let sst = GameProfile::super_star_trek();
+        assert_eq!(sst.ending(&lines(&["COMMAND?"])), None);
+        assert_eq!(sst.ending(&lines(&["IT IS STARDATE 2310", "Mission accomplished"])), Some(GameResult::Victory));
+        assert_eq!(sst.ending(&lines(&["GAME OVER"])), Some(GameResult::Unknown));
+        assert_eq!(sst.ending(&lines(&["GAME OVER", "YOU HAVE BEEN KILLED"])), Some(GameResult::Destroyed));
+        
+        let json = Json::parse(r#"{"name": "sst-1978", "endings": [{"pattern": "you win", "result": "victory"}]}"#).unwrap();
+        let variant = GameProfile::from_json(&json).unwrap();
+        assert_eq!(variant.ending(&lines(&["YOU WIN!"])), Some(GameResult::Victory));
+        assert_eq!(variant.ending(&lines(&["MISSION ACCOMPLISHED"])), None);
+        
+        let json = Json::parse(r#"{"name": "bad", "endings": [{"pattern": "X", "result": "draw"}]}"#).unwrap();
+        assert!(GameProfile::from_json(&json).is_err());
+    }
+    
+    #[test]
+    fn test_program_match() {
+        let json = Json::parse(r#"{"name": "sst-1978", "programs": ["sst-1978*.bas", "digest:00ff"], "endings": []}"#).unwrap();
+        let profile = GameProfile::from_json(&json).unwrap();
+        assert!(profile.matches("SST-1978-fixed.BAS", None));
+        assert!(profile.matches("sst-1978.bas", None));
+        assert!(profile.matches("anything.bas", Some("00ff")));
+        assert!(!profile.matches("superstartrek.bas", Some("1234")));
+        assert!(!GameProfile::super_star_trek().matches("superstartrek.bas", None));
+        
+        assert!(wildcard_match("a?c*", "abcdef"));
+        assert!(wildcard_match("*.bas", ".bas"));
+        assert!(!wildcard_match("*.bas", "sst.bass"));
+    }
+}
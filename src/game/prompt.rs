@@ -0,0 +1,277 @@
+use super::Line;
+use crate::json::Json;
+
+/// A menu the game prints before asking for a choice. Its header and entries are
+/// never prompts on their own; the block ends at the prompt that follows it.
+pub struct MenuBlock {
+    pub header: &'static str,
+    pub entries: &'static [&'static str],
+    pub prompt: &'static str,
+}
+
+/// Every menu in the program
+pub const MENU_BLOCKS: &[MenuBlock] = &[
+    // Printed after an unrecognized command (lines 2160-2260)
+    MenuBlock {
+        header: "ENTER ONE OF THE FOLLOWING:",
+        entries: &[
+            "NAV  (TO SET COURSE)",
+            "SRS  (FOR SHORT RANGE SENSOR SCAN)",
+            "LRS  (FOR LONG RANGE SENSOR SCAN)",
+            "PHA  (TO FIRE PHASERS)",
+            "TOR  (TO FIRE PHOTON TORPEDOES)",
+            "SHE  (TO RAISE OR LOWER SHIELDS)",
+            "DAM  (FOR DAMAGE CONTROL REPORTS)",
+            "COM  (TO CALL ON LIBRARY-COMPUTER)",
+            "XXX  (TO RESIGN YOUR COMMAND)",
+        ],
+        prompt: "COMMAND",
+    },
+    // Printed after an invalid library computer option (lines 7360-7380)
+    MenuBlock {
+        header: "FUNCTIONS AVAILABLE FROM LIBRARY-COMPUTER:",
+        entries: &[
+            "0 = CUMULATIVE GALACTIC RECORD",
+            "1 = STATUS REPORT",
+            "2 = PHOTON TORPEDO DATA",
+            "3 = STARBASE NAV DATA",
+            "4 = DIRECTION/DISTANCE CALCULATOR",
+            "5 = GALAXY 'REGION NAME' MAP",
+        ],
+        prompt: "COMPUTER ACTIVE AND AWAITING COMMAND",
+    },
+];
+
+/// Whether a line is the header or an entry of a menu block
+pub fn is_menu_line(line: &str) -> bool {
+    let line = line.trim();
+    MENU_BLOCKS.iter().any(|menu| line == menu.header || menu.entries.contains(&line))
+}
+
+/// What the game is asking for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PromptKind {
+    /// The main COMMAND prompt
+    Command,
+    /// Course for the NAV command
+    Course,
+    WarpFactor,
+    TorpedoCourse,
+    /// Energy to fire at the Klingons in the quadrant
+    PhaserUnits,
+    /// Energy to put into the shields
+    ShieldUnits,
+    /// An "ENERGY AVAILABLE = N" prompt without its question; any amount up to N is accepted
+    EnergyUnits,
+    /// Library computer option
+    ComputerCommand,
+    /// Start of the direction/distance calculator
+    InitialCoordinates,
+    FinalCoordinates,
+    /// Starbase offer to repair damaged devices
+    RepairAuthorization,
+    /// Asks whether to start a new game after the mission ends
+    Aye,
+    /// A status message the game stopped at; answered with an empty line
+    Message,
+    /// Not recognized
+    #[default]
+    Unknown,
+}
+
+/// Text identifying each prompt that needs an answer
+const PROMPT_KINDS: &[(&str, PromptKind)] = &[
+    ("COMPUTER ACTIVE AND AWAITING COMMAND", PromptKind::ComputerCommand),
+    ("PHOTON TORPEDO COURSE", PromptKind::TorpedoCourse),
+    ("COURSE (0-9)", PromptKind::Course),
+    ("WARP FACTOR", PromptKind::WarpFactor),
+    ("NUMBER OF UNITS TO FIRE", PromptKind::PhaserUnits),
+    ("NUMBER OF UNITS TO SHIELDS", PromptKind::ShieldUnits),
+    ("INITIAL COORDINATES (X,Y)", PromptKind::InitialCoordinates),
+    ("FINAL COORDINATES (X,Y)", PromptKind::FinalCoordinates),
+    ("WILL YOU AUTHORIZE THE REPAIR ORDER", PromptKind::RepairAuthorization),
+    ("LET HIM STEP FORWARD AND ENTER 'AYE'", PromptKind::Aye),
+];
+
+/// Status messages that can end a batch of output; the game only needs Enter to go on
+const MESSAGES: &[&str] = &[
+    "PLEASE ENTER",
+    "LT. UHURA REPORTS MESSAGE",
+    "UNITS PER YOUR COMMAND",
+    "DEFLECTOR CONTROL ROOM REPORT",
+    "DAMAGE CONTROL REPORT",
+    "ENGINEERING REPORTS",
+    "CHIEF ENGINEER SCOTT REPORTS",
+    "STARBASE SHIELDS PROTECT",
+    "SENSORS SHOW NO DAMAGE",
+    "UNIT HIT ON",
+    "KLINGON DESTROYED",
+    "TORPEDO TRACK",
+    "ABSORBED TORPEDO",
+    "STARBASE DESTROYED",
+    "TORPEDO MISSED",
+    "SHIELDS UNCHANGED",
+    "CONDITION RED",
+    "WARP ENGINES SHUT DOWN",
+    "PERMISSION TO ATTEMPT CROSSING",
+    "NOW ENTERING",
+];
+
+impl PromptKind {
+    pub const ALL: &'static [PromptKind] = &[
+        PromptKind::Command, PromptKind::Course, PromptKind::WarpFactor, PromptKind::TorpedoCourse,
+        PromptKind::PhaserUnits, PromptKind::ShieldUnits, PromptKind::EnergyUnits, PromptKind::ComputerCommand,
+        PromptKind::InitialCoordinates, PromptKind::FinalCoordinates, PromptKind::RepairAuthorization,
+        PromptKind::Aye, PromptKind::Message, PromptKind::Unknown,
+    ];
+    
+    /// The kind with this name as reports show it, e.g. "WarpFactor", ignoring case
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|kind| format!("{:?}", kind).eq_ignore_ascii_case(name.trim()))
+    }
+    
+    /// Classify a prompt line. A bare "?" is the second half of an INPUT whose
+    /// question was printed on the lines before it, so those are checked too.
+    pub fn classify(prompt: &str, block: &[Line]) -> Self {
+        let prompt = prompt.trim();
+        if prompt.starts_with("COMMAND") {
+            return PromptKind::Command;
+        }
+        if let Some(kind) = Self::find(prompt) {
+            return kind;
+        }
+        if prompt == "?" {
+            return block.iter().rev().take(3).find_map(|line| Self::find(line)).unwrap_or(PromptKind::Unknown);
+        }
+        if prompt.contains("ENERGY AVAILABLE") {
+            return PromptKind::EnergyUnits;
+        }
+        if MESSAGES.iter().any(|message| prompt.contains(message)) {
+            return PromptKind::Message;
+        }
+        PromptKind::Unknown
+    }
+    
+    fn find(text: &str) -> Option<Self> {
+        PROMPT_KINDS.iter()
+            .find(|(marker, _)| text.contains(marker))
+            .map(|(_, kind)| *kind)
+    }
+}
+
+/// The prompt the game is waiting on, classified, with the output that led up to it.
+/// A menu and its prompt arrive as one block, so the strategy answers once.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PromptContext {
+    pub kind: PromptKind,
+    /// The prompt line itself, e.g. "COMPUTER ACTIVE AND AWAITING COMMAND?"
+    pub prompt: String,
+    /// Output printed since the last command, before the prompt
+    pub block: Vec<Line>,
+    /// Header and entries of the menu shown with this prompt; empty without a menu
+    pub menu: Vec<String>,
+    /// From "ENERGY AVAILABLE = N" before a shield or phaser prompt
+    pub energy_available: Option<i32>,
+    /// From "WARP FACTOR (0-8)", narrower when the engines are damaged
+    pub warp_range: Option<(f32, f32)>,
+}
+
+impl PromptContext {
+    /// Build the context for the prompt ending a batch of output
+    pub fn from_output(output: &[Line], prompt: &str) -> Self {
+        let block = match output.last() {
+            Some(last) if &**last == prompt => output[..output.len() - 1].to_vec(),
+            _ => output.to_vec(),
+        };
+        
+        let menu = MENU_BLOCKS.iter()
+            .find(|menu| prompt.trim().starts_with(menu.prompt))
+            .and_then(|menu| {
+                let start = block.iter().rposition(|line| line.trim() == menu.header)?;
+                Some(block[start..].iter()
+                    .filter(|line| is_menu_line(line))
+                    .map(|line| line.trim().to_string())
+                    .collect())
+            })
+            .unwrap_or_default();
+        
+        let recent = || std::iter::once(prompt).chain(block.iter().rev().map(|line| &**line));
+        Self {
+            kind: PromptKind::classify(prompt, &block),
+            prompt: prompt.to_string(),
+            energy_available: recent().find_map(super::parse_energy_available),
+            warp_range: recent().take(4).find_map(super::parse_warp_factor_range),
+            block,
+            menu,
+        }
+    }
+    
+    /// The prompt as JSON: its kind, text and the energy it offers
+    pub fn to_json(&self) -> Json {
+        Json::object()
+            .with("kind", format!("{:?}", self.kind))
+            .with("text", self.prompt.trim())
+            .with("energy_available", self.energy_available)
+    }
+    
+    /// The choices offered by the menu, e.g. "NAV" or "3", in the order listed
+    pub fn menu_choices(&self) -> Vec<&str> {
+        self.menu.iter()
+            .skip(1)
+            .filter_map(|entry| entry.split([' ', '=']).next())
+            .filter(|choice| !choice.is_empty())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn lines(text: &[&str]) -> Vec<Line> {
+        text.iter().map(|line| Line::from(*line)).collect()
+    }
+    
+    #[test]
+    fn test_computer_menu_block() {
+        let output = lines(&[
+            "",
+            "FUNCTIONS AVAILABLE FROM LIBRARY-COMPUTER:",
+            "   0 = CUMULATIVE GALACTIC RECORD",
+            "   1 = STATUS REPORT",
+            "   2 = PHOTON TORPEDO DATA",
+            "   3 = STARBASE NAV DATA",
+            "   4 = DIRECTION/DISTANCE CALCULATOR",
+            "   5 = GALAXY 'REGION NAME' MAP",
+            "",
+            "COMPUTER ACTIVE AND AWAITING COMMAND?",
+        ]);
+        assert!(output[..9].iter().all(|line| !crate::interpreter::is_game_prompt(line)));
+        
+        let context = PromptContext::from_output(&output, &output[9]);
+        assert_eq!(context.menu.len(), 7);
+        assert_eq!(context.menu_choices(), vec!["0", "1", "2", "3", "4", "5"]);
+    }
+    
+    #[test]
+    fn test_prompt_without_menu() {
+        let output = lines(&["SHORT RANGE SCAN", "COMMAND?"]);
+        let context = PromptContext::from_output(&output, "COMMAND?");
+        assert!(context.menu.is_empty());
+        assert!(context.menu_choices().is_empty());
+        assert!(!crate::interpreter::is_game_prompt("ENTER ONE OF THE FOLLOWING:"));
+    }
+    
+    #[test]
+    fn test_prompt_kinds() {
+        let output = lines(&["COURSE (0-9)", "?"]);
+        assert_eq!(PromptContext::from_output(&output, "?").kind, PromptKind::Course);
+        assert_eq!(PromptContext::from_output(&output, "?").block, lines(&["COURSE (0-9)"]));
+        
+        assert_eq!(PromptKind::classify("COMMAND?", &[]), PromptKind::Command);
+        assert_eq!(PromptKind::classify("COMPUTER ACTIVE AND AWAITING COMMAND?", &[]), PromptKind::ComputerCommand);
+        assert_eq!(PromptKind::classify("PHOTON TORPEDO COURSE (1-9)?", &[]), PromptKind::TorpedoCourse);
+        assert_eq!(PromptKind::classify("NOW ENTERING ANTARES II QUADRANT . . .", &[]), PromptKind::Message);
+        assert_eq!(PromptKind::classify("?", &lines(&["SHORT RANGE SCAN"])), PromptKind::Unknown);
+    }
+}
@@ -1,7 +1,57 @@
+use super::{share_lines, CourseReading, GalacticRecord, Line, PromptContext};
+use crate::json::Json;
+use crate::verbosity::{self, Verbosity};
 use anyhow::Result;
 use regex::Regex;
 use std::collections::HashMap;
 
+/// Lines of a turn's output kept in last_output by default
+pub const DEFAULT_MAX_OUTPUT_LINES: usize = 500;
+
+/// What the library computer knows of the whole galaxy, row by row
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GalaxyMap {
+    /// Each quadrant's three-digit code (Klingons, starbases, stars), None until scanned
+    pub quadrants: Vec<Vec<Option<String>>>,
+    /// Left (quadrants 1-4) and right (5-8) region names
+    pub regions: Vec<(String, String)>,
+}
+
+impl GalaxyMap {
+    pub fn update(&mut self, record: GalacticRecord) {
+        match record {
+            GalacticRecord::Quadrants(quadrants) => self.quadrants = quadrants,
+            GalacticRecord::Regions(regions) => self.regions = regions,
+        }
+    }
+}
+
+/// How a game has ended according to its parsed state
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TerminalCondition {
+    KlingonsDestroyed,
+    OutOfEnergy,
+    DeadlinePassed,
+}
+
+/// A field of the game state that differs from the previous turn
+#[derive(Debug, Clone, PartialEq)]
+pub enum StateChange {
+    /// A count that went up or down, e.g. energy -220
+    Amount { field: &'static str, delta: i32 },
+    /// Anything else, or a count seen for the first time, e.g. condition GREEN → RED
+    Value { field: &'static str, from: String, to: String },
+}
+
+impl std::fmt::Display for StateChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StateChange::Amount { field, delta } => write!(f, "{} {:+}", field, delta),
+            StateChange::Value { field, from, to } => write!(f, "{} {}→{}", field, from, to),
+        }
+    }
+}
+
 /// Current game state extracted from interpreter output
 #[derive(Debug, Clone)]
 pub struct GameState {
@@ -14,12 +64,25 @@ pub struct GameState {
     pub time_remaining: Option<i32>,
     pub starbases: Option<i32>,
     pub stardate: Option<i32>,
+    /// Stardate by which the Klingons must be destroyed, from the orders at the start
+    pub deadline: Option<i32>,
     pub last_prompt: Option<String>,
-    pub last_output: Vec<String>,
+    /// The last prompt with the menu printed before it, if any
+    pub prompt_context: Option<PromptContext>,
+    pub last_output: Vec<Line>,
     pub condition: Option<String>,
     pub damage_report: HashMap<String, f32>,
     pub galaxy_map: Option<Vec<Vec<String>>>,
     pub sector_map: Option<Vec<Vec<String>>>,
+    /// The whole galaxy, from the library computer's galactic record
+    pub galaxy: GalaxyMap,
+    /// Courses the library computer reported in the latest output
+    pub course_readings: Vec<CourseReading>,
+    /// Sectors the torpedo fired in the latest output passed through
+    pub torpedo_track: Option<Vec<(i32, i32)>>,
+    /// Only the end of longer output is kept in last_output, so an interpreter printing
+    /// without ever prompting can't grow it without bound
+    pub max_output_lines: usize,
 }
 
 impl GameState {
@@ -34,23 +97,36 @@ impl GameState {
             time_remaining: None,
             starbases: None,
             stardate: None,
+            deadline: None,
             last_prompt: None,
+            prompt_context: None,
             last_output: Vec::new(),
             condition: None,
             damage_report: HashMap::new(),
             galaxy_map: None,
             sector_map: None,
+            galaxy: GalaxyMap::default(),
+            course_readings: Vec::new(),
+            torpedo_track: None,
+            max_output_lines: DEFAULT_MAX_OUTPUT_LINES,
         }
     }
     
     /// Update the game state with new output from the interpreter
     pub fn update(&mut self, output: &[String]) -> Result<()> {
-        self.last_output = output.to_vec();
+        self.update_with(&share_lines(output.to_vec()), crate::interpreter::is_game_prompt)
+    }
+    
+    /// Update the game state using a custom prompt classifier (e.g. one that honors interpreter
+    /// quirks). The lines are shared with last_output and the prompt context, not copied.
+    pub fn update_with(&mut self, output: &[Line], is_prompt: impl Fn(&str) -> bool) -> Result<()> {
+        self.last_output = output[output.len().saturating_sub(self.max_output_lines)..].to_vec();
         
         // Find the last prompt
         if let Some(last_line) = output.last() {
-            if crate::interpreter::is_game_prompt(last_line) {
-                self.last_prompt = Some(last_line.clone());
+            if is_prompt(last_line) {
+                self.last_prompt = Some(last_line.to_string());
+                self.prompt_context = Some(PromptContext::from_output(output, last_line));
             }
         }
         
@@ -65,9 +141,30 @@ impl GameState {
             self.parse_quadrant(line)?;
             self.parse_sector(line)?;
             self.parse_stardate(line)?;
+            if let Some(deadline) = crate::game::parse_deadline(line) {
+                self.deadline = Some(deadline);
+            }
             self.parse_damage_report(line)?;
         }
         
+        // Keep the most recent long range scan
+        if let Some(galaxy_map) = crate::game::parse_long_range_scan(output) {
+            self.galaxy_map = Some(galaxy_map);
+        }
+        if let Some(sector_map) = crate::game::parse_short_range_scan(output) {
+            self.sector_map = Some(sector_map);
+        }
+        if let Some(record) = crate::game::parse_galactic_record(output) {
+            self.galaxy.update(record);
+        }
+        self.course_readings = crate::game::parse_course_readings(output);
+        self.torpedo_track = crate::game::parse_torpedo_track(output);
+        if let Some(mission) = crate::game::parse_status_report(output) {
+            self.klingons_remaining = Some(mission.klingons);
+            self.time_remaining = Some(mission.stardates as i32);
+            self.starbases = Some(mission.starbases);
+        }
+        
         Ok(())
     }
     
@@ -149,7 +246,9 @@ impl GameState {
     }
     
     fn parse_condition(&mut self, line: &str) -> Result<()> {
-        if line.contains("CONDITION") && line.contains("RED") {
+        if line.contains("CONDITION") && line.contains("DOCKED") {
+            self.condition = Some("DOCKED".to_string());
+        } else if line.contains("CONDITION") && line.contains("RED") {
             self.condition = Some("RED".to_string());
         } else if line.contains("CONDITION") && line.contains("GREEN") {
             self.condition = Some("GREEN".to_string());
@@ -211,16 +310,16 @@ impl GameState {
         Ok(())
     }
     
-    /// Get the current prompt, if any
-    pub fn get_current_prompt(&self) -> Option<&str> {
-        self.last_prompt.as_deref()
-    }
-    
     /// Check if the game is in a combat situation
     pub fn is_in_combat(&self) -> bool {
         self.condition.as_deref() == Some("RED")
     }
     
+    /// Check if the Enterprise is docked at a starbase
+    pub fn is_docked(&self) -> bool {
+        self.condition.as_deref() == Some("DOCKED")
+    }
+    
     /// Check if shields are dangerously low
     pub fn are_shields_low(&self) -> bool {
         self.shields.map_or(false, |s| s < 200)
@@ -231,8 +330,29 @@ impl GameState {
         self.damage_report.get(system).map_or(false, |&damage| damage < 0.0)
     }
     
-    /// Display current game state in a concise format
+    /// Whether the state alone says the game is over, for interpreters whose ending text
+    /// isn't recognized: no Klingons left, no energy or shields, or past the deadline
+    pub fn terminal_condition(&self) -> Option<TerminalCondition> {
+        if self.klingons_remaining == Some(0) {
+            Some(TerminalCondition::KlingonsDestroyed)
+        } else if self.energy.is_some_and(|energy| energy <= 0) && self.shields.is_some_and(|shields| shields <= 0) {
+            Some(TerminalCondition::OutOfEnergy)
+        } else if let (Some(stardate), Some(deadline)) = (self.stardate, self.deadline) {
+            (stardate > deadline).then_some(TerminalCondition::DeadlinePassed)
+        } else {
+            None
+        }
+    }
+    
+    /// Display current game state in a concise format, unless running quietly
     pub fn display_status(&self) {
+        if verbosity::shows(Verbosity::Normal) {
+            println!("📊 Turn Status: {}", self.status_line());
+        }
+    }
+    
+    /// One-line summary of the state, as shown after every turn
+    pub fn status_line(&self) -> String {
         let stardate = self.stardate.map_or("???".to_string(), |d| d.to_string());
         let klingons = self.klingons_remaining.map_or("?".to_string(), |k| k.to_string());
         let energy = self.energy.map_or("????".to_string(), |e| e.to_string());
@@ -252,8 +372,64 @@ impl GameState {
             "(??,??)".to_string()
         };
         
-        println!("📊 Turn Status: Stardate {} | Klingons {} | Energy {} | Shields {} | Torpedoes {} | {} | Q{} S{}", 
-                 stardate, klingons, energy, shields, torpedoes, condition, quadrant, sector);
+        format!("Stardate {} | Klingons {} | Energy {} | Shields {} | Torpedoes {} | {} | Q{} S{}",
+                stardate, klingons, energy, shields, torpedoes, condition, quadrant, sector)
+    }
+    
+    /// The parsed state as JSON, as strategies outside TrekBot and turn records see it
+    pub fn to_json(&self) -> Json {
+        let pair = |pair: Option<(i32, i32)>| pair.map(|(a, b)| vec![a, b]);
+        let mut damage: Vec<(&String, &f32)> = self.damage_report.iter().collect();
+        damage.sort_by(|a, b| a.0.cmp(b.0));
+        Json::object()
+            .with("quadrant", pair(self.current_quadrant))
+            .with("sector", pair(self.current_sector))
+            .with("energy", self.energy)
+            .with("shields", self.shields)
+            .with("torpedoes", self.torpedoes)
+            .with("klingons", self.klingons_remaining)
+            .with("starbases", self.starbases)
+            .with("stardate", self.stardate)
+            .with("time_remaining", self.time_remaining)
+            .with("condition", self.condition.clone())
+            .with("docked", self.is_docked())
+            .with("damage", Json::Object(damage.into_iter().map(|(system, &repair)| (system.clone(), Json::from(repair as f64))).collect()))
+            .with("sector_map", self.sector_map.clone())
+    }
+    
+    /// The status line fields that changed since the previous turn; fields the
+    /// parser lost track of are left out rather than reported as changes
+    pub fn changes_since(&self, previous: &GameState) -> Vec<StateChange> {
+        let mut changes = Vec::new();
+        let amounts = [
+            ("stardate", previous.stardate, self.stardate),
+            ("klingons", previous.klingons_remaining, self.klingons_remaining),
+            ("energy", previous.energy, self.energy),
+            ("shields", previous.shields, self.shields),
+            ("torpedoes", previous.torpedoes, self.torpedoes),
+        ];
+        for (field, from, to) in amounts {
+            match (from, to) {
+                (Some(from), Some(to)) if from != to => changes.push(StateChange::Amount { field, delta: to - from }),
+                (None, Some(to)) => changes.push(StateChange::Value { field, from: "?".to_string(), to: to.to_string() }),
+                _ => {}
+            }
+        }
+        
+        let position = |position: Option<(i32, i32)>| position.map(|(x, y)| format!("({},{})", x, y));
+        let values = [
+            ("condition", previous.condition.clone(), self.condition.clone()),
+            ("quadrant", position(previous.current_quadrant), position(self.current_quadrant)),
+            ("sector", position(previous.current_sector), position(self.current_sector)),
+        ];
+        for (field, from, to) in values {
+            if let Some(to) = to {
+                if from.as_ref() != Some(&to) {
+                    changes.push(StateChange::Value { field, from: from.unwrap_or_else(|| "?".to_string()), to });
+                }
+            }
+        }
+        changes
     }
 }
 
@@ -0,0 +1,117 @@
+use crate::json::Json;
+use crate::metadata::utc_timestamp;
+use anyhow::{Context, Result};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// How one interpreter did in its latest smoke game
+#[derive(Debug, Clone, PartialEq)]
+pub struct InterpreterHealth {
+    pub name: String,
+    pub healthy: bool,
+    /// The game's result, or why it couldn't be played
+    pub detail: String,
+    pub checked_at: SystemTime,
+    pub consecutive_failures: usize,
+}
+
+/// Health of every interpreter a health check loop watches, written out after each
+/// round so monitoring can pick it up: as JSON, or in the Prometheus textfile format
+/// when the file name ends in .prom
+#[derive(Debug, Clone, Default)]
+pub struct HealthReport {
+    pub interpreters: Vec<InterpreterHealth>,
+}
+
+impl HealthReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Record a check; returns true when the interpreter changed between healthy and not
+    pub fn record(&mut self, name: &str, healthy: bool, detail: String) -> bool {
+        let checked_at = SystemTime::now();
+        match self.interpreters.iter_mut().find(|interpreter| interpreter.name == name) {
+            Some(interpreter) => {
+                let changed = interpreter.healthy != healthy;
+                interpreter.consecutive_failures = if healthy { 0 } else { interpreter.consecutive_failures + 1 };
+                interpreter.healthy = healthy;
+                interpreter.detail = detail;
+                interpreter.checked_at = checked_at;
+                changed
+            }
+            None => {
+                let consecutive_failures = if healthy { 0 } else { 1 };
+                self.interpreters.push(InterpreterHealth { name: name.to_string(), healthy, detail, checked_at, consecutive_failures });
+                !healthy
+            }
+        }
+    }
+    
+    pub fn all_healthy(&self) -> bool {
+        self.interpreters.iter().all(|interpreter| interpreter.healthy)
+    }
+    
+    pub fn to_json(&self) -> Json {
+        let interpreters = self.interpreters.iter().map(|interpreter| {
+            Json::object()
+                .with("name", interpreter.name.as_str())
+                .with("healthy", interpreter.healthy)
+                .with("detail", interpreter.detail.as_str())
+                .with("checked_at", utc_timestamp(interpreter.checked_at))
+                .with("consecutive_failures", interpreter.consecutive_failures)
+        });
+        Json::object()
+            .with("healthy", self.all_healthy())
+            .with("interpreters", Json::Array(interpreters.collect()))
+    }
+    
+    /// Metrics for node_exporter's textfile collector
+    pub fn to_prometheus(&self) -> String {
+        let mut text = String::new();
+        text.push_str("# HELP trekbot_interpreter_up Whether the interpreter's last smoke game succeeded\n");
+        text.push_str("# TYPE trekbot_interpreter_up gauge\n");
+        for interpreter in &self.interpreters {
+            text.push_str(&format!("trekbot_interpreter_up{{interpreter=\"{}\"}} {}\n", interpreter.name, interpreter.healthy as u8));
+        }
+        text.push_str("# HELP trekbot_interpreter_consecutive_failures Failed smoke games in a row\n");
+        text.push_str("# TYPE trekbot_interpreter_consecutive_failures gauge\n");
+        for interpreter in &self.interpreters {
+            text.push_str(&format!("trekbot_interpreter_consecutive_failures{{interpreter=\"{}\"}} {}\n", interpreter.name, interpreter.consecutive_failures));
+        }
+        text.push_str("# HELP trekbot_interpreter_last_check_seconds Unix time of the last smoke game\n");
+        text.push_str("# TYPE trekbot_interpreter_last_check_seconds gauge\n");
+        for interpreter in &self.interpreters {
+            let seconds = interpreter.checked_at.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+            text.push_str(&format!("trekbot_interpreter_last_check_seconds{{interpreter=\"{}\"}} {}\n", interpreter.name, seconds));
+        }
+        text
+    }
+    
+    /// Replace the status file, writing a temporary file first so readers never see
+    /// half of it
+    pub fn save(&self, path: &str) -> Result<()> {
+        let contents = if path.ends_with(".prom") { self.to_prometheus() } else { format!("{}\n", self.to_json()) };
+        let partial = format!("{}.tmp", path);
+        std::fs::write(&partial, contents).with_context(|| format!("Failed to write {}", partial))?;
+        std::fs::rename(&partial, path).with_context(|| format!("Failed to write {}", path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_record() {
+        let mut report = HealthReport::new();
+        assert!(!report.record("basic-rs", true, "max_turns".to_string()));
+        assert!(report.record("cbm", false, "no prompt".to_string()));
+        assert!(!report.record("cbm", false, "no prompt".to_string()));
+        assert!(!report.all_healthy());
+        assert_eq!(report.interpreters[1].consecutive_failures, 2);
+        
+        assert!(report.record("cbm", true, "max_turns".to_string()));
+        assert!(report.all_healthy());
+        assert!(report.to_prometheus().contains("trekbot_interpreter_up{interpreter=\"cbm\"} 1\n"));
+    }
+}
@@ -0,0 +1,173 @@
+use crate::json::Json;
+use crate::player::GameStats;
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::process::{Child, Command};
+
+/// Commands run when a benchmark ends, e.g. to send a custom notification, upload
+/// artifacts or start an interpreter bisect. Each runs with `sh -c`, so it can use pipes
+/// and redirection. What it needs comes in environment variables rather than being
+/// pasted into the command line, which leaves no quoting to get wrong:
+/// - TREKBOT_SUMMARY: path of a JSON summary of the run
+/// - TREKBOT_STATUS: "complete" or "failure"
+/// - TREKBOT_RESULTS_FILE: the --results-file, when one was saved
+///
+/// A hook that fails or runs past its timeout is logged and never fails the run. One that
+/// runs past it is killed along with everything it started, and the summary is removed
+/// once the hooks are done.
+#[derive(Debug, Clone)]
+pub struct Hooks {
+    /// Run after every benchmark that finishes
+    pub on_complete: Option<String>,
+    /// Run after a benchmark that stopped with an error or had games end in a BASIC
+    /// error or crash
+    pub on_failure: Option<String>,
+    pub timeout: Duration,
+    pub results_file: Option<String>,
+}
+
+impl Hooks {
+    pub fn is_empty(&self) -> bool {
+        self.on_complete.is_none() && self.on_failure.is_none()
+    }
+    
+    /// Run the hooks that apply to how the benchmark ended
+    pub async fn run(&self, run: &str, outcome: &Result<GameStats>) {
+        if self.is_empty() {
+            return;
+        }
+        let failed = match outcome {
+            Ok(stats) => stats.basic_errors + stats.crashes > 0,
+            Err(_) => true,
+        };
+        let status = if failed { "failure" } else { "complete" };
+        
+        let summary = match write_summary(run, status, outcome) {
+            Ok(summary) => summary,
+            Err(e) => {
+                log::warn!("Could not write the summary for hooks: {}", e);
+                return;
+            }
+        };
+        let hooks = [(outcome.is_ok(), &self.on_complete), (failed, &self.on_failure)];
+        for (applies, command) in hooks {
+            if let (true, Some(command)) = (applies, command) {
+                if let Err(e) = self.run_hook(command, status, &summary).await {
+                    log::warn!("Hook '{}' failed: {}", command, e);
+                }
+            }
+        }
+        if let Err(e) = std::fs::remove_file(&summary) {
+            log::debug!("Could not remove {}: {}", summary.display(), e);
+        }
+    }
+    
+    async fn run_hook(&self, command: &str, status: &str, summary: &Path) -> Result<()> {
+        let mut child = Command::new("sh");
+        child.arg("-c").arg(command)
+            .env("TREKBOT_SUMMARY", summary)
+            .env("TREKBOT_STATUS", status)
+            .kill_on_drop(true);
+        // Its own process group, so a timeout can kill the pipelines and background jobs
+        // the command started as well as sh
+        #[cfg(unix)]
+        child.process_group(0);
+        if let Some(results_file) = &self.results_file {
+            child.env("TREKBOT_RESULTS_FILE", results_file);
+        }
+        
+        let mut child = child.spawn().context("could not start sh")?;
+        match tokio::time::timeout(self.timeout, child.wait()).await {
+            Ok(status) => {
+                let status = status?;
+                if !status.success() {
+                    anyhow::bail!("exited with {}", status);
+                }
+                log::info!("Hook '{}' finished", command);
+                Ok(())
+            }
+            Err(_) => {
+                kill_group(&child);
+                child.kill().await.ok();
+                anyhow::bail!("killed after {}s", self.timeout.as_secs())
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+mod signal {
+    use std::ffi::c_int;
+    
+    pub const SIGKILL: c_int = 9;
+    
+    extern "C" {
+        pub fn kill(pid: c_int, signal: c_int) -> c_int;
+    }
+}
+
+/// Kill every process in the hook's process group
+#[cfg(unix)]
+fn kill_group(child: &Child) {
+    if let Some(pid) = child.id() {
+        // SAFETY: kill only sends a signal; a negative pid names the hook's own group
+        unsafe { signal::kill(-(pid as std::ffi::c_int), signal::SIGKILL) };
+    }
+}
+
+#[cfg(not(unix))]
+fn kill_group(_child: &Child) {}
+
+/// Save the run's summary for the hooks to read, in the temporary directory
+fn write_summary(run: &str, status: &str, outcome: &Result<GameStats>) -> Result<PathBuf> {
+    let mut summary = Json::object().with("run", run).with("status", status);
+    summary = match outcome {
+        Ok(stats) => summary.with("stats", stats.to_json()).with("crash_rate", stats.crash_rate()),
+        Err(e) => summary.with("error", format!("{:#}", e)),
+    };
+    let path = std::env::temp_dir().join(format!("trekbot-{}-{}.json", run, std::process::id()));
+    std::fs::write(&path, summary.to_string() + "\n").with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_write_summary() {
+        let path = write_summary("hooks-test", "failure", &Err(anyhow::anyhow!("interpreter not found"))).unwrap();
+        let summary = Json::parse(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(summary.get("status").and_then(Json::as_str), Some("failure"));
+        assert_eq!(summary.get("error").and_then(Json::as_str), Some("interpreter not found"));
+    }
+    
+    /// Whether a process is running, rather than gone or a zombie no one has reaped
+    #[cfg(target_os = "linux")]
+    fn is_alive(pid: &str) -> bool {
+        std::fs::read_to_string(format!("/proc/{}/stat", pid))
+            .is_ok_and(|stat| stat.rsplit_once(") ").is_some_and(|(_, rest)| !rest.starts_with('Z')))
+    }
+    
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_timeout_kills_process_group() {
+        let dir = std::env::temp_dir().join(format!("trekbot-hooks-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let hooks = Hooks {
+            on_complete: Some(format!("echo \"$TREKBOT_SUMMARY\" > {0}/summary; sleep 30 & echo $! > {0}/pid; wait", dir.display())),
+            on_failure: None,
+            timeout: Duration::from_millis(500),
+            results_file: None,
+        };
+        hooks.run("hooks-timeout-test", &Ok(GameStats::new())).await;
+        
+        let summary = std::fs::read_to_string(dir.join("summary")).unwrap();
+        let pid = std::fs::read_to_string(dir.join("pid")).unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+        assert!(!Path::new(summary.trim()).exists(), "{} was left behind", summary.trim());
+        assert!(!is_alive(pid.trim()), "sleep {} outlived the hook", pid.trim());
+    }
+}
@@ -1,12 +1,17 @@
+use crate::coverage::CoverageLaunch;
+use crate::metadata::InterpreterIdentity;
 use anyhow::Result;
-use super::{Interpreter, SubprocessInterpreter, is_game_prompt};
+use super::{Capabilities, Interpreter, Quirks, RestartMethod, ReadTimeout, ReadTimeouts, Sandbox, SubprocessInterpreter, WriteChunking};
+use std::path::PathBuf;
+use std::process::ExitStatus;
+use std::time::Duration;
 
 /// BasicRS interpreter implementation
 pub struct BasicRSInterpreter {
     subprocess: SubprocessInterpreter,
+    quirks: Quirks,
     basicrs_path: String,
-    coverage_file: Option<String>,
-    reset_coverage: bool,
+    coverage: Option<CoverageLaunch>,
 }
 
 impl BasicRSInterpreter {
@@ -14,99 +19,123 @@ impl BasicRSInterpreter {
         let default_path = "/Users/tomhill/RustroverProjects/BasicRS/target/debug/basic_rs".to_string();
         Self {
             subprocess: SubprocessInterpreter::new(),
+            quirks: Quirks::for_interpreter("basic-rs"),
             basicrs_path: basicrs_path.unwrap_or(default_path),
-            coverage_file: None,
-            reset_coverage: false,
+            coverage: None,
         }
     }
-    
-    pub fn set_coverage_file(&mut self, coverage_file: Option<String>) {
-        println!("🔍 Setting coverage file: {:?}", coverage_file);
-        self.coverage_file = coverage_file;
-    }
-    
-    pub fn set_reset_coverage(&mut self, reset: bool) {
-        self.reset_coverage = reset;
-    }
 }
 
 #[async_trait::async_trait]
 impl Interpreter for BasicRSInterpreter {
+    fn name(&self) -> &'static str {
+        "basic-rs"
+    }
+    
+    fn identity(&self) -> InterpreterIdentity {
+        InterpreterIdentity::probe(self.name(), &self.basicrs_path, &self.basicrs_path, &["--version"])
+    }
+    
+    fn quirks(&self) -> &Quirks {
+        &self.quirks
+    }
+    
+    fn quirks_mut(&mut self) -> &mut Quirks {
+        &mut self.quirks
+    }
+    
+    fn set_coverage(&mut self, launch: CoverageLaunch) {
+        log::debug!("Setting coverage arguments: {:?}", launch.program_args);
+        self.coverage = Some(launch);
+    }
+    
+    fn set_startup_timeout(&mut self, timeout: Duration) {
+        self.subprocess.set_startup_timeout(timeout);
+    }
+    
+    fn set_working_dir(&mut self, dir: PathBuf) {
+        self.subprocess.set_working_dir(dir);
+    }
+    
+    fn set_sandbox(&mut self, sandbox: Sandbox) {
+        self.subprocess.set_sandbox(sandbox);
+    }
+    
+    fn set_write_chunking(&mut self, chunking: WriteChunking) {
+        self.subprocess.set_write_chunking(chunking);
+    }
+    
+    fn set_read_timeouts(&mut self, timeouts: ReadTimeouts) {
+        self.subprocess.set_read_timeouts(timeouts);
+    }
+    
+    fn set_max_output_lines(&mut self, lines: usize) {
+        self.subprocess.set_max_output_lines(lines);
+    }
+    
     async fn launch(&mut self, program_path: &str) -> Result<()> {
         log::info!("Launching BasicRS interpreter with program: {}", program_path);
         
         // Build arguments for BasicRS
         let mut args = vec![program_path];
         
-        // Add coverage arguments if specified
-        let coverage_file = self.coverage_file.as_deref().unwrap_or("coverage.json");
-        args.push("--coverage-file");
-        args.push(coverage_file);
-        println!("🔍 Coverage file set to: {}", coverage_file);
-        println!("🔍 Full coverage path: {}", std::path::Path::new(coverage_file).canonicalize().unwrap_or_else(|_| coverage_file.into()).display());
+        // Add coverage arguments if specified; BasicRS always records coverage
+        let default_coverage = ["--coverage-file".to_string(), "coverage.json".to_string()];
+        let coverage_args = self.coverage.as_ref().map_or(&default_coverage[..], |c| &c.program_args[..]);
+        args.extend(coverage_args.iter().map(String::as_str));
         
-        if self.reset_coverage {
-            args.push("--reset-coverage");
-            println!("🔍 Coverage reset enabled");
-        }
-        
-        println!("🔍 BasicRS command: {} {:?}", self.basicrs_path, args);
+        log::debug!("BasicRS command: {} {:?}", self.basicrs_path, args);
         
         // Launch the BasicRS interpreter with the program and arguments
+        self.subprocess.set_env(self.quirks.env());
         self.subprocess.spawn_process(&self.basicrs_path, &args).await?;
         
-        // Read initial output until we get a prompt
-        let _initial_output = self.read_until_prompt().await?;
+        // Read initial output until we get a prompt, failing fast if none arrives
+        let startup_timeout = self.subprocess.startup_timeout();
+        let initial_output = match tokio::time::timeout(startup_timeout, self.read_until_prompt()).await {
+            Ok(output) => Some(output?),
+            Err(_) => None,
+        };
+        let quirks = &self.quirks;
+        self.subprocess.check_banner(initial_output.as_deref(), |line| quirks.is_prompt(line)).await?;
         
         Ok(())
     }
     
+    fn capabilities(&self) -> Capabilities {
+        Capabilities { restart: RestartMethod::Run }
+    }
+    
+    async fn restart(&mut self) -> Result<()> {
+        self.subprocess.restart_impl(self.name(), self.capabilities(), &self.quirks).await
+    }
+    
     async fn send_command(&mut self, command: &str) -> Result<()> {
         log::debug!("Sending command: {}", command);
         self.subprocess.write_line(command).await
     }
     
-    async fn read_line(&mut self) -> Result<Option<String>> {
-        self.subprocess.read_line_impl().await
+    async fn read_until_prompt(&mut self) -> Result<Vec<String>> {
+        let quirks = &self.quirks;
+        self.subprocess.read_until_prompt_impl(|line| quirks.is_prompt(line)).await
     }
     
-    async fn read_until_prompt(&mut self) -> Result<Vec<String>> {
-        use tokio::time::{timeout, Duration};
-        
-        let mut lines = Vec::new();
-        
-        loop {
-            match timeout(Duration::from_secs(2), self.read_line()).await {
-                Ok(Ok(Some(line))) => {
-                    lines.push(line.clone());
-                    log::debug!("Read line: {}", line);
-                    
-                    if is_game_prompt(&line) {
-                        log::debug!("Found game prompt: {}", line);
-                        break;
-                    }
-                }
-                Ok(Ok(None)) => {
-                    log::debug!("End of output reached");
-                    break;
-                }
-                Ok(Err(e)) => {
-                    return Err(e);
-                }
-                Err(_) => {
-                    log::debug!("Timeout reading line, stopping");
-                    break;
-                }
-            }
-        }
-        
-        Ok(lines)
+    fn read_timed_out(&self) -> Option<ReadTimeout> {
+        self.subprocess.read_timed_out()
     }
     
     fn is_running(&mut self) -> bool {
         self.subprocess.is_running_impl()
     }
     
+    fn channel_closed(&self) -> bool {
+        self.subprocess.channel_closed_impl()
+    }
+    
+    fn exit_status(&mut self) -> Option<ExitStatus> {
+        self.subprocess.exit_status_impl()
+    }
+    
     async fn terminate(&mut self) -> Result<()> {
         log::info!("Terminating BasicRS interpreter");
         self.subprocess.terminate_impl().await
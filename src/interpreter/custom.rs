@@ -0,0 +1,144 @@
+use crate::coverage::CoverageLaunch;
+use crate::metadata::InterpreterIdentity;
+use anyhow::Result;
+use super::{Capabilities, Interpreter, InterpreterProfile, Quirks, ReadTimeout, ReadTimeouts, Sandbox, SubprocessInterpreter, WriteChunking};
+use std::path::PathBuf;
+use std::process::ExitStatus;
+use std::time::Duration;
+
+/// An interpreter defined by a profile in the config file rather than in code
+pub struct CustomInterpreter {
+    subprocess: SubprocessInterpreter,
+    quirks: Quirks,
+    profile: InterpreterProfile,
+}
+
+impl CustomInterpreter {
+    pub fn new(profile: InterpreterProfile) -> Self {
+        let mut quirks = Quirks::for_interpreter(&profile.name);
+        for quirk in &profile.quirks {
+            quirks.add(quirk.clone());
+        }
+        Self {
+            subprocess: SubprocessInterpreter::new(),
+            quirks,
+            profile,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Interpreter for CustomInterpreter {
+    fn name(&self) -> &'static str {
+        "custom"
+    }
+    
+    fn identity(&self) -> InterpreterIdentity {
+        match &self.profile.version_args {
+            Some(args) => {
+                let args: Vec<&str> = args.iter().map(String::as_str).collect();
+                InterpreterIdentity::probe(&self.profile.name, &self.profile.command, &self.profile.command, &args)
+            }
+            None => InterpreterIdentity {
+                name: self.profile.name.clone(),
+                executable: self.profile.command.clone(),
+                version: None,
+            },
+        }
+    }
+    
+    fn quirks(&self) -> &Quirks {
+        &self.quirks
+    }
+    
+    fn quirks_mut(&mut self) -> &mut Quirks {
+        &mut self.quirks
+    }
+    
+    fn set_coverage(&mut self, _launch: CoverageLaunch) {
+        log::warn!("Interpreter '{}' is a profile; TrekBot can't record its coverage", self.profile.name);
+    }
+    
+    fn set_startup_timeout(&mut self, timeout: Duration) {
+        self.subprocess.set_startup_timeout(timeout);
+    }
+    
+    fn set_working_dir(&mut self, dir: PathBuf) {
+        self.subprocess.set_working_dir(dir);
+    }
+    
+    fn set_sandbox(&mut self, sandbox: Sandbox) {
+        self.subprocess.set_sandbox(sandbox);
+    }
+    
+    fn set_write_chunking(&mut self, chunking: WriteChunking) {
+        self.subprocess.set_write_chunking(chunking);
+    }
+    
+    fn set_read_timeouts(&mut self, timeouts: ReadTimeouts) {
+        self.subprocess.set_read_timeouts(timeouts);
+    }
+    
+    fn set_max_output_lines(&mut self, lines: usize) {
+        self.subprocess.set_max_output_lines(lines);
+    }
+    
+    async fn launch(&mut self, program_path: &str) -> Result<()> {
+        log::info!("Launching interpreter '{}' with program: {}", self.profile.name, program_path);
+        
+        let args = self.profile.program_args(program_path);
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        self.subprocess.set_env(self.quirks.env());
+        self.subprocess.spawn_process(&self.profile.command, &args).await?;
+        
+        // Read initial output until we get a prompt, failing fast if none arrives
+        let startup_timeout = self.subprocess.startup_timeout();
+        let initial_output = match tokio::time::timeout(startup_timeout, self.read_until_prompt()).await {
+            Ok(output) => Some(output?),
+            Err(_) => None,
+        };
+        let quirks = &self.quirks;
+        self.subprocess.check_banner(initial_output.as_deref(), |line| quirks.is_prompt(line)).await?;
+        
+        Ok(())
+    }
+    
+    fn capabilities(&self) -> Capabilities {
+        Capabilities { restart: self.profile.restart }
+    }
+    
+    async fn restart(&mut self) -> Result<()> {
+        self.subprocess.restart_impl(&self.profile.name, self.capabilities(), &self.quirks).await
+    }
+    
+    async fn send_command(&mut self, command: &str) -> Result<()> {
+        log::debug!("Sending command: {}", command);
+        self.subprocess.write_line(command).await
+    }
+    
+    async fn read_until_prompt(&mut self) -> Result<Vec<String>> {
+        let quirks = &self.quirks;
+        self.subprocess.read_until_prompt_impl(|line| quirks.is_prompt(line)).await
+    }
+    
+    fn read_timed_out(&self) -> Option<ReadTimeout> {
+        self.subprocess.read_timed_out()
+    }
+    
+    fn is_running(&mut self) -> bool {
+        self.subprocess.is_running_impl()
+    }
+    
+    fn channel_closed(&self) -> bool {
+        self.subprocess.channel_closed_impl()
+    }
+    
+    fn exit_status(&mut self) -> Option<ExitStatus> {
+        self.subprocess.exit_status_impl()
+    }
+    
+    async fn terminate(&mut self) -> Result<()> {
+        log::info!("Terminating interpreter '{}'", self.profile.name);
+        self.subprocess.terminate_impl().await
+    }
+}
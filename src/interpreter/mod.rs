@@ -1,39 +1,338 @@
+use crate::coverage::CoverageLaunch;
+use crate::game::{Line, DEFAULT_MAX_OUTPUT_LINES};
+use crate::metadata::InterpreterIdentity;
 use anyhow::Result;
 use tokio::process::Child;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, AsyncReadExt, BufReader};
-use tokio::process::{ChildStdin, ChildStdout};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
+use tokio::process::{ChildStderr, ChildStdout};
+use tokio::task::JoinHandle;
+use std::collections::VecDeque;
+use std::fmt;
+use std::path::PathBuf;
+use std::process::ExitStatus;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 pub mod basicrs;
+pub mod custom;
+pub mod profile;
+pub mod quirks;
+pub mod sandbox;
+pub mod stdin;
 pub mod trekbasic;
 pub mod trekbasicj;
 
+pub use profile::InterpreterProfile;
+pub use quirks::{Quirk, Quirks, Tolerance};
+pub use sandbox::Sandbox;
+pub use stdin::{StdinQueue, WriteChunking};
+
 /// Trait for communicating with different BASIC interpreters
 #[async_trait::async_trait]
 pub trait Interpreter {
+    /// Short name used for quirk lookup and reports, e.g. "basic-rs"
+    fn name(&self) -> &'static str;
+    
+    /// Which executable this is and its version, for run metadata
+    fn identity(&self) -> InterpreterIdentity;
+    
+    /// Known quirks of this interpreter, consulted by the prompt classifier and normalizer
+    fn quirks(&self) -> &Quirks;
+    
+    /// Mutable access to the quirks, e.g. to add ones given on the command line
+    fn quirks_mut(&mut self) -> &mut Quirks;
+    
+    /// Make the next launch record coverage (see CoverageProvider::configure)
+    fn set_coverage(&mut self, launch: CoverageLaunch);
+    
+    /// How long launch() waits for the first prompt before giving up
+    fn set_startup_timeout(&mut self, timeout: Duration);
+    
+    /// Directory the next launch runs in, instead of TrekBot's own
+    fn set_working_dir(&mut self, dir: PathBuf);
+    
+    /// Resource limits and isolation for the next launch
+    fn set_sandbox(&mut self, sandbox: Sandbox);
+    
+    /// How the next launch writes commands to stdin
+    fn set_write_chunking(&mut self, chunking: WriteChunking);
+    
+    /// How long read_until_prompt() waits for more output
+    fn set_read_timeouts(&mut self, timeouts: ReadTimeouts);
+    
+    /// Most lines read_until_prompt() keeps of one reply; earlier ones are dropped
+    fn set_max_output_lines(&mut self, lines: usize);
+    
     /// Launch the interpreter with the given BASIC program
     async fn launch(&mut self, program_path: &str) -> Result<()>;
     
+    /// What the backend supports beyond one game per launch
+    fn capabilities(&self) -> Capabilities;
+    
+    /// Start a new game in the running process the way capabilities() says, after a
+    /// game ended at the call for a new commander. Like launch(), returns once the
+    /// first prompt is read.
+    async fn restart(&mut self) -> Result<()>;
+    
     /// Send a command to the interpreter
     async fn send_command(&mut self, command: &str) -> Result<()>;
     
-    /// Read the next line of output from the interpreter
-    async fn read_line(&mut self) -> Result<Option<String>>;
-    
     /// Read all available output until a prompt is detected
     async fn read_until_prompt(&mut self) -> Result<Vec<String>>;
     
+    /// Which timeout ended the last read_until_prompt(), if one did
+    fn read_timed_out(&self) -> Option<ReadTimeout>;
+    
     /// Check if the interpreter process is still running
     fn is_running(&mut self) -> bool;
     
+    /// Whether the interpreter's stdout has reached EOF
+    fn channel_closed(&self) -> bool;
+    
+    /// Exit status of the interpreter process, once it has exited
+    fn exit_status(&mut self) -> Option<ExitStatus>;
+    
     /// Terminate the interpreter process
     async fn terminate(&mut self) -> Result<()>;
 }
 
+/// Lets a player borrow an interpreter that outlives it, to restart it for the next game
+#[async_trait::async_trait]
+impl<I: Interpreter + Send + ?Sized> Interpreter for &mut I {
+    fn name(&self) -> &'static str {
+        (**self).name()
+    }
+    
+    fn identity(&self) -> InterpreterIdentity {
+        (**self).identity()
+    }
+    
+    fn quirks(&self) -> &Quirks {
+        (**self).quirks()
+    }
+    
+    fn quirks_mut(&mut self) -> &mut Quirks {
+        (**self).quirks_mut()
+    }
+    
+    fn set_coverage(&mut self, launch: CoverageLaunch) {
+        (**self).set_coverage(launch)
+    }
+    
+    fn set_startup_timeout(&mut self, timeout: Duration) {
+        (**self).set_startup_timeout(timeout)
+    }
+    
+    fn set_working_dir(&mut self, dir: PathBuf) {
+        (**self).set_working_dir(dir)
+    }
+    
+    fn set_sandbox(&mut self, sandbox: Sandbox) {
+        (**self).set_sandbox(sandbox)
+    }
+    
+    fn set_write_chunking(&mut self, chunking: WriteChunking) {
+        (**self).set_write_chunking(chunking)
+    }
+    
+    fn set_read_timeouts(&mut self, timeouts: ReadTimeouts) {
+        (**self).set_read_timeouts(timeouts)
+    }
+    
+    fn set_max_output_lines(&mut self, lines: usize) {
+        (**self).set_max_output_lines(lines)
+    }
+    
+    async fn launch(&mut self, program_path: &str) -> Result<()> {
+        (**self).launch(program_path).await
+    }
+    
+    fn capabilities(&self) -> Capabilities {
+        (**self).capabilities()
+    }
+    
+    async fn restart(&mut self) -> Result<()> {
+        (**self).restart().await
+    }
+    
+    async fn send_command(&mut self, command: &str) -> Result<()> {
+        (**self).send_command(command).await
+    }
+    
+    async fn read_until_prompt(&mut self) -> Result<Vec<String>> {
+        (**self).read_until_prompt().await
+    }
+    
+    fn read_timed_out(&self) -> Option<ReadTimeout> {
+        (**self).read_timed_out()
+    }
+    
+    fn is_running(&mut self) -> bool {
+        (**self).is_running()
+    }
+    
+    fn channel_closed(&self) -> bool {
+        (**self).channel_closed()
+    }
+    
+    fn exit_status(&mut self) -> Option<ExitStatus> {
+        (**self).exit_status()
+    }
+    
+    async fn terminate(&mut self) -> Result<()> {
+        (**self).terminate().await
+    }
+}
+
+/// A batch of interpreter output as seen by the Player
+#[derive(Debug, Clone, PartialEq)]
+pub enum InterpreterEvent {
+    /// Output up to (and including) the next prompt, or up to a read timeout
+    Output(Vec<Line>),
+    /// stdout closed; holds whatever arrived first, possibly ending in a partial line
+    ChannelClosed(Vec<Line>),
+}
+
+/// Default time launch() waits for the interpreter to print its first prompt
+pub const STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long read_until_prompt() waits for more output, told apart by whether anything
+/// arrived since the last prompt
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReadTimeouts {
+    /// Some output but no prompt, most likely a prompt TrekBot doesn't recognize
+    pub partial: Duration,
+    /// No output at all, most likely a hung interpreter
+    pub hang: Duration,
+}
+
+impl ReadTimeouts {
+    /// No timeouts: a read ends only at a prompt or the end of the output, so the game
+    /// plays out the same however fast or loaded the machine is
+    pub const UNLIMITED: Self = Self { partial: Duration::MAX, hang: Duration::MAX };
+    
+    pub fn is_unlimited(&self) -> bool {
+        *self == Self::UNLIMITED
+    }
+}
+
+impl Default for ReadTimeouts {
+    fn default() -> Self {
+        Self { partial: Duration::from_secs(5), hang: Duration::from_secs(30) }
+    }
+}
+
+/// Which of the ReadTimeouts expired
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReadTimeout {
+    Partial,
+    Hang,
+}
+
+/// How a running interpreter can start the program over without a new process
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RestartMethod {
+    /// It can't; the program is launched again
+    #[default]
+    Relaunch,
+    /// Answer the game's call for a new commander with AYE, which starts a new game
+    Volunteer,
+    /// Decline the call so the program ends, then RUN it at the interpreter's own prompt
+    Run,
+}
+
+impl RestartMethod {
+    /// Lines typed at the call for a new commander
+    pub fn lines(&self) -> &'static [&'static str] {
+        match self {
+            RestartMethod::Relaunch => &[],
+            RestartMethod::Volunteer => &["AYE"],
+            RestartMethod::Run => &["NO", "RUN"],
+        }
+    }
+}
+
+impl std::str::FromStr for RestartMethod {
+    type Err = anyhow::Error;
+    
+    fn from_str(text: &str) -> Result<Self> {
+        match text {
+            "relaunch" => Ok(RestartMethod::Relaunch),
+            "volunteer" => Ok(RestartMethod::Volunteer),
+            "run" => Ok(RestartMethod::Run),
+            _ => anyhow::bail!("Unknown restart method '{}', expected relaunch, volunteer or run", text),
+        }
+    }
+}
+
+/// What an interpreter backend supports
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Capabilities {
+    pub restart: RestartMethod,
+}
+
+/// Why an interpreter could not be started
+#[derive(Debug)]
+pub enum LaunchError {
+    /// The interpreter executable could not be started at all
+    SpawnFailed {
+        command: String,
+        error: std::io::Error,
+    },
+    /// The interpreter started but never printed a prompt, e.g. a wrong JAR path
+    NoBanner {
+        command: String,
+        timeout: Duration,
+        exit_status: Option<ExitStatus>,
+        stderr: String,
+    },
+}
+
+impl fmt::Display for LaunchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LaunchError::SpawnFailed { command, error } => {
+                write!(f, "Failed to start interpreter '{}': {}", command, error)
+            }
+            LaunchError::NoBanner { command, timeout, exit_status, stderr } => {
+                match exit_status {
+                    Some(status) => write!(f, "Interpreter '{}' exited ({}) before printing a prompt", command, status)?,
+                    None => write!(f, "Interpreter '{}' printed no prompt within {:.1} seconds", command, timeout.as_secs_f64())?,
+                }
+                if stderr.trim().is_empty() {
+                    write!(f, " (no stderr output)")
+                } else {
+                    write!(f, "\nstderr:\n{}", stderr.trim_end())
+                }
+            }
+        }
+    }
+}
+
+impl std::error::Error for LaunchError {}
+
 /// Base structure for subprocess-based interpreters
 pub struct SubprocessInterpreter {
     process: Option<Child>,
-    stdin: Option<ChildStdin>,
+    stdin: Option<StdinQueue>,
     stdout: Option<ChildStdout>,
+    exit_status: Option<ExitStatus>,
+    stdout_closed: bool,
+    command: String,
+    stderr: Arc<Mutex<String>>,
+    stderr_task: Option<JoinHandle<()>>,
+    startup_timeout: Duration,
+    working_dir: Option<PathBuf>,
+    sandbox: Sandbox,
+    env: Vec<(String, String)>,
+    chunking: WriteChunking,
+    read_timeouts: ReadTimeouts,
+    timed_out: Option<ReadTimeout>,
+    /// Lines of one reply kept by read_until_prompt_impl(), so an interpreter printing
+    /// without ever prompting can't grow it without bound
+    max_output_lines: usize,
+    /// Start of a line still being read, kept when a read times out
+    partial_line: String,
 }
 
 impl SubprocessInterpreter {
@@ -42,74 +341,180 @@ impl SubprocessInterpreter {
             process: None,
             stdin: None,
             stdout: None,
+            exit_status: None,
+            stdout_closed: false,
+            command: String::new(),
+            stderr: Arc::new(Mutex::new(String::new())),
+            stderr_task: None,
+            startup_timeout: STARTUP_TIMEOUT,
+            working_dir: None,
+            sandbox: Sandbox::default(),
+            env: Vec::new(),
+            chunking: WriteChunking::default(),
+            read_timeouts: ReadTimeouts::default(),
+            timed_out: None,
+            max_output_lines: DEFAULT_MAX_OUTPUT_LINES,
+            partial_line: String::new(),
         }
     }
     
+    pub fn set_startup_timeout(&mut self, timeout: Duration) {
+        self.startup_timeout = timeout;
+    }
+    
+    pub fn startup_timeout(&self) -> Duration {
+        self.startup_timeout
+    }
+    
+    pub fn set_working_dir(&mut self, dir: PathBuf) {
+        self.working_dir = Some(dir);
+    }
+    
+    pub fn set_sandbox(&mut self, sandbox: Sandbox) {
+        self.sandbox = sandbox;
+    }
+    
+    pub fn set_write_chunking(&mut self, chunking: WriteChunking) {
+        self.chunking = chunking;
+    }
+    
+    pub fn set_read_timeouts(&mut self, timeouts: ReadTimeouts) {
+        self.read_timeouts = timeouts;
+    }
+    
+    pub fn set_max_output_lines(&mut self, lines: usize) {
+        self.max_output_lines = lines.max(1);
+    }
+    
+    pub fn read_timed_out(&self) -> Option<ReadTimeout> {
+        self.timed_out
+    }
+    
+    /// Environment variables for the next spawned process, e.g. from Env quirks
+    pub fn set_env(&mut self, env: Vec<(String, String)>) {
+        self.env = env;
+    }
+    
     pub async fn spawn_process(&mut self, command: &str, args: &[&str]) -> Result<()> {
         use tokio::process::Command;
         
-        let mut cmd = Command::new(command);
-        cmd.args(args);
+        let (command, args) = self.sandbox.wrap(command, args, self.working_dir.as_deref());
+        let mut cmd = Command::new(&command);
+        cmd.args(&args);
         cmd.stdin(std::process::Stdio::piped());
         cmd.stdout(std::process::Stdio::piped());
         cmd.stderr(std::process::Stdio::piped());
+        if let Some(dir) = &self.working_dir {
+            cmd.current_dir(dir);
+        }
+        cmd.envs(self.env.iter().map(|(name, value)| (name, value)));
+        
+        self.command = format!("{} {}", command, args.join(" "));
+        if self.sandbox.is_enabled() {
+            log::info!("Sandboxed interpreter command: {}", self.command);
+        }
         
-        let mut child = cmd.spawn()?;
+        let mut child = cmd.spawn().map_err(|error| LaunchError::SpawnFailed {
+            command: self.command.clone(),
+            error,
+        })?;
         
         let stdin = child.stdin.take().unwrap();
         let stdout = child.stdout.take().unwrap();
+        let stderr = child.stderr.take().unwrap();
         
         self.process = Some(child);
-        self.stdin = Some(stdin);
+        self.stdin = Some(StdinQueue::new(stdin, self.chunking));
         self.stdout = Some(stdout);
+        self.exit_status = None;
+        self.stdout_closed = false;
+        self.stderr = Arc::new(Mutex::new(String::new()));
+        self.stderr_task = Some(Self::capture_stderr(stderr, self.stderr.clone()));
         
         Ok(())
     }
     
+    /// Collect stderr in the background so it can be reported if the interpreter fails
+    fn capture_stderr(stderr: ChildStderr, captured: Arc<Mutex<String>>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(stderr).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                log::debug!("Interpreter stderr: {}", line);
+                let mut captured = captured.lock().unwrap();
+                captured.push_str(&line);
+                captured.push('\n');
+            }
+        })
+    }
+    
+    /// Everything the interpreter has written to stderr so far
+    pub fn captured_stderr(&self) -> String {
+        self.stderr.lock().unwrap().clone()
+    }
+    
+    /// Check the output read during launch() contains a prompt, failing with
+    /// a NoBanner error (including captured stderr) if it doesn't
+    pub async fn check_banner(&mut self, initial_output: Option<&[String]>, is_prompt: impl Fn(&str) -> bool) -> Result<()> {
+        let found_prompt = initial_output.is_some_and(|lines| lines.iter().any(|line| is_prompt(line)));
+        if found_prompt {
+            return Ok(());
+        }
+        
+        // If the process is gone, give the stderr reader a moment to drain what it wrote
+        let exited = !self.is_running_impl();
+        if let (true, Some(task)) = (exited, self.stderr_task.take()) {
+            let _ = tokio::time::timeout(Duration::from_secs(1), task).await;
+        }
+        
+        Err(LaunchError::NoBanner {
+            command: self.command.clone(),
+            timeout: self.startup_timeout,
+            exit_status: self.exit_status,
+            stderr: self.captured_stderr(),
+        }
+        .into())
+    }
+    
+    /// Start the program over in the running process the way `capabilities` says, then
+    /// read up to the first prompt as launch() does, failing the same way if none arrives.
+    /// Interpreter::restart for every backend that runs a subprocess.
+    pub async fn restart_impl(&mut self, name: &str, capabilities: Capabilities, quirks: &Quirks) -> Result<()> {
+        let method = capabilities.restart;
+        if method == RestartMethod::Relaunch || !self.is_running_impl() {
+            anyhow::bail!("Interpreter '{}' can't be restarted in place", self.command);
+        }
+        log::info!("Restarting the program in the running {} interpreter ({:?})", name, method);
+        for line in method.lines() {
+            self.write_line(line).await?;
+        }
+        let is_prompt = |line: &str| quirks.is_prompt(line);
+        let initial_output = match tokio::time::timeout(self.startup_timeout, self.read_until_prompt_impl(&is_prompt)).await {
+            Ok(output) => Some(output?),
+            Err(_) => None,
+        };
+        self.check_banner(initial_output.as_deref(), is_prompt).await
+    }
+    
+    /// Queue a line for the interpreter's stdin. Fails with a WriteError if the
+    /// interpreter stops reading its input or the pipe has closed.
     pub async fn write_line(&mut self, line: &str) -> Result<()> {
-        if let Some(stdin) = &mut self.stdin {
-            match stdin.write_all(line.as_bytes()).await {
-                Ok(_) => {
-                    match stdin.write_all(b"\n").await {
-                        Ok(_) => {
-                            match stdin.flush().await {
-                                Ok(_) => Ok(()),
-                                Err(e) => {
-                                    log::error!("Failed to flush stdin: {}", e);
-                                    // Check if the process has exited
-                                    if !self.is_running_impl() {
-                                        log::error!("Process has already exited, cannot send more commands");
-                                    }
-                                    Err(e.into())
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            log::error!("Failed to write newline to stdin: {}", e);
-                            if !self.is_running_impl() {
-                                log::error!("Process has already exited, cannot send more commands");
-                            }
-                            Err(e.into())
-                        }
-                    }
-                }
-                Err(e) => {
-                    log::error!("Failed to write command '{}' to stdin: {}", line, e);
-                    if !self.is_running_impl() {
-                        log::error!("Process has already exited, cannot send more commands");
-                    }
-                    Err(e.into())
-                }
+        let result = match &self.stdin {
+            Some(stdin) => stdin.send(line).await,
+            None => {
+                log::error!("No stdin available for writing");
+                return Ok(());
             }
-        } else {
-            log::error!("No stdin available for writing");
-            Ok(())
+        };
+        if result.is_err() && !self.is_running_impl() {
+            log::error!("Process has already exited, cannot send more commands");
         }
+        result
     }
     
     pub async fn read_line_impl(&mut self) -> Result<Option<String>> {
         if let Some(stdout) = &mut self.stdout {
-            let mut buffer = String::new();
+            // The line is built in partial_line so a read cancelled by a timeout loses nothing
+            let buffer = &mut self.partial_line;
             let mut byte_buffer = [0u8; 1];
             
             loop {
@@ -117,13 +522,14 @@ impl SubprocessInterpreter {
                     Ok(0) => {
                         // EOF - process has likely terminated
                         log::debug!("EOF reached while reading from process");
+                        self.stdout_closed = true;
                         if !self.is_running_impl() {
                             log::warn!("Process has terminated while reading output");
                         }
-                        if buffer.is_empty() {
+                        if self.partial_line.is_empty() {
                             return Ok(None);
                         } else {
-                            return Ok(Some(buffer));
+                            return Ok(Some(std::mem::take(&mut self.partial_line)));
                         }
                     }
                     Ok(_) => {
@@ -135,13 +541,13 @@ impl SubprocessInterpreter {
                             if buffer.ends_with('\r') {
                                 buffer.pop();
                             }
-                            return Ok(Some(buffer));
+                            return Ok(Some(std::mem::take(buffer)));
                         }
                         
                         // Check for prompt character without newline
                         if ch == '?' {
                             buffer.push(ch);
-                            return Ok(Some(buffer));
+                            return Ok(Some(std::mem::take(buffer)));
                         }
                         
                         // Regular character
@@ -161,6 +567,70 @@ impl SubprocessInterpreter {
         }
     }
     
+    /// Read lines up to and including the next prompt. Gives up after the partial timeout
+    /// once anything has arrived, or the hang timeout if nothing has, returning what was
+    /// read so far with the start of an unfinished line as its last line. Only the last
+    /// max_output_lines lines are kept.
+    pub async fn read_until_prompt_impl(&mut self, is_prompt: impl Fn(&str) -> bool) -> Result<Vec<String>> {
+        let mut lines = VecDeque::new();
+        let mut dropped = 0;
+        self.timed_out = None;
+        
+        loop {
+            let (limit, timeout) = if lines.is_empty() && self.partial_line.is_empty() {
+                (self.read_timeouts.hang, ReadTimeout::Hang)
+            } else {
+                (self.read_timeouts.partial, ReadTimeout::Partial)
+            };
+            let read = if self.read_timeouts.is_unlimited() {
+                Ok(self.read_line_impl().await)
+            } else {
+                tokio::time::timeout(limit, self.read_line_impl()).await
+            };
+            match read {
+                Ok(Ok(Some(line))) => {
+                    log::debug!("Read line: {}", line);
+                    let found = is_prompt(&line);
+                    if lines.len() == self.max_output_lines {
+                        lines.pop_front();
+                        dropped += 1;
+                    }
+                    lines.push_back(line);
+                    if found {
+                        log::debug!("Found game prompt");
+                        break;
+                    }
+                }
+                Ok(Ok(None)) => {
+                    log::debug!("End of output reached");
+                    break;
+                }
+                Ok(Err(e)) => {
+                    return Err(e);
+                }
+                Err(_) => {
+                    // The start of a line may have arrived while waiting for the first one
+                    let timeout = if self.partial_line.is_empty() { timeout } else { ReadTimeout::Partial };
+                    log::debug!("Timeout reading line ({:?}), stopping", timeout);
+                    if !self.partial_line.is_empty() {
+                        lines.push_back(std::mem::take(&mut self.partial_line));
+                        if lines.len() > self.max_output_lines {
+                            lines.pop_front();
+                            dropped += 1;
+                        }
+                    }
+                    self.timed_out = Some(timeout);
+                    break;
+                }
+            }
+        }
+        
+        if dropped > 0 {
+            log::warn!("Kept the last {} lines of the interpreter's output, {} earlier ones dropped", lines.len(), dropped);
+        }
+        Ok(lines.into())
+    }
+    
     pub fn is_running_impl(&mut self) -> bool {
         if let Some(process) = &mut self.process {
             // For tokio::process::Child, we can use try_wait to check if the process has exited
@@ -169,6 +639,7 @@ impl SubprocessInterpreter {
                 Ok(Some(exit_status)) => {
                     // Process has exited - log the exit code
                     log::warn!("BasicRS process has exited with status: {:?}", exit_status);
+                    self.exit_status = Some(exit_status);
                     false
                 }
                 Ok(None) => true,     // Process is still running
@@ -182,12 +653,28 @@ impl SubprocessInterpreter {
         }
     }
     
+    pub fn channel_closed_impl(&self) -> bool {
+        self.stdout_closed
+    }
+    
+    pub fn exit_status_impl(&mut self) -> Option<ExitStatus> {
+        if self.exit_status.is_none() {
+            self.is_running_impl();
+        }
+        self.exit_status
+    }
+    
     pub async fn terminate_impl(&mut self) -> Result<()> {
         if let Some(mut process) = self.process.take() {
             // First try to send a quit command to allow graceful shutdown
             if let Err(e) = self.write_line("XXX").await {
                 log::debug!("Failed to send quit command: {}", e);
             }
+            if let Some(stdin) = &self.stdin {
+                if !stdin.flush(Duration::from_millis(500)).await {
+                    log::debug!("{} commands still queued at shutdown", stdin.pending());
+                }
+            }
             
             // Wait a bit for graceful shutdown
             tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
@@ -195,6 +682,7 @@ impl SubprocessInterpreter {
             // Check if process has exited gracefully
             if let Ok(Some(exit_status)) = process.try_wait() {
                 log::debug!("Process exited gracefully with status: {:?}", exit_status);
+                self.exit_status = Some(exit_status);
             } else {
                 // Process hasn't exited, kill it
                 log::debug!("Process didn't exit gracefully, killing it");
@@ -204,6 +692,9 @@ impl SubprocessInterpreter {
         }
         self.stdin = None;
         self.stdout = None;
+        if let Some(task) = self.stderr_task.take() {
+            task.abort();
+        }
         Ok(())
     }
 }
@@ -232,16 +723,8 @@ pub const GAME_PROMPTS: &[&str] = &[
 pub fn is_game_prompt(line: &str) -> bool {
     let line = line.trim();
     
-    // Skip help menu lines - these are informational, not prompts
-    if line.contains("NAV  (TO SET COURSE)") ||
-       line.contains("SRS  (FOR SHORT RANGE SENSOR SCAN)") ||
-       line.contains("LRS  (FOR LONG RANGE SENSOR SCAN)") ||
-       line.contains("PHA  (TO FIRE PHASERS)") ||
-       line.contains("TOR  (TO FIRE PHOTON TORPEDOES)") ||
-       line.contains("SHE  (TO RAISE OR LOWER SHIELDS)") ||
-       line.contains("DAM  (FOR DAMAGE CONTROL REPORTS)") ||
-       line.contains("COM  (TO CALL ON LIBRARY-COMPUTER)") ||
-       line.contains("XXX  (TO RESIGN YOUR COMMAND)") {
+    // Menu headers and entries come before the real prompt, which ends the block
+    if crate::game::is_menu_line(line) {
         return false;
     }
     
@@ -313,6 +796,79 @@ pub fn is_game_prompt(line: &str) -> bool {
     false
 }
 
+/// Text of every INPUT statement in the program, i.e. the prompts a line can end with
+pub const INPUT_PROMPTS: &[&str] = &[
+    "COMMAND",
+    "COURSE (0-9)",
+    "WARP FACTOR",
+    "NUMBER OF UNITS TO FIRE",
+    "PHOTON TORPEDO COURSE (1-9)",
+    "NUMBER OF UNITS TO SHIELDS",
+    "WILL YOU AUTHORIZE THE REPAIR ORDER (Y/N)",
+    "LET HIM STEP FORWARD AND ENTER 'AYE'",
+    "COMPUTER ACTIVE AND AWAITING COMMAND",
+    "INITIAL COORDINATES (X,Y)",
+    "FINAL COORDINATES (X,Y)",
+];
+
+/// Split a line that mixes status text with a trailing prompt, e.g.
+/// "ENERGY AVAILABLE = 3000 NUMBER OF UNITS TO SHIELDS?", into the status
+/// fragment and the prompt. Lines that are only a prompt, or no prompt at all,
+/// come back unchanged with no status.
+pub fn split_prompt_line(line: &str) -> (Option<String>, String) {
+    let trimmed = line.trim_end();
+    if !trimmed.ends_with('?') {
+        return (None, line.to_string());
+    }
+    
+    // Earliest prompt wins; for prompts starting at the same place, the longest
+    let start = INPUT_PROMPTS.iter()
+        .filter_map(|prompt| trimmed.find(prompt).map(|pos| (pos, prompt.len())))
+        .min_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)))
+        .map(|(pos, _)| pos);
+    
+    match start {
+        Some(pos) if !trimmed[..pos].trim().is_empty() => {
+            let status = trimmed[..pos].trim_end().to_string();
+            let prompt = trimmed[pos..].to_string();
+            (Some(status), prompt)
+        }
+        _ => (None, line.to_string()),
+    }
+}
+
+/// Apply split_prompt_line to a batch of output, so the status fragment and the
+/// prompt end up on separate lines before state parsing and prompt classification
+pub fn split_prompt_lines(output: Vec<String>) -> Vec<String> {
+    let mut lines = Vec::with_capacity(output.len());
+    for line in output {
+        match split_prompt_line(&line) {
+            (Some(status), prompt) => {
+                lines.push(status);
+                lines.push(prompt);
+            }
+            (None, line) => lines.push(line),
+        }
+    }
+    lines
+}
+
+/// Check if a line looks like an error reported by the BASIC interpreter itself,
+/// rather than text printed by the game
+pub fn is_basic_error(line: &str) -> bool {
+    let line = line.trim().to_uppercase();
+    
+    // The game prints its own "FATAL ERROR" when the ship is stranded
+    if line.contains("FATAL ERROR") {
+        return false;
+    }
+    
+    line.contains("ERROR")
+        || (line.starts_with('?') && line.len() > 1)
+        || line.contains("ILLEGAL FUNCTION CALL")
+        || line.contains("UNDEFINED LINE")
+}
+
 /// Check if we should send an initial response to start the game
 pub fn needs_initial_response(line: &str) -> bool {
     let line = line.trim().to_uppercase();
@@ -321,4 +877,113 @@ pub fn needs_initial_response(line: &str) -> bool {
         || line.contains("WHEN READY")
         || (line.contains("COMMAND") && !line.contains("="))
         || line.contains("INPUT")
-} 
\ No newline at end of file
+} 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    /// Mixed status/prompt lines as printed by each interpreter
+    const BASICRS_FIXTURES: &[&str] = &[
+        "ENERGY AVAILABLE = 3000 NUMBER OF UNITS TO SHIELDS?",
+        "ENERGY AVAILABLE = 2750 UNITS NUMBER OF UNITS TO FIRE?",
+    ];
+    const TREKBASIC_FIXTURES: &[&str] = &[
+        "ENERGY AVAILABLE = 3000NUMBER OF UNITS TO SHIELDS?",
+        "PHASERS LOCKED ON TARGET;  ENERGY AVAILABLE = 2750 UNITS NUMBER OF UNITS TO FIRE?",
+    ];
+    const TREKBASICJ_FIXTURES: &[&str] = &[
+        "ENERGY AVAILABLE = 3000.0 NUMBER OF UNITS TO SHIELDS? ",
+        "PHASERS LOCKED ON TARGET; ENERGY AVAILABLE = 2750.0 UNITS NUMBER OF UNITS TO FIRE?",
+    ];
+    
+    #[test]
+    fn test_restart_methods() {
+        assert_eq!(basicrs::BasicRSInterpreter::new(None).capabilities().restart, RestartMethod::Run);
+        assert_eq!(trekbasic::TrekBasicInterpreter::new(None, None).capabilities().restart, RestartMethod::Relaunch);
+        assert_eq!(trekbasicj::TrekBasicJInterpreter::new(None, None).capabilities().restart, RestartMethod::Relaunch);
+        assert_eq!(RestartMethod::Run.lines(), ["NO", "RUN"]);
+    }
+    
+    #[test]
+    fn test_split_fixtures_from_all_interpreters() {
+        for fixtures in [BASICRS_FIXTURES, TREKBASIC_FIXTURES, TREKBASICJ_FIXTURES] {
+            for line in fixtures {
+                let (status, prompt) = split_prompt_line(line);
+                let status = status.expect(line);
+                assert!(status.contains("ENERGY AVAILABLE ="), "{}", line);
+                assert!(!is_game_prompt(&status), "{}", line);
+                assert!(prompt.starts_with("NUMBER OF UNITS TO"), "{}", line);
+                assert!(is_game_prompt(&prompt), "{}", line);
+            }
+        }
+    }
+    
+    #[test]
+    fn test_split_leaves_plain_lines_alone() {
+        assert_eq!(split_prompt_line("COMMAND?"), (None, "COMMAND?".to_string()));
+        assert_eq!(
+            split_prompt_line("COMPUTER ACTIVE AND AWAITING COMMAND?"),
+            (None, "COMPUTER ACTIVE AND AWAITING COMMAND?".to_string())
+        );
+        assert_eq!(split_prompt_line("  INITIAL COORDINATES (X,Y)?").0, None);
+        assert_eq!(split_prompt_line("ENERGY AVAILABLE = 3000 UNITS").0, None);
+    }
+    
+    #[test]
+    fn test_split_prompt_lines() {
+        let output = vec![
+            "SHIELD CONTROL".to_string(),
+            "ENERGY AVAILABLE = 3000 NUMBER OF UNITS TO SHIELDS?".to_string(),
+        ];
+        assert_eq!(
+            split_prompt_lines(output),
+            vec!["SHIELD CONTROL", "ENERGY AVAILABLE = 3000", "NUMBER OF UNITS TO SHIELDS?"]
+        );
+    }
+    
+    #[tokio::test]
+    async fn test_read_keeps_last_output_lines() {
+        let mut subprocess = SubprocessInterpreter::new();
+        subprocess.set_max_output_lines(3);
+        subprocess.spawn_process("sh", &["-c", "for i in 1 2 3 4 5 6; do echo LINE $i; done; echo 'COMMAND?'; read x"]).await.unwrap();
+        let lines = subprocess.read_until_prompt_impl(is_game_prompt).await.unwrap();
+        subprocess.terminate_impl().await.unwrap();
+        assert_eq!(lines, ["LINE 5", "LINE 6", "COMMAND?"]);
+    }
+    
+    #[tokio::test]
+    async fn test_read_timeouts() {
+        let cases = [("printf 'ABC'; exec sleep 5", ReadTimeout::Partial, vec!["ABC"]), ("exec sleep 5", ReadTimeout::Hang, vec![])];
+        for (script, timeout, expected) in cases {
+            let mut subprocess = SubprocessInterpreter::new();
+            subprocess.set_read_timeouts(ReadTimeouts { partial: Duration::from_millis(200), hang: Duration::from_millis(500) });
+            subprocess.spawn_process("sh", &["-c", script]).await.unwrap();
+            let lines = subprocess.read_until_prompt_impl(is_game_prompt).await.unwrap();
+            assert_eq!(subprocess.read_timed_out(), Some(timeout), "{}", script);
+            subprocess.terminate_impl().await.unwrap();
+            assert_eq!(lines, expected, "{}", script);
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_no_banner_reports_stderr() {
+        let mut subprocess = SubprocessInterpreter::new();
+        subprocess.spawn_process("sh", &["-c", "echo 'LOADING'; echo 'Unable to access jarfile' >&2; exit 3"]).await.unwrap();
+        let output = subprocess.read_until_prompt_impl(is_game_prompt).await.unwrap();
+        assert_eq!(output, ["LOADING"]);
+        while subprocess.is_running_impl() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        
+        let error = subprocess.check_banner(Some(&output), is_game_prompt).await.unwrap_err();
+        match error.downcast_ref::<LaunchError>() {
+            Some(LaunchError::NoBanner { exit_status, stderr, .. }) => {
+                assert_eq!(exit_status.and_then(|status| status.code()), Some(3));
+                assert_eq!(stderr, "Unable to access jarfile\n");
+            }
+            other => panic!("expected NoBanner, got {:?}", other),
+        }
+        assert!(error.to_string().contains("before printing a prompt\nstderr:\nUnable to access jarfile"));
+    }
+}
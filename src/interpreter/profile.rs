@@ -0,0 +1,124 @@
+use crate::json::Json;
+use anyhow::{Context, Result};
+use std::path::Path;
+use super::{Quirk, RestartMethod};
+
+/// Config file read for interpreter profiles when --config isn't given
+pub const DEFAULT_CONFIG: &str = "trekbot.json";
+
+/// Stands for the BASIC program's path in a profile's arguments
+const PROGRAM_PLACEHOLDER: &str = "{program}";
+
+/// How TrekBot talks to an interpreter
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Transport {
+    /// Commands on stdin, output read from stdout
+    Stdio,
+}
+
+/// A named interpreter from the config file, so new interpreters can be added without
+/// code changes:
+///
+/// ```json
+/// {"interpreters": [
+///   {"name": "cbm", "command": "/opt/cbmbasic/cbmbasic", "args": ["{program}"],
+///    "transport": "stdio", "quirks": ["extra-blank-lines", "numeric-rounding=6"]}
+/// ]}
+/// ```
+///
+/// The program path replaces `{program}` in the arguments, or is appended when there is
+/// none. `version_args`, if given, are used to ask the command for its version, and
+/// `restart` ("volunteer", "run" or "relaunch", the default) says how a new game is
+/// started in the same process.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InterpreterProfile {
+    pub name: String,
+    pub command: String,
+    pub args: Vec<String>,
+    pub transport: Transport,
+    pub quirks: Vec<Quirk>,
+    pub version_args: Option<Vec<String>>,
+    pub restart: RestartMethod,
+}
+
+impl InterpreterProfile {
+    /// The profile called `name` in the config file at `path`
+    pub fn find(path: &str, name: &str) -> Result<Self> {
+        if !Path::new(path).exists() {
+            anyhow::bail!("Unknown interpreter '{}': not built in, and there is no config file {} to define it", name, path);
+        }
+        load_profiles(path)?.into_iter()
+            .find(|profile| profile.name == name)
+            .with_context(|| format!("Unknown interpreter '{}': not built in or defined in {}", name, path))
+    }
+    
+    /// Arguments for running `program`
+    pub fn program_args(&self, program: &str) -> Vec<String> {
+        let mut args: Vec<String> = self.args.iter().map(|arg| arg.replace(PROGRAM_PLACEHOLDER, program)).collect();
+        if !self.args.iter().any(|arg| arg.contains(PROGRAM_PLACEHOLDER)) {
+            args.push(program.to_string());
+        }
+        args
+    }
+    
+    fn from_json(json: &Json) -> Result<Self> {
+        let name = json.get("name").and_then(Json::as_str).context("interpreter profile without a \"name\"")?;
+        let strings = |key: &str| -> Result<Option<Vec<String>>> {
+            json.get(key).map(|value| {
+                value.as_array().and_then(|values| values.iter().map(|value| value.as_str().map(str::to_string)).collect())
+                    .with_context(|| format!("\"{}\" of interpreter '{}' must be a list of strings", key, name))
+            }).transpose()
+        };
+        let transport = match json.get("transport").and_then(Json::as_str).unwrap_or("stdio") {
+            "stdio" => Transport::Stdio,
+            other => anyhow::bail!("Interpreter '{}' has unsupported transport '{}' (only stdio is)", name, other),
+        };
+        Ok(Self {
+            name: name.to_string(),
+            command: json.get("command").and_then(Json::as_str)
+                .with_context(|| format!("Interpreter '{}' has no \"command\"", name))?.to_string(),
+            args: strings("args")?.unwrap_or_default(),
+            transport,
+            quirks: strings("quirks")?.unwrap_or_default().iter().map(|quirk| quirk.parse()).collect::<Result<_>>()?,
+            version_args: strings("version_args")?,
+            restart: json.get("restart").and_then(Json::as_str).map(str::parse).transpose()?.unwrap_or_default(),
+        })
+    }
+}
+
+/// Every interpreter profile in a config file
+pub fn load_profiles(path: &str) -> Result<Vec<InterpreterProfile>> {
+    let text = std::fs::read_to_string(path).with_context(|| format!("Failed to read config file {}", path))?;
+    let json = Json::parse(&text).with_context(|| format!("Invalid config file {}", path))?;
+    let Some(profiles) = json.get("interpreters") else {
+        return Ok(Vec::new());
+    };
+    profiles.as_array().with_context(|| format!("\"interpreters\" in {} must be a list", path))?
+        .iter()
+        .map(InterpreterProfile::from_json)
+        .collect::<Result<_>>()
+        .with_context(|| format!("Invalid interpreter profile in {}", path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_profile() {
+        let json = Json::parse(r#"{"name": "cbm", "command": "cbmbasic", "args": ["-q", "{program}"], "quirks": ["extra-blank-lines"]}"#).unwrap();
+        let profile = InterpreterProfile::from_json(&json).unwrap();
+        assert_eq!(profile.transport, Transport::Stdio);
+        assert_eq!(profile.quirks, [Quirk::ExtraBlankLines]);
+        assert_eq!(profile.program_args("sst.bas"), ["-q", "sst.bas"]);
+        assert_eq!(profile.restart, RestartMethod::Relaunch);
+        
+        let json = Json::parse(r#"{"name": "gw", "command": "pcbasic", "args": ["--interface=none"], "restart": "run"}"#).unwrap();
+        let profile = InterpreterProfile::from_json(&json).unwrap();
+        assert_eq!(profile.program_args("sst.bas"), ["--interface=none", "sst.bas"]);
+        assert_eq!(profile.restart.lines(), ["NO", "RUN"]);
+        
+        let json = Json::parse(r#"{"name": "tty", "command": "basic", "transport": "pty"}"#).unwrap();
+        assert!(InterpreterProfile::from_json(&json).is_err());
+    }
+}
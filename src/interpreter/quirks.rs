@@ -0,0 +1,357 @@
+use anyhow::Result;
+use regex::Regex;
+use std::str::FromStr;
+
+use super::is_game_prompt;
+
+/// A known deviation in how an interpreter presents the game
+#[derive(Debug, Clone, PartialEq)]
+pub enum Quirk {
+    /// Prints blank lines the reference interpreter doesn't; blank lines are dropped
+    ExtraBlankLines,
+    /// Echoes each command back on stdout
+    EchoesInput,
+    /// Prints floats with spurious precision (2.8200001); round to this many decimals
+    NumericRounding(usize),
+    /// Text the prompt classifier must never treat as a prompt
+    NotAPrompt(String),
+    /// Text the prompt classifier must always treat as a prompt
+    Prompt(String),
+    /// Extra argument for the runtime (JVM or Python) before the interpreter itself
+    RuntimeArg(String),
+    /// Environment variable set for the interpreter process
+    Env(String, String),
+}
+
+impl FromStr for Quirk {
+    type Err = anyhow::Error;
+    
+    /// Parse quirks written as `name` or `name=value`, e.g. `numeric-rounding=4`
+    fn from_str(text: &str) -> Result<Self> {
+        let (name, value) = match text.split_once('=') {
+            Some((name, value)) => (name.trim(), Some(value.trim())),
+            None => (text.trim(), None),
+        };
+        
+        match (name, value) {
+            ("extra-blank-lines", None) => Ok(Quirk::ExtraBlankLines),
+            ("echoes-input", None) => Ok(Quirk::EchoesInput),
+            ("numeric-rounding", Some(decimals)) => Ok(Quirk::NumericRounding(decimals.parse()?)),
+            ("not-a-prompt", Some(text)) => Ok(Quirk::NotAPrompt(text.to_string())),
+            ("prompt", Some(text)) => Ok(Quirk::Prompt(text.to_string())),
+            ("runtime-arg", Some(arg)) => Ok(Quirk::RuntimeArg(arg.to_string())),
+            ("env", Some(variable)) => match variable.split_once('=') {
+                Some((name, value)) => Ok(Quirk::Env(name.to_string(), value.to_string())),
+                None => Err(anyhow::anyhow!("Quirk env needs NAME=VALUE: '{}'", text)),
+            },
+            _ => Err(anyhow::anyhow!("Unknown quirk: '{}'", text)),
+        }
+    }
+}
+
+/// Registry of known quirks, keyed by interpreter name.
+/// Add an entry here instead of special-casing an interpreter elsewhere.
+const KNOWN_QUIRKS: &[(&str, &str)] = &[
+    // Java's float formatting exposes single-precision noise
+    ("trek-basic-j", "numeric-rounding=6"),
+];
+
+/// Launch settings applied with --fast-startup, keyed by interpreter name. They trade
+/// peak speed for a quicker start, which is what short games spend most of their time on.
+const FAST_STARTUP: &[(&str, &str)] = &[
+    // C1 only: the JIT's second tier rarely pays off within one game
+    ("trek-basic-j", "runtime-arg=-XX:TieredStopAtLevel=1"),
+    ("trek-basic-j", "runtime-arg=-XX:+UseSerialGC"),
+    ("trek-basic-j", "runtime-arg=-Xshare:auto"),
+    ("trek-basic", "runtime-arg=-OO"),
+    ("trek-basic", "runtime-arg=-Xfrozen_modules=on"),
+    ("trek-basic", "env=PYTHONDONTWRITEBYTECODE=1"),
+];
+
+/// The set of quirks that apply to one interpreter
+#[derive(Debug, Clone, Default)]
+pub struct Quirks {
+    quirks: Vec<Quirk>,
+}
+
+impl Quirks {
+    /// Look up the registered quirks for an interpreter
+    pub fn for_interpreter(name: &str) -> Self {
+        let quirks = KNOWN_QUIRKS.iter()
+            .filter(|(interpreter, _)| *interpreter == name)
+            .filter_map(|(_, quirk)| quirk.parse().ok())
+            .collect();
+        Self { quirks }
+    }
+    
+    /// Add the --fast-startup launch settings registered for an interpreter
+    pub fn add_fast_startup(&mut self, name: &str) {
+        for (_, quirk) in FAST_STARTUP.iter().filter(|(interpreter, _)| *interpreter == name) {
+            if let Ok(quirk) = quirk.parse() {
+                self.add(quirk);
+            }
+        }
+    }
+    
+    /// Add a quirk, e.g. one given on the command line
+    pub fn add(&mut self, quirk: Quirk) {
+        if !self.quirks.contains(&quirk) {
+            self.quirks.push(quirk);
+        }
+    }
+    
+    pub fn has(&self, quirk: &Quirk) -> bool {
+        self.quirks.contains(quirk)
+    }
+    
+    /// Runtime arguments from RuntimeArg quirks, in order
+    pub fn runtime_args(&self) -> Vec<String> {
+        self.quirks.iter()
+            .filter_map(|quirk| match quirk {
+                Quirk::RuntimeArg(arg) => Some(arg.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+    
+    /// Environment variables from Env quirks
+    pub fn env(&self) -> Vec<(String, String)> {
+        self.quirks.iter()
+            .filter_map(|quirk| match quirk {
+                Quirk::Env(name, value) => Some((name.clone(), value.clone())),
+                _ => None,
+            })
+            .collect()
+    }
+    
+    /// Prompt classification that honors NotAPrompt/Prompt quirks
+    pub fn is_prompt(&self, line: &str) -> bool {
+        for quirk in &self.quirks {
+            match quirk {
+                Quirk::NotAPrompt(text) if line.contains(text.as_str()) => return false,
+                Quirk::Prompt(text) if line.contains(text.as_str()) => return true,
+                _ => {}
+            }
+        }
+        is_game_prompt(line)
+    }
+    
+    /// Drop the echo of the last command from the start of the output. Only interpreters
+    /// with the EchoesInput quirk are stripped; for others a matching line is just
+    /// reported, since the game itself may legitimately print the same text.
+    pub fn strip_echo(&self, output: Vec<String>, sent: Option<&str>) -> Vec<String> {
+        let mut output = output;
+        let sent = match sent {
+            Some(sent) => sent.trim(),
+            None => return output,
+        };
+        
+        let echoed = output.first().is_some_and(|line| line.trim().eq_ignore_ascii_case(sent));
+        if echoed {
+            if self.has(&Quirk::EchoesInput) {
+                output.remove(0);
+            } else if !sent.is_empty() {
+                log::debug!("Output starts with the command just sent ('{}'); consider --quirk echoes-input", sent);
+            }
+        }
+        output
+    }
+    
+    /// Normalize interpreter output so the rest of TrekBot sees the reference presentation
+    pub fn normalize(&self, output: Vec<String>) -> Vec<String> {
+        let mut output = output;
+        
+        if self.has(&Quirk::ExtraBlankLines) {
+            output.retain(|line| !line.trim().is_empty());
+        }
+        
+        for quirk in &self.quirks {
+            if let Quirk::NumericRounding(decimals) = quirk {
+                output = output.iter().map(|line| round_numbers(line, *decimals)).collect();
+            }
+        }
+        
+        output
+    }
+}
+
+/// Round every decimal number in a line to the given number of decimals,
+/// dropping trailing zeros so "2.8200001" becomes "2.82"
+pub fn round_numbers(line: &str, decimals: usize) -> String {
+    let number_regex = Regex::new(r"-?\d+\.\d+").unwrap();
+    number_regex
+        .replace_all(line, |caps: &regex::Captures| {
+            let text = &caps[0];
+            match text.parse::<f64>() {
+                Ok(value) => {
+                    let rounded = format!("{:.*}", decimals, value);
+                    let trimmed = rounded.trim_end_matches('0').trim_end_matches('.');
+                    trimmed.to_string()
+                }
+                Err(_) => text.to_string(),
+            }
+        })
+        .to_string()
+}
+
+/// How far numbers may differ when comparing the output of two games, so that 2.82,
+/// 2.8200001 and " 2.82" count as the same. Text between numbers must match apart from
+/// spacing. A field tolerance applies to the numbers following text with its label in,
+/// e.g. "DIRECTION =", instead of the default; the label found last in the text decides.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Tolerance {
+    pub default: f64,
+    /// Label and tolerance, matched regardless of case
+    pub fields: Vec<(String, f64)>,
+}
+
+/// Part of a line: text, or a number in it
+#[derive(Debug, Clone, PartialEq)]
+enum Token<'a> {
+    Text(&'a str),
+    Number(f64),
+}
+
+impl Tolerance {
+    pub fn new(default: f64) -> Self {
+        Self { default, fields: Vec::new() }
+    }
+    
+    /// Add a field tolerance written as `LABEL=TOLERANCE`, e.g. `DIRECTION=0.01`
+    pub fn add_field(&mut self, spec: &str) -> Result<()> {
+        let (label, tolerance) = spec.rsplit_once('=')
+            .ok_or_else(|| anyhow::anyhow!("Field tolerance needs LABEL=TOLERANCE: '{}'", spec))?;
+        self.fields.push((label.trim().to_uppercase(), tolerance.trim().parse()?));
+        Ok(())
+    }
+    
+    /// Tolerance of the label found last in `text`, if any
+    fn field(&self, text: &str) -> Option<f64> {
+        let text = text.to_uppercase();
+        self.fields.iter()
+            .filter_map(|(label, tolerance)| text.rfind(label.as_str()).map(|at| (at, *tolerance)))
+            .max_by_key(|(at, _)| *at)
+            .map(|(_, tolerance)| tolerance)
+    }
+    
+    /// Whether two lines are the same within the tolerances
+    pub fn lines_match(&self, expected: &str, actual: &str) -> bool {
+        let (expected, actual) = (tokens(expected), tokens(actual));
+        if expected.len() != actual.len() {
+            return false;
+        }
+        let mut tolerance = self.default;
+        expected.iter().zip(&actual).all(|pair| match pair {
+            (Token::Text(expected), Token::Text(actual)) => {
+                tolerance = self.field(expected).unwrap_or(self.default);
+                expected.split_whitespace().eq(actual.split_whitespace())
+            }
+            (Token::Number(expected), Token::Number(actual)) => (expected - actual).abs() <= tolerance,
+            _ => false,
+        })
+    }
+}
+
+/// Split a line into text and numbers (-12, 2.82, .5); text made of spaces only is
+/// dropped so padding around numbers doesn't matter
+fn tokens(line: &str) -> Vec<Token<'_>> {
+    let bytes = line.as_bytes();
+    let digit = |at: usize| bytes.get(at).is_some_and(u8::is_ascii_digit);
+    let mut tokens = Vec::new();
+    let (mut text_start, mut at) = (0, 0);
+    while at < bytes.len() {
+        let sign = usize::from(bytes[at] == b'-');
+        let starts_number = digit(at + sign) || (bytes.get(at + sign) == Some(&b'.') && digit(at + sign + 1));
+        // A digit straight after a letter is part of a word, e.g. "X1"
+        let in_word = at > 0 && bytes[at - 1].is_ascii_alphabetic();
+        if !starts_number || in_word {
+            at += 1;
+            continue;
+        }
+        let mut end = at + sign;
+        while digit(end) {
+            end += 1;
+        }
+        if bytes.get(end) == Some(&b'.') && digit(end + 1) {
+            end += 1;
+            while digit(end) {
+                end += 1;
+            }
+        }
+        if let Ok(number) = line[at..end].parse() {
+            if !line[text_start..at].trim().is_empty() {
+                tokens.push(Token::Text(&line[text_start..at]));
+            }
+            tokens.push(Token::Number(number));
+            text_start = end;
+        }
+        at = end;
+    }
+    if !line[text_start..].trim().is_empty() {
+        tokens.push(Token::Text(&line[text_start..]));
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_parse_quirks() {
+        assert_eq!("extra-blank-lines".parse::<Quirk>().unwrap(), Quirk::ExtraBlankLines);
+        assert_eq!("numeric-rounding=4".parse::<Quirk>().unwrap(), Quirk::NumericRounding(4));
+        assert_eq!("not-a-prompt=READY".parse::<Quirk>().unwrap(), Quirk::NotAPrompt("READY".to_string()));
+        assert!("bogus".parse::<Quirk>().is_err());
+        assert_eq!("env=A=1".parse::<Quirk>().unwrap(), Quirk::Env("A".to_string(), "1".to_string()));
+        assert!("env=A".parse::<Quirk>().is_err());
+    }
+    
+    #[test]
+    fn test_fast_startup() {
+        let mut quirks = Quirks::for_interpreter("trek-basic-j");
+        quirks.add_fast_startup("trek-basic-j");
+        assert_eq!(quirks.runtime_args()[0], "-XX:TieredStopAtLevel=1");
+        assert!(quirks.env().is_empty());
+        
+        let mut quirks = Quirks::default();
+        quirks.add_fast_startup("trek-basic");
+        assert_eq!(quirks.env(), vec![("PYTHONDONTWRITEBYTECODE".to_string(), "1".to_string())]);
+    }
+    
+    #[test]
+    fn test_strip_echo() {
+        let output = vec!["NAV".to_string(), "COURSE (0-9)?".to_string()];
+        let echoing = Quirks { quirks: vec![Quirk::EchoesInput] };
+        assert_eq!(echoing.strip_echo(output.clone(), Some("NAV")), vec!["COURSE (0-9)?"]);
+        assert_eq!(echoing.strip_echo(output.clone(), Some("SRS")), output);
+        assert_eq!(Quirks::default().strip_echo(output.clone(), Some("NAV")), output);
+    }
+    
+    #[test]
+    fn test_normalize() {
+        let quirks = Quirks { quirks: vec![Quirk::ExtraBlankLines, Quirk::NumericRounding(6)] };
+        let output = vec!["DIRECTION = 2.8200001".to_string(), "".to_string(), "COMMAND?".to_string()];
+        assert_eq!(quirks.normalize(output), vec!["DIRECTION = 2.82", "COMMAND?"]);
+    }
+    
+    #[test]
+    fn test_tolerance() {
+        let exact = Tolerance::default();
+        assert!(exact.lines_match("DIRECTION = 2.82", "DIRECTION =  2.8200"));
+        assert!(exact.lines_match("ENERGY 3000 ", "ENERGY  3000"));
+        assert!(!exact.lines_match("DIRECTION = 2.82", "DIRECTION = 2.8200001"));
+        assert!(!exact.lines_match("ENERGY 3000", "ENERGY 3000 UNITS"));
+        assert_eq!(tokens("SECTOR 2 , -4 AT .5 IN Q1"), [
+            Token::Text("SECTOR "), Token::Number(2.0), Token::Text(" , "), Token::Number(-4.0),
+            Token::Text(" AT "), Token::Number(0.5), Token::Text(" IN Q1"),
+        ]);
+        
+        let mut tolerance = Tolerance::new(1e-6);
+        tolerance.add_field("distance=0.5").unwrap();
+        assert!(tolerance.lines_match("DIRECTION = 2.82", "DIRECTION = 2.8200001"));
+        assert!(tolerance.lines_match("DIRECTION = 2.82 DISTANCE = 3", "DIRECTION = 2.82 DISTANCE = 3.4"));
+        assert!(!tolerance.lines_match("DISTANCE = 3 DIRECTION = 2.82", "DISTANCE = 3 DIRECTION = 2.9"));
+        assert!(tolerance.add_field("DISTANCE").is_err());
+    }
+}
@@ -0,0 +1,162 @@
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Tool that isolates the interpreter from the rest of the host (Linux only)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SandboxWrapper {
+    /// firejail with its default seccomp filter and no network
+    Firejail,
+    /// bubblewrap with a read-only root; only the game directory is writable
+    Bubblewrap,
+}
+
+impl FromStr for SandboxWrapper {
+    type Err = anyhow::Error;
+    
+    fn from_str(text: &str) -> anyhow::Result<Self> {
+        match text {
+            "firejail" => Ok(SandboxWrapper::Firejail),
+            "bubblewrap" | "bwrap" => Ok(SandboxWrapper::Bubblewrap),
+            _ => anyhow::bail!("Unknown sandbox '{}', expected firejail or bubblewrap", text),
+        }
+    }
+}
+
+/// Limits and isolation for spawned interpreters, so a buggy interpreter can't take
+/// down the host or fill the disk, and scheduling settings that keep timings comparable.
+/// Off unless a limit or wrapper is set. Limits are applied with `prlimit` and CPU
+/// pinning with `taskset` from util-linux, CPU quotas with `systemd-run` (cgroups).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Sandbox {
+    pub cpu_seconds: Option<u64>,
+    pub memory_mb: Option<u64>,
+    /// Largest file the interpreter may write
+    pub file_size_mb: Option<u64>,
+    pub wrapper: Option<SandboxWrapper>,
+    /// Directories the interpreter may write to besides its working directory,
+    /// e.g. where coverage is recorded
+    pub writable: Vec<PathBuf>,
+    /// Niceness, from -20 (greedy) to 19 (polite); below 0 needs privileges
+    pub nice: Option<i32>,
+    /// CPUs the interpreter may run on, as a taskset list such as "2" or "0-3,6"
+    pub cpus: Option<String>,
+    /// Share of one CPU the interpreter's cgroup may use, in percent (200 is two CPUs)
+    pub cpu_quota: Option<u32>,
+}
+
+impl Sandbox {
+    pub fn is_enabled(&self) -> bool {
+        self.cpu_seconds.is_some() || self.memory_mb.is_some() || self.file_size_mb.is_some() || self.wrapper.is_some()
+            || self.nice.is_some() || self.cpus.is_some() || self.cpu_quota.is_some()
+    }
+    
+    /// The command line that runs `command args` inside the sandbox
+    pub fn wrap(&self, command: &str, args: &[&str], working_dir: Option<&Path>) -> (String, Vec<String>) {
+        let mut line: Vec<String> = Vec::new();
+        
+        // Scheduling comes first so it also covers the wrapper's own processes
+        if let Some(quota) = self.cpu_quota {
+            line.extend(["systemd-run", "--user", "--scope", "--quiet", "-p"].map(String::from));
+            line.push(format!("CPUQuota={}%", quota));
+            line.push("--".to_string());
+        }
+        if let Some(nice) = self.nice {
+            line.extend(["nice".to_string(), "-n".to_string(), nice.to_string(), "--".to_string()]);
+        }
+        if let Some(cpus) = &self.cpus {
+            line.extend(["taskset".to_string(), "-c".to_string(), cpus.clone(), "--".to_string()]);
+        }
+        
+        match self.wrapper {
+            Some(SandboxWrapper::Firejail) => {
+                line.extend(["firejail", "--quiet", "--noprofile", "--seccomp", "--net=none", "--"].map(String::from));
+            }
+            Some(SandboxWrapper::Bubblewrap) => {
+                line.extend(["bwrap", "--die-with-parent", "--unshare-all", "--ro-bind", "/", "/", "--dev", "/dev", "--proc", "/proc", "--tmpfs", "/tmp"].map(String::from));
+                for dir in working_dir.into_iter().chain(self.writable.iter().map(PathBuf::as_path)) {
+                    let dir = dir.to_string_lossy().to_string();
+                    line.extend(["--bind".to_string(), dir.clone(), dir]);
+                }
+                if let Some(dir) = working_dir {
+                    line.extend(["--chdir".to_string(), dir.to_string_lossy().to_string()]);
+                }
+                line.push("--".to_string());
+            }
+            None => {}
+        }
+        
+        let limits: Vec<String> = [
+            self.cpu_seconds.map(|seconds| format!("--cpu={}", seconds)),
+            self.memory_mb.map(|mb| format!("--as={}", mb * 1024 * 1024)),
+            self.file_size_mb.map(|mb| format!("--fsize={}", mb * 1024 * 1024)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if !limits.is_empty() {
+            line.push("prlimit".to_string());
+            line.extend(limits);
+            line.push("--".to_string());
+        }
+        
+        line.push(command.to_string());
+        line.extend(args.iter().map(|arg| arg.to_string()));
+        let command = line.remove(0);
+        (command, line)
+    }
+}
+
+/// The CPUs in a taskset style list such as "0-3,6"
+pub fn parse_cpu_list(text: &str) -> anyhow::Result<Vec<usize>> {
+    let mut cpus = Vec::new();
+    for part in text.split(',').map(str::trim).filter(|part| !part.is_empty()) {
+        let parse = |cpu: &str| cpu.trim().parse::<usize>().map_err(|_| anyhow::anyhow!("Bad CPU '{}' in '{}'", cpu, text));
+        match part.split_once('-') {
+            Some((first, last)) => cpus.extend(parse(first)?..=parse(last)?),
+            None => cpus.push(parse(part)?),
+        }
+    }
+    if cpus.is_empty() {
+        anyhow::bail!("No CPUs in '{}'", text);
+    }
+    Ok(cpus)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_wrap() {
+        let off = Sandbox::default();
+        assert!(!off.is_enabled());
+        assert_eq!(off.wrap("basic_rs", &["game.bas"], None), ("basic_rs".to_string(), vec!["game.bas".to_string()]));
+        
+        let limited = Sandbox {
+            cpu_seconds: Some(60),
+            memory_mb: Some(512),
+            wrapper: Some(SandboxWrapper::Firejail),
+            ..Sandbox::default()
+        };
+        let (command, args) = limited.wrap("basic_rs", &["game.bas"], None);
+        assert_eq!(command, "firejail");
+        assert_eq!(args[4..].join(" "), "-- prlimit --cpu=60 --as=536870912 -- basic_rs game.bas");
+        
+        let bwrap = Sandbox { wrapper: Some(SandboxWrapper::Bubblewrap), ..Sandbox::default() };
+        let (command, args) = bwrap.wrap("java", &["-jar", "t.jar"], Some(Path::new("/runs/game-0001")));
+        assert_eq!(command, "bwrap");
+        assert!(args.join(" ").ends_with("--bind /runs/game-0001 /runs/game-0001 --chdir /runs/game-0001 -- java -jar t.jar"));
+    }
+    
+    #[test]
+    fn test_scheduling() {
+        let pinned = Sandbox { nice: Some(5), cpus: Some("2".to_string()), cpu_quota: Some(100), ..Sandbox::default() };
+        assert!(pinned.is_enabled());
+        let (command, args) = pinned.wrap("basic_rs", &["game.bas"], None);
+        assert_eq!(command, "systemd-run");
+        assert_eq!(args.join(" "), "--user --scope --quiet -p CPUQuota=100% -- nice -n 5 -- taskset -c 2 -- basic_rs game.bas");
+        
+        assert_eq!(parse_cpu_list("0-3, 6").unwrap(), vec![0, 1, 2, 3, 6]);
+        assert!(parse_cpu_list("x").is_err());
+    }
+}
@@ -0,0 +1,245 @@
+use anyhow::Result;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::ChildStdin;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// Commands that may be waiting for the interpreter to read its stdin
+pub const OUTBOUND_QUEUE: usize = 16;
+
+/// How long a command may wait for room in a full queue before the game fails
+pub const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Why a command could not be sent to the interpreter
+#[derive(Debug)]
+pub enum WriteError {
+    /// The queue stayed full: the interpreter stopped reading its input
+    Timeout {
+        command: String,
+        timeout: Duration,
+        pending: usize,
+    },
+    /// An earlier write failed, e.g. the pipe closed when the interpreter exited
+    Failed {
+        command: String,
+        error: String,
+    },
+}
+
+impl fmt::Display for WriteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WriteError::Timeout { command, timeout, pending } => write!(
+                f,
+                "Interpreter did not read its input within {:.1} seconds; '{}' not sent ({} commands still queued)",
+                timeout.as_secs_f64(), command, pending
+            ),
+            WriteError::Failed { command, error } => {
+                write!(f, "Failed to write to interpreter stdin, '{}' not sent: {}", command, error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for WriteError {}
+
+/// Each command written to stdin in several pieces with a pause between them, to test
+/// how an interpreter handles fragmented input. One piece is an ordinary write.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WriteChunking {
+    pub pieces: usize,
+    pub pause: Duration,
+}
+
+impl Default for WriteChunking {
+    fn default() -> Self {
+        Self { pieces: 1, pause: Duration::ZERO }
+    }
+}
+
+impl WriteChunking {
+    /// A line and its newline, split into at most `pieces` parts
+    fn split<'a>(&self, bytes: &'a [u8]) -> std::slice::Chunks<'a, u8> {
+        let size = bytes.len().div_ceil(self.pieces.max(1)).max(1);
+        bytes.chunks(size)
+    }
+}
+
+#[derive(Debug, Default)]
+struct QueueStatus {
+    queued: usize,
+    flushed: usize,
+    error: Option<String>,
+}
+
+/// Outbound queue in front of the interpreter's stdin. A background task does the
+/// writing, so a slow pipe never holds up the game loop, and a pipe that stops
+/// draining turns into a WriteError for the game instead of a hang.
+pub struct StdinQueue {
+    sender: mpsc::Sender<String>,
+    status: Arc<Mutex<QueueStatus>>,
+    task: JoinHandle<()>,
+    /// How long send() waits for room, WRITE_TIMEOUT outside tests
+    timeout: Duration,
+}
+
+impl StdinQueue {
+    pub fn new(stdin: ChildStdin, chunking: WriteChunking) -> Self {
+        Self::with_timeout(stdin, chunking, WRITE_TIMEOUT)
+    }
+    
+    fn with_timeout(stdin: ChildStdin, chunking: WriteChunking, timeout: Duration) -> Self {
+        let (sender, receiver) = mpsc::channel(OUTBOUND_QUEUE);
+        let status = Arc::new(Mutex::new(QueueStatus::default()));
+        let task = tokio::spawn(Self::write_lines(stdin, receiver, status.clone(), chunking));
+        Self { sender, status, task, timeout }
+    }
+    
+    async fn write_lines(mut stdin: ChildStdin, mut receiver: mpsc::Receiver<String>, status: Arc<Mutex<QueueStatus>>, chunking: WriteChunking) {
+        while let Some(line) = receiver.recv().await {
+            let result: std::io::Result<()> = async {
+                let bytes = format!("{}\n", line).into_bytes();
+                for (index, piece) in chunking.split(&bytes).enumerate() {
+                    if index > 0 {
+                        tokio::time::sleep(chunking.pause).await;
+                    }
+                    stdin.write_all(piece).await?;
+                    stdin.flush().await?;
+                }
+                Ok(())
+            }
+            .await;
+            
+            let mut status = status.lock().unwrap();
+            match result {
+                Ok(()) => status.flushed += 1,
+                Err(e) => {
+                    log::error!("Failed to write command '{}' to stdin: {}", line, e);
+                    status.error = Some(e.to_string());
+                    break;
+                }
+            }
+        }
+    }
+    
+    /// Queue a line for the interpreter, waiting at most WRITE_TIMEOUT for room
+    pub async fn send(&self, line: &str) -> Result<()> {
+        if let Some(error) = self.status.lock().unwrap().error.clone() {
+            return Err(WriteError::Failed { command: line.to_string(), error }.into());
+        }
+        
+        self.status.lock().unwrap().queued += 1;
+        let sent = tokio::time::timeout(self.timeout, self.sender.send(line.to_string())).await;
+        if !matches!(sent, Ok(Ok(()))) {
+            self.status.lock().unwrap().queued -= 1;
+        }
+        
+        match sent {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(_)) => {
+                let error = self.status.lock().unwrap().error.clone().unwrap_or_else(|| "stdin closed".to_string());
+                Err(WriteError::Failed { command: line.to_string(), error }.into())
+            }
+            Err(_) => Err(WriteError::Timeout {
+                command: line.to_string(),
+                timeout: self.timeout,
+                pending: self.pending(),
+            }
+            .into()),
+        }
+    }
+    
+    /// Lines queued but not yet flushed to the interpreter
+    pub fn pending(&self) -> usize {
+        let status = self.status.lock().unwrap();
+        status.queued.saturating_sub(status.flushed)
+    }
+    
+    /// Wait until every queued line has been flushed, up to `timeout`.
+    /// Returns whether the queue drained.
+    pub async fn flush(&self, timeout: Duration) -> bool {
+        let drained = async {
+            while self.pending() > 0 && self.status.lock().unwrap().error.is_none() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        };
+        tokio::time::timeout(timeout, drained).await.is_ok() && self.pending() == 0
+    }
+}
+
+impl Drop for StdinQueue {
+    fn drop(&mut self) {
+        // Don't leave a writer blocked on a pipe nobody will read
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_write_error_messages() {
+        let timeout = WriteError::Timeout { command: "NAV".to_string(), timeout: WRITE_TIMEOUT, pending: 16 };
+        assert_eq!(
+            timeout.to_string(),
+            "Interpreter did not read its input within 5.0 seconds; 'NAV' not sent (16 commands still queued)"
+        );
+        let failed = WriteError::Failed { command: "SRS".to_string(), error: "Broken pipe".to_string() };
+        assert_eq!(failed.to_string(), "Failed to write to interpreter stdin, 'SRS' not sent: Broken pipe");
+    }
+    
+    #[test]
+    fn test_write_chunking() {
+        let chunking = WriteChunking { pieces: 3, pause: Duration::ZERO };
+        assert_eq!(chunking.split(b"NAV\n").collect::<Vec<_>>(), [&b"NA"[..], b"V\n"]);
+        assert_eq!(chunking.split(b"1\n").collect::<Vec<_>>(), [&b"1"[..], b"\n"]);
+        assert_eq!(WriteChunking::default().split(b"SRS\n").count(), 1);
+    }
+    
+    /// A child process that runs `command` with its stdin piped to a queue
+    fn queue_for(command: &str) -> (tokio::process::Child, StdinQueue) {
+        let mut child = tokio::process::Command::new("sh")
+            .args(["-c", command])
+            .stdin(std::process::Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .unwrap();
+        let stdin = child.stdin.take().unwrap();
+        (child, StdinQueue::with_timeout(stdin, WriteChunking::default(), Duration::from_millis(100)))
+    }
+    
+    /// Send lines until one fails, giving up after `limit`
+    async fn send_until_error(queue: &StdinQueue, line: &str, limit: usize) -> anyhow::Error {
+        for _ in 0..limit {
+            if let Err(e) = queue.send(line).await {
+                return e;
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        panic!("every line was sent");
+    }
+    
+    #[tokio::test]
+    async fn test_full_queue_times_out() {
+        // Lines bigger than the pipe's buffer, to a process that never reads them
+        let (_child, queue) = queue_for("sleep 10");
+        let error = send_until_error(&queue, &"W".repeat(1 << 17), OUTBOUND_QUEUE + 8).await;
+        match error.downcast_ref::<WriteError>() {
+            Some(WriteError::Timeout { pending, .. }) => assert!(*pending >= OUTBOUND_QUEUE),
+            other => panic!("expected a timeout, got {:?}", other),
+        }
+        assert!(!queue.flush(Duration::from_millis(50)).await);
+    }
+    
+    #[tokio::test]
+    async fn test_closed_pipe_fails() {
+        let (mut child, queue) = queue_for("exit 0");
+        child.wait().await.unwrap();
+        let error = send_until_error(&queue, "NAV", 50).await;
+        assert!(matches!(error.downcast_ref::<WriteError>(), Some(WriteError::Failed { .. })), "got {:?}", error);
+    }
+}
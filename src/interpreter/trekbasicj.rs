@@ -1,11 +1,18 @@
+use crate::coverage::CoverageLaunch;
+use crate::metadata::InterpreterIdentity;
 use anyhow::Result;
-use super::{Interpreter, SubprocessInterpreter, is_game_prompt};
+use super::{Capabilities, Interpreter, Quirks, ReadTimeout, ReadTimeouts, Sandbox, SubprocessInterpreter, WriteChunking};
+use std::path::PathBuf;
+use std::process::ExitStatus;
+use std::time::Duration;
 
 /// TrekBasicJ (Java) interpreter implementation
 pub struct TrekBasicJInterpreter {
     subprocess: SubprocessInterpreter,
+    quirks: Quirks,
     java_path: String,
     jar_path: String,
+    coverage: CoverageLaunch,
 }
 
 impl TrekBasicJInterpreter {
@@ -15,55 +22,117 @@ impl TrekBasicJInterpreter {
         
         Self {
             subprocess: SubprocessInterpreter::new(),
+            quirks: Quirks::for_interpreter("trek-basic-j"),
             java_path: java_path.unwrap_or(default_java),
             jar_path: jar_path.unwrap_or(default_jar),
+            coverage: CoverageLaunch::default(),
         }
     }
 }
 
 #[async_trait::async_trait]
 impl Interpreter for TrekBasicJInterpreter {
+    fn name(&self) -> &'static str {
+        "trek-basic-j"
+    }
+    
+    fn identity(&self) -> InterpreterIdentity {
+        InterpreterIdentity::probe(self.name(), &self.jar_path, &self.java_path, &["-version"])
+    }
+    
+    fn quirks(&self) -> &Quirks {
+        &self.quirks
+    }
+    
+    fn quirks_mut(&mut self) -> &mut Quirks {
+        &mut self.quirks
+    }
+    
+    fn set_coverage(&mut self, launch: CoverageLaunch) {
+        self.coverage = launch;
+    }
+    
+    fn set_startup_timeout(&mut self, timeout: Duration) {
+        self.subprocess.set_startup_timeout(timeout);
+    }
+    
+    fn set_working_dir(&mut self, dir: PathBuf) {
+        self.subprocess.set_working_dir(dir);
+    }
+    
+    fn set_sandbox(&mut self, sandbox: Sandbox) {
+        self.subprocess.set_sandbox(sandbox);
+    }
+    
+    fn set_write_chunking(&mut self, chunking: WriteChunking) {
+        self.subprocess.set_write_chunking(chunking);
+    }
+    
+    fn set_read_timeouts(&mut self, timeouts: ReadTimeouts) {
+        self.subprocess.set_read_timeouts(timeouts);
+    }
+    
+    fn set_max_output_lines(&mut self, lines: usize) {
+        self.subprocess.set_max_output_lines(lines);
+    }
+    
     async fn launch(&mut self, program_path: &str) -> Result<()> {
         log::info!("Launching TrekBasicJ interpreter with program: {}", program_path);
         
-        // Launch the Java interpreter with the JAR file and program
-        self.subprocess.spawn_process(&self.java_path, &["-jar", &self.jar_path, program_path]).await?;
+        // Launch the Java interpreter with the JAR file and program, plus any runtime-arg quirks and coverage arguments
+        let runtime_args = self.quirks.runtime_args();
+        let mut args: Vec<&str> = runtime_args.iter().chain(&self.coverage.runtime_args).map(String::as_str).collect();
+        args.extend(["-jar", self.jar_path.as_str(), program_path]);
+        args.extend(self.coverage.program_args.iter().map(String::as_str));
+        self.subprocess.set_env(self.quirks.env());
+        self.subprocess.spawn_process(&self.java_path, &args).await?;
         
-        // Read initial output until we get a prompt
-        let _initial_output = self.read_until_prompt().await?;
+        // Read initial output until we get a prompt, failing fast if none arrives
+        let startup_timeout = self.subprocess.startup_timeout();
+        let initial_output = match tokio::time::timeout(startup_timeout, self.read_until_prompt()).await {
+            Ok(output) => Some(output?),
+            Err(_) => None,
+        };
+        let quirks = &self.quirks;
+        self.subprocess.check_banner(initial_output.as_deref(), |line| quirks.is_prompt(line)).await?;
         
         Ok(())
     }
     
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::default()
+    }
+    
+    async fn restart(&mut self) -> Result<()> {
+        self.subprocess.restart_impl(self.name(), self.capabilities(), &self.quirks).await
+    }
+    
     async fn send_command(&mut self, command: &str) -> Result<()> {
         log::debug!("Sending command: {}", command);
         self.subprocess.write_line(command).await
     }
     
-    async fn read_line(&mut self) -> Result<Option<String>> {
-        self.subprocess.read_line_impl().await
+    async fn read_until_prompt(&mut self) -> Result<Vec<String>> {
+        let quirks = &self.quirks;
+        self.subprocess.read_until_prompt_impl(|line| quirks.is_prompt(line)).await
     }
     
-    async fn read_until_prompt(&mut self) -> Result<Vec<String>> {
-        let mut lines = Vec::new();
-        
-        while let Some(line) = self.read_line().await? {
-            lines.push(line.clone());
-            log::debug!("Read line: {}", line);
-            
-            if is_game_prompt(&line) {
-                log::debug!("Found game prompt: {}", line);
-                break;
-            }
-        }
-        
-        Ok(lines)
+    fn read_timed_out(&self) -> Option<ReadTimeout> {
+        self.subprocess.read_timed_out()
     }
     
     fn is_running(&mut self) -> bool {
         self.subprocess.is_running_impl()
     }
     
+    fn channel_closed(&self) -> bool {
+        self.subprocess.channel_closed_impl()
+    }
+    
+    fn exit_status(&mut self) -> Option<ExitStatus> {
+        self.subprocess.exit_status_impl()
+    }
+    
     async fn terminate(&mut self) -> Result<()> {
         log::info!("Terminating TrekBasicJ interpreter");
         self.subprocess.terminate_impl().await
@@ -0,0 +1,167 @@
+use crate::compress;
+use crate::json::Json;
+use anyhow::{Context, Result};
+use std::path::Path;
+
+/// Rating a strategy starts the ladder with
+const INITIAL_RATING: f64 = 1500.0;
+
+/// How far one match can move a rating
+const K_FACTOR: f64 = 32.0;
+
+/// Win rates closer than this count as a draw
+const DRAW_MARGIN: f64 = 0.01;
+
+/// A strategy's place on the ladder
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rating {
+    pub strategy: String,
+    pub rating: f64,
+    pub wins: usize,
+    pub draws: usize,
+    pub losses: usize,
+}
+
+impl Rating {
+    fn new(strategy: &str) -> Self {
+        Self { strategy: strategy.to_string(), rating: INITIAL_RATING, wins: 0, draws: 0, losses: 0 }
+    }
+    
+    fn to_json(&self) -> Json {
+        Json::object()
+            .with("strategy", self.strategy.as_str())
+            .with("rating", self.rating)
+            .with("wins", self.wins)
+            .with("draws", self.draws)
+            .with("losses", self.losses)
+    }
+    
+    fn from_json(json: &Json) -> Option<Self> {
+        Some(Self {
+            strategy: json.get("strategy")?.as_str()?.to_string(),
+            rating: json.get("rating")?.as_f64()?,
+            wins: json.get("wins")?.as_usize()?,
+            draws: json.get("draws")?.as_usize()?,
+            losses: json.get("losses")?.as_usize()?,
+        })
+    }
+}
+
+/// Elo-style ratings of strategies, so a change that makes a strategy weaker shows up
+/// as it sliding down the standings. Every round plays the same games with each
+/// strategy; each pair of strategies then counts as a match won by the higher win rate.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Ladder {
+    ratings: Vec<Rating>,
+    pub rounds: usize,
+}
+
+impl Ladder {
+    /// Load the ladder saved at `path`, or start an empty one if there is none yet
+    pub fn load_or_new(path: &str) -> Result<Self> {
+        if !Path::new(path).exists() {
+            return Ok(Self::default());
+        }
+        let json = Json::parse(&compress::read_file(path)?)?;
+        Self::from_json(&json).with_context(|| format!("{} is not a TrekBot ladder file", path))
+    }
+    
+    pub fn save(&self, path: &str) -> Result<()> {
+        compress::write_file(path, &(self.to_json().to_string() + "\n"))
+    }
+    
+    fn to_json(&self) -> Json {
+        Json::object()
+            .with("rounds", self.rounds)
+            .with("ratings", Json::Array(self.ratings.iter().map(Rating::to_json).collect()))
+    }
+    
+    fn from_json(json: &Json) -> Option<Self> {
+        Some(Self {
+            rounds: json.get("rounds")?.as_usize()?,
+            ratings: json.get("ratings")?.as_array()?.iter().filter_map(Rating::from_json).collect(),
+        })
+    }
+    
+    fn rating_mut(&mut self, strategy: &str) -> &mut Rating {
+        match self.ratings.iter().position(|rating| rating.strategy == strategy) {
+            Some(index) => &mut self.ratings[index],
+            None => {
+                self.ratings.push(Rating::new(strategy));
+                self.ratings.last_mut().unwrap()
+            }
+        }
+    }
+    
+    /// Update the ratings after a match; `score` is 1 if `a` won, 0.5 for a draw, 0 if `b` won
+    pub fn record_match(&mut self, a: &str, b: &str, score: f64) {
+        let rating_a = self.rating_mut(a).rating;
+        let rating_b = self.rating_mut(b).rating;
+        let expected = 1.0 / (1.0 + 10f64.powf((rating_b - rating_a) / 400.0));
+        let change = K_FACTOR * (score - expected);
+        
+        for (strategy, delta, score) in [(a, change, score), (b, -change, 1.0 - score)] {
+            let rating = self.rating_mut(strategy);
+            rating.rating += delta;
+            match score {
+                s if s > 0.5 => rating.wins += 1,
+                s if s < 0.5 => rating.losses += 1,
+                _ => rating.draws += 1,
+            }
+        }
+    }
+    
+    /// Record a round: every pair of strategies is a match decided by win rate
+    pub fn record_round(&mut self, win_rates: &[(String, f64)]) {
+        for (i, (a, rate_a)) in win_rates.iter().enumerate() {
+            for (b, rate_b) in &win_rates[i + 1..] {
+                let score = if (rate_a - rate_b).abs() < DRAW_MARGIN {
+                    0.5
+                } else if rate_a > rate_b {
+                    1.0
+                } else {
+                    0.0
+                };
+                self.record_match(a, b, score);
+            }
+        }
+        self.rounds += 1;
+    }
+    
+    /// Ratings from best to worst
+    pub fn standings(&self) -> Vec<&Rating> {
+        let mut standings: Vec<&Rating> = self.ratings.iter().collect();
+        standings.sort_by(|a, b| b.rating.total_cmp(&a.rating));
+        standings
+    }
+    
+    pub fn print_standings(&self) {
+        println!("=== Strategy Ladder ({} rounds) ===", self.rounds);
+        println!("{:<4} {:<12} {:>7} {:>5} {:>5} {:>6}", "Rank", "Strategy", "Rating", "Won", "Drawn", "Lost");
+        for (rank, rating) in self.standings().iter().enumerate() {
+            println!("{:<4} {:<12} {:>7.0} {:>5} {:>5} {:>6}",
+                     rank + 1, rating.strategy, rating.rating, rating.wins, rating.draws, rating.losses);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_round_updates_ratings() {
+        let mut ladder = Ladder::default();
+        ladder.record_round(&[("speedrun".to_string(), 0.4), ("random".to_string(), 0.0), ("risk".to_string(), 0.4)]);
+        
+        let standings = ladder.standings();
+        assert_eq!(standings.last().unwrap().strategy, "random");
+        assert_eq!(standings.last().unwrap().losses, 2);
+        assert_eq!(standings[0].draws, 1);
+        assert!(standings[..2].iter().all(|rating| rating.rating > INITIAL_RATING + 15.0));
+        
+        let total: f64 = standings.iter().map(|rating| rating.rating).sum();
+        assert!((total - 3.0 * INITIAL_RATING).abs() < 1e-9);
+        assert_eq!(Ladder::from_json(&Json::parse(&ladder.to_json().to_string()).unwrap()), Some(ladder));
+    }
+}
@@ -0,0 +1,193 @@
+use crate::player::GameProgress;
+use std::collections::VecDeque;
+use std::io::{IsTerminal, Write};
+
+/// Points kept for each chart; older turns scroll off the left
+const CHART_WIDTH: usize = 24;
+
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Draw values as a one-line bar chart scaled between their minimum and maximum
+pub fn sparkline(values: &[i32]) -> String {
+    let (min, max) = match (values.iter().min(), values.iter().max()) {
+        (Some(min), Some(max)) => (*min, *max),
+        _ => return String::new(),
+    };
+    values.iter()
+        .map(|value| {
+            if max == min {
+                BARS[BARS.len() / 2]
+            } else {
+                let level = (value - min) as f64 / (max - min) as f64;
+                BARS[(level * (BARS.len() - 1) as f64).round() as usize]
+            }
+        })
+        .collect()
+}
+
+/// Recent values of one game state field
+#[derive(Default)]
+struct History(VecDeque<i32>);
+
+impl History {
+    fn push(&mut self, value: Option<i32>) {
+        if let Some(value) = value {
+            if self.0.len() == CHART_WIDTH {
+                self.0.pop_front();
+            }
+            self.0.push_back(value);
+        }
+    }
+    
+    fn chart(&self) -> String {
+        sparkline(&self.0.iter().copied().collect::<Vec<_>>())
+    }
+}
+
+/// One row of the live table
+#[derive(Default)]
+struct Row {
+    turn: usize,
+    condition: String,
+    energy: History,
+    shields: History,
+    klingons: History,
+    outcome: Option<String>,
+}
+
+impl Row {
+    fn status(&self) -> &'static str {
+        if self.turn == 0 && self.condition.is_empty() {
+            "waiting"
+        } else {
+            "running"
+        }
+    }
+}
+
+/// Compact table of concurrently running games. On a terminal it is redrawn in
+/// place after every update; otherwise only finished games are printed.
+pub struct LiveTable {
+    rows: Vec<Row>,
+    /// Win rate over finished games, in percent, after each game finishes
+    win_rates: Vec<i32>,
+    wins: usize,
+    finished: usize,
+    redraw: bool,
+    drawn_lines: usize,
+}
+
+impl LiveTable {
+    pub fn new(games: usize) -> Self {
+        Self {
+            rows: (0..games).map(|_| Row::default()).collect(),
+            win_rates: Vec::new(),
+            wins: 0,
+            finished: 0,
+            redraw: std::io::stdout().is_terminal(),
+            drawn_lines: 0,
+        }
+    }
+    
+    /// Apply a progress update and show it
+    pub fn update(&mut self, progress: GameProgress) {
+        let game = progress.game;
+        let row = match self.rows.get_mut(game) {
+            Some(row) => row,
+            None => return,
+        };
+        
+        row.turn = progress.turn.max(row.turn);
+        if let Some(condition) = progress.condition {
+            row.condition = condition;
+        }
+        if progress.result.is_none() && progress.error.is_none() {
+            row.energy.push(progress.energy);
+            row.shields.push(progress.shields);
+            row.klingons.push(progress.klingons);
+        }
+        let won = progress.result.as_ref().is_some_and(|result| result.is_success());
+        let outcome = match (progress.result, progress.error) {
+            (_, Some(error)) => Some(format!("ERROR: {}", error.lines().next().unwrap_or(""))),
+            (Some(result), None) => Some(format!("{:?}", result)),
+            (None, None) => None,
+        };
+        
+        if let Some(outcome) = outcome {
+            if !self.redraw {
+                println!("Game {:>3}: {} ({} turns)", game + 1, outcome, row.turn);
+            }
+            row.outcome = Some(outcome);
+            
+            self.finished += 1;
+            self.wins += won as usize;
+            self.win_rates.push((self.wins * 100 / self.finished) as i32);
+        }
+        
+        if self.redraw {
+            self.draw();
+        }
+    }
+    
+    fn draw(&mut self) {
+        let mut out = std::io::stdout().lock();
+        
+        // Move back to the top of the previous table and overwrite it
+        if self.drawn_lines > 0 {
+            let _ = write!(out, "\x1b[{}A", self.drawn_lines);
+        }
+        let _ = writeln!(
+            out,
+            "\x1b[2KGAME   TURN  CONDITION  {:<w$}  {:<w$}  {:<w$}  RESULT",
+            "ENERGY", "SHIELDS", "KLINGONS",
+            w = CHART_WIDTH,
+        );
+        for (i, row) in self.rows.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "\x1b[2K{:>4}  {:>5}  {:<9}  {:<w$}  {:<w$}  {:<w$}  {}",
+                i + 1,
+                row.turn,
+                row.condition,
+                row.energy.chart(),
+                row.shields.chart(),
+                row.klingons.chart(),
+                row.outcome.as_deref().unwrap_or(row.status()),
+                w = CHART_WIDTH,
+            );
+        }
+        let _ = writeln!(
+            out,
+            "\x1b[2KWIN RATE {} {}% of {} finished",
+            sparkline(&self.win_rates),
+            self.win_rates.last().copied().unwrap_or(0),
+            self.finished,
+        );
+        let _ = out.flush();
+        self.drawn_lines = self.rows.len() + 2;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_sparkline() {
+        assert_eq!(sparkline(&[]), "");
+        assert_eq!(sparkline(&[0, 50, 100]), "▁▅█");
+        assert_eq!(sparkline(&[3000, 3000]), "▅▅");
+    }
+    
+    #[test]
+    fn test_history_keeps_chart_width() {
+        let mut history = History::default();
+        for value in 0..100 {
+            history.push(Some(value));
+        }
+        history.push(None);
+        assert_eq!(history.0.len(), CHART_WIDTH);
+        assert_eq!(history.0.front(), Some(&(100 - CHART_WIDTH as i32)));
+        assert_eq!(history.chart().chars().count(), CHART_WIDTH);
+    }
+}
@@ -1,26 +1,109 @@
+#[cfg(test)]
+mod bench;
+mod bisect;
+mod combat;
+mod commentary;
+mod compress;
+mod coverage;
+mod display;
+mod duel;
+mod export;
+mod failures;
 mod game;
+mod health;
+mod hooks;
 mod interpreter;
+mod json;
+mod ladder;
+mod live;
+mod metadata;
+mod notify;
+mod pacing;
 mod player;
+mod recorder;
+mod replay;
+mod responses;
+mod results;
+mod scenario;
+mod setup;
+mod source;
 mod strategy;
+mod transcript;
+mod tune;
+mod turn;
+mod typeahead;
+mod verbosity;
+mod workdir;
 
-use anyhow::Result;
-use clap::{Parser, Subcommand};
+use anyhow::{Context, Result};
+use bisect::{Bisection, Divergence};
+use clap::{Args, Parser, Subcommand};
+use combat::CombatSummary;
+use coverage::{
+    parse_program, BasicRSCoverage, CoverageData, CoverageProvider, CoveragePyCoverage,
+    JacocoCoverage, LineRange, ReachabilityReport, SearchAttempt, TargetScenario,
+};
 use interpreter::{
     basicrs::BasicRSInterpreter, 
+    custom::CustomInterpreter,
     trekbasic::TrekBasicInterpreter, 
     trekbasicj::TrekBasicJInterpreter,
-    Interpreter
+    sandbox,
+    Interpreter,
+    InterpreterProfile,
+    ReadTimeouts,
+    Sandbox,
+    Tolerance,
+    WriteChunking,
+};
+use display::{DisplayFilter, DisplayStyle, StatusLine, StatusLineMode};
+use duel::{DuelGame, DuelReport};
+use failures::{FailureStats, PrecedingInput};
+use game::{Advisory, AdvisoryKind};
+use health::HealthReport;
+use hooks::Hooks;
+use ladder::Ladder;
+use live::LiveTable;
+use metadata::{InterpreterIdentity, RunMetadata};
+use notify::{FailedGame, Notifier};
+use pacing::{InjectedLatency, Pacer, PacingStats};
+use player::{GameResult, GameStats, GroupedStats, Player, ProgressReporter, Retention, SeedBucket, SharedStats};
+use results::{group_results, merge_results, parse_tag, RunConfig, RunResults};
+use recorder::ScenarioRecorder;
+use responses::ResponseStats;
+use scenario::Scenario;
+use setup::{GalaxySetup, SETUP_COMMANDS};
+use source::ProgramIndex;
+use transcript::Transcript;
+use tune::{Evolution, Param, ParamValues};
+use turn::TurnLog;
+use strategy::{
+    menu_selections, BoundaryStrategy, CheatStrategy, CommandWeights, DiscoveryStrategy, DockingConfig, DockingStrategy, ExplorationStrategy, ExternalStrategy,
+    NumericPolicy, PluginStrategy, Policy, RandomParams, RandomStrategy, RhaiStrategy, RiskStrategy, RlStrategy, ScriptedStrategy, SpeedrunStrategy, Strategy,
+    WasmStrategy, WeightedRandomStrategy,
 };
-use player::{GameStats, Player};
-use strategy::{CheatStrategy, RandomStrategy};
+use std::cell::Cell;
+use std::fmt;
 use std::fs;
-use std::time::Instant;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use verbosity::Verbosity;
+use workdir::RunLayout;
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+    
+    /// Print less: only results and errors
+    #[arg(short, long, action = clap::ArgAction::Count, global = true)]
+    quiet: u8,
+    
+    /// Print more: -v adds info logs, -vv debug logs, -vvv trace logs
+    #[arg(short, long, action = clap::ArgAction::Count, global = true)]
+    verbose: u8,
 }
 
 #[derive(Subcommand)]
@@ -31,367 +114,2980 @@ enum Commands {
         #[arg(short, long)]
         program: String,
         
-        /// Interpreter to use
-        #[arg(short, long, default_value = "basic-rs")]
-        interpreter: InterpreterType,
+        #[command(flatten)]
+        interpreter: InterpreterArgs,
         
-        /// Strategy to use
-        #[arg(short, long, default_value = "random")]
-        strategy: StrategyType,
+        #[command(flatten)]
+        game: GameArgs,
         
-        /// Display game output
-        #[arg(short, long, default_value_t = false)]
-        display: bool,
+        /// Number of games to play; more than one shows a live table instead of game output
+        #[arg(long, default_value_t = 1)]
+        count: usize,
         
-        /// Maximum number of turns
-        #[arg(short, long, default_value_t = 100)]
-        max_turns: usize,
+        /// Number of games to run at the same time when --count is more than one
+        #[arg(long, default_value_t = 4)]
+        jobs: usize,
+        
+        /// Record coverage and save it to this file. With --count, each worker records
+        /// its own coverage.worker<N>.json in the run directory and they are merged here.
+        #[arg(long)]
+        coverage_file: Option<String>,
+        
+        /// Save the raw interpreter I/O as JSON lines; .gz or .zst compresses it. Not with
+        /// --count
+        #[arg(long, conflicts_with = "count")]
+        transcript: Option<String>,
+        
+        /// Instead of the summary, print one line for scripts:
+        /// result=victory turns=87 duration=12.3s. Nothing else goes to stdout, so not
+        /// with --count, --display or --commentary
+        #[arg(long, default_value_t = false, conflicts_with_all = ["count", "display", "commentary"])]
+        porcelain: bool,
+        
+        /// Describe each turn in a line of play-by-play ("Turn 34: Entered DENEB quadrant,
+        /// 2 Klingons detected"); without --display it's all that is shown of the game.
+        /// Not with --count
+        #[arg(long, default_value_t = false, conflicts_with = "count")]
+        commentary: bool,
+    },
+    
+    /// Run multiple games and collect statistics
+    Benchmark {
+        /// BASIC program to play, or a directory of *.bas programs; repeat to play a
+        /// whole corpus in one run, with stats for each program
+        #[arg(short, long = "program", required = true)]
+        programs: Vec<String>,
+        
+        #[command(flatten)]
+        interpreter: InterpreterArgs,
+        
+        #[command(flatten)]
+        game: GameArgs,
+        
+        /// Number of games to play
+        #[arg(short, long, default_value_t = 10)]
+        games: usize,
+        
+        /// Keep starting games until this many seconds have passed instead of playing
+        /// --games, so runs with slow and fast interpreters get the same wall-clock time;
+        /// a game in progress when it runs out is played to the end
+        #[arg(long, conflicts_with = "games")]
+        time_budget: Option<u64>,
+        
+        /// Enable coverage tracking and save to file
+        #[arg(long)]
+        coverage_file: Option<String>,
         
-        /// Path to BasicRS executable
+        /// Save the run's settings and statistics; .csv writes CSV, anything else JSON
         #[arg(long)]
-        basicrs_path: Option<String>,
+        results_file: Option<String>,
         
-        /// Path to Python executable
+        /// Label the saved results, e.g. --tag nightly --tag basicrs-v0.9, to pick and
+        /// group runs by with `stats merge`
+        #[arg(long = "tag", value_parser = parse_tag, requires = "results_file")]
+        tags: Vec<String>,
+        
+        /// POST a JSON summary to this URL (e.g. a Slack webhook) when the run finishes
         #[arg(long)]
-        python_path: Option<String>,
+        notify_url: Option<String>,
+        
+        /// Also notify as soon as the fraction of BASIC errors and crashes exceeds this (0-1)
+        #[arg(long, requires = "notify_url")]
+        notify_crash_rate: Option<f64>,
         
-        /// Path to TrekBasic script
+        /// Shell command to run when the run finishes; $TREKBOT_SUMMARY is the path of a
+        /// JSON summary and $TREKBOT_STATUS "complete" or "failure"
         #[arg(long)]
-        trekbasic_path: Option<String>,
+        on_complete: Option<String>,
         
-        /// Path to Java executable
+        /// Shell command to run when the run stops with an error or any game ends in a
+        /// BASIC error or crash, with the same environment as --on-complete
         #[arg(long)]
-        java_path: Option<String>,
+        on_failure: Option<String>,
         
-        /// Path to TrekBasicJ JAR
+        /// Seconds a hook may run before it is killed
+        #[arg(long, default_value_t = 300)]
+        hook_timeout: u64,
+        
+        /// Save victories, games reaching new lines (with --coverage-file) and rare
+        /// results here as scenarios, with their seed and transcript
         #[arg(long)]
-        trekbasicj_path: Option<String>,
+        scenario_library: Option<String>,
+        
+        /// Most scenarios kept per category in the library
+        #[arg(long, default_value_t = 10)]
+        scenario_cap: usize,
+        
+        /// Play every game with the same strategy, keeping what it learns from one game
+        /// to the next instead of starting each with a fresh one
+        #[arg(long, default_value_t = false, conflicts_with = "scenario_library")]
+        same_session: bool,
+        
+        /// Start each game in the interpreter process the last one left running, where
+        /// the interpreter can restart the program in place, instead of launching it again
+        #[arg(long, default_value_t = false)]
+        reuse_process: bool,
+        
+        /// Seed every game's strategy and also show the results grouped by ranges of
+        /// this many seeds
+        #[arg(long, value_parser = clap::value_parser!(u64).range(1..), conflicts_with = "same_session")]
+        seed_bucket: Option<u64>,
     },
     
-    /// Run multiple games and collect statistics
-    Benchmark {
+    /// Exercise every command and library computer option, one short game per selection
+    Discover {
         /// Path to the Super Star Trek BASIC program
         #[arg(short, long)]
         program: String,
         
-        /// Interpreter to use
-        #[arg(short, long, default_value = "basic-rs")]
-        interpreter: InterpreterType,
+        #[command(flatten)]
+        interpreter: InterpreterArgs,
         
-        /// Strategy to use
-        #[arg(short, long, default_value = "random")]
-        strategy: StrategyType,
+        /// Times each selection is repeated before resigning
+        #[arg(long, default_value_t = 3)]
+        rounds: usize,
         
-        /// Number of games to play
-        #[arg(short, long, default_value_t = 10)]
-        games: usize,
+        /// Maximum number of turns per selection
+        #[arg(short, long, default_value_t = 50)]
+        max_turns: usize,
         
         /// Display game output
         #[arg(short, long, default_value_t = false)]
         display: bool,
         
-        /// Maximum number of turns per game
+        /// Include invalid library computer options (6+) that trigger the known crash
+        #[arg(long, default_value_t = false)]
+        allow_known_bugs: bool,
+        
+        /// Directory for per-selection coverage files
+        #[arg(long)]
+        coverage_dir: Option<String>,
+    },
+    
+    /// Report BASIC lines that coverage never reached, grouped by routine
+    Reachability {
+        /// Path to the Super Star Trek BASIC program
+        #[arg(short, long)]
+        program: String,
+        
+        /// BasicRS coverage files to merge, e.g. from benchmark --coverage-file or discover --coverage-dir
+        #[arg(long = "coverage-file", required = true)]
+        coverage_files: Vec<String>,
+    },
+    
+    /// Search for a game that executes the given BASIC lines and save it as a scenario
+    CoverTarget {
+        /// Path to the Super Star Trek BASIC program
+        #[arg(short, long)]
+        program: String,
+        
+        #[command(flatten)]
+        interpreter: InterpreterArgs,
+        
+        /// BASIC lines to reach, e.g. 4670-4700; reaching any of them counts
+        #[arg(long)]
+        lines: String,
+        
+        /// Number of games to try before giving up
+        #[arg(long, default_value_t = 50)]
+        attempts: usize,
+        
+        /// Maximum number of turns for the first attempts; later attempts play longer
         #[arg(short, long, default_value_t = 100)]
         max_turns: usize,
         
-        /// Path to BasicRS executable
+        /// Seed for the first attempt, random if not given
         #[arg(long)]
-        basicrs_path: Option<String>,
+        seed: Option<u64>,
         
-        /// Path to Python executable
-        #[arg(long)]
-        python_path: Option<String>,
+        /// Send inputs that trigger known bugs in the original program (e.g. COM options above 5)
+        #[arg(long, default_value_t = false)]
+        allow_known_bugs: bool,
+        
+        /// Directory for the found scenario and its transcript
+        #[arg(long, default_value = "scenarios")]
+        output_dir: String,
+        
+        /// Display game output
+        #[arg(short, long, default_value_t = false)]
+        display: bool,
+    },
+    
+    /// Replay a transcript saved with play --transcript, without an interpreter
+    View {
+        /// Transcript file (JSON lines, optionally .gz or .zst)
+        transcript: String,
+        
+        /// Playback speed relative to the recorded game; 0 shows everything at once
+        #[arg(long, default_value_t = 1.0)]
+        speed: f64,
+        
+        /// Wait for Enter before each turn
+        #[arg(long, default_value_t = false)]
+        step: bool,
+    },
+    
+    /// Summarize the combat in a transcript saved with play --transcript: shots fired per
+    /// weapon, hits and misses, damage dealt and taken, Klingons destroyed per quadrant
+    Analyze {
+        /// Transcript file (JSON lines, optionally .gz or .zst)
+        transcript: String,
+    },
+    
+    /// Find the interpreter build that first plays a golden transcript differently: its
+    /// commands are sent again and the output compared turn by turn
+    Bisect {
+        /// Path to the Super Star Trek BASIC program
+        #[arg(short, long)]
+        program: String,
         
-        /// Path to TrekBasic script
+        /// Builds are swapped in for the path of the selected interpreter
+        #[command(flatten)]
+        interpreter: InterpreterArgs,
+        
+        /// Transcript of the game every build should reproduce, saved with play --transcript
         #[arg(long)]
-        trekbasic_path: Option<String>,
+        golden: String,
         
-        /// Path to Java executable
+        /// Directory of interpreter builds, oldest first by name. Without it only the
+        /// configured interpreter is tested, exiting 0 (same), 1 (diverges) or 125 (could
+        /// not play), for use with `git bisect run`
         #[arg(long)]
-        java_path: Option<String>,
+        builds: Option<String>,
+        
+        #[command(flatten)]
+        comparison: ComparisonArgs,
+    },
+    
+    /// Check the interpreter handles type-ahead: play a game one prompt at a time, then send
+    /// the same commands to a fresh interpreter all at once and compare what it prints
+    TypeAhead {
+        /// Path to the Super Star Trek BASIC program
+        #[arg(short, long)]
+        program: String,
+        
+        /// The interpreter's own random numbers must be seeded too, e.g. with --quirk runtime-arg
+        #[command(flatten)]
+        interpreter: InterpreterArgs,
+        
+        /// Settings for the lockstep game; its strategy is seeded with 1 unless --seed
+        /// says otherwise
+        #[command(flatten)]
+        game: GameArgs,
+        
+        #[command(flatten)]
+        comparison: ComparisonArgs,
+    },
+    
+    /// Turn a transcript saved with play --transcript into a file to share, or the turns
+    /// of many transcripts into one table to analyze
+    Export {
+        /// Transcript files (JSON lines, optionally .gz or .zst); html and cast take one
+        #[arg(required = true)]
+        transcripts: Vec<String>,
+        
+        #[arg(long, value_enum, default_value = "html")]
+        format: ExportFormat,
+        
+        /// Where to write the export; defaults to the transcript name with the format's
+        /// extension, and must be given for several transcripts
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    
+    /// Play two strategies on the same seeds and compare them game by game
+    Duel {
+        /// Path to the Super Star Trek BASIC program
+        #[arg(short, long)]
+        program: String,
+        
+        #[command(flatten)]
+        interpreter: InterpreterArgs,
+        
+        /// Settings for every game; --strategy is replaced by the two dueling strategies
+        #[command(flatten)]
+        game: GameArgs,
+        
+        #[arg(value_parser = parse_strategy)]
+        first: StrategyType,
+        
+        #[arg(value_parser = parse_strategy)]
+        second: StrategyType,
+        
+        /// Seeds for the strategies' random choices, comma separated; each is one game per strategy
+        #[arg(long, value_delimiter = ',', default_value = "1,2,3,4,5")]
+        seeds: Vec<u64>,
+    },
+    
+    /// Rate strategies against each other; without --program just show the standings
+    Ladder {
+        /// Path to the Super Star Trek BASIC program
+        #[arg(short, long)]
+        program: Option<String>,
+        
+        #[command(flatten)]
+        interpreter: InterpreterArgs,
+        
+        /// Settings for every game; --strategy is replaced by each of --strategies
+        #[command(flatten)]
+        game: GameArgs,
+        
+        /// Strategies to rate, comma separated
+        #[arg(long, value_parser = parse_strategy, value_delimiter = ',', default_value = "random,speedrun,risk,exploration")]
+        strategies: Vec<StrategyType>,
+        
+        /// Rounds to play; each one is a match between every pair of strategies
+        #[arg(long, default_value_t = 1)]
+        rounds: usize,
+        
+        /// Games per strategy in each round
+        #[arg(short, long, default_value_t = 10)]
+        games: usize,
+        
+        /// Where the ratings are kept between runs
+        #[arg(long, default_value = "ladder.json")]
+        ladder_file: String,
+    },
+    
+    /// Search for the settings that make the random or risk strategy win most: evolve
+    /// them over generations of candidates, or sweep one across its range
+    Tune {
+        /// Path to the Super Star Trek BASIC program
+        #[arg(short, long)]
+        program: String,
         
-        /// Path to TrekBasicJ JAR
+        #[command(flatten)]
+        interpreter: InterpreterArgs,
+        
+        /// Settings for every game; --strategy is the one tuned, and --seed seeds the
+        /// search as well as the games, to repeat a run
+        #[command(flatten)]
+        game: GameArgs,
+        
+        /// Games played with each candidate, on the same strategy seeds for all
+        #[arg(short, long, default_value_t = 10)]
+        games: usize,
+        
+        /// Generations to evolve
+        #[arg(long, default_value_t = 5)]
+        generations: usize,
+        
+        /// Candidates in each generation
+        #[arg(long, default_value_t = 8)]
+        population: usize,
+        
+        /// Try this one setting at even steps across its range, the rest at their
+        /// defaults, instead of evolving them all
         #[arg(long)]
-        trekbasicj_path: Option<String>,
+        sweep: Option<String>,
         
-        /// Enable coverage tracking and save to file
+        /// Values --sweep tries
+        #[arg(long, default_value_t = 5, requires = "sweep")]
+        steps: usize,
+        
+        /// Save the best settings here, for --strategy-params
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    
+    /// Compare the galaxy each interpreter deals at the start of a game, before any play.
+    /// Interpreters must be seeded alike, e.g. with their profile's args.
+    GalaxySetup {
+        /// Path to the Super Star Trek BASIC program
+        #[arg(short, long)]
+        program: String,
+        
+        #[command(flatten)]
+        interpreter: InterpreterArgs,
+        
+        /// Interpreters to compare, comma separated; defaults to --interpreter and every
+        /// profile in the config file
+        #[arg(long, value_delimiter = ',')]
+        interpreters: Vec<InterpreterType>,
+        
+        /// Save each interpreter's setup to this JSON file
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    
+    /// Keep checking that interpreters still work: every --interval seconds play a short
+    /// smoke game on each one and write their status to --status-file
+    Health {
+        /// Path to the Super Star Trek BASIC program
+        #[arg(short, long)]
+        program: String,
+        
+        #[command(flatten)]
+        interpreter: InterpreterArgs,
+        
+        /// Interpreters to check, comma separated; defaults to --interpreter and every
+        /// profile in the config file
+        #[arg(long, value_delimiter = ',')]
+        interpreters: Vec<InterpreterType>,
+        
+        /// Seconds between rounds of checks; 0 checks once and exits non-zero if any
+        /// interpreter is unhealthy
+        #[arg(long, default_value_t = 300)]
+        interval: u64,
+        
+        /// Turns in each smoke game
+        #[arg(short, long, default_value_t = 10)]
+        max_turns: usize,
+        
+        /// Status of every interpreter after each round: JSON, or Prometheus textfile
+        /// metrics for a name ending in .prom
+        #[arg(long, default_value = "health.json")]
+        status_file: String,
+        
+        /// POST an alert to this URL when an interpreter starts failing or recovers
         #[arg(long)]
-        coverage_file: Option<String>,
+        notify_url: Option<String>,
+    },
+    
+    /// Work with saved benchmark results
+    Stats {
+        #[command(subcommand)]
+        command: StatsCommands,
+    },
+    
+    /// Run suites of scenario files
+    Scenario {
+        #[command(subcommand)]
+        command: ScenarioCommands,
+    },
+    
+    /// Work with the prompts of a BASIC program
+    Prompts {
+        #[command(subcommand)]
+        command: PromptsCommands,
     },
 }
 
-#[derive(clap::ValueEnum, Clone, Debug)]
-enum InterpreterType {
-    #[value(name = "basic-rs")]
-    BasicRS,
-    #[value(name = "trek-basic")]
-    TrekBasic,
-    #[value(name = "trek-basic-j")]
-    TrekBasicJ,
+#[derive(Subcommand)]
+enum PromptsCommands {
+    /// Draft prompt rules from the text shown before each INPUT statement
+    Suggest {
+        /// The BASIC program
+        program: String,
+        
+        /// Write the draft to this file instead of printing it
+        #[arg(short, long)]
+        output: Option<String>,
+    },
 }
 
-#[derive(clap::ValueEnum, Clone, Debug)]
-enum StrategyType {
-    Random,
-    Cheat,
+#[derive(Subcommand)]
+enum ScenarioCommands {
+    /// Play every scenario (*.json) in a directory and report which pass
+    Run {
+        /// Directory of scenario files
+        dir: String,
+        
+        #[command(flatten)]
+        interpreter: InterpreterArgs,
+        
+        /// Settings for every game; the strategy, seed, docking and turn limit come from
+        /// each scenario
+        #[command(flatten)]
+        game: GameArgs,
+    },
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    env_logger::init();
+#[derive(Subcommand)]
+enum StatsCommands {
+    /// Combine result files from separate runs with the same settings into one report
+    Merge {
+        /// Result files written by benchmark --results-file (JSON or CSV)
+        #[arg(required = true)]
+        files: Vec<String>,
+        
+        /// Save the combined results (JSON, or CSV for a .csv name)
+        #[arg(short, long)]
+        output: Option<String>,
+        
+        /// Settings the files may differ in, comma-separated: keep runs with different
+        /// values apart and show one row for each combination instead of merging them
+        #[arg(long, value_delimiter = ',', conflicts_with = "output")]
+        group_by: Vec<GroupBy>,
+        
+        /// Only combine files saved with this tag (repeatable; files need every one)
+        #[arg(long = "tag", value_parser = parse_tag)]
+        tags: Vec<String>,
+    },
+}
+
+/// Interpreter selection and executable locations
+#[derive(Args, Clone, Debug)]
+struct InterpreterArgs {
+    /// Interpreter to use: basic-rs, trek-basic, trek-basic-j, or the name of a profile
+    /// in the config file
+    #[arg(short, long, default_value = "basic-rs")]
+    interpreter: InterpreterType,
     
-    let cli = Cli::parse();
+    /// Config file defining interpreter profiles
+    #[arg(long, default_value = interpreter::profile::DEFAULT_CONFIG)]
+    config: String,
     
-    match &cli.command {
-        Commands::Play {
-            program,
-            interpreter,
-            strategy,
-            display,
-            max_turns,
-            basicrs_path,
-            python_path,
-            trekbasic_path,
-            java_path,
-            trekbasicj_path,
-        } => {
-            play_single_game(
-                program,
-                interpreter,
-                strategy,
-                *display,
-                *max_turns,
-                basicrs_path,
-                python_path,
-                trekbasic_path,
-                java_path,
-                trekbasicj_path,
-            )
-            .await?;
-        }
-        Commands::Benchmark {
-            program,
-            interpreter,
-            strategy,
-            games,
-            display,
-            max_turns,
-            basicrs_path,
-            python_path,
-            trekbasic_path,
-            java_path,
-            trekbasicj_path,
-            coverage_file,
-        } => {
-            run_benchmark(
-                program,
-                interpreter,
-                strategy,
-                *games,
-                *display,
-                *max_turns,
-                basicrs_path,
-                python_path,
-                trekbasic_path,
-                java_path,
-                trekbasicj_path,
-                coverage_file,
-            )
-            .await?;
+    /// Path to BasicRS executable
+    #[arg(long)]
+    basicrs_path: Option<String>,
+    
+    /// Path to Python executable
+    #[arg(long)]
+    python_path: Option<String>,
+    
+    /// Path to TrekBasic script
+    #[arg(long)]
+    trekbasic_path: Option<String>,
+    
+    /// Path to Java executable
+    #[arg(long)]
+    java_path: Option<String>,
+    
+    /// Path to TrekBasicJ JAR
+    #[arg(long)]
+    trekbasicj_path: Option<String>,
+    
+    /// Path to the JaCoCo agent JAR, needed for TrekBasicJ coverage
+    #[arg(long)]
+    jacoco_agent: Option<String>,
+    
+    /// Path to the JaCoCo CLI JAR, needed for TrekBasicJ coverage
+    #[arg(long)]
+    jacoco_cli: Option<String>,
+    
+    /// Extra interpreter quirk, e.g. extra-blank-lines, echoes-input, numeric-rounding=6,
+    /// not-a-prompt=TEXT, prompt=TEXT, runtime-arg=ARG or env=NAME=VALUE (repeatable)
+    #[arg(long = "quirk")]
+    quirks: Vec<String>,
+    
+    /// Launch JVM and Python interpreters with settings that shorten their startup
+    #[arg(long, default_value_t = false)]
+    fast_startup: bool,
+    
+    /// Seconds to wait for the interpreter's first prompt before giving up
+    #[arg(long, default_value_t = 10.0)]
+    startup_timeout: f64,
+    
+    /// Seconds to wait for a prompt once some output has arrived; the game then ends as no_prompt
+    #[arg(long, default_value_t = 5.0)]
+    prompt_timeout: f64,
+    
+    /// Seconds to wait for any output at all; the game then ends as hung
+    #[arg(long, default_value_t = 30.0)]
+    hang_timeout: f64,
+    
+    /// Wait for the interpreter's prompts without any timeout, so games don't depend on
+    /// machine speed (for CI); only for interpreters that always finish their output with
+    /// a prompt TrekBot recognizes, since a hang then lasts until the process exits
+    #[arg(long, default_value_t = false)]
+    no_timeouts: bool,
+    
+    /// Never send more than this many commands per second, for interpreters that drop
+    /// input when flooded (pacing is otherwise adaptive)
+    #[arg(long)]
+    max_commands_per_second: Option<f64>,
+    
+    /// Wait this many milliseconds before sending each command, to test interpreters
+    /// with a slow user
+    #[arg(long, default_value_t = 0)]
+    inject_delay_ms: u64,
+    
+    /// Add a random 0 to this many milliseconds to each command's delay
+    #[arg(long, default_value_t = 0)]
+    inject_jitter_ms: u64,
+    
+    /// Write each command to stdin in this many pieces, to test fragmented input
+    #[arg(long, default_value_t = 1)]
+    split_writes: usize,
+    
+    /// Milliseconds between the pieces of a split write
+    #[arg(long, default_value_t = 20)]
+    split_pause_ms: u64,
+    
+    /// Keep each game's working directory (files the interpreter writes) under this
+    /// directory; by default games run in a temporary directory removed afterwards
+    #[arg(long)]
+    work_dir: Option<String>,
+    
+    /// Run interpreters inside firejail or bubblewrap (Linux)
+    #[arg(long)]
+    sandbox: Option<String>,
+    
+    /// Kill an interpreter after this much CPU time
+    #[arg(long)]
+    sandbox_cpu_seconds: Option<u64>,
+    
+    /// Limit an interpreter's address space to this many megabytes
+    #[arg(long)]
+    sandbox_memory_mb: Option<u64>,
+    
+    /// Limit the size of any file an interpreter writes to this many megabytes
+    #[arg(long)]
+    sandbox_file_size_mb: Option<u64>,
+    
+    /// Run interpreters at this niceness (-20 to 19)
+    #[arg(long, allow_hyphen_values = true)]
+    nice: Option<i32>,
+    
+    /// Run interpreters only on these CPUs, e.g. 0-3,6 (taskset)
+    #[arg(long)]
+    cpus: Option<String>,
+    
+    /// Give each concurrent game a CPU of its own, from --cpus or all CPUs
+    #[arg(long, default_value_t = false)]
+    pin_workers: bool,
+    
+    /// Cap each interpreter at this percentage of one CPU (systemd-run cgroup scope)
+    #[arg(long)]
+    cpu_quota: Option<u32>,
+}
+
+impl InterpreterArgs {
+    /// The same settings with file paths made absolute, since interpreters run in a
+    /// game's working directory
+    fn with_absolute_paths(&self) -> Self {
+        let file = |path: &Option<String>| path.as_deref().map(workdir::absolute);
+        let command = |path: &Option<String>| path.as_deref().map(workdir::absolute_command);
+        Self {
+            basicrs_path: command(&self.basicrs_path),
+            python_path: command(&self.python_path),
+            trekbasic_path: file(&self.trekbasic_path),
+            java_path: command(&self.java_path),
+            trekbasicj_path: file(&self.trekbasicj_path),
+            jacoco_agent: file(&self.jacoco_agent),
+            jacoco_cli: file(&self.jacoco_cli),
+            ..self.clone()
         }
     }
     
-    Ok(())
+    /// Limits and isolation for the interpreter, allowing writes to `writable` besides
+    /// the game's working directory. `worker` is the concurrent game slot, which picks
+    /// the CPU with --pin-workers.
+    fn sandbox(&self, writable: Vec<PathBuf>, worker: Option<usize>) -> Result<Sandbox> {
+        let cpus = match worker.filter(|_| self.pin_workers) {
+            Some(worker) => {
+                let cpus = match &self.cpus {
+                    Some(list) => sandbox::parse_cpu_list(list)?,
+                    None => (0..std::thread::available_parallelism().map_or(1, |n| n.get())).collect(),
+                };
+                Some(cpus[worker % cpus.len()].to_string())
+            }
+            None => self.cpus.clone(),
+        };
+        Ok(Sandbox {
+            cpu_seconds: self.sandbox_cpu_seconds,
+            memory_mb: self.sandbox_memory_mb,
+            file_size_mb: self.sandbox_file_size_mb,
+            wrapper: self.sandbox.as_deref().map(str::parse).transpose()?,
+            writable,
+            nice: self.nice,
+            cpus,
+            cpu_quota: self.cpu_quota,
+        })
+    }
 }
 
-async fn play_single_game(
-    program: &str,
-    interpreter_type: &InterpreterType,
-    strategy_type: &StrategyType,
+/// Options controlling how each game is played
+#[derive(Args, Clone, Debug)]
+struct GameArgs {
+    /// Strategy to use: random, cheat, discovery, speedrun, exploration, risk, boundary,
+    /// rl, scripted, replay, weighted, plugin:<path> for one built as a shared library,
+    /// script:<path> for one written in Rhai, wasm:<path> for one compiled to WebAssembly
+    /// or external:<command> for a program answering JSON turns over stdin and stdout
+    #[arg(short, long, default_value = "random", value_parser = parse_strategy)]
+    strategy: StrategyType,
+    
+    /// Display game output
+    #[arg(short, long, default_value_t = false)]
     display: bool,
+    
+    /// Maximum number of turns per game
+    #[arg(short, long, default_value_t = 100)]
     max_turns: usize,
-    basicrs_path: &Option<String>,
-    python_path: &Option<String>,
-    trekbasic_path: &Option<String>,
-    java_path: &Option<String>,
-    trekbasicj_path: &Option<String>,
-) -> Result<()> {
-    let start_time = Instant::now();
-    match (interpreter_type, strategy_type) {
-        (InterpreterType::BasicRS, StrategyType::Random) => {
-            let interpreter = BasicRSInterpreter::new(basicrs_path.clone());
-            let strategy = RandomStrategy::new();
-            let mut player = Player::new(interpreter, strategy, display);
-            player.set_max_turns(max_turns);
-            
-            let result = player.play_game(program).await?;
-            println!("Game Result: {} ({})", result.description(), player.get_turn_count());
-        }
-        (InterpreterType::BasicRS, StrategyType::Cheat) => {
-            let interpreter = BasicRSInterpreter::new(basicrs_path.clone());
-            let strategy = CheatStrategy::new();
-            let mut player = Player::new(interpreter, strategy, display);
-            player.set_max_turns(max_turns);
-            
-            let result = player.play_game(program).await?;
-            println!("Game Result: {} ({})", result.description(), player.get_turn_count());
-        }
-        (InterpreterType::TrekBasic, StrategyType::Random) => {
-            let interpreter = TrekBasicInterpreter::new(python_path.clone(), trekbasic_path.clone());
-            let strategy = RandomStrategy::new();
-            let mut player = Player::new(interpreter, strategy, display);
-            player.set_max_turns(max_turns);
-            
-            let result = player.play_game(program).await?;
-            println!("Game Result: {} ({})", result.description(), player.get_turn_count());
-        }
-        (InterpreterType::TrekBasic, StrategyType::Cheat) => {
-            let interpreter = TrekBasicInterpreter::new(python_path.clone(), trekbasic_path.clone());
-            let strategy = CheatStrategy::new();
-            let mut player = Player::new(interpreter, strategy, display);
-            player.set_max_turns(max_turns);
-            
-            let result = player.play_game(program).await?;
-            println!("Game Result: {} ({})", result.description(), player.get_turn_count());
-        }
-        (InterpreterType::TrekBasicJ, StrategyType::Random) => {
-            let interpreter = TrekBasicJInterpreter::new(java_path.clone(), trekbasicj_path.clone());
-            let strategy = RandomStrategy::new();
-            let mut player = Player::new(interpreter, strategy, display);
-            player.set_max_turns(max_turns);
-            
-            let result = player.play_game(program).await?;
-            println!("Game Result: {} ({})", result.description(), player.get_turn_count());
-        }
-        (InterpreterType::TrekBasicJ, StrategyType::Cheat) => {
-            let interpreter = TrekBasicJInterpreter::new(java_path.clone(), trekbasicj_path.clone());
-            let strategy = CheatStrategy::new();
-            let mut player = Player::new(interpreter, strategy, display);
-            player.set_max_turns(max_turns);
-            
-            let result = player.play_game(program).await?;
-            println!("Game Result: {} ({})", result.description(), player.get_turn_count());
-        }
-    }
     
+    /// Seed for the strategy's random choices, so the same seed sends the same commands
+    /// to the same prompts; the games after the first in a run take the seeds after it
+    #[arg(long)]
+    seed: Option<u64>,
+    
+    /// Game profile from the config file, saying how the game's endings are worded; by
+    /// default the first whose "programs" match the program, or the built-in superstartrek
+    #[arg(long)]
+    game_profile: Option<String>,
+    
+    /// Head for a starbase to refuel and repair when energy is low or systems are damaged
+    #[arg(long, default_value_t = false)]
+    dock: bool,
+    
+    /// Energy level below which --dock seeks a starbase
+    #[arg(long, default_value_t = 1000)]
+    dock_energy_threshold: i32,
+    
+    /// Answer N instead of Y at the repair authorization prompt when docked
+    #[arg(long, default_value_t = false)]
+    dock_decline_repairs: bool,
+    
+    /// Send inputs that trigger known bugs in the original program (e.g. COM options above 5)
+    #[arg(long, default_value_t = false)]
+    allow_known_bugs: bool,
+    
+    /// Stop with a diagnostic dump at the first unknown prompt or unexpected output,
+    /// e.g. when trying out new prompt rules or a new interpreter
+    #[arg(long, default_value_t = false)]
+    strict: bool,
+    
+    /// Check each torpedo track the interpreter prints against TrekBot's own course
+    /// model and warn about any that differ (with --strict, stop the game)
+    #[arg(long, default_value_t = false)]
+    verify_torpedoes: bool,
+    
+    /// Check each total energy the game reports against the energy expected from the
+    /// shots, moves, hits and docking since the last reading, and warn about unexplained
+    /// jumps (with --strict, stop the game)
+    #[arg(long, default_value_t = false)]
+    verify_energy: bool,
+    
+    /// Warn when the ship is in danger, when the strategy sends input the game should
+    /// refuse, and when the game accepts it anyway (a sign of an interpreter bug)
+    #[arg(long, default_value_t = false)]
+    advise: bool,
+    
+    /// Keep at most this many lines of each turn's output in memory
+    #[arg(long, default_value_t = game::DEFAULT_MAX_OUTPUT_LINES)]
+    max_output_lines: usize,
+    
+    /// Keep only this many of the most recent commands in memory (all by default)
+    #[arg(long)]
+    max_command_history: Option<usize>,
+    
+    /// Keep this many of the most recent output lines, across turns, for strategies to
+    /// look back on
+    #[arg(long, default_value_t = player::DEFAULT_HISTORY_LINES)]
+    max_history_lines: usize,
+    
+    /// With --transcript, record only this many of the most recent lines (all by
+    /// default); a transcript cut short this way can't be replayed
+    #[arg(long)]
+    max_transcript_entries: Option<usize>,
+    
+    /// With --display, show only these kinds of lines: prompts, commands, combat, menus,
+    /// scans, status, text (comma separated)
+    #[arg(long, value_delimiter = ',')]
+    show: Vec<String>,
+    
+    /// With --display, leave out these kinds of lines (same names as --show)
+    #[arg(long, value_delimiter = ',')]
+    hide: Vec<String>,
+    
+    /// Print a status line after every turn: full (one per turn) or compact (one line
+    /// updated in place); off by default
+    #[arg(long, value_enum, default_value = "off", num_args = 0..=1, default_missing_value = "full")]
+    status_line: StatusLineChoice,
+    
+    /// Color displayed lines by kind: auto (when printing to a terminal), always or never
+    #[arg(long, value_enum, default_value = "auto")]
+    color: ColorChoice,
+    
+    /// How much the risk strategy avoids danger, from 0 (fight unless destruction is
+    /// more likely than not) to 1 (never stay under fire)
+    #[arg(long, default_value_t = 0.5)]
+    risk_aversion: f64,
+    
+    /// Policy the rl strategy starts from and, while training, saves what it learns to
+    #[arg(long)]
+    policy_file: Option<String>,
+    
+    /// Whether the rl strategy trains its policy or is evaluated on it
+    #[arg(long, value_enum, default_value = "train")]
+    policy_mode: PolicyMode,
+    
+    /// Answers for the scripted strategy, one per line, each optionally after the kind
+    /// of prompt it is for, e.g. "WarpFactor: 2"
+    #[arg(long)]
+    script: Option<String>,
+    
+    /// What the scripted strategy answers when its script has nothing for a prompt
+    /// ("" for Enter); without it the game stops there
+    #[arg(long)]
+    script_default: Option<String>,
+    
+    /// Transcript whose inputs the replay strategy sends, saved with --transcript
+    #[arg(long)]
+    replay: Option<String>,
+    
+    /// Settings for the random or risk strategy found by `tune --output`, replacing its
+    /// defaults (and --risk-aversion)
+    #[arg(long)]
+    strategy_params: Option<String>,
+    
+    /// JSON file of how likely the weighted strategy is to pick each command, relative
+    /// to the others, e.g. {"NAV": 3, "PHA": 1}; commands left out keep weight 1
+    #[arg(long)]
+    command_weights: Option<String>,
+    
+    /// Weight of one command for the weighted strategy as COMMAND=WEIGHT, e.g. TOR=2,
+    /// over the file's (repeatable)
+    #[arg(long)]
+    command_weight: Vec<String>,
+    
+    /// Fraction of the random strategy's numeric answers at the edges of the valid range
+    #[arg(long, default_value_t = 0.0)]
+    boundary_values: f64,
+    
+    /// Fraction of the random strategy's numeric answers outside the valid range
+    #[arg(long, default_value_t = 0.0)]
+    out_of_range_values: f64,
+}
+
+/// How the output of two games is compared
+#[derive(Args, Clone, Debug)]
+struct ComparisonArgs {
+    /// How far numbers in the output may differ and still count as the same; spacing
+    /// and trailing zeros never matter
+    #[arg(long, default_value_t = 0.0)]
+    tolerance: f64,
+    
+    /// Tolerance for the numbers after a label instead, as LABEL=TOLERANCE, e.g.
+    /// DIRECTION=0.01 (repeatable)
+    #[arg(long)]
+    field_tolerance: Vec<String>,
+}
+
+impl ComparisonArgs {
+    fn tolerance(&self) -> Result<Tolerance> {
+        let mut tolerance = Tolerance::new(self.tolerance);
+        for field in &self.field_tolerance {
+            tolerance.add_field(field)?;
+        }
+        Ok(tolerance)
+    }
+}
+
+impl GameArgs {
+    /// Strategy seed of the game at `index` in a run, from --seed
+    fn game_seed(&self, index: usize) -> Option<u64> {
+        self.seed.map(|seed| seed.wrapping_add(index as u64))
+    }
+    
+    /// Which lines --display prints, from --show and --hide
+    fn display_filter(&self) -> Result<DisplayFilter> {
+        DisplayFilter::new(&self.show, &self.hide)
+    }
+    
+    /// How much output and command history each game keeps in memory
+    fn retention(&self) -> Retention {
+        Retention {
+            output_lines: self.max_output_lines,
+            commands: self.max_command_history,
+            history_lines: self.max_history_lines,
+            transcript_entries: self.max_transcript_entries,
+        }
+    }
+    
+    fn status_line(&self) -> StatusLineMode {
+        match self.status_line {
+            StatusLineChoice::Off => StatusLineMode::Off,
+            StatusLineChoice::Full => StatusLineMode::Full,
+            StatusLineChoice::Compact => StatusLineMode::Compact,
+        }
+    }
+    
+    /// Colors and turn numbers for --display
+    fn display_style(&self) -> DisplayStyle {
+        let style = DisplayStyle::for_terminal();
+        match self.color {
+            ColorChoice::Auto => style,
+            ColorChoice::Always => DisplayStyle { color: true, ..style },
+            ColorChoice::Never => DisplayStyle { color: false, ..style },
+        }
+    }
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+enum StatusLineChoice {
+    Off,
+    Full,
+    Compact,
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Clone, Debug)]
+enum InterpreterType {
+    BasicRS,
+    TrekBasic,
+    TrekBasicJ,
+    /// A profile from the config file, looked up when the interpreter is launched
+    Custom(String),
+}
+
+impl InterpreterType {
+    /// Name recorded in results and metadata
+    fn slug(&self) -> String {
+        match self {
+            InterpreterType::Custom(name) => name.clone(),
+            builtin => format!("{:?}", builtin).to_lowercase(),
+        }
+    }
+}
+
+impl std::str::FromStr for InterpreterType {
+    type Err = anyhow::Error;
+    
+    fn from_str(name: &str) -> Result<Self> {
+        Ok(match name {
+            "basic-rs" => InterpreterType::BasicRS,
+            "trek-basic" => InterpreterType::TrekBasic,
+            "trek-basic-j" => InterpreterType::TrekBasicJ,
+            "" => anyhow::bail!("Interpreter name is empty"),
+            profile => InterpreterType::Custom(profile.to_string()),
+        })
+    }
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+enum ExportFormat {
+    /// Standalone page with a timeline, the transcript and the maps of each turn
+    Html,
+    /// asciinema v2 recording of the terminal session
+    Cast,
+    /// One CSV row per turn: the game, the prompt, the command and the state before and after
+    Csv,
+    /// The CSV table as typed Parquet, one row group per game (needs the parquet feature)
+    Parquet,
+    /// One JSON line per turn, with the whole state before and after
+    Turns,
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+enum StrategyType {
+    Random,
+    /// Play to win: hunt Klingons with a galactic record, aimed weapons and docking
+    Cheat,
+    Discovery,
+    /// Fewest turns to victory: beeline to the Klingons, no defensive play
+    Speedrun,
+    /// Visit every quadrant, scanning and mapping as it goes, no combat
+    Exploration,
+    /// Fight, raise shields or flee depending on the chance of being destroyed
+    Risk,
+    /// Answer numeric prompts with boundary and out-of-range values in a fixed rotation
+    Boundary,
+    /// Learn when to fight, raise shields or flee over many games (see --policy-file)
+    Rl,
+    /// Send the answers in a script file, for deterministic regression tests (see --script)
+    Scripted,
+    /// Send the inputs of a recorded game again exactly, to reproduce it (see --replay)
+    Replay,
+    /// Random play with the commands drawn by weight (see --command-weights)
+    Weighted,
+    /// A strategy built as a shared library, see PluginStrategy
+    #[value(skip)]
+    Plugin(String),
+    /// A strategy written as a Rhai script, see RhaiStrategy
+    #[value(skip)]
+    Script(String),
+    /// A strategy compiled to WebAssembly, run sandboxed, see WasmStrategy
+    #[value(skip)]
+    Wasm(String),
+    /// A program run alongside TrekBot, speaking JSON over stdio, see ExternalStrategy
+    #[value(skip)]
+    External(String),
+}
+
+impl fmt::Display for StrategyType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StrategyType::Plugin(path) => write!(f, "plugin:{}", path),
+            StrategyType::Script(path) => write!(f, "script:{}", path),
+            StrategyType::Wasm(path) => write!(f, "wasm:{}", path),
+            StrategyType::External(command) => write!(f, "external:{}", command),
+            other => f.write_str(&format!("{:?}", other).to_lowercase()),
+        }
+    }
+}
+
+/// A built-in strategy by name, `plugin:`, `script:` or `wasm:` and the path of a
+/// strategy plugin, script or WebAssembly module, or `external:` and a command to run
+fn parse_strategy(name: &str) -> Result<StrategyType, String> {
+    if let Some(path) = name.strip_prefix("plugin:") {
+        return Ok(StrategyType::Plugin(path.to_string()));
+    }
+    if let Some(path) = name.strip_prefix("script:") {
+        return Ok(StrategyType::Script(path.to_string()));
+    }
+    if let Some(path) = name.strip_prefix("wasm:") {
+        return Ok(StrategyType::Wasm(path.to_string()));
+    }
+    if let Some(command) = name.strip_prefix("external:") {
+        return Ok(StrategyType::External(command.to_string()));
+    }
+    <StrategyType as clap::ValueEnum>::from_str(name, true)
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+enum PolicyMode {
+    /// Explore and update the policy, saving it to --policy-file after each game
+    Train,
+    /// Play the best known action every time and leave the policy as it is
+    Evaluate,
+}
+
+/// Setting of saved results that `stats merge --group-by` keeps runs apart by
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+enum GroupBy {
+    Program,
+    Interpreter,
+    Strategy,
+    MaxTurns,
+    Dock,
+    /// Each --tag of the run; a run with several counts under each
+    Tag,
+}
+
+impl GroupBy {
+    /// The setting's name in result files
+    fn field(self) -> &'static str {
+        match self {
+            GroupBy::Program => "program",
+            GroupBy::Interpreter => "interpreter",
+            GroupBy::Strategy => "strategy",
+            GroupBy::MaxTurns => "max_turns",
+            GroupBy::Dock => "dock",
+            GroupBy::Tag => "tag",
+        }
+    }
+}
+
+/// Coverage settings for a single game
+struct CoverageOptions {
+    file: String,
+    reset: bool,
+}
+
+/// How a single game went
+struct GameRun {
+    result: GameResult,
+    turns: usize,
+    pacing: PacingStats,
+    commands: Vec<String>,
+    responses: ResponseStats,
+    /// The last command sent, to tell what an interpreter failure followed
+    preceding: Option<PrecedingInput>,
+    /// Torpedo tracks and energy readings that differed from TrekBot's models, with
+    /// --verify-torpedoes or --verify-energy
+    discrepancies: usize,
+    /// Invalid input the game accepted, with --advise
+    accepted_invalid: usize,
+    /// Launching the interpreter until its first prompt
+    startup: Duration,
+    /// The rest of the game
+    play: Duration,
+    /// The game state as the game ended
+    state: game::GameState,
+    /// The strategy as the game left it, to play the next game of its session
+    strategy: Box<dyn Strategy>,
+    /// The interpreter, still running, when it can start the next game in place
+    running: Option<Box<dyn Interpreter + Send>>,
+}
+
+/// How a single game is run, beyond the interpreter and strategy
+struct RunOptions {
+    display: bool,
+    max_turns: usize,
+    coverage: Option<CoverageOptions>,
+    progress: Option<ProgressReporter>,
+    transcript: Option<String>,
+    /// Stamped on the transcript
+    metadata: Option<RunMetadata>,
+    /// Working directory for the interpreter, from RunLayout::game_dir
+    work_dir: Option<PathBuf>,
+    strict: bool,
+    verify_torpedoes: bool,
+    verify_energy: bool,
+    advise: bool,
+    display_filter: DisplayFilter,
+    display_style: DisplayStyle,
+    status_line: StatusLineMode,
+    /// Slot of a game played concurrently with others, for --pin-workers
+    worker: Option<usize>,
+    retention: Retention,
+    commentary: bool,
+    /// Send these commands all at once instead of playing the strategy, see Player::set_type_ahead
+    type_ahead: Option<Vec<String>>,
+    /// Looked up in the interpreter's config file; picked by program if None
+    game_profile: Option<String>,
+    /// The strategy comes from an earlier game and keeps what it learned there
+    continue_session: bool,
+    /// Leave the interpreter running at the end of the game if it can start another in place
+    reuse_process: bool,
+    /// An interpreter an earlier game left running, restarted instead of launching a new one
+    running: Option<Box<dyn Interpreter + Send>>,
+    /// Stdout is kept for the --porcelain line, so notices go to stderr
+    porcelain: bool,
+}
+
+impl RunOptions {
+    /// Options for a game played with the GameArgs flags that apply to every game: strict
+    /// mode, verification, advisories, the display and what is kept in memory
+    fn for_game(game: &GameArgs, display: bool, max_turns: usize) -> Result<Self> {
+        let mut options = Self::new(display, max_turns);
+        options.strict = game.strict;
+        options.game_profile = game.game_profile.clone();
+        options.verify_torpedoes = game.verify_torpedoes;
+        options.verify_energy = game.verify_energy;
+        options.advise = game.advise;
+        options.display_filter = game.display_filter()?;
+        options.display_style = game.display_style();
+        options.status_line = game.status_line();
+        options.retention = game.retention();
+        Ok(options)
+    }
+    
+    fn new(display: bool, max_turns: usize) -> Self {
+        Self {
+            display,
+            max_turns,
+            coverage: None,
+            progress: None,
+            transcript: None,
+            metadata: None,
+            work_dir: None,
+            strict: false,
+            verify_torpedoes: false,
+            verify_energy: false,
+            advise: false,
+            display_filter: DisplayFilter::default(),
+            display_style: DisplayStyle::default(),
+            status_line: StatusLineMode::Off,
+            worker: None,
+            retention: Retention::default(),
+            commentary: false,
+            type_ahead: None,
+            game_profile: None,
+            continue_session: false,
+            reuse_process: false,
+            running: None,
+            porcelain: false,
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+    Verbosity::from_flags(cli.quiet, cli.verbose).install();
+    
+    match &cli.command {
+        Commands::Play {
+            program,
+            interpreter,
+            game,
+            count,
+            jobs,
+            coverage_file,
+            transcript,
+            porcelain,
+            commentary,
+        } => {
+            if *count > 1 {
+                play_concurrent_games(program, interpreter, game, *count, *jobs, coverage_file.as_deref()).await?;
+            } else {
+                play_single_game(program, interpreter, game, transcript, coverage_file.as_deref(), *porcelain, *commentary).await?;
+            }
+        }
+        Commands::Benchmark {
+            programs,
+            interpreter,
+            game,
+            games,
+            time_budget,
+            coverage_file,
+            results_file,
+            tags,
+            notify_url,
+            notify_crash_rate,
+            on_complete,
+            on_failure,
+            hook_timeout,
+            scenario_library,
+            scenario_cap,
+            same_session,
+            reuse_process,
+            seed_bucket,
+        } => {
+            let programs = expand_programs(programs)?;
+            let batch = programs.len() > 1;
+            let hooks = Hooks {
+                on_complete: on_complete.clone(),
+                on_failure: on_failure.clone(),
+                timeout: Duration::from_secs(*hook_timeout),
+                results_file: results_file.clone().filter(|_| !batch),
+            };
+            let reuse = Reuse { strategy: *same_session, process: *reuse_process };
+            let budget = match time_budget {
+                Some(seconds) => Budget::Time(Duration::from_secs(*seconds)),
+                None => Budget::Games(*games),
+            };
+            let mut by_program = GroupedStats::new();
+            let mut outcome = Ok(GameStats::new());
+            for program in &programs {
+                if batch {
+                    println!("=== {} ===", program);
+                }
+                // Each program of a batch saves to its own coverage and results files
+                let file_for = |file: &Option<String>| file.as_deref().map(|file| if batch { program_file(file, program) } else { file.to_string() });
+                let outputs = BenchmarkOutputs {
+                    coverage_file: file_for(coverage_file),
+                    results_file: file_for(results_file),
+                    tags: tags.clone(),
+                    notifier: notify_url.clone().map(|url| Notifier::new(url, *notify_crash_rate)),
+                    recorder: scenario_library.as_deref().map(|dir| ScenarioRecorder::new(dir, *scenario_cap)).transpose()?,
+                };
+                outcome = run_benchmark(program, interpreter, game, budget, reuse, *seed_bucket, outputs).await;
+                match &outcome {
+                    Ok(stats) => by_program.merge(program.clone(), stats),
+                    Err(_) => break,
+                }
+            }
+            if batch && outcome.is_ok() {
+                println!();
+                by_program.print_table("Program");
+                outcome = Ok(by_program.total());
+            }
+            hooks.run("benchmark", &outcome).await;
+            outcome?;
+        }
+        Commands::Discover {
+            program,
+            interpreter,
+            rounds,
+            max_turns,
+            display,
+            allow_known_bugs,
+            coverage_dir,
+        } => {
+            run_discovery(
+                program,
+                interpreter,
+                *rounds,
+                *max_turns,
+                *display,
+                *allow_known_bugs,
+                coverage_dir,
+            )
+            .await?;
+        }
+        Commands::Reachability { program, coverage_files } => {
+            report_reachability(program, coverage_files)?;
+        }
+        Commands::CoverTarget {
+            program,
+            interpreter,
+            lines,
+            attempts,
+            max_turns,
+            seed,
+            allow_known_bugs,
+            output_dir,
+            display,
+        } => {
+            let target = TargetSearch {
+                lines: lines.parse()?,
+                attempts: *attempts,
+                max_turns: *max_turns,
+                seed: seed.unwrap_or_else(|| rand::random::<u32>() as u64),
+                allow_known_bugs: *allow_known_bugs,
+                display: *display,
+            };
+            cover_target(program, interpreter, &target, output_dir).await?;
+        }
+        Commands::View { transcript, speed, step } => {
+            replay::view(&Transcript::load(transcript)?, *speed, *step).await?;
+        }
+        Commands::Analyze { transcript } => {
+            analyze_transcript(transcript)?;
+        }
+        Commands::Bisect { program, interpreter, golden, builds, comparison } => {
+            run_bisect(program, interpreter, golden, builds.as_deref(), &comparison.tolerance()?).await?;
+        }
+        Commands::TypeAhead { program, interpreter, game, comparison } => {
+            run_type_ahead(program, interpreter, game, game.seed.unwrap_or(1), &comparison.tolerance()?).await?;
+        }
+        Commands::Export { transcripts, format, output } => {
+            export_transcripts(transcripts, format, output.as_deref())?;
+        }
+        Commands::Duel { program, interpreter, game, first, second, seeds } => {
+            run_duel(program, interpreter, game, [first, second], seeds).await?;
+        }
+        Commands::Ladder { program, interpreter, game, strategies, rounds, games, ladder_file } => {
+            run_ladder(program.as_deref(), interpreter, game, strategies, *rounds, *games, ladder_file).await?;
+        }
+        Commands::Tune { program, interpreter, game, games, generations, population, sweep, steps, output } => {
+            let search = match sweep {
+                Some(name) => Search::Sweep { name: name.clone(), steps: *steps },
+                None => Search::Evolve { generations: *generations, population: *population },
+            };
+            run_tune(program, interpreter, game, search, *games, game.seed, output.as_deref()).await?;
+        }
+        Commands::GalaxySetup { program, interpreter, interpreters, output } => {
+            compare_galaxy_setups(program, interpreter, interpreters, output.as_deref()).await?;
+        }
+        Commands::Health { program, interpreter, interpreters, interval, max_turns, status_file, notify_url } => {
+            let notifier = notify_url.clone().map(|url| Notifier::new(url, None));
+            let check = HealthCheck { interval: Duration::from_secs(*interval), max_turns: *max_turns, status_file: status_file.clone() };
+            run_health_checks(program, interpreter, interpreters, &check, notifier).await?;
+        }
+        Commands::Stats { command: StatsCommands::Merge { files, group_by, tags, .. } } if !group_by.is_empty() => {
+            group_stats(files, tags, group_by)?;
+        }
+        Commands::Stats { command: StatsCommands::Merge { files, output, tags, .. } } => {
+            merge_stats(files, tags, output)?;
+        }
+        Commands::Scenario { command: ScenarioCommands::Run { dir, interpreter, game } } => {
+            run_scenarios(dir, interpreter, game).await?;
+        }
+        Commands::Prompts { command: PromptsCommands::Suggest { program, output } } => {
+            suggest_prompts(program, output)?;
+        }
+    }
+    
+    Ok(())
+}
+
+/// Print the combat summary of a saved transcript
+fn analyze_transcript(path: &str) -> Result<()> {
+    let log = TurnLog::from_transcript(&Transcript::load(path)?)?;
+    println!("=== Combat in {} ===", path);
+    for line in CombatSummary::from_turns(&log.turns).lines() {
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+/// Play the golden transcript's commands on each build, or just the configured
+/// interpreter, and report where the output first differs
+async fn run_bisect(program: &str, interpreter_args: &InterpreterArgs, golden: &str, builds: Option<&str>, tolerance: &Tolerance) -> Result<()> {
+    let golden_turns = TurnLog::from_transcript(&Transcript::load(golden)?)?;
+    let Some(builds) = builds else {
+        let code = match replay_golden(program, interpreter_args, &golden_turns, tolerance).await {
+            Ok(None) => 0,
+            Ok(Some(_)) => 1,
+            Err(e) => {
+                eprintln!("Could not play the game: {:#}", e);
+                125
+            }
+        };
+        std::process::exit(code);
+    };
+    
+    let builds = bisect::builds(builds)?;
+    println!("Bisecting {} builds against {}...", builds.len(), golden);
+    let mut bisection = Bisection::new(builds.len());
+    let mut first_divergence = None;
+    while let Some(index) = bisection.next() {
+        let build = &builds[index];
+        let mut build_args = interpreter_args.clone();
+        let path = Some(build.to_string_lossy().to_string());
+        match build_args.interpreter {
+            InterpreterType::BasicRS => build_args.basicrs_path = path,
+            InterpreterType::TrekBasic => build_args.trekbasic_path = path,
+            InterpreterType::TrekBasicJ => build_args.trekbasicj_path = path,
+            InterpreterType::Custom(name) => anyhow::bail!("Bisect swaps built-in interpreter paths; interpreter '{}' is a profile", name),
+        }
+        let divergence = replay_golden(program, &build_args, &golden_turns, tolerance).await
+            .with_context(|| format!("Failed to play on {}", build.display()))?;
+        println!("  {}: {}", build.display(), if divergence.is_some() { "diverges" } else { "same" });
+        bisection.record(divergence.is_some());
+        if divergence.is_some() {
+            first_divergence = divergence;
+        }
+    }
+    
+    match (bisection.first_bad(), first_divergence) {
+        (Some(index), Some(divergence)) => {
+            println!("First diverging build: {}", builds[index].display());
+            let turn = match divergence.turn {
+                0 => "opening".to_string(),
+                turn => format!("turn {}", turn),
+            };
+            println!("  {}: expected {:?}, got {:?}", turn, divergence.expected, divergence.actual);
+        }
+        _ => println!("Every build reproduces {}", golden),
+    }
+    Ok(())
+}
+
+/// Send the golden game's commands to the interpreter and compare what it prints
+async fn replay_golden(program: &str, interpreter_args: &InterpreterArgs, golden: &TurnLog, tolerance: &Tolerance) -> Result<Option<Divergence>> {
+    let commands = golden.commands();
+    let transcript = std::env::temp_dir().join(format!("trekbot-bisect-{}.jsonl", std::process::id()));
+    let mut options = RunOptions::new(false, commands.len());
+    options.transcript = Some(transcript.to_string_lossy().to_string());
+    run_game(program, interpreter_args, Box::new(ScriptedStrategy::new(commands)), options).await?;
+    
+    let actual = TurnLog::from_transcript(&Transcript::load(&transcript.to_string_lossy())?)?;
+    fs::remove_file(&transcript).ok();
+    Ok(bisect::first_divergence(golden, &actual, tolerance))
+}
+
+/// Play a seeded game in lockstep, then the same commands as type-ahead, and fail if the
+/// interpreter printed anything different
+async fn run_type_ahead(program: &str, interpreter_args: &InterpreterArgs, game: &GameArgs, seed: u64, tolerance: &Tolerance) -> Result<()> {
+    if interpreter_args.no_timeouts {
+        anyhow::bail!("type-ahead reads until the interpreter goes quiet, which needs the hang timeout; drop --no-timeouts");
+    }
+    let temp_transcript = |mode: &str| {
+        std::env::temp_dir().join(format!("trekbot-{}-{}.jsonl", mode, std::process::id())).to_string_lossy().to_string()
+    };
+    let lockstep_file = temp_transcript("lockstep");
+    let mut options = RunOptions::for_game(game, game.display, game.max_turns)?;
+    options.transcript = Some(lockstep_file.clone());
+    let lockstep = run_game(program, interpreter_args, create_seeded_strategy(game, Some(seed))?, options).await?;
+    println!("Lockstep: {} after {} commands", lockstep.result.description(), lockstep.commands.len());
+    
+    let type_ahead_file = temp_transcript("type-ahead");
+    let mut options = RunOptions::for_game(game, false, game.max_turns)?;
+    options.transcript = Some(type_ahead_file.clone());
+    options.type_ahead = Some(lockstep.commands);
+    let type_ahead = run_game(program, interpreter_args, Box::new(ScriptedStrategy::new(Vec::new())), options).await?;
+    println!("Type-ahead: {}", type_ahead.result.description());
+    
+    let expected = typeahead::output_lines(&Transcript::load(&lockstep_file)?);
+    let actual = typeahead::output_lines(&Transcript::load(&type_ahead_file)?);
+    fs::remove_file(&lockstep_file).ok();
+    fs::remove_file(&type_ahead_file).ok();
+    match typeahead::first_mismatch(&expected, &actual, tolerance) {
+        None => {
+            println!("Type-ahead output matches lockstep ({} lines)", expected.len());
+            Ok(())
+        }
+        Some(mismatch) => {
+            println!("  line {}: expected {:?}, got {:?}", mismatch.line, mismatch.expected, mismatch.actual);
+            anyhow::bail!("Interpreter output differs when commands are typed ahead")
+        }
+    }
+}
+
+/// Write saved transcripts out in another format
+fn export_transcripts(paths: &[String], format: &ExportFormat, output: Option<&str>) -> Result<()> {
+    let stem = |path: &str| path.trim_end_matches(".gz").trim_end_matches(".zst").trim_end_matches(".jsonl").to_string();
+    let extension = match format {
+        ExportFormat::Html => "html",
+        ExportFormat::Cast => "cast",
+        ExportFormat::Csv => "csv",
+        ExportFormat::Parquet => "parquet",
+        ExportFormat::Turns => "turns.jsonl",
+    };
+    let output = match (output, paths) {
+        (Some(output), _) => output.to_string(),
+        (None, [path]) => format!("{}.{}", stem(path), extension),
+        (None, _) => anyhow::bail!("Give --output to export {} transcripts into one file", paths.len()),
+    };
+    
+    match (format, paths) {
+        (ExportFormat::Html | ExportFormat::Cast, [path]) => {
+            let transcript = Transcript::load(path)?;
+            let contents = match format {
+                ExportFormat::Html => export::to_html(&transcript)?,
+                _ => export::to_cast(&transcript),
+            };
+            fs::write(&output, contents).with_context(|| format!("Failed to write {}", output))?;
+        }
+        (ExportFormat::Turns, [path]) => {
+            let log = TurnLog::from_transcript(&Transcript::load(path)?)?;
+            compress::write_file(&output, &log.to_jsonl())?;
+        }
+        (ExportFormat::Html | ExportFormat::Cast | ExportFormat::Turns, _) => anyhow::bail!("The {} format takes one transcript", extension),
+        (ExportFormat::Csv | ExportFormat::Parquet, _) => {
+            // Each game is named after its file, without directories or extensions, and
+            // loaded only when its turns are written
+            let games = paths.iter().map(|path| {
+                let name = Path::new(&stem(path)).file_name().map_or(path.clone(), |name| name.to_string_lossy().to_string());
+                Ok((name, Transcript::load(path)?))
+            });
+            match format {
+                ExportFormat::Parquet => export::write_parquet(games, &output)?,
+                _ => compress::write_file(&output, &export::to_turn_csv(&games.collect::<Result<Vec<_>>>()?)?)?,
+            }
+        }
+    }
+    match paths {
+        [path] => println!("Exported {} to {}", path, output),
+        _ => println!("Exported {} transcripts to {}", paths.len(), output),
+    }
+    Ok(())
+}
+
+/// Build the docking configuration from the command-line flags
+fn docking_config(game: &GameArgs) -> Option<DockingConfig> {
+    if !game.dock {
+        return None;
+    }
+    Some(DockingConfig {
+        energy_threshold: game.dock_energy_threshold,
+        authorize_repairs: !game.dock_decline_repairs,
+        ..DockingConfig::default()
+    })
+}
+
+/// Create the requested strategy, optionally wrapped with docking behavior, with random
+/// choices fixed by `seed` where the strategy has them
+fn create_seeded_strategy(game: &GameArgs, seed: Option<u64>) -> Result<Box<dyn Strategy>> {
+    let params = match &game.strategy_params {
+        Some(path) => tune::load(path)?,
+        None => ParamValues::new(),
+    };
+    create_tuned_strategy(game, seed, &params)
+}
+
+/// Like create_seeded_strategy(), with tuned settings in place of the strategy's own
+fn create_tuned_strategy(game: &GameArgs, seed: Option<u64>, params: &ParamValues) -> Result<Box<dyn Strategy>> {
+    let strategy: Box<dyn Strategy> = match &game.strategy {
+        StrategyType::Random => {
+            let mut strategy = seed.map_or_else(RandomStrategy::new, RandomStrategy::with_seed);
+            strategy.set_params(RandomParams::from_values(params));
+            strategy.set_allow_known_bugs(game.allow_known_bugs);
+            strategy.set_numeric_policy(NumericPolicy {
+                boundary: game.boundary_values,
+                out_of_range: game.out_of_range_values,
+            });
+            Box::new(strategy)
+        }
+        StrategyType::Cheat => Box::new(seed.map_or_else(CheatStrategy::new, CheatStrategy::with_seed)),
+        StrategyType::Discovery => {
+            let mut strategy = DiscoveryStrategy::full_menu(game.allow_known_bugs, 1);
+            if let Some(seed) = seed {
+                strategy.set_seed(seed);
+            }
+            Box::new(strategy)
+        }
+        StrategyType::Speedrun => Box::new(seed.map_or_else(SpeedrunStrategy::new, SpeedrunStrategy::with_seed)),
+        StrategyType::Exploration => Box::new(seed.map_or_else(ExplorationStrategy::new, ExplorationStrategy::with_seed)),
+        StrategyType::Risk => {
+            let aversion = params.get("risk_aversion").copied().unwrap_or(game.risk_aversion);
+            Box::new(match seed {
+                Some(seed) => RiskStrategy::with_seed(aversion, seed),
+                None => RiskStrategy::new(aversion),
+            })
+        }
+        StrategyType::Boundary => {
+            let mut strategy = BoundaryStrategy::new();
+            strategy.set_allow_known_bugs(game.allow_known_bugs);
+            Box::new(strategy)
+        }
+        StrategyType::Rl => {
+            let policy = match &game.policy_file {
+                Some(path) => Policy::load_or_new(path)?,
+                None => Policy::new(),
+            };
+            let learning = matches!(game.policy_mode, PolicyMode::Train);
+            let mut strategy = match seed {
+                Some(seed) => RlStrategy::with_seed(policy, learning, seed),
+                None => RlStrategy::new(policy, learning),
+            };
+            if let Some(path) = &game.policy_file {
+                strategy.set_policy_file(path.clone());
+            }
+            Box::new(strategy)
+        }
+        StrategyType::Scripted => {
+            let path = game.script.as_deref().context("The scripted strategy needs --script")?;
+            let mut strategy = ScriptedStrategy::load(path)?;
+            strategy.set_default(game.script_default.clone());
+            Box::new(strategy)
+        }
+        StrategyType::Replay => {
+            let path = game.replay.as_deref().context("The replay strategy needs --replay")?;
+            Box::new(ScriptedStrategy::from_transcript(&Transcript::load(path)?))
+        }
+        StrategyType::Weighted => {
+            let mut weights = match &game.command_weights {
+                Some(path) => CommandWeights::load(path)?,
+                None => CommandWeights::new(),
+            };
+            for spec in &game.command_weight {
+                weights.add(spec)?;
+            }
+            let mut strategy = match seed {
+                Some(seed) => WeightedRandomStrategy::with_seed(weights, seed),
+                None => WeightedRandomStrategy::new(weights),
+            };
+            let random = strategy.parent_mut();
+            random.set_params(RandomParams::from_values(params));
+            random.set_allow_known_bugs(game.allow_known_bugs);
+            random.set_numeric_policy(NumericPolicy {
+                boundary: game.boundary_values,
+                out_of_range: game.out_of_range_values,
+            });
+            Box::new(strategy)
+        }
+        StrategyType::Plugin(path) => Box::new(PluginStrategy::load(path, seed.unwrap_or_else(rand::random))?),
+        StrategyType::Script(path) => Box::new(RhaiStrategy::load(path, seed)?),
+        StrategyType::Wasm(path) => Box::new(WasmStrategy::load(path, seed.unwrap_or_else(rand::random))?),
+        StrategyType::External(command) => Box::new(ExternalStrategy::spawn(command, seed.unwrap_or_else(rand::random))?),
+    };
+    
+    Ok(match docking_config(game) {
+        Some(config) => Box::new(DockingStrategy::new(strategy, config)),
+        None => strategy,
+    })
+}
+
+/// Play one game with the given interpreter and strategy, returning the result and turn count
+async fn play_with(
+    mut interpreter: Box<dyn Interpreter + Send>,
+    interpreter_args: &InterpreterArgs,
+    mut strategy: Box<dyn Strategy>,
+    program: &str,
+    options: RunOptions,
+    restart: bool,
+) -> Result<GameRun> {
+    // A restarted interpreter keeps what it was set up with at launch
+    if !restart {
+        if interpreter_args.fast_startup {
+            let name = interpreter.name();
+            interpreter.quirks_mut().add_fast_startup(name);
+        }
+        for quirk in &interpreter_args.quirks {
+            interpreter.quirks_mut().add(quirk.parse()?);
+        }
+        if interpreter_args.no_timeouts {
+            // A timeout this long never expires
+            interpreter.set_startup_timeout(Duration::MAX);
+            interpreter.set_read_timeouts(ReadTimeouts::UNLIMITED);
+        } else {
+            interpreter.set_startup_timeout(Duration::from_secs_f64(interpreter_args.startup_timeout));
+            interpreter.set_read_timeouts(ReadTimeouts {
+                partial: Duration::from_secs_f64(interpreter_args.prompt_timeout),
+                hang: Duration::from_secs_f64(interpreter_args.hang_timeout),
+            });
+        }
+        let mut writable = Vec::new();
+        if let Some(coverage) = &options.coverage {
+            let file = workdir::absolute(&coverage.file);
+            let provider = coverage_provider(program, interpreter_args, &file)?;
+            interpreter.set_coverage(provider.configure(coverage.reset));
+            writable.extend(std::path::Path::new(&file).parent().map(|dir| dir.to_path_buf()));
+        }
+        if let Some(dir) = &options.work_dir {
+            interpreter.set_working_dir(dir.clone());
+        }
+        interpreter.set_sandbox(interpreter_args.sandbox(writable, options.worker)?);
+        interpreter.set_write_chunking(WriteChunking {
+            pieces: interpreter_args.split_writes,
+            pause: Duration::from_millis(interpreter_args.split_pause_ms),
+        });
+    }
+    
+    let mut player = Player::new(&mut *interpreter, &mut strategy, options.display);
+    player.set_max_turns(options.max_turns);
+    let mut pacer = Pacer::new(interpreter_args.max_commands_per_second);
+    pacer.set_latency(InjectedLatency {
+        base: Duration::from_millis(interpreter_args.inject_delay_ms),
+        jitter: Duration::from_millis(interpreter_args.inject_jitter_ms),
+    });
+    player.set_pacer(pacer);
+    player.set_strict(options.strict);
+    if options.strict {
+        match ProgramIndex::load(program) {
+            Ok(index) => player.set_source(index),
+            Err(e) => log::warn!("Strict mode failures won't name source lines: {:#}", e),
+        }
+    }
+    player.set_verify_torpedoes(options.verify_torpedoes);
+    player.set_verify_energy(options.verify_energy);
+    player.set_commentary(options.commentary);
+    let accepted_invalid = Rc::new(Cell::new(0));
+    if options.advise {
+        player.set_advisories(true);
+        let accepted = accepted_invalid.clone();
+        player.add_observer(Box::new(move |_turn: usize, advisory: &Advisory| {
+            if advisory.kind == AdvisoryKind::AcceptedInvalidInput {
+                accepted.set(accepted.get() + 1);
+            }
+        }));
+    }
+    player.set_display_filter(options.display_filter);
+    player.set_display_style(options.display_style);
+    player.set_status_line(StatusLine::new(options.status_line));
+    player.set_retention(options.retention);
+    if let Some(progress) = options.progress {
+        player.set_progress(progress);
+    }
+    if options.transcript.is_some() {
+        player.record_transcript();
+    }
+    if let Some(commands) = options.type_ahead {
+        player.set_type_ahead(commands);
+    }
+    player.set_game_profile(game::GameProfile::select(&interpreter_args.config, options.game_profile.as_deref(), program)?);
+    player.set_new_session(!options.continue_session);
+    player.set_reuse_process(options.reuse_process);
+    player.set_restartable(restart);
+    
+    let game_start = Instant::now();
+    let result = player.play_game(&workdir::absolute(program)).await;
+    let game_time = game_start.elapsed();
+    
+    // Save the transcript even if the game failed, that's when it's most useful
+    if let (Some(path), Some(mut transcript)) = (&options.transcript, player.take_transcript()) {
+        if let Some(metadata) = options.metadata {
+            transcript.set_metadata(metadata);
+        }
+        transcript.save(path)?;
+        match verbosity::shows(Verbosity::Normal) {
+            true if options.porcelain => eprintln!("Transcript saved to {}", path),
+            true => println!("Transcript saved to {}", path),
+            false => {}
+        }
+    }
+    
+    let result = result?;
+    let turns = player.get_turn_count();
+    let pacing = player.pacing_stats().clone();
+    let commands = player.commands();
+    let responses = player.responses().clone();
+    let preceding = player.preceding_input();
+    let discrepancies = player.discrepancies();
+    let startup = player.startup_time();
+    let restartable = player.is_restartable();
+    let state = player.get_game_state().clone();
+    // The player only borrowed the strategy and interpreter; hand them back for the next game
+    drop(player);
+    Ok(GameRun {
+        result,
+        turns,
+        pacing,
+        commands,
+        responses,
+        preceding,
+        discrepancies,
+        accepted_invalid: accepted_invalid.get(),
+        startup,
+        play: game_time.saturating_sub(startup),
+        state,
+        strategy,
+        running: restartable.then_some(interpreter),
+    })
+}
+
+/// Coverage provider for the selected interpreter, recording into `file`
+fn coverage_provider(program: &str, interpreter_args: &InterpreterArgs, file: &str) -> Result<Box<dyn CoverageProvider>> {
+    match &interpreter_args.interpreter {
+        InterpreterType::BasicRS => Ok(Box::new(BasicRSCoverage::new(file.to_string(), program))),
+        InterpreterType::TrekBasic => {
+            let python_path = interpreter_args.python_path.clone().unwrap_or_else(|| "python3".to_string());
+            Ok(Box::new(CoveragePyCoverage::new(python_path, file.to_string())))
+        }
+        InterpreterType::TrekBasicJ => {
+            let java_path = interpreter_args.java_path.clone().unwrap_or_else(|| "java".to_string());
+            let agent = interpreter_args.jacoco_agent.clone().context("TrekBasicJ coverage needs --jacoco-agent")?;
+            let cli = interpreter_args.jacoco_cli.clone().context("TrekBasicJ coverage needs --jacoco-cli")?;
+            let class_files = interpreter_args.trekbasicj_path.clone().context("TrekBasicJ coverage needs --trekbasicj-path")?;
+            Ok(Box::new(JacocoCoverage::new(java_path, agent, cli, class_files, file.to_string())))
+        }
+        InterpreterType::Custom(name) => anyhow::bail!("Coverage isn't available for interpreter profile '{}'", name),
+    }
+}
+
+/// Read back and print the coverage recorded in `file`
+fn report_coverage(program: &str, interpreter_args: &InterpreterArgs, file: &str) -> Result<CoverageData> {
+    let provider = coverage_provider(program, interpreter_args, file)?;
+    let data = provider.collect()?;
+    provider.summarize(&data).print(provider.name());
+    match ProgramIndex::load(program) {
+        Ok(index) => print_unreached_inputs(&index, &data.lines(program)),
+        Err(e) => log::warn!("Not listing unreached prompts: {:#}", e),
+    }
+    Ok(data)
+}
+
+/// INPUT statements the games never got to, with the prompt each would have shown
+fn print_unreached_inputs(index: &ProgramIndex, covered: &[u32]) {
+    let unreached: Vec<_> = index.inputs().iter().filter(|input| !covered.contains(&input.line)).collect();
+    if unreached.is_empty() || covered.is_empty() {
+        return;
+    }
+    println!("Prompts never reached:");
+    for input in unreached {
+        println!("  {:>5}  {:<40} reads {}", input.line, input.prompt.as_deref().unwrap_or("?").trim(), input.reads());
+    }
+}
+
+/// Launch the selected interpreter, or restart the one options.running holds, and play
+/// one game on it
+async fn run_game(
+    program: &str,
+    interpreter_args: &InterpreterArgs,
+    strategy: Box<dyn Strategy>,
+    mut options: RunOptions,
+) -> Result<GameRun> {
+    let interpreter_args = &interpreter_args.with_absolute_paths();
+    match options.running.take() {
+        Some(running) => play_with(running, interpreter_args, strategy, program, options, true).await,
+        None => play_with(new_interpreter(interpreter_args)?, interpreter_args, strategy, program, options, false).await,
+    }
+}
+
+/// The selected interpreter, not launched yet
+fn new_interpreter(interpreter_args: &InterpreterArgs) -> Result<Box<dyn Interpreter + Send>> {
+    Ok(match &interpreter_args.interpreter {
+        InterpreterType::BasicRS => Box::new(BasicRSInterpreter::new(interpreter_args.basicrs_path.clone())),
+        InterpreterType::TrekBasic => Box::new(TrekBasicInterpreter::new(
+            interpreter_args.python_path.clone(),
+            interpreter_args.trekbasic_path.clone(),
+        )),
+        InterpreterType::TrekBasicJ => Box::new(TrekBasicJInterpreter::new(
+            interpreter_args.java_path.clone(),
+            interpreter_args.trekbasicj_path.clone(),
+        )),
+        InterpreterType::Custom(name) => {
+            let mut profile = InterpreterProfile::find(&interpreter_args.config, name)?;
+            profile.command = workdir::absolute_command(&profile.command);
+            Box::new(CustomInterpreter::new(profile))
+        }
+    })
+}
+
+async fn play_single_game(
+    program: &str,
+    interpreter_args: &InterpreterArgs,
+    game: &GameArgs,
+    transcript: &Option<String>,
+    coverage_file: Option<&str>,
+    porcelain: bool,
+    commentary: bool,
+) -> Result<()> {
+    let start_time = Instant::now();
+    // Always seeded, so a game worth looking into can be played again with --seed
+    let seed = game.seed.unwrap_or_else(|| rand::random::<u32>() as u64);
+    let strategy = create_seeded_strategy(game, Some(seed))?;
+    
+    let layout = RunLayout::new(interpreter_args.work_dir.as_deref())?;
+    let mut options = RunOptions::for_game(game, game.display, game.max_turns)?;
+    options.work_dir = Some(layout.game_dir(0)?);
+    options.transcript = transcript.clone();
+    options.coverage = coverage_file.map(|file| CoverageOptions { file: file.to_string(), reset: true });
+    options.metadata = Some(run_metadata(program, interpreter_args, game));
+    options.commentary = commentary;
+    if porcelain {
+        options.status_line = StatusLineMode::Off;
+        options.porcelain = true;
+    }
+    let run = run_game(program, interpreter_args, strategy, options).await?;
+    let elapsed = start_time.elapsed();
+    if porcelain {
+        println!("result={} turns={} duration={:.1}s seed={}", run.result.slug(), run.turns, elapsed.as_secs_f64(), seed);
+        return Ok(());
+    }
+    println!("Game Result: {} ({})", run.result.description(), run.turns);
+    println!("Strategy seed: {} (play it again with --seed {})", seed, seed);
+    
+    if verbosity::shows(Verbosity::Normal) {
+        println!("Total elapsed time: {:.2} seconds ({:.2}s interpreter startup, {:.2}s playing)",
+                 elapsed.as_secs_f64(), run.startup.as_secs_f64(), run.play.as_secs_f64());
+        println!("Pacing: {}", run.pacing);
+    }
+    if let Some(file) = coverage_file {
+        report_coverage(program, interpreter_args, file)?;
+    }
+    if interpreter_args.work_dir.is_some() {
+        println!("Game files kept in {}", layout.root().display());
+    }
+    
+    Ok(())
+}
+
+/// Play several games at once with a live table of their turn, condition and result.
+/// Strategies aren't Send, so the games share one thread on a LocalSet; they spend
+/// nearly all their time waiting on the interpreters anyway.
+async fn play_concurrent_games(
+    program: &str,
+    interpreter_args: &InterpreterArgs,
+    game: &GameArgs,
+    count: usize,
+    jobs: usize,
+    coverage_file: Option<&str>,
+) -> Result<()> {
+    let start_time = Instant::now();
+    let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+    let next_game = Rc::new(Cell::new(0));
+    let layout = Rc::new(RunLayout::new(interpreter_args.work_dir.as_deref())?);
+    let stats = SharedStats::new();
+    let local = tokio::task::LocalSet::new();
+    
+    println!("Playing {} games, {} at a time...", count, jobs.clamp(1, count));
+    
+    for worker in 0..jobs.clamp(1, count) {
+        let next_game = next_game.clone();
+        let sender = sender.clone();
+        let program = program.to_string();
+        let interpreter_args = interpreter_args.clone();
+        let game = game.clone();
+        let layout = layout.clone();
+        let stats = stats.clone();
+        // Workers sharing one file would overwrite each other's coverage
+        let coverage_file = coverage_file.map(|_| worker_coverage_file(&layout, worker));
+        
+        local.spawn_local(async move {
+            let mut reset_coverage = true;
+            loop {
+                let index = next_game.get();
+                if index >= count {
+                    break;
+                }
+                next_game.set(index + 1);
+                
+                let progress = ProgressReporter::new(index, sender.clone());
+                let coverage = coverage_file.clone().map(|file| CoverageOptions {
+                    file,
+                    reset: std::mem::take(&mut reset_coverage),
+                });
+                let setup = RunOptions::for_game(&game, false, game.max_turns).and_then(|options| {
+                    Ok((options, layout.game_dir(index)?, create_seeded_strategy(&game, game.game_seed(index))?))
+                });
+                let outcome = match setup {
+                    Ok((mut options, dir, strategy)) => {
+                        // The live table has the terminal to itself
+                        options.status_line = StatusLineMode::Off;
+                        options.progress = Some(progress.clone());
+                        options.worker = Some(worker);
+                        options.coverage = coverage;
+                        options.work_dir = Some(dir);
+                        run_game(&program, &interpreter_args, strategy, options).await
+                    }
+                    Err(e) => Err(e),
+                };
+                match outcome {
+                    Ok(run) => {
+                        stats.add_game(run.result.clone(), run.turns, run.startup, run.play);
+                        progress.finished(run.turns, run.result);
+                    }
+                    Err(e) => {
+                        log::error!("Game {} failed: {}", index + 1, e);
+                        progress.failed(&e);
+                    }
+                }
+            }
+        });
+    }
+    drop(sender);
+    
+    // The channel closes once every worker has finished
+    let mut table = LiveTable::new(count);
+    local
+        .run_until(async {
+            while let Some(progress) = receiver.recv().await {
+                table.update(progress);
+            }
+        })
+        .await;
+    
+    let stats = stats.snapshot();
+    if stats.total_games > 0 {
+        println!();
+        stats.print_summary();
+    }
     let elapsed = start_time.elapsed();
     println!("Total elapsed time: {:.2} seconds", elapsed.as_secs_f64());
     
+    if let Some(file) = coverage_file {
+        let workers: Vec<String> = (0..jobs.clamp(1, count)).map(|worker| worker_coverage_file(&layout, worker)).collect();
+        merge_worker_coverage(program, interpreter_args, &workers, file)?;
+    }
+    
+    Ok(())
+}
+
+/// The programs named on the command line, with a directory standing for every *.bas
+/// file in it, in name order
+fn expand_programs(paths: &[String]) -> Result<Vec<String>> {
+    let mut programs = Vec::new();
+    for path in paths {
+        if !Path::new(path).is_dir() {
+            programs.push(path.clone());
+            continue;
+        }
+        let mut files: Vec<PathBuf> = fs::read_dir(path)
+            .with_context(|| format!("Failed to read program directory {}", path))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|file| file.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("bas")))
+            .collect();
+        if files.is_empty() {
+            anyhow::bail!("No *.bas programs in {}", path);
+        }
+        files.sort();
+        programs.extend(files.iter().map(|file| file.to_string_lossy().to_string()));
+    }
+    Ok(programs)
+}
+
+/// `file` with the program's name before the extension, e.g. results.json becomes
+/// results.sst-1978.json for sst-1978.bas
+fn program_file(file: &str, program: &str) -> String {
+    let name = Path::new(program).file_stem().map_or_else(|| program.to_string(), |stem| stem.to_string_lossy().to_string());
+    let path = Path::new(file);
+    match (path.file_stem(), path.extension()) {
+        (Some(stem), Some(extension)) => {
+            let renamed = format!("{}.{}.{}", stem.to_string_lossy(), name, extension.to_string_lossy());
+            path.with_file_name(renamed).to_string_lossy().to_string()
+        }
+        _ => format!("{}.{}", file, name),
+    }
+}
+
+/// Coverage file of one concurrent worker, in the run directory
+fn worker_coverage_file(layout: &RunLayout, worker: usize) -> String {
+    layout.root().join(format!("coverage.worker{}.json", worker)).to_string_lossy().to_string()
+}
+
+/// Merge the workers' coverage, print it and save it to `file`
+fn merge_worker_coverage(program: &str, interpreter_args: &InterpreterArgs, workers: &[String], file: &str) -> Result<()> {
+    let mut merged = CoverageData::new();
+    let mut name = "coverage";
+    for worker_file in workers.iter().filter(|worker_file| Path::new(worker_file).exists()) {
+        let provider = coverage_provider(program, interpreter_args, worker_file)?;
+        match provider.collect() {
+            Ok(data) => provider.merge(&mut merged, &data),
+            Err(e) => log::warn!("Could not read coverage from {}: {}", worker_file, e),
+        }
+        name = provider.name();
+    }
+    merged.summary().print(name);
+    fs::write(file, format!("{}\n", merged.to_json())).with_context(|| format!("Failed to write {}", file))?;
+    println!("Coverage of {} workers merged into {}", workers.len(), file);
     Ok(())
 }
 
+/// How long a benchmark keeps starting games
+#[derive(Debug, Clone, Copy)]
+enum Budget {
+    Games(usize),
+    /// Wall-clock time, with --time-budget
+    Time(Duration),
+}
+
+impl Budget {
+    /// Whether to start another game, after `played` of them in `elapsed`
+    fn allows(&self, played: usize, elapsed: Duration) -> bool {
+        match self {
+            Budget::Games(games) => played < *games,
+            Budget::Time(time) => elapsed < *time,
+        }
+    }
+    
+    /// Progress shown before game `number`, counting from 1
+    fn progress(&self, number: usize, elapsed: Duration) -> String {
+        match self {
+            Budget::Games(games) => format!("Game {}/{}", number, games),
+            Budget::Time(time) => format!("Game {} ({}s of {}s)", number, elapsed.as_secs(), time.as_secs()),
+        }
+    }
+}
+
+impl fmt::Display for Budget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Budget::Games(games) => write!(f, "{} games", games),
+            Budget::Time(time) => write!(f, "games for {}s", time.as_secs()),
+        }
+    }
+}
+
+/// What a benchmark carries over from one game to the next
+#[derive(Debug, Clone, Copy, Default)]
+struct Reuse {
+    /// The strategy, with --same-session
+    strategy: bool,
+    /// The interpreter process, with --reuse-process
+    process: bool,
+}
+
+/// Where a benchmark saves and sends what it finds, besides the terminal
+struct BenchmarkOutputs {
+    coverage_file: Option<String>,
+    results_file: Option<String>,
+    /// Saved with the results
+    tags: Vec<String>,
+    notifier: Option<Notifier>,
+    recorder: Option<ScenarioRecorder>,
+}
+
 async fn run_benchmark(
     program: &str,
-    interpreter_type: &InterpreterType,
-    strategy_type: &StrategyType,
-    games: usize,
-    display: bool,
-    max_turns: usize,
-    basicrs_path: &Option<String>,
-    python_path: &Option<String>,
-    trekbasic_path: &Option<String>,
-    java_path: &Option<String>,
-    trekbasicj_path: &Option<String>,
-    coverage_file: &Option<String>,
-) -> Result<()> {
+    interpreter_args: &InterpreterArgs,
+    game: &GameArgs,
+    budget: Budget,
+    reuse: Reuse,
+    seed_bucket: Option<u64>,
+    outputs: BenchmarkOutputs,
+) -> Result<GameStats> {
+    let BenchmarkOutputs { coverage_file, results_file, tags, mut notifier, mut recorder } = outputs;
+    let start_time = Instant::now();
     let mut stats = GameStats::new();
+    let mut by_seed = GroupedStats::new();
+    let mut pacing = PacingStats::default();
+    let mut responses = ResponseStats::new();
+    let mut failures = FailureStats::new();
+    // Where to look into each failed game, for notifications
+    let mut failed_games = Vec::new();
+    let mut discrepancies = 0;
+    let mut accepting_invalid = 0;
+    let layout = RunLayout::new(interpreter_args.work_dir.as_deref())?;
+    let mut win_rates = Vec::new();
+    // Lines covered so far, to tell which games reached new ones
+    let mut covered = Vec::new();
+    let metadata = run_metadata(program, interpreter_args, game);
+    // With --same-session, the strategy the last game finished with
+    let mut session = None;
+    // With --reuse-process, the interpreter the last game left running
+    let mut running = None;
     
-    // Coverage will be handled by BasicRS itself
+    println!("Running {} with {} interpreter and {} strategy...", 
+             budget, 
+             interpreter_args.interpreter.slug(), 
+             game.strategy);
     
-    println!("Running {} games with {} interpreter and {} strategy...", 
-             games, 
-             format!("{:?}", interpreter_type).to_lowercase(), 
-             format!("{:?}", strategy_type).to_lowercase());
-    
-    for i in 0..games {
-        println!("Game {}/{}", i + 1, games);
+    let mut i = 0;
+    while budget.allows(i, start_time.elapsed()) {
+        if verbosity::shows(Verbosity::Normal) {
+            println!("{}", budget.progress(i + 1, start_time.elapsed()));
+        }
         
-        let result = match (interpreter_type, strategy_type) {
-            (InterpreterType::BasicRS, StrategyType::Random) => {
-                let mut interpreter = BasicRSInterpreter::new(basicrs_path.clone());
-                
-                // Set coverage options if requested
-                if let Some(ref coverage_file) = coverage_file {
-                    interpreter.set_coverage_file(Some(coverage_file.clone()));
-                    interpreter.set_reset_coverage(i == 0); // Reset only on first game
+        // A recorded game needs a known seed to be played again
+        let seed = game.game_seed(i)
+            .or_else(|| (recorder.is_some() || seed_bucket.is_some()).then(|| rand::random::<u32>() as u64));
+        let mut options = RunOptions::for_game(game, game.display, game.max_turns)?;
+        let strategy = match session.take() {
+            Some(strategy) => {
+                options.continue_session = true;
+                strategy
+            }
+            None => create_seeded_strategy(game, seed)?,
+        };
+        options.coverage = coverage_file.as_ref().map(|file| CoverageOptions {
+            file: file.clone(),
+            reset: i == 0, // Reset only on first game
+        });
+        options.transcript = recorder.as_ref().map(|recorder| recorder.pending_transcript().to_string_lossy().to_string());
+        let work_dir = layout.game_dir(i)?;
+        options.work_dir = Some(work_dir.clone());
+        options.reuse_process = reuse.process;
+        options.running = running.take();
+        let run = run_game(program, interpreter_args, strategy, options).await?;
+        if reuse.strategy {
+            session = Some(run.strategy);
+        }
+        running = run.running;
+        stats.add_game(run.result.clone(), run.turns);
+        stats.add_timing(run.startup, run.play);
+        if let (Some(seed), Some(width)) = (seed, seed_bucket) {
+            by_seed.add_game(SeedBucket::of(seed, width), run.result.clone(), run.turns);
+        }
+        pacing.merge(&run.pacing);
+        responses.merge(&run.responses);
+        failures.record(&run.result, run.preceding.as_ref());
+        discrepancies += run.discrepancies;
+        if run.accepted_invalid > 0 {
+            accepting_invalid += 1;
+        }
+        win_rates.push((stats.victories * 100 / stats.total_games) as i32);
+        
+        if verbosity::shows(Verbosity::Normal) {
+            println!("  Result: {}", run.result.description());
+        }
+        
+        let mut saved = None;
+        if let Some(recorder) = &mut recorder {
+            let new_lines = match &coverage_file {
+                Some(file) if matches!(interpreter_args.interpreter, InterpreterType::BasicRS) => {
+                    let lines = BasicRSCoverage::new(file.clone(), program).collect()?.lines(program);
+                    let new_lines: Vec<u32> = lines.iter().copied().filter(|line| !covered.contains(line)).collect();
+                    covered = lines;
+                    new_lines
                 }
-                
-                let strategy = RandomStrategy::new();
-                let mut player = Player::new(interpreter, strategy, display);
-                player.set_max_turns(max_turns);
-                
-                let result = player.play_game(program).await?;
-                let turns = player.get_turn_count();
-                stats.add_game(result.clone(), turns);
-                result
+                _ => Vec::new(),
+            };
+            let scenario = Scenario {
+                name: format!("game-{}", i + 1),
+                program: program.to_string(),
+                strategy: game.strategy.to_string(),
+                seed,
+                dock: game.dock,
+                max_turns: game.max_turns,
+                allow_known_bugs: game.allow_known_bugs,
+                commands: None,
+                expect_result: Some(run.result.slug().to_string()),
+                expect_lines: new_lines.clone(),
+            };
+            let preceding = run.preceding.as_ref().filter(|_| failures::is_failure(&run.result));
+            if let Some(path) = recorder.promote(&scenario, &run.result, new_lines.len(), preceding, &metadata)? {
+                println!("  Saved scenario {}", path.display());
+                saved = Some(path);
             }
-            (InterpreterType::BasicRS, StrategyType::Cheat) => {
-                let mut interpreter = BasicRSInterpreter::new(basicrs_path.clone());
-                
-                // Set coverage options if requested
-                if let Some(ref coverage_file) = coverage_file {
-                    interpreter.set_coverage_file(Some(coverage_file.clone()));
-                    interpreter.set_reset_coverage(i == 0); // Reset only on first game
+        }
+        if failures::is_failure(&run.result) {
+            failed_games.push(FailedGame {
+                game: i,
+                result: run.result.slug(),
+                // A temporary run directory is gone by the time anyone reads the notification
+                work_dir: interpreter_args.work_dir.is_some().then_some(work_dir),
+                transcript: saved.map(|path| path.with_extension("jsonl")).filter(|path| path.exists()),
+            });
+        }
+        
+        if let Some(notifier) = &mut notifier {
+            notifier.check_crash_rate("benchmark", &stats, &failed_games).await;
+        }
+        i += 1;
+    }
+    
+    if let Some(mut interpreter) = running {
+        // Terminating gracefully lets the interpreter save coverage data
+        if let Err(e) = interpreter.terminate().await {
+            log::warn!("Failed to terminate interpreter gracefully: {}", e);
+        }
+    }
+    
+    stats.print_summary();
+    if let Budget::Time(time) = budget {
+        println!("Games completed in the {}s budget: {} ({:.1}s in all)",
+                 time.as_secs(), stats.total_games, start_time.elapsed().as_secs_f64());
+    }
+    println!("Win rate trend: {}", live::sparkline(&win_rates));
+    if seed_bucket.is_some() {
+        by_seed.print_table("Seeds");
+    }
+    if game.verify_torpedoes || game.verify_energy {
+        println!("Possible interpreter arithmetic bugs: {}", discrepancies);
+    }
+    if game.advise {
+        println!("Games that accepted invalid input (possible interpreter validation bugs): {}", accepting_invalid);
+    }
+    if verbosity::shows(Verbosity::Normal) {
+        println!("Pacing: {}", pacing);
+        responses.print_summary();
+    }
+    if !failures.is_empty() {
+        failures.print_summary();
+    }
+    
+    if let Some(file) = &coverage_file {
+        if let Err(e) = report_coverage(program, interpreter_args, file) {
+            log::warn!("Could not read coverage from {}: {}", file, e);
+        }
+    }
+    
+    if let Some(path) = &results_file {
+        RunResults::new(run_config(program, interpreter_args, game), stats.clone())
+            .with_metadata(metadata)
+            .with_tags(tags)
+            .save(path)?;
+        println!("Results saved to {}", path);
+    }
+    
+    if let Some(notifier) = &notifier {
+        notifier.run_finished("benchmark", &stats, &failed_games, start_time.elapsed().as_secs_f64()).await;
+    }
+    Ok(stats)
+}
+
+/// Play both strategies on every seed and report wins, losses and where their commands split
+async fn run_duel(
+    program: &str,
+    interpreter_args: &InterpreterArgs,
+    game: &GameArgs,
+    strategies: [&StrategyType; 2],
+    seeds: &[u64],
+) -> Result<()> {
+    let layout = RunLayout::new(interpreter_args.work_dir.as_deref())?;
+    let [first, second] = strategies.map(|strategy| GameArgs { strategy: strategy.clone(), ..game.clone() });
+    let mut report = DuelReport {
+        first: first.strategy.to_string(),
+        second: second.strategy.to_string(),
+        games: Vec::new(),
+    };
+    
+    for (index, &seed) in seeds.iter().enumerate() {
+        let mut runs = Vec::new();
+        for (side, game) in [&first, &second].into_iter().enumerate() {
+            let mut options = RunOptions::for_game(game, game.display, game.max_turns)?;
+            options.work_dir = Some(layout.game_dir(index * 2 + side)?);
+            runs.push(run_game(program, interpreter_args, create_seeded_strategy(game, Some(seed))?, options).await?);
+        }
+        if verbosity::shows(Verbosity::Normal) {
+            println!("Seed {}: {} vs {}", seed, runs[0].result.description(), runs[1].result.description());
+        }
+        report.games.push(DuelGame::new(
+            seed,
+            (runs[0].result.clone(), runs[0].turns, &runs[0].commands),
+            (runs[1].result.clone(), runs[1].turns, &runs[1].commands),
+        ));
+    }
+    
+    report.print();
+    Ok(())
+}
+
+/// Play rounds of games with each strategy, rate them by who won more, and show the ladder
+async fn run_ladder(
+    program: Option<&str>,
+    interpreter_args: &InterpreterArgs,
+    game: &GameArgs,
+    strategies: &[StrategyType],
+    rounds: usize,
+    games: usize,
+    ladder_file: &str,
+) -> Result<()> {
+    let mut ladder = Ladder::load_or_new(ladder_file)?;
+    
+    if let Some(program) = program {
+        let layout = RunLayout::new(interpreter_args.work_dir.as_deref())?;
+        let mut game_number = 0;
+        for round in 0..rounds {
+            if verbosity::shows(Verbosity::Normal) {
+                println!("Round {}/{}", round + 1, rounds);
+            }
+            let mut win_rates = Vec::new();
+            for strategy in strategies {
+                let game = GameArgs { strategy: strategy.clone(), ..game.clone() };
+                let mut stats = GameStats::new();
+                for index in 0..games {
+                    let mut options = RunOptions::for_game(&game, false, game.max_turns)?;
+                    options.work_dir = Some(layout.game_dir(game_number)?);
+                    game_number += 1;
+                    let run = run_game(program, interpreter_args, create_seeded_strategy(&game, game.game_seed(index))?, options).await?;
+                    stats.add_game(run.result, run.turns);
                 }
                 
-                let strategy = CheatStrategy::new();
-                let mut player = Player::new(interpreter, strategy, display);
-                player.set_max_turns(max_turns);
-                
-                let result = player.play_game(program).await?;
-                let turns = player.get_turn_count();
-                stats.add_game(result.clone(), turns);
-                result
+                let mut name = strategy.to_string();
+                if game.dock {
+                    name.push_str("+dock");
+                }
+                if verbosity::shows(Verbosity::Normal) {
+                    println!("  {:<12} won {} of {} games", name, stats.victories, stats.total_games);
+                }
+                win_rates.push((name, stats.success_rate()));
             }
-            (InterpreterType::TrekBasic, StrategyType::Random) => {
-                let interpreter = TrekBasicInterpreter::new(python_path.clone(), trekbasic_path.clone());
-                let strategy = RandomStrategy::new();
-                let mut player = Player::new(interpreter, strategy, display);
-                player.set_max_turns(max_turns);
-                
-                let result = player.play_game(program).await?;
-                let turns = player.get_turn_count();
-                stats.add_game(result.clone(), turns);
-                result
+            ladder.record_round(&win_rates);
+        }
+        ladder.save(ladder_file)?;
+        println!("Ladder saved to {}", ladder_file);
+    }
+    
+    ladder.print_standings();
+    Ok(())
+}
+
+/// How `tune` picks the settings to try
+enum Search {
+    Evolve { generations: usize, population: usize },
+    Sweep { name: String, steps: usize },
+}
+
+/// Settings the strategy has for `tune` to vary
+fn tunable_params(strategy: &StrategyType) -> Result<&'static [Param]> {
+    match strategy {
+        StrategyType::Random => Ok(RandomParams::TUNABLE),
+        StrategyType::Risk => Ok(RiskStrategy::TUNABLE),
+        other => anyhow::bail!("The {} strategy has no settings to tune; use random or risk", other),
+    }
+}
+
+/// Try settings for the strategy, each on the same seeds, and report the best
+async fn run_tune(
+    program: &str,
+    interpreter_args: &InterpreterArgs,
+    game: &GameArgs,
+    search: Search,
+    games: usize,
+    seed: Option<u64>,
+    output: Option<&str>,
+) -> Result<()> {
+    let params = tunable_params(&game.strategy)?;
+    let first_seed = seed.unwrap_or_else(|| rand::random::<u32>() as u64);
+    let mut tuning = Tuning::new(program, interpreter_args, game, games, first_seed)?;
+    println!("Tuning the {} strategy with {} games per candidate (first seed {})...",
+             game.strategy, games, first_seed);
+    
+    match search {
+        Search::Sweep { name, steps } => {
+            println!("Sweeping {} over {} values", name, steps.max(2));
+            for values in tune::sweep(params, &name, steps)? {
+                tuning.score(values).await?;
             }
-            (InterpreterType::TrekBasic, StrategyType::Cheat) => {
-                let interpreter = TrekBasicInterpreter::new(python_path.clone(), trekbasic_path.clone());
-                let strategy = CheatStrategy::new();
-                let mut player = Player::new(interpreter, strategy, display);
-                player.set_max_turns(max_turns);
-                
-                let result = player.play_game(program).await?;
-                let turns = player.get_turn_count();
-                stats.add_game(result.clone(), turns);
-                result
+        }
+        Search::Evolve { generations, population } => {
+            let mut evolution = Evolution::new(params, population, seed);
+            let mut generation = evolution.first_generation();
+            for number in 0..generations {
+                println!("Generation {}/{}", number + 1, generations);
+                let mut scored = Vec::new();
+                for values in generation {
+                    let score = tuning.score(values.clone()).await?;
+                    scored.push((values, score));
+                }
+                generation = evolution.next_generation(&scored);
             }
-            (InterpreterType::TrekBasicJ, StrategyType::Random) => {
-                let interpreter = TrekBasicJInterpreter::new(java_path.clone(), trekbasicj_path.clone());
-                let strategy = RandomStrategy::new();
-                let mut player = Player::new(interpreter, strategy, display);
-                player.set_max_turns(max_turns);
-                
-                let result = player.play_game(program).await?;
-                let turns = player.get_turn_count();
-                stats.add_game(result.clone(), turns);
-                result
+        }
+    }
+    
+    let (best, score) = tuning.best().context("No candidates were tried")?;
+    println!("Best settings: {} (score {:.3})", tune::describe(best), score);
+    if let Some(path) = output {
+        tune::save(best, path)?;
+        println!("Settings saved to {}; play with them using --strategy-params {}", path, path);
+    }
+    Ok(())
+}
+
+/// Plays the games of `tune`, remembering every candidate's score so one kept from
+/// generation to generation is played only once
+struct Tuning<'a> {
+    program: &'a str,
+    interpreter_args: &'a InterpreterArgs,
+    game: &'a GameArgs,
+    seeds: Vec<u64>,
+    layout: RunLayout,
+    games_played: usize,
+    scores: Vec<(ParamValues, f64)>,
+}
+
+impl<'a> Tuning<'a> {
+    fn new(program: &'a str, interpreter_args: &'a InterpreterArgs, game: &'a GameArgs, games: usize, first_seed: u64) -> Result<Self> {
+        Ok(Self {
+            program,
+            interpreter_args,
+            game,
+            seeds: (0..games as u64).map(|game| first_seed.wrapping_add(game)).collect(),
+            layout: RunLayout::new(interpreter_args.work_dir.as_deref())?,
+            games_played: 0,
+            scores: Vec::new(),
+        })
+    }
+    
+    /// Play the games with these settings, unless they were played already, and score them
+    async fn score(&mut self, values: ParamValues) -> Result<f64> {
+        if let Some((_, score)) = self.scores.iter().find(|(tried, _)| *tried == values) {
+            return Ok(*score);
+        }
+        
+        let mut stats = GameStats::new();
+        for &seed in &self.seeds {
+            let mut options = RunOptions::for_game(self.game, false, self.game.max_turns)?;
+            options.work_dir = Some(self.layout.game_dir(self.games_played)?);
+            self.games_played += 1;
+            let strategy = create_tuned_strategy(self.game, Some(seed), &values)?;
+            let run = run_game(self.program, self.interpreter_args, strategy, options).await?;
+            stats.add_game(run.result, run.turns);
+        }
+        
+        let score = tune::score(&stats);
+        if verbosity::shows(Verbosity::Normal) {
+            println!("  {}  score {:.3} ({} of {} won)", tune::describe(&values), score, stats.victories, stats.total_games);
+        }
+        self.scores.push((values, score));
+        Ok(score)
+    }
+    
+    /// The best scoring settings tried, the first of them on a tie
+    fn best(&self) -> Option<(&ParamValues, f64)> {
+        self.scores.iter().fold(None, |best, (values, score)| match best {
+            Some((_, best_score)) if best_score >= *score => best,
+            _ => Some((values, *score)),
+        })
+    }
+}
+
+/// Settings recorded with saved results, used to check runs can be merged
+fn run_config(program: &str, interpreter_args: &InterpreterArgs, game: &GameArgs) -> RunConfig {
+    let program_name = std::path::Path::new(program)
+        .file_name()
+        .map_or(program.to_string(), |name| name.to_string_lossy().to_string());
+    RunConfig {
+        program: program_name,
+        interpreter: interpreter_args.interpreter.slug(),
+        strategy: game.strategy.to_string(),
+        max_turns: game.max_turns,
+        dock: game.dock,
+        allow_known_bugs: game.allow_known_bugs,
+    }
+}
+
+/// The interpreter a run uses, with its version where the executable reports one
+fn interpreter_identity(interpreter_args: &InterpreterArgs) -> InterpreterIdentity {
+    match &interpreter_args.interpreter {
+        InterpreterType::BasicRS => BasicRSInterpreter::new(interpreter_args.basicrs_path.clone()).identity(),
+        InterpreterType::TrekBasic => TrekBasicInterpreter::new(
+            interpreter_args.python_path.clone(),
+            interpreter_args.trekbasic_path.clone(),
+        )
+        .identity(),
+        InterpreterType::TrekBasicJ => TrekBasicJInterpreter::new(
+            interpreter_args.java_path.clone(),
+            interpreter_args.trekbasicj_path.clone(),
+        )
+        .identity(),
+        InterpreterType::Custom(name) => match InterpreterProfile::find(&interpreter_args.config, name) {
+            Ok(profile) => CustomInterpreter::new(profile).identity(),
+            Err(_) => InterpreterIdentity { name: name.clone(), executable: String::new(), version: None },
+        },
+    }
+}
+
+/// Provenance for the artifacts of a run
+fn run_metadata(program: &str, interpreter_args: &InterpreterArgs, game: &GameArgs) -> RunMetadata {
+    let mut metadata = RunMetadata::new(run_config(program, interpreter_args, game).digest(), vec![interpreter_identity(interpreter_args)]);
+    metadata.profile = game::GameProfile::select(&interpreter_args.config, game.game_profile.as_deref(), program)
+        .map(|profile| profile.name)
+        .ok();
+    metadata
+}
+
+/// Saved results carrying all of `tags`, with the name of the file each came from
+fn load_tagged_results(files: &[String], tags: &[String]) -> Result<Vec<(String, RunResults)>> {
+    let mut runs = Vec::new();
+    for file in files {
+        let run = RunResults::load(file)?;
+        if run.has_tags(tags) {
+            runs.push((file.clone(), run));
+        }
+    }
+    if runs.len() < files.len() {
+        println!("Skipped {} of {} files without the tags {}", files.len() - runs.len(), files.len(), tags.join(", "));
+    }
+    Ok(runs)
+}
+
+/// Combine saved results and print a report with one row per file plus the totals
+fn merge_stats(files: &[String], tags: &[String], output: &Option<String>) -> Result<()> {
+    let runs = load_tagged_results(files, tags)?;
+    let merged = merge_results(&runs)?;
+    
+    let config = &merged.config;
+    println!("Merging {} runs of {} with {} interpreter and {} strategy (max {} turns)",
+             runs.len(), config.program, config.interpreter, config.strategy, config.max_turns);
+    println!("{:<40} {:>6} {:>9} {:>7} {:>9}", "File", "Games", "Victories", "Crashes", "Avg turns");
+    for (file, run) in &runs {
+        println!("{:<40} {:>6} {:>9} {:>7} {:>9.1}",
+                 file, run.stats.total_games, run.stats.victories,
+                 run.stats.basic_errors + run.stats.crashes, run.stats.avg_turns);
+    }
+    println!();
+    merged.stats.print_summary();
+    
+    if let Some(path) = output {
+        merged.save(path)?;
+        println!("Combined results saved to {}", path);
+    }
+    Ok(())
+}
+
+/// Combine saved results into one row per combination of the `group_by` settings
+fn group_stats(files: &[String], tags: &[String], group_by: &[GroupBy]) -> Result<()> {
+    let runs = load_tagged_results(files, tags)?;
+    let fields: Vec<&str> = group_by.iter().map(|group| group.field()).collect();
+    let grouped = group_results(&runs, &fields)?;
+    
+    println!("Grouping {} runs by {}", runs.len(), fields.join(", "));
+    grouped.print_table(&fields.join(" / "));
+    println!();
+    grouped.total().print_summary();
+    Ok(())
+}
+
+/// Print or save the draft prompt rules for a program
+fn suggest_prompts(program: &str, output: &Option<String>) -> Result<()> {
+    let draft = ProgramIndex::load(program)?.draft_rules();
+    match output {
+        Some(path) => {
+            fs::write(path, format!("{}\n", draft))
+                .with_context(|| format!("Failed to write {}", path))?;
+            let count = draft.get("prompts").and_then(|prompts| prompts.as_array()).map_or(0, |prompts| prompts.len());
+            println!("Wrote {} draft prompt rules to {}", count, path);
+        }
+        None => println!("{}", draft),
+    }
+    Ok(())
+}
+
+/// Merge BASIC line coverage and print the code that was never reached
+fn report_reachability(program: &str, coverage_files: &[String]) -> Result<()> {
+    let source = fs::read_to_string(program)
+        .with_context(|| format!("Failed to read program {}", program))?;
+    
+    let mut coverage = CoverageData::new();
+    for file in coverage_files {
+        let provider = BasicRSCoverage::new(file.clone(), program);
+        let data = provider.collect()?;
+        provider.merge(&mut coverage, &data);
+    }
+    
+    ReachabilityReport::from_coverage(&parse_program(&source), &coverage, program).print();
+    Ok(())
+}
+
+/// Settings of a health check loop
+struct HealthCheck {
+    interval: Duration,
+    max_turns: usize,
+    status_file: String,
+}
+
+/// Play a short game on each interpreter, round after round, and report which work. A
+/// game is healthy if it runs to its end or the turn limit without a BASIC error or crash.
+async fn run_health_checks(
+    program: &str,
+    interpreter_args: &InterpreterArgs,
+    interpreters: &[InterpreterType],
+    check: &HealthCheck,
+    notifier: Option<Notifier>,
+) -> Result<()> {
+    let interpreters = selected_interpreters(interpreter_args, interpreters)?;
+    let mut report = HealthReport::new();
+    let mut round = 0;
+    loop {
+        let layout = RunLayout::new(interpreter_args.work_dir.as_deref())?;
+        for (index, (name, args)) in interpreters.iter().enumerate() {
+            let mut options = RunOptions::new(false, check.max_turns);
+            options.work_dir = Some(layout.game_dir(index)?);
+            let strategy = Box::new(RandomStrategy::with_seed(round));
+            let (healthy, detail) = match run_game(program, args, strategy, options).await {
+                Ok(run) => (!failures::is_failure(&run.result), run.result.slug().to_string()),
+                Err(e) => (false, format!("{:#}", e)),
+            };
+            if verbosity::shows(Verbosity::Normal) {
+                println!("{} {}: {}", if healthy { "OK  " } else { "FAIL" }, name, detail);
             }
-            (InterpreterType::TrekBasicJ, StrategyType::Cheat) => {
-                let interpreter = TrekBasicJInterpreter::new(java_path.clone(), trekbasicj_path.clone());
-                let strategy = CheatStrategy::new();
-                let mut player = Player::new(interpreter, strategy, display);
-                player.set_max_turns(max_turns);
-                
-                let result = player.play_game(program).await?;
-                let turns = player.get_turn_count();
-                stats.add_game(result.clone(), turns);
-                result
+            if report.record(name, healthy, detail.clone()) {
+                if let Some(notifier) = &notifier {
+                    notifier.health_changed(name, healthy, &detail).await;
+                }
+            }
+        }
+        report.save(&check.status_file)?;
+        
+        if check.interval.is_zero() {
+            if !report.all_healthy() {
+                anyhow::bail!("Health check failed");
+            }
+            return Ok(());
+        }
+        round += 1;
+        tokio::time::sleep(check.interval).await;
+    }
+}
+
+/// Name and settings of each interpreter to try: those listed, or by default the selected
+/// interpreter and every profile in the config file
+fn selected_interpreters(interpreter_args: &InterpreterArgs, interpreters: &[InterpreterType]) -> Result<Vec<(String, InterpreterArgs)>> {
+    let mut interpreters = interpreters.to_vec();
+    if interpreters.is_empty() {
+        interpreters.push(interpreter_args.interpreter.clone());
+        if Path::new(&interpreter_args.config).exists() {
+            let profiles = interpreter::profile::load_profiles(&interpreter_args.config)?;
+            let selected = interpreter_args.interpreter.slug();
+            interpreters.extend(profiles.into_iter()
+                .filter(|profile| profile.name != selected)
+                .map(|profile| InterpreterType::Custom(profile.name)));
+        }
+    }
+    Ok(interpreters.into_iter()
+        .map(|interpreter| (interpreter.slug(), InterpreterArgs { interpreter, ..interpreter_args.clone() }))
+        .collect())
+}
+
+/// Start a game on each interpreter, show the galaxy around the Enterprise without
+/// playing, and report where each setup differs from the first interpreter's
+async fn compare_galaxy_setups(
+    program: &str,
+    interpreter_args: &InterpreterArgs,
+    interpreters: &[InterpreterType],
+    output: Option<&str>,
+) -> Result<()> {
+    let layout = RunLayout::new(interpreter_args.work_dir.as_deref())?;
+    let mut setups: Vec<(String, GalaxySetup)> = Vec::new();
+    let mut failed = false;
+    for (index, (name, args)) in selected_interpreters(interpreter_args, interpreters)?.into_iter().enumerate() {
+        let mut options = RunOptions::new(false, SETUP_COMMANDS.len());
+        options.work_dir = Some(layout.game_dir(index)?);
+        let commands = SETUP_COMMANDS.iter().map(|command| command.to_string()).collect();
+        match run_game(program, &args, Box::new(ScriptedStrategy::new(commands)), options).await {
+            Ok(run) => setups.push((name, GalaxySetup::from_state(&run.state))),
+            Err(e) => {
+                println!("{}: could not be played: {:#}", name, e);
+                failed = true;
+            }
+        }
+    }
+    
+    let Some((reference, first)) = setups.first() else { anyhow::bail!("No interpreter could be played") };
+    for (name, setup) in &setups {
+        let differences = first.differences(setup);
+        if name == reference {
+            for (field, value) in &setup.fields {
+                println!("{:<14} {}", field, value);
+            }
+        } else if differences.is_empty() {
+            println!("{}: same setup as {}", name, reference);
+        } else {
+            println!("{}: setup differs from {}", name, reference);
+            for difference in &differences {
+                println!("  {}", difference);
+            }
+            failed = true;
+        }
+    }
+    
+    if let Some(path) = output {
+        fs::write(path, format!("{}\n", setup::to_json(&setups))).with_context(|| format!("Failed to write {}", path))?;
+        println!("Setups saved to {}", path);
+    }
+    if failed {
+        anyhow::bail!("Galaxy setups differ");
+    }
+    Ok(())
+}
+
+/// Play each scenario in `dir` and check it against its expectations. Coverage is only
+/// checked with BasicRS, the interpreter that records BASIC lines.
+async fn run_scenarios(dir: &str, interpreter_args: &InterpreterArgs, game: &GameArgs) -> Result<()> {
+    let files = scenario::scenario_files(dir)?;
+    let layout = RunLayout::new(interpreter_args.work_dir.as_deref())?;
+    let records_lines = matches!(interpreter_args.interpreter, InterpreterType::BasicRS);
+    let coverage_file = std::env::temp_dir().join(format!("trekbot-scenario-{}.coverage.json", std::process::id()))
+        .to_string_lossy().to_string();
+    println!("Running {} scenarios from {}...", files.len(), dir);
+    
+    let mut failed = 0;
+    for (index, file) in files.iter().enumerate() {
+        let scenario = Scenario::load(file)?;
+        let strategy: Box<dyn Strategy> = match &scenario.commands {
+            Some(commands) => Box::new(ScriptedStrategy::new(commands.clone())),
+            None => {
+                let strategy = parse_strategy(&scenario.strategy)
+                    .map_err(|_| anyhow::anyhow!("Scenario {} has unknown strategy '{}'", scenario.name, scenario.strategy))?;
+                let game = GameArgs {
+                    strategy,
+                    max_turns: scenario.max_turns,
+                    dock: scenario.dock,
+                    allow_known_bugs: scenario.allow_known_bugs,
+                    ..game.clone()
+                };
+                create_seeded_strategy(&game, scenario.seed)?
             }
         };
+        let max_turns = scenario.commands.as_ref().map_or(scenario.max_turns, |commands| commands.len());
+        
+        let mut options = RunOptions::for_game(game, game.display, max_turns)?;
+        options.work_dir = Some(layout.game_dir(index)?);
+        let check_lines = records_lines && !scenario.expect_lines.is_empty();
+        if check_lines {
+            options.coverage = Some(CoverageOptions { file: coverage_file.clone(), reset: true });
+        }
         
-        println!("  Result: {}", result.description());
+        let failures = match run_game(&scenario.program, interpreter_args, strategy, options).await {
+            Ok(run) => {
+                let covered = match check_lines {
+                    true => Some(BasicRSCoverage::new(coverage_file.clone(), &scenario.program).collect()?.lines(&scenario.program)),
+                    false => None,
+                };
+                scenario.check(run.result.slug(), covered.as_deref())
+            }
+            Err(e) => vec![format!("failed to play: {:#}", e)],
+        };
+        if failures.is_empty() {
+            println!("  PASS {}", scenario.name);
+        } else {
+            failed += 1;
+            println!("  FAIL {}: {}", scenario.name, failures.join("; "));
+        }
+    }
+    fs::remove_file(&coverage_file).ok();
+    
+    println!("{} passed, {} failed", files.len() - failed, failed);
+    if failed > 0 {
+        anyhow::bail!("{} of {} scenarios failed", failed, files.len());
+    }
+    Ok(())
+}
+
+/// Settings for a search for a game that reaches some BASIC lines
+struct TargetSearch {
+    lines: LineRange,
+    attempts: usize,
+    max_turns: usize,
+    seed: u64,
+    allow_known_bugs: bool,
+    display: bool,
+}
+
+/// Play games with varied seeds and strategy settings until one executes the target
+/// lines, then save its settings and transcript as a regression scenario
+async fn cover_target(
+    program: &str,
+    interpreter_args: &InterpreterArgs,
+    target: &TargetSearch,
+    output_dir: &str,
+) -> Result<()> {
+    if !matches!(interpreter_args.interpreter, InterpreterType::BasicRS) {
+        anyhow::bail!("cover-target needs BASIC line coverage, which only the basic-rs interpreter records");
+    }
+    fs::create_dir_all(output_dir)?;
+    
+    let name = format!("lines-{}", target.lines);
+    let path = |extension: &str| std::path::Path::new(output_dir).join(format!("{}.{}", name, extension)).to_string_lossy().to_string();
+    let coverage_file = path("coverage.json");
+    let transcript_file = path("jsonl");
+    
+    let config = RunConfig {
+        program: program.to_string(),
+        interpreter: interpreter_args.interpreter.slug(),
+        strategy: "random".to_string(),
+        max_turns: target.max_turns,
+        dock: false,
+        allow_known_bugs: target.allow_known_bugs,
+    };
+    let metadata = RunMetadata::new(config.digest(), vec![interpreter_identity(interpreter_args)]);
+    let layout = RunLayout::new(interpreter_args.work_dir.as_deref())?;
+    
+    println!("Searching for a game that reaches lines {} (up to {} attempts, first seed {})...",
+             target.lines, target.attempts, target.seed);
+    
+    for index in 0..target.attempts {
+        let attempt = SearchAttempt::nth(index, target.seed, target.max_turns);
+        
+        let mut random = RandomStrategy::with_seed(attempt.seed);
+        random.set_allow_known_bugs(target.allow_known_bugs);
+        let strategy: Box<dyn Strategy> = if attempt.dock {
+            Box::new(DockingStrategy::new(random, DockingConfig::default()))
+        } else {
+            Box::new(random)
+        };
+        
+        let mut options = RunOptions::new(target.display, attempt.max_turns);
+        options.coverage = Some(CoverageOptions { file: coverage_file.clone(), reset: true });
+        options.transcript = Some(transcript_file.clone());
+        options.metadata = Some(metadata.clone());
+        options.work_dir = Some(layout.game_dir(index)?);
+        
+        let result = run_game(program, interpreter_args, strategy, options).await;
+        let outcome = match &result {
+            Ok(run) => format!("{} ({} turns)", run.result.description(), run.turns),
+            Err(e) => format!("failed: {}", e),
+        };
+        
+        let coverage = BasicRSCoverage::new(coverage_file.clone(), program).collect()?;
+        let reached = target.lines.reached(&coverage.lines(program));
+        if verbosity::shows(Verbosity::Normal) {
+            println!("  attempt {:>3}: seed {} dock {} max {} turns -> {}, {} target lines",
+                     index + 1, attempt.seed, attempt.dock, attempt.max_turns, outcome, reached.len());
+        }
+        
+        if !reached.is_empty() {
+            let scenario = TargetScenario {
+                program: program.to_string(),
+                interpreter: interpreter_args.interpreter.slug(),
+                lines: target.lines,
+                reached,
+                attempt,
+                allow_known_bugs: target.allow_known_bugs,
+                transcript: transcript_file.clone(),
+                metadata: metadata.clone(),
+            };
+            let scenario_file = path("json");
+            scenario.save(&scenario_file)?;
+            let _ = fs::remove_file(&coverage_file);
+            println!("Lines {} reached; scenario saved to {}", target.lines, scenario_file);
+            return Ok(());
+        }
+    }
+    
+    let _ = fs::remove_file(&coverage_file);
+    let _ = fs::remove_file(&transcript_file);
+    anyhow::bail!("No game reached lines {} in {} attempts", target.lines, target.attempts)
+}
+
+/// Play one short game per menu selection so each selection's coverage can be inspected separately
+async fn run_discovery(
+    program: &str,
+    interpreter_args: &InterpreterArgs,
+    rounds: usize,
+    max_turns: usize,
+    display: bool,
+    allow_known_bugs: bool,
+    coverage_dir: &Option<String>,
+) -> Result<()> {
+    let selections = menu_selections(allow_known_bugs);
+    
+    if let Some(dir) = coverage_dir {
+        fs::create_dir_all(dir)?;
+    }
+    
+    println!("Discovering {} menu selections with {} interpreter...",
+             selections.len(),
+             interpreter_args.interpreter.slug());
+    
+    let mut total_coverage = CoverageData::new();
+    let layout = RunLayout::new(interpreter_args.work_dir.as_deref())?;
+    
+    for (index, selection) in selections.into_iter().enumerate() {
+        let label = selection.label();
+        let mut options = RunOptions::new(display, max_turns);
+        options.work_dir = Some(layout.game_dir(index)?);
+        options.coverage = coverage_dir.as_ref().map(|dir| CoverageOptions {
+            file: std::path::Path::new(dir).join(format!("{}.json", label)).to_string_lossy().to_string(),
+            reset: true,
+        });
+        let coverage_path = options.coverage.as_ref().map(|c| c.file.clone());
+        
+        let strategy = Box::new(DiscoveryStrategy::new(vec![selection], rounds));
+        let GameRun { result, turns, .. } = run_game(program, interpreter_args, strategy, options).await?;
+        
+        match coverage_path {
+            Some(path) => {
+                println!("  {:<6} {} ({} turns) coverage: {}", label, result.description(), turns, path);
+                let provider = coverage_provider(program, interpreter_args, &path)?;
+                match provider.collect() {
+                    Ok(data) => provider.merge(&mut total_coverage, &data),
+                    Err(e) => log::warn!("Could not read coverage from {}: {}", path, e),
+                }
+            }
+            None => println!("  {:<6} {} ({} turns)", label, result.description(), turns),
+        }
+    }
+    
+    if coverage_dir.is_some() {
+        total_coverage.summary().print("all selections");
     }
     
-    stats.print_summary();
     Ok(())
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_play_count_conflicts() {
+        let play = |extra: &[&str]| Cli::try_parse_from(["trekbot", "play", "--program", "sst.bas"].iter().chain(extra));
+        assert!(play(&["--count", "4"]).is_ok());
+        assert!(play(&["--porcelain", "--transcript", "game.jsonl"]).is_ok());
+        assert!(play(&["--display", "--transcript", "game.jsonl", "--commentary"]).is_ok());
+        for flag in [&["--porcelain"][..], &["--transcript", "game.jsonl"], &["--commentary"]] {
+            let error = play(&[&["--count", "4"][..], flag].concat()).err().unwrap();
+            assert_eq!(error.kind(), clap::error::ErrorKind::ArgumentConflict);
+        }
+        for flag in ["--display", "--commentary"] {
+            let error = play(&["--porcelain", flag]).err().unwrap();
+            assert_eq!(error.kind(), clap::error::ErrorKind::ArgumentConflict);
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use crate::json::Json;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// An interpreter a run used, as far as it could be identified
+#[derive(Debug, Clone, PartialEq)]
+pub struct InterpreterIdentity {
+    pub name: String,
+    /// Executable or script that was launched
+    pub executable: String,
+    /// First line of its version output, if it reports one
+    pub version: Option<String>,
+}
+
+impl InterpreterIdentity {
+    /// Identify an interpreter, asking `command version_args` for its version
+    pub fn probe(name: &str, executable: &str, command: &str, version_args: &[&str]) -> Self {
+        Self {
+            name: name.to_string(),
+            executable: executable.to_string(),
+            version: command_output(command, version_args),
+        }
+    }
+    
+    fn to_json(&self) -> Json {
+        Json::object()
+            .with("name", self.name.as_str())
+            .with("executable", self.executable.as_str())
+            .with("version", self.version.clone())
+    }
+    
+    fn from_json(json: &Json) -> Option<Self> {
+        Some(Self {
+            name: json.get("name")?.as_str()?.to_string(),
+            executable: json.get("executable")?.as_str()?.to_string(),
+            version: json.get("version").and_then(Json::as_str).map(str::to_string),
+        })
+    }
+}
+
+/// Provenance stamped on every file TrekBot writes, so results from different runs
+/// can be checked against the code, settings and interpreters that produced them
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunMetadata {
+    pub trekbot_version: String,
+    pub git_hash: Option<String>,
+    /// Digest of the settings that affect results, see RunConfig::digest
+    pub config_digest: String,
+    pub interpreters: Vec<InterpreterIdentity>,
+    pub profile: Option<String>,
+    /// UTC, e.g. "2024-05-01T12:00:00Z"
+    pub timestamp: String,
+}
+
+impl RunMetadata {
+    pub fn new(config_digest: String, interpreters: Vec<InterpreterIdentity>) -> Self {
+        Self {
+            trekbot_version: env!("CARGO_PKG_VERSION").to_string(),
+            git_hash: git_hash(),
+            config_digest,
+            interpreters,
+            profile: None,
+            timestamp: utc_timestamp(SystemTime::now()),
+        }
+    }
+    
+    pub fn to_json(&self) -> Json {
+        Json::object()
+            .with("trekbot_version", self.trekbot_version.as_str())
+            .with("git_hash", self.git_hash.clone())
+            .with("config_digest", self.config_digest.as_str())
+            .with("interpreters", Json::Array(self.interpreters.iter().map(InterpreterIdentity::to_json).collect()))
+            .with("profile", self.profile.clone())
+            .with("timestamp", self.timestamp.as_str())
+    }
+    
+    pub fn from_json(json: &Json) -> Option<Self> {
+        let optional = |key: &str| json.get(key).and_then(Json::as_str).map(str::to_string);
+        Some(Self {
+            trekbot_version: json.get("trekbot_version")?.as_str()?.to_string(),
+            git_hash: optional("git_hash"),
+            config_digest: json.get("config_digest")?.as_str()?.to_string(),
+            interpreters: json.get("interpreters")?.as_array()?.iter().filter_map(InterpreterIdentity::from_json).collect(),
+            profile: optional("profile"),
+            timestamp: json.get("timestamp")?.as_str()?.to_string(),
+        })
+    }
+    
+    /// The metadata as "# metadata: {...}" for the top of a CSV file
+    pub fn csv_comment(&self) -> String {
+        format!("# metadata: {}\n", self.to_json())
+    }
+    
+    /// Read back a header written by csv_comment()
+    pub fn from_csv_comment(line: &str) -> Option<Self> {
+        let json = line.trim().strip_prefix("# metadata:")?;
+        Self::from_json(&Json::parse(json.trim()).ok()?)
+    }
+}
+
+/// Stable digest of some text (64-bit FNV-1a, as hex)
+pub fn digest(text: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in text.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
+
+/// Commit TrekBot was built from: TREKBOT_GIT_HASH at build time, or the checkout it
+/// was built in
+fn git_hash() -> Option<String> {
+    if let Some(hash) = option_env!("TREKBOT_GIT_HASH") {
+        return Some(hash.to_string());
+    }
+    command_output("git", &["-C", env!("CARGO_MANIFEST_DIR"), "rev-parse", "--short", "HEAD"])
+}
+
+/// First non-empty line a command prints on stdout or stderr, if it succeeds
+fn command_output(command: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(command).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let text = format!("{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+    text.lines().map(str::trim).find(|line| !line.is_empty()).map(str::to_string)
+}
+
+/// Format a time as an RFC 3339 UTC timestamp without pulling in a date library
+pub fn utc_timestamp(time: SystemTime) -> String {
+    let seconds = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let (days, rest) = (seconds / 86400, seconds % 86400);
+    
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    
+    format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z", year, month, day, rest / 3600, rest % 3600 / 60, rest % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    
+    #[test]
+    fn test_utc_timestamp() {
+        assert_eq!(utc_timestamp(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        assert_eq!(utc_timestamp(UNIX_EPOCH + Duration::from_secs(1_700_000_000)), "2023-11-14T22:13:20Z");
+        assert_eq!(utc_timestamp(UNIX_EPOCH + Duration::from_secs(951_782_400)), "2000-02-29T00:00:00Z");
+    }
+    
+    #[test]
+    fn test_metadata_round_trip() {
+        let mut metadata = RunMetadata::new(digest("superstartrek.bas,basic-rs"), vec![InterpreterIdentity {
+            name: "basic-rs".to_string(),
+            executable: "basic_rs".to_string(),
+            version: None,
+        }]);
+        metadata.profile = Some("superstartrek".to_string());
+        
+        assert_eq!(RunMetadata::from_json(&Json::parse(&metadata.to_json().to_string()).unwrap()), Some(metadata.clone()));
+        assert_eq!(RunMetadata::from_csv_comment(&metadata.csv_comment()), Some(metadata));
+        assert_eq!(digest(""), "cbf29ce484222325");
+    }
+}
@@ -0,0 +1,153 @@
+use crate::json::Json;
+use crate::player::GameStats;
+use anyhow::Result;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Games that must finish before the crash-rate alert can fire, so one early
+/// crash doesn't page anyone
+const MIN_GAMES_FOR_ALERT: usize = 5;
+
+/// Longest a webhook may take to accept a notification
+const POST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A game that ended in a BASIC error or crash, and where to look into it
+pub struct FailedGame {
+    /// Numbered from 0, like the games of a run
+    pub game: usize,
+    pub result: &'static str,
+    /// Only set when the directory outlives the run (`--work-dir`)
+    pub work_dir: Option<PathBuf>,
+    /// Only set when the game was saved as a scenario with its transcript
+    pub transcript: Option<PathBuf>,
+}
+
+impl FailedGame {
+    fn to_json(&self) -> Json {
+        let path = |path: &Option<PathBuf>| path.as_ref().map(|path| path.to_string_lossy().to_string());
+        Json::object()
+            .with("game", self.game)
+            .with("result", self.result)
+            .with("work_dir", path(&self.work_dir))
+            .with("transcript", path(&self.transcript))
+    }
+}
+
+/// Posts JSON summaries of a run to a webhook (e.g. a Slack incoming webhook).
+/// A failed notification is logged and never fails the run.
+pub struct Notifier {
+    url: String,
+    crash_rate_threshold: Option<f64>,
+    alerted: bool,
+}
+
+impl Notifier {
+    pub fn new(url: String, crash_rate_threshold: Option<f64>) -> Self {
+        Self {
+            url,
+            crash_rate_threshold,
+            alerted: false,
+        }
+    }
+    
+    /// Send the final summary when a run finishes
+    pub async fn run_finished(&self, run: &str, stats: &GameStats, failures: &[FailedGame], elapsed_seconds: f64) {
+        let text = format!(
+            "TrekBot {} finished: {} games, {} victories, {} BASIC errors, {} crashes ({:.1}% failing) in {:.0}s",
+            run,
+            stats.total_games,
+            stats.victories,
+            stats.basic_errors,
+            stats.crashes,
+            stats.crash_rate() * 100.0,
+            elapsed_seconds
+        );
+        let body = summary("run_finished", text, run, stats, failures).with("elapsed_seconds", elapsed_seconds);
+        self.post(&body).await;
+    }
+    
+    /// Send a one-off alert once the crash rate goes over the threshold
+    pub async fn check_crash_rate(&mut self, run: &str, stats: &GameStats, failures: &[FailedGame]) {
+        let threshold = match self.crash_rate_threshold {
+            Some(threshold) => threshold,
+            None => return,
+        };
+        if self.alerted || stats.total_games < MIN_GAMES_FOR_ALERT || stats.crash_rate() <= threshold {
+            return;
+        }
+        
+        self.alerted = true;
+        let text = format!(
+            "TrekBot {} crash rate {:.1}% exceeds {:.1}% after {} games",
+            run,
+            stats.crash_rate() * 100.0,
+            threshold * 100.0,
+            stats.total_games
+        );
+        let body = summary("crash_rate_exceeded", text, run, stats, failures).with("threshold", threshold);
+        self.post(&body).await;
+    }
+    
+    /// Send an alert when a health check finds an interpreter broken, or working again
+    pub async fn health_changed(&self, interpreter: &str, healthy: bool, detail: &str) {
+        let state = if healthy { "recovered" } else { "is failing" };
+        let body = Json::object()
+            .with("event", "health_changed")
+            .with("text", format!("TrekBot health check: interpreter {} {} ({})", interpreter, state, detail))
+            .with("interpreter", interpreter)
+            .with("healthy", healthy)
+            .with("detail", detail);
+        self.post(&body).await;
+    }
+    
+    async fn post(&self, body: &Json) {
+        if let Err(e) = self.try_post(body).await {
+            log::warn!("Failed to notify {}: {}", self.url, e);
+        }
+    }
+    
+    async fn try_post(&self, body: &Json) -> Result<()> {
+        let url = self.url.clone();
+        let body = body.to_string();
+        tokio::task::spawn_blocking(move || -> Result<()> {
+            ureq::post(&url)
+                .timeout(POST_TIMEOUT)
+                .set("Content-Type", "application/json")
+                .send_string(&body)?;
+            Ok(())
+        }).await??;
+        log::info!("Notified {}", self.url);
+        Ok(())
+    }
+}
+
+/// Common fields of every run notification. `text` is what chat webhooks display.
+fn summary(event: &str, text: String, run: &str, stats: &GameStats, failures: &[FailedGame]) -> Json {
+    Json::object()
+        .with("event", event)
+        .with("text", text)
+        .with("run", run)
+        .with("stats", stats.to_json())
+        .with("crash_rate", stats.crash_rate())
+        .with("failures", failures.iter().map(FailedGame::to_json).collect::<Vec<_>>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_summary_failures() {
+        let failures = [FailedGame {
+            game: 3,
+            result: "basic_error",
+            work_dir: Some(PathBuf::from("/runs/game-0004")),
+            transcript: None,
+        }];
+        let body = summary("run_finished", String::new(), "benchmark", &GameStats::new(), &failures);
+        let failure = &body.get("failures").and_then(Json::as_array).unwrap()[0];
+        assert_eq!(failure.get("game").and_then(Json::as_usize), Some(3));
+        assert_eq!(failure.get("work_dir").and_then(Json::as_str), Some("/runs/game-0004"));
+        assert_eq!(failure.get("transcript"), Some(&Json::Null));
+    }
+}
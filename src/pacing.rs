@@ -0,0 +1,189 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// A response slower than this counts as the interpreter falling behind
+const LAG_THRESHOLD: Duration = Duration::from_millis(250);
+
+/// First backoff step once the interpreter lags; doubled on each further lag
+const MIN_BACKOFF: Duration = Duration::from_millis(10);
+
+const MAX_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Artificial delay before each command, on top of any pacing, to test how an
+/// interpreter copes with a slow user: `base` plus a random amount up to `jitter`
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct InjectedLatency {
+    pub base: Duration,
+    pub jitter: Duration,
+}
+
+impl InjectedLatency {
+    pub fn sample(&self, rng: &mut impl rand::Rng) -> Duration {
+        if self.jitter.is_zero() {
+            return self.base;
+        }
+        self.base + self.jitter.mul_f64(rng.gen::<f64>())
+    }
+}
+
+/// Decides how long to wait before sending each command. There is no delay while the
+/// interpreter keeps up; when its output lags the delay backs off exponentially and
+/// recovers again as responses speed up. An optional rate limit caps the commands per
+/// second for interpreters that drop input when flooded.
+#[derive(Debug, Clone)]
+pub struct Pacer {
+    max_rate: Option<f64>,
+    backoff: Duration,
+    last_sent: Option<Instant>,
+    latency: InjectedLatency,
+    stats: PacingStats,
+}
+
+impl Pacer {
+    pub fn new(max_rate: Option<f64>) -> Self {
+        Self {
+            max_rate: max_rate.filter(|rate| *rate > 0.0),
+            backoff: Duration::ZERO,
+            last_sent: None,
+            latency: InjectedLatency::default(),
+            stats: PacingStats::default(),
+        }
+    }
+    
+    pub fn set_latency(&mut self, latency: InjectedLatency) {
+        self.latency = latency;
+    }
+    
+    /// Record how long the interpreter took to answer the last command
+    pub fn response(&mut self, elapsed: Duration) {
+        self.stats.slowest_response = self.stats.slowest_response.max(elapsed);
+        if elapsed > LAG_THRESHOLD {
+            self.lagged();
+        } else {
+            self.backoff /= 2;
+            if self.backoff < MIN_BACKOFF {
+                self.backoff = Duration::ZERO;
+            }
+        }
+    }
+    
+    /// Record that output lagged, e.g. a read came back empty
+    pub fn lagged(&mut self) {
+        self.stats.lagged_responses += 1;
+        self.backoff = (self.backoff * 2).clamp(MIN_BACKOFF, MAX_BACKOFF);
+        self.stats.max_backoff = self.stats.max_backoff.max(self.backoff);
+    }
+    
+    /// Delay needed before a command sent at `now`
+    pub fn delay(&self, now: Instant) -> Duration {
+        let rate_delay = match (self.max_rate, self.last_sent) {
+            (Some(rate), Some(last_sent)) => {
+                Duration::from_secs_f64(1.0 / rate).saturating_sub(now.duration_since(last_sent))
+            }
+            _ => Duration::ZERO,
+        };
+        rate_delay.max(self.backoff)
+    }
+    
+    /// Wait until the next command may be sent
+    pub async fn wait(&mut self) {
+        let delay = self.delay(Instant::now());
+        if !delay.is_zero() {
+            self.stats.delayed_commands += 1;
+            self.stats.total_delay += delay;
+            tokio::time::sleep(delay).await;
+        }
+        let injected = self.latency.sample(&mut rand::thread_rng());
+        if !injected.is_zero() {
+            tokio::time::sleep(injected).await;
+        }
+        self.stats.commands += 1;
+        self.last_sent = Some(Instant::now());
+    }
+    
+    pub fn stats(&self) -> &PacingStats {
+        &self.stats
+    }
+}
+
+/// How commands were paced over one or more games
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PacingStats {
+    pub commands: usize,
+    pub delayed_commands: usize,
+    pub total_delay: Duration,
+    pub lagged_responses: usize,
+    pub slowest_response: Duration,
+    pub max_backoff: Duration,
+}
+
+impl PacingStats {
+    pub fn merge(&mut self, other: &PacingStats) {
+        self.commands += other.commands;
+        self.delayed_commands += other.delayed_commands;
+        self.total_delay += other.total_delay;
+        self.lagged_responses += other.lagged_responses;
+        self.slowest_response = self.slowest_response.max(other.slowest_response);
+        self.max_backoff = self.max_backoff.max(other.max_backoff);
+    }
+}
+
+impl fmt::Display for PacingStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} commands, {} delayed ({:.2}s total), {} slow responses (slowest {:.2}s, max backoff {:.2}s)",
+               self.commands,
+               self.delayed_commands,
+               self.total_delay.as_secs_f64(),
+               self.lagged_responses,
+               self.slowest_response.as_secs_f64(),
+               self.max_backoff.as_secs_f64())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    
+    #[test]
+    fn test_backoff() {
+        let mut pacer = Pacer::new(None);
+        let now = Instant::now();
+        pacer.response(Duration::from_millis(5));
+        assert_eq!(pacer.delay(now), Duration::ZERO);
+        
+        pacer.response(Duration::from_millis(500));
+        pacer.lagged();
+        assert_eq!(pacer.delay(now), Duration::from_millis(20));
+        
+        pacer.response(Duration::from_millis(5));
+        pacer.response(Duration::from_millis(5));
+        assert_eq!(pacer.delay(now), Duration::ZERO);
+        assert_eq!(pacer.stats().lagged_responses, 2);
+    }
+    
+    #[test]
+    fn test_rate_limit() {
+        let mut pacer = Pacer::new(Some(10.0));
+        let now = Instant::now();
+        assert_eq!(pacer.delay(now), Duration::ZERO);
+        
+        pacer.last_sent = Some(now);
+        assert_eq!(pacer.delay(now), Duration::from_millis(100));
+        assert_eq!(pacer.delay(now + Duration::from_millis(40)), Duration::from_millis(60));
+        assert_eq!(pacer.delay(now + Duration::from_millis(200)), Duration::ZERO);
+    }
+    
+    #[test]
+    fn test_injected_latency() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+        let latency = InjectedLatency { base: Duration::from_millis(50), jitter: Duration::ZERO };
+        assert_eq!(latency.sample(&mut rng), Duration::from_millis(50));
+        
+        let latency = InjectedLatency { base: Duration::from_millis(50), jitter: Duration::from_millis(100) };
+        for _ in 0..20 {
+            let delay = latency.sample(&mut rng);
+            assert!(delay >= Duration::from_millis(50) && delay <= Duration::from_millis(150));
+        }
+    }
+}
@@ -1,17 +1,103 @@
-use crate::game::GameState;
-use crate::interpreter::Interpreter;
-use crate::strategy::Strategy;
+use crate::commentary::Commentary;
+use crate::display::{DisplayCategory, DisplayFilter, DisplayStyle, StatusLine};
+use crate::game::{share_lines, Advisory, AdvisoryEngine, AdvisoryObserver, EnergyModel, GameProfile, GameState, Line, PromptContext, PromptKind, TerminalCondition, DEFAULT_MAX_OUTPUT_LINES};
+use crate::json::Json;
+use crate::pacing::{Pacer, PacingStats};
+use crate::failures::PrecedingInput;
+use crate::responses::ResponseStats;
+use crate::source::{ProgramIndex, PromptOrigin};
+use crate::interpreter::{Interpreter, InterpreterEvent, ReadTimeout, RestartMethod, is_basic_error, split_prompt_lines};
+use crate::strategy::navigation::torpedo_track;
+use crate::strategy::{Strategy, TurnContext};
+use crate::transcript::Transcript;
+use crate::verbosity::{self, Verbosity};
 use anyhow::Result;
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::sync::{Arc, PoisonError, RwLock};
+use tokio::sync::mpsc::UnboundedSender;
+use std::time::Instant;
 use tokio::time::{sleep, Duration};
 
+/// Output lines across turns handed to strategies when --max-history-lines isn't given
+pub const DEFAULT_HISTORY_LINES: usize = 200;
+
+/// How much of a game the Player holds on to, so long soak runs have bounded memory
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Retention {
+    /// Lines of each turn's output kept in GameState::last_output
+    pub output_lines: usize,
+    /// Most recent commands kept for commands(); all of them if None
+    pub commands: Option<usize>,
+    /// Most recent output lines across turns kept for TurnContext::history
+    pub history_lines: usize,
+    /// Most recent entries kept in a recorded transcript; all of them if None
+    pub transcript_entries: Option<usize>,
+}
+
+impl Default for Retention {
+    fn default() -> Self {
+        Self {
+            output_lines: DEFAULT_MAX_OUTPUT_LINES,
+            commands: None,
+            history_lines: DEFAULT_HISTORY_LINES,
+            transcript_entries: None,
+        }
+    }
+}
+
 /// Player orchestrates the game by connecting interpreter, state, and strategy
 pub struct Player<I: Interpreter, S: Strategy> {
     interpreter: I,
     strategy: S,
     game_state: GameState,
     display_output: bool,
+    display_filter: DisplayFilter,
+    display_style: DisplayStyle,
     max_turns: usize,
     turn_count: usize,
+    /// Command whose echo is still to be stripped from the next output
+    last_command: Option<String>,
+    /// Most recent command sent, kept for diagnostics
+    last_sent: Option<String>,
+    /// Kind of prompt the most recent command answered
+    last_prompt: Option<PromptKind>,
+    /// Commands sent this game, in order; a ring of the most recent with a Retention limit
+    commands: VecDeque<String>,
+    /// Recent output lines across turns, for strategies to look back on
+    history: VecDeque<Line>,
+    /// What was answered at each kind of prompt this game
+    responses: ResponseStats,
+    status_line: StatusLine,
+    progress: Option<ProgressReporter>,
+    transcript: Option<Transcript>,
+    pacer: Pacer,
+    strict: bool,
+    /// The program being played, for tracing a strict mode failure back to its source line
+    source: Option<ProgramIndex>,
+    verify_torpedoes: bool,
+    /// Sector and course of a torpedo fired with the last command, whose track is still to be checked
+    torpedo_fired: Option<((i32, i32), f64)>,
+    /// Expected energy, when energy readings are checked
+    energy: Option<EnergyModel>,
+    /// Torpedo tracks and energy readings this game that didn't follow TrekBot's models
+    discrepancies: usize,
+    commentary: Option<Commentary>,
+    /// Warnings about dangerous state and input the game shouldn't accept, when enabled
+    advisories: Option<AdvisoryEngine>,
+    observers: Vec<Box<dyn AdvisoryObserver>>,
+    /// From launching the interpreter to its first prompt
+    startup_time: Duration,
+    retention: Retention,
+    /// Commands sent all at once after launch, instead of one per prompt
+    type_ahead: Option<Vec<String>>,
+    game_profile: GameProfile,
+    /// The next game starts the strategy's session over instead of continuing it
+    new_session: bool,
+    /// Leave the interpreter running after a game that can be followed by another in place
+    reuse_process: bool,
+    /// The interpreter is still running, at the call for a new commander that ended the last game
+    restartable: bool,
 }
 
 impl<I: Interpreter, S: Strategy> Player<I, S> {
@@ -21,8 +107,36 @@ impl<I: Interpreter, S: Strategy> Player<I, S> {
             strategy,
             game_state: GameState::new(),
             display_output,
+            display_filter: DisplayFilter::default(),
+            display_style: DisplayStyle::default(),
             max_turns: 1000, // Prevent infinite loops
             turn_count: 0,
+            last_command: None,
+            last_sent: None,
+            last_prompt: None,
+            commands: VecDeque::new(),
+            history: VecDeque::new(),
+            responses: ResponseStats::new(),
+            status_line: StatusLine::default(),
+            progress: None,
+            transcript: None,
+            pacer: Pacer::new(None),
+            strict: false,
+            source: None,
+            verify_torpedoes: false,
+            torpedo_fired: None,
+            energy: None,
+            discrepancies: 0,
+            commentary: None,
+            advisories: None,
+            observers: Vec::new(),
+            startup_time: Duration::ZERO,
+            retention: Retention::default(),
+            type_ahead: None,
+            game_profile: GameProfile::super_star_trek(),
+            new_session: true,
+            reuse_process: false,
+            restartable: false,
         }
     }
     
@@ -31,105 +145,372 @@ impl<I: Interpreter, S: Strategy> Player<I, S> {
         self.max_turns = max_turns;
     }
     
+    /// Choose which kinds of lines are displayed
+    pub fn set_display_filter(&mut self, filter: DisplayFilter) {
+        self.display_filter = filter;
+    }
+    
+    /// Colors and turn numbers for displayed lines
+    pub fn set_display_style(&mut self, style: DisplayStyle) {
+        self.display_style = style;
+    }
+    
+    /// Print the status line after each turn, see --status-line
+    pub fn set_status_line(&mut self, status_line: StatusLine) {
+        self.status_line = status_line;
+    }
+    
+    /// Whether the next game starts a fresh strategy session (the default) or continues
+    /// one, keeping what the strategy learned. Later games on the same player always continue it.
+    pub fn set_new_session(&mut self, new_session: bool) {
+        self.new_session = new_session;
+    }
+    
+    /// Keep the interpreter running at the end of a game when its backend can start the
+    /// next one in place (see Interpreter::capabilities), instead of terminating it
+    pub fn set_reuse_process(&mut self, reuse_process: bool) {
+        self.reuse_process = reuse_process;
+    }
+    
+    /// Whether the interpreter was left running by an earlier game, so the next game
+    /// restarts it instead of launching it
+    pub fn set_restartable(&mut self, restartable: bool) {
+        self.restartable = restartable;
+    }
+    
+    pub fn is_restartable(&self) -> bool {
+        self.restartable
+    }
+    
+    /// Report turn-by-turn progress, e.g. to a live table of concurrent games
+    pub fn set_progress(&mut self, progress: ProgressReporter) {
+        self.progress = Some(progress);
+    }
+    
+    /// Replace the default pacing, e.g. to cap the commands per second
+    pub fn set_pacer(&mut self, pacer: Pacer) {
+        self.pacer = pacer;
+    }
+    
+    /// How commands were paced so far
+    pub fn pacing_stats(&self) -> &PacingStats {
+        self.pacer.stats()
+    }
+    
+    /// How long the last game's interpreter took to start and show its first prompt
+    pub fn startup_time(&self) -> Duration {
+        self.startup_time
+    }
+    
+    /// End the game with a ProtocolAnomaly at the first unknown prompt or unexpected
+    /// output, instead of carrying on as best it can
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+    
+    /// Index of the BASIC program, so a strict mode failure names the line its prompt
+    /// came from and suggests a prompt rule for an unknown one
+    pub fn set_source(&mut self, source: ProgramIndex) {
+        self.source = Some(source);
+    }
+    
+    /// Check every torpedo track the interpreter prints against TrekBot's own course model,
+    /// warning about (or with strict mode, stopping at) a track that differs
+    pub fn set_verify_torpedoes(&mut self, verify: bool) {
+        self.verify_torpedoes = verify;
+    }
+    
+    /// Check every total energy reading against what the game's arithmetic says it should
+    /// be after the shots, moves, hits and docking since the last one, warning about (or
+    /// with strict mode, stopping at) an unexplained change
+    pub fn set_verify_energy(&mut self, verify: bool) {
+        self.energy = verify.then(EnergyModel::new);
+    }
+    
+    /// Print a line of play-by-play commentary for each turn where something happened
+    pub fn set_commentary(&mut self, commentary: bool) {
+        self.commentary = commentary.then(Commentary::new);
+    }
+    
+    /// Warn about dangerous or inconsistent state and about invalid input, e.g. shields
+    /// down in condition RED or more shield units than the energy available, and above
+    /// all about invalid input the game accepted
+    pub fn set_advisories(&mut self, advisories: bool) {
+        self.advisories = advisories.then(AdvisoryEngine::new);
+    }
+    
+    /// Tell `observer` about every advisory, besides the log and the display
+    pub fn add_observer(&mut self, observer: Box<dyn AdvisoryObserver>) {
+        self.observers.push(observer);
+    }
+    
+    /// Torpedo tracks and energy readings in the current or last game that differed from
+    /// TrekBot's models
+    pub fn discrepancies(&self) -> usize {
+        self.discrepancies
+    }
+    
+    /// Limit how much output, command history and transcript is kept
+    pub fn set_retention(&mut self, retention: Retention) {
+        self.retention = retention;
+        self.game_state.max_output_lines = retention.output_lines;
+        self.interpreter.set_max_output_lines(retention.output_lines);
+    }
+    
+    /// Send these commands right after launch without waiting for any prompts, then read
+    /// everything the interpreter prints, to check it copes with type-ahead. The strategy
+    /// isn't asked for commands.
+    pub fn set_type_ahead(&mut self, commands: Vec<String>) {
+        self.type_ahead = Some(commands);
+    }
+    
+    /// The game being played, for how its endings are worded
+    pub fn set_game_profile(&mut self, profile: GameProfile) {
+        self.game_profile = profile;
+    }
+    
+    /// Record the raw interpreter I/O of the next game
+    pub fn record_transcript(&mut self) {
+        self.transcript = Some(Transcript::with_limit(self.retention.transcript_entries));
+    }
+    
+    /// The transcript recorded since record_transcript() was called
+    pub fn take_transcript(&mut self) -> Option<Transcript> {
+        self.transcript.take()
+    }
+    
     /// Play one complete game
     pub async fn play_game(&mut self, program_path: &str) -> Result<GameResult> {
+        let result = self.play_turns(program_path).await.map(|result| self.with_state_fallback(result));
+        // Move past a compact status line before anything else is printed
+        self.status_line.finish();
+        result
+    }
+    
+    async fn play_turns(&mut self, program_path: &str) -> Result<GameResult> {
         log::info!("Starting game with strategy: {}", self.strategy.name());
         
-        // Launch the interpreter
-        self.interpreter.launch(program_path).await?;
+        // Launch the interpreter, or restart the one the last game left running; both
+        // return once the first prompt is read
+        let launch_start = Instant::now();
+        self.startup_time = Duration::ZERO;
+        if !std::mem::take(&mut self.restartable) || !self.restart_in_place().await {
+            self.interpreter.launch(program_path).await?;
+        }
+        self.startup_time = launch_start.elapsed();
         
-        // Reset strategy and game state
-        self.strategy.reset();
+        // Reset strategy and game state; the strategy keeps what it learned unless the session is new
+        if self.new_session {
+            self.strategy.reset();
+        } else {
+            self.strategy.new_game();
+        }
+        self.new_session = false;
         self.game_state = GameState::new();
+        self.game_state.max_output_lines = self.retention.output_lines;
         self.turn_count = 0;
+        self.last_command = None;
+        self.last_sent = None;
+        self.last_prompt = None;
+        self.commands.clear();
+        self.history.clear();
+        self.responses = ResponseStats::new();
+        self.torpedo_fired = None;
+        self.discrepancies = 0;
+        if let Some(energy) = &mut self.energy {
+            *energy = EnergyModel::new();
+        }
+        if let Some(commentary) = &mut self.commentary {
+            *commentary = Commentary::new();
+        }
+        if let Some(advisories) = &mut self.advisories {
+            *advisories = AdvisoryEngine::new();
+        }
+        if let Some(commands) = self.type_ahead.clone() {
+            return self.play_type_ahead(&commands).await;
+        }
         
         // Main game loop
+        let mut out_of_commands = false;
         while self.interpreter.is_running() && self.turn_count < self.max_turns {
-            // Read output from interpreter
-            let output = self.interpreter.read_until_prompt().await?;
+            // Read output from interpreter, stopping right away if stdout closed
+            let read_start = Instant::now();
+            let output = match self.read_event().await? {
+                InterpreterEvent::Output(output) => output,
+                InterpreterEvent::ChannelClosed(partial) => {
+                    return self.finish_channel_closed(partial).await;
+                }
+            };
+            if let Some(timeout) = self.interpreter.read_timed_out() {
+                return self.finish_timed_out(timeout, output).await;
+            }
             
             if output.is_empty() {
+                if self.strict {
+                    return self.fail_with_anomaly("no output received from the interpreter".to_string()).await;
+                }
                 log::warn!("No output received from interpreter");
+                self.pacer.lagged();
                 sleep(Duration::from_millis(100)).await;
                 continue;
             }
+            self.pacer.response(read_start.elapsed());
+            
+            let quirks = self.interpreter.quirks();
+            let at_prompt = output.last().is_some_and(|line| quirks.is_prompt(line));
             
             // Display output if requested
-            if self.display_output {
-                for line in &output {
-                    println!("{}", line);
+            let turn = self.turn_count + 1;
+            let display_output = self.display_output && verbosity::shows(Verbosity::Normal);
+            if display_output {
+                self.status_line.clear();
+                for (category, line) in self.display_filter.visible(&output, at_prompt) {
+                    println!("{}", self.display_style.line(turn, category, line));
                 }
             }
             
             // Update game state
-            self.game_state.update(&output)?;
+            let condition = self.game_state.condition.clone();
+            self.game_state.update_with(&output, |line| quirks.is_prompt(line))?;
+            self.history.extend(output.iter().cloned());
+            let excess = self.history.len().saturating_sub(self.retention.history_lines);
+            self.history.drain(..excess);
+            if let (true, Some(from), Some(to)) = (display_output, &condition, &self.game_state.condition) {
+                if from != to && self.display_filter.shows(DisplayCategory::Combat) {
+                    println!("{}", self.display_style.condition_change(turn, from, to));
+                }
+            }
+            
+            if let Some(commentary) = &mut self.commentary {
+                if let Some(line) = commentary.turn(turn, &output, &self.game_state) {
+                    if verbosity::shows(Verbosity::Normal) {
+                        self.status_line.clear();
+                        println!("{}", line);
+                    }
+                }
+            }
+            
+            if let Some(engine) = &mut self.advisories {
+                let advisories = engine.check(&output, &self.game_state);
+                self.advise(turn, advisories);
+            }
             
             // Display current game status (unless it's the first turn without state)
-            if self.turn_count > 0 || self.game_state.stardate.is_some() {
-                self.game_state.display_status();
+            let show_status = self.status_line.is_enabled()
+                && self.display_filter.shows(DisplayCategory::Status)
+                && verbosity::shows(Verbosity::Normal);
+            if show_status && (self.turn_count > 0 || self.game_state.stardate.is_some()) {
+                let status = format!("📊 Turn Status: {}", self.game_state.status_line());
+                self.status_line.show(&self.display_style.line(turn, DisplayCategory::Status, &status));
+            }
+            if let Some(progress) = &self.progress {
+                progress.turn(self.turn_count, &self.game_state);
+            }
+            
+            // A strategy with expectations of the output fails the game when one is broken,
+            // even by the output ending it
+            if let Some(broken) = self.strategy.check_output(&output) {
+                return self.fail_with_anomaly(broken).await;
             }
             
             // Check for game end conditions
             if self.is_game_over(&output) {
                 let result = self.determine_game_result(&output);
                 log::info!("Game ended: {:?}", result);
-                // Try to terminate interpreter gracefully to allow coverage data saving
-                if let Err(e) = self.interpreter.terminate().await {
+                if self.can_restart() {
+                    log::info!("Leaving the interpreter running for the next game");
+                    self.restartable = true;
+                } else if let Err(e) = self.interpreter.terminate().await {
+                    // Terminating gracefully lets the interpreter save coverage data
                     log::warn!("Failed to terminate interpreter gracefully: {}", e);
                 }
                 return Ok(result);
             }
             
-            // Get next command from strategy
-            let command = self.strategy.get_command(&self.game_state)?;
+            if self.strict {
+                if let Some(reason) = self.anomaly(at_prompt) {
+                    return self.fail_with_anomaly(reason).await;
+                }
+            }
+            let mut mismatches: Vec<String> = self.check_torpedo_track().into_iter().collect();
+            if let Some(energy) = &mut self.energy {
+                mismatches.extend(energy.check(&output));
+            }
+            for mismatch in mismatches {
+                self.discrepancies += 1;
+                if self.strict {
+                    return self.fail_with_anomaly(mismatch).await;
+                }
+                log::warn!("Possible interpreter arithmetic bug: {}", mismatch);
+            }
+            
+            // A strategy playing a fixed list ends the game like the turn limit once it is
+            // used up, after the output of its last command has been seen
+            if self.strategy.commands_left() == Some(0) {
+                out_of_commands = true;
+                break;
+            }
+            
+            // Get next command from strategy; the prompt is borrowed from the game state
+            let no_prompt = PromptContext::default();
+            let prompt = self.game_state.prompt_context.as_ref().unwrap_or(&no_prompt);
+            let prompt_kind = prompt.kind;
+            let context = TurnContext {
+                turn: self.turn_count,
+                history: self.history.make_contiguous(),
+                commands: self.commands.make_contiguous(),
+                ..TurnContext::new(prompt, &self.game_state)
+            };
+            let command = self.strategy.get_command(&context).await?;
             log::debug!("Sending command: {}", command);
+            if let Some(reason) = self.strategy.last_decision() {
+                log::info!("{} chose {}: {}", self.strategy.name(), command.trim(), reason);
+            }
+            let advisories = self.advisories.as_mut().map(|engine| engine.answered(prompt, &self.game_state, &command));
             
-            // DEBUG: Check for blank commands and provide detailed info
-            if command.trim().is_empty() {
-                // Check if this is an expected blank command response
-                let current_prompt = self.game_state.get_current_prompt().unwrap_or("").trim();
-                let is_expected_blank = match current_prompt {
-                    "PLEASE ENTER" => true,
-                    "ENTER ONE OF THE FOLLOWING:" => true,
-                    p if p.contains("SHIELDS NOW AT") && p.contains("UNITS PER YOUR COMMAND") => true,
-                    p if p.contains("DEFLECTOR CONTROL ROOM REPORT") => true,
-                    p if p.contains("NOW ENTERING") && p.contains("QUADRANT") => true,
-                    _ => false,
-                };
-                
-                if !is_expected_blank {
-                    eprintln!("🚨 DEBUG: About to send blank command!");
-                    eprintln!("  Current prompt: {:?}", self.game_state.get_current_prompt());
-                    eprintln!("  Last 5 output lines:");
-                    for (i, line) in self.game_state.last_output.iter().rev().take(5).enumerate() {
-                        eprintln!("    -{}: {}", i+1, line);
-                    }
-                    eprintln!("  Game state: stardate={:?}, condition={:?}", 
-                              self.game_state.stardate, self.game_state.condition);
-                    eprintln!("  Strategy: {}", self.strategy.name());
-                    std::process::exit(1);
-                }
+            // Status messages are expected to be answered with a blank line. Anywhere else the
+            // game would stall, so it fails, leaving any games played alongside to finish.
+            if command.trim().is_empty() && prompt_kind != PromptKind::Message {
+                return self.fail_with_anomaly(format!("{} sent an empty line to a {:?} prompt", self.strategy.name(), prompt_kind)).await;
             }
             
             // Display command if output is enabled
-            if self.display_output {
+            if display_output && self.display_filter.shows(DisplayCategory::Commands) {
             //     if command.trim().is_empty() {
             //         println!("🤖 TrekBot sends: [ENTER]");
             //     } else {
-                    println!("🤖 TrekBot sends: {}", command);
+                    println!("{}", self.display_style.command(self.turn_count + 1, &command));
                 // }
+                if let Some(reason) = self.strategy.last_decision() {
+                    println!("{}", self.display_style.decision(self.turn_count + 1, reason));
+                }
             }
+            self.advise(self.turn_count + 1, advisories.unwrap_or_default());
             
-            // Send command to interpreter
+            // Send command to interpreter, holding back if it is falling behind
+            self.pacer.wait().await;
             self.interpreter.send_command(&command).await?;
+            if let Some(transcript) = &mut self.transcript {
+                transcript.record_decision(&command, self.strategy.last_decision());
+            }
+            self.last_sent = Some(command.clone());
+            self.last_prompt = Some(prompt_kind);
+            self.remember_command(&command);
+            self.responses.record(prompt_kind, &command);
+            if let Some(energy) = &mut self.energy {
+                energy.answered(prompt_kind, &command);
+            }
+            if self.verify_torpedoes && prompt_kind == PromptKind::TorpedoCourse {
+                if let (Some(sector), Ok(course)) = (self.game_state.current_sector, command.trim().parse()) {
+                    self.torpedo_fired = Some((sector, course));
+                }
+            }
+            self.last_command = Some(command);
             
             self.turn_count += 1;
-            
-            // Small delay to prevent overwhelming the interpreter
-            sleep(Duration::from_millis(10)).await;
         }
         
-        if self.turn_count >= self.max_turns {
+        if self.turn_count >= self.max_turns || out_of_commands {
             log::warn!("Game ended due to max turns limit");
             // Try to terminate interpreter gracefully to allow coverage data saving
             if let Err(e) = self.interpreter.terminate().await {
@@ -137,41 +518,266 @@ impl<I: Interpreter, S: Strategy> Player<I, S> {
             }
             Ok(GameResult::MaxTurnsReached)
         } else {
-            log::info!("Game ended - interpreter stopped");
-            Ok(GameResult::InterpreterStopped)
+            let result = self.classify_interpreter_stop();
+            log::info!("Game ended - interpreter stopped: {:?}", result);
+            Ok(result)
         }
     }
     
-    /// Check if the game has ended based on output
-    fn is_game_over(&self, output: &[String]) -> bool {
-        for line in output {
-            let line = line.to_uppercase();
-            if line.contains("MISSION ACCOMPLISHED") 
-                || line.contains("YOU HAVE BEEN KILLED") 
-                || line.contains("GAME OVER") 
-                || line.contains("FEDERATION DESTROYED")
-                || line.contains("TIME HAS RUN OUT") {
-                return true;
+    /// Pass on advisories to the log, the display and every observer
+    fn advise(&mut self, turn: usize, advisories: Vec<Advisory>) {
+        for advisory in advisories {
+            log::warn!("Turn {} advisory ({}): {}", turn, advisory.kind.label(), advisory.message);
+            if self.display_output && self.display_filter.shows(DisplayCategory::Status) && verbosity::shows(Verbosity::Normal) {
+                self.status_line.clear();
+                println!("{}", self.display_style.advisory(turn, &advisory));
+            }
+            for observer in &mut self.observers {
+                observer.advise(turn, &advisory);
+            }
+        }
+    }
+    
+    /// Start the next game in the interpreter the last one left running, telling whether
+    /// that worked; if not the interpreter is stopped so it can be launched again
+    async fn restart_in_place(&mut self) -> bool {
+        match self.interpreter.restart().await {
+            Ok(()) => true,
+            Err(e) => {
+                log::warn!("Could not restart {} in place, launching it again: {}", self.interpreter.name(), e);
+                if let Err(e) = self.interpreter.terminate().await {
+                    log::warn!("Failed to terminate interpreter gracefully: {}", e);
+                }
+                false
+            }
+        }
+    }
+    
+    /// Whether the game that just ended left the interpreter able to start another in place
+    fn can_restart(&mut self) -> bool {
+        self.reuse_process
+            && self.interpreter.capabilities().restart != RestartMethod::Relaunch
+            && self.game_state.prompt_context.as_ref().is_some_and(|context| context.kind == PromptKind::Aye)
+            && self.interpreter.is_running()
+    }
+    
+    /// Read the next batch of output with any echo of the last command removed,
+    /// normalized for the interpreter's known quirks and with mixed status/prompt
+    /// lines split apart
+    async fn read_event(&mut self) -> Result<InterpreterEvent> {
+        let output = self.interpreter.read_until_prompt().await?;
+        if let Some(transcript) = &mut self.transcript {
+            transcript.record_output(&output);
+        }
+        let output = self.interpreter.quirks().strip_echo(output, self.last_command.take().as_deref());
+        let output = self.interpreter.quirks().normalize(output);
+        let output = share_lines(split_prompt_lines(output));
+        
+        if self.interpreter.channel_closed() {
+            log::debug!("Interpreter stdout closed after {} lines", output.len());
+            Ok(InterpreterEvent::ChannelClosed(output))
+        } else {
+            Ok(InterpreterEvent::Output(output))
+        }
+    }
+    
+    /// Anything in the latest output that strict mode refuses to play through
+    fn anomaly(&self, at_prompt: bool) -> Option<String> {
+        if let Some(error) = self.game_state.last_output.iter().find(|line| is_basic_error(line)) {
+            return Some(format!("interpreter reported an error: {}", error.trim()));
+        }
+        if !at_prompt {
+            return Some("output did not end at a prompt".to_string());
+        }
+        match &self.game_state.prompt_context {
+            Some(prompt) if prompt.kind != PromptKind::Unknown => None,
+            _ => Some("unknown prompt".to_string()),
+        }
+    }
+    
+    /// How the torpedo fired with the last command strayed from the course model, if it did
+    fn check_torpedo_track(&mut self) -> Option<String> {
+        let (sector, course) = self.torpedo_fired.take()?;
+        let track = self.game_state.torpedo_track.as_ref()?;
+        let expected = torpedo_track(sector, course);
+        if expected.starts_with(track) {
+            return None;
+        }
+        Some(format!("torpedo from sector {:?} on course {} went {:?}, expected {:?}", sector, course, track, expected))
+    }
+    
+    /// Stop the interpreter and fail the game with a dump of where it went wrong
+    async fn fail_with_anomaly(&mut self, reason: String) -> Result<GameResult> {
+        if let Err(e) = self.interpreter.terminate().await {
+            log::warn!("Failed to terminate interpreter gracefully: {}", e);
+        }
+        let prompt = self.game_state.prompt_context.clone();
+        let origin = prompt.as_ref().and_then(|prompt| self.source.as_ref()?.locate(&prompt.prompt));
+        let suggestion = prompt.as_ref()
+            .filter(|prompt| prompt.kind == PromptKind::Unknown)
+            .and_then(|prompt| self.source.as_ref()?.suggest_rule(&prompt.prompt));
+        Err(ProtocolAnomaly {
+            reason,
+            turn: self.turn_count,
+            strategy: self.strategy.name(),
+            last_command: self.last_sent.clone(),
+            prompt,
+            origin,
+            suggestion,
+            output: self.game_state.last_output.iter().map(|line| line.to_string()).collect(),
+        }
+        .into())
+    }
+    
+    /// Add a command sent to commands(), dropping the oldest beyond the Retention limit
+    fn remember_command(&mut self, command: &str) {
+        if self.retention.commands.is_some_and(|limit| self.commands.len() >= limit) {
+            self.commands.pop_front();
+        }
+        self.commands.push_back(command.to_string());
+    }
+    
+    /// Append lines to output read across several replies, keeping only the last
+    /// Retention::output_lines of them
+    fn keep_recent(&self, output: &mut Vec<Line>, lines: Vec<Line>) {
+        output.extend(lines);
+        let excess = output.len().saturating_sub(self.retention.output_lines);
+        output.drain(..excess);
+    }
+    
+    /// Send every command at once, then read until the interpreter goes quiet or closes stdout
+    async fn play_type_ahead(&mut self, commands: &[String]) -> Result<GameResult> {
+        for command in commands {
+            self.interpreter.send_command(command).await?;
+            if let Some(transcript) = &mut self.transcript {
+                transcript.record_input(command);
             }
+            self.remember_command(command);
         }
-        false
-    }
-    
-    /// Determine the game result based on output
-    fn determine_game_result(&self, output: &[String]) -> GameResult {
-        for line in output {
-            let line = line.to_uppercase();
-            if line.contains("MISSION ACCOMPLISHED") {
-                return GameResult::Victory;
-            } else if line.contains("YOU HAVE BEEN KILLED") {
-                return GameResult::Destroyed;
-            } else if line.contains("TIME HAS RUN OUT") {
-                return GameResult::TimeUp;
-            } else if line.contains("FEDERATION DESTROYED") {
-                return GameResult::FederationDestroyed;
+        self.turn_count = commands.len();
+        
+        let mut output = Vec::new();
+        loop {
+            match self.read_event().await? {
+                InterpreterEvent::Output(lines) if lines.is_empty() => break,
+                InterpreterEvent::Output(lines) => self.keep_recent(&mut output, lines),
+                InterpreterEvent::ChannelClosed(partial) => {
+                    self.keep_recent(&mut output, partial);
+                    return self.finish_channel_closed(output).await;
+                }
             }
         }
-        GameResult::Unknown
+        
+        let quirks = self.interpreter.quirks();
+        self.game_state.update_with(&output, |line| quirks.is_prompt(line))?;
+        let result = if self.is_game_over(&output) {
+            self.determine_game_result(&output)
+        } else {
+            GameResult::MaxTurnsReached
+        };
+        if let Err(e) = self.interpreter.terminate().await {
+            log::warn!("Failed to terminate interpreter gracefully: {}", e);
+        }
+        log::info!("Type-ahead game ended: {:?}", result);
+        Ok(result)
+    }
+    
+    /// Finalize a game whose interpreter closed stdout. The partial output is kept
+    /// in the game state, but never treated as a prompt, so no command is sent to
+    /// a dead process.
+    async fn finish_channel_closed(&mut self, partial: Vec<Line>) -> Result<GameResult> {
+        if self.display_output && verbosity::shows(Verbosity::Normal) {
+            for (category, line) in self.display_filter.visible(&partial, false) {
+                println!("{}", self.display_style.line(self.turn_count + 1, category, line));
+            }
+        }
+        
+        if !partial.is_empty() {
+            self.game_state.update_with(&partial, |_| false)?;
+        }
+        self.game_state.last_prompt = None;
+        self.game_state.prompt_context = None;
+        
+        // Reap the process so its exit status is known
+        if let Err(e) = self.interpreter.terminate().await {
+            log::warn!("Failed to terminate interpreter gracefully: {}", e);
+        }
+        
+        let result = if self.is_game_over(&partial) {
+            self.determine_game_result(&partial)
+        } else {
+            self.classify_interpreter_stop()
+        };
+        log::info!("Game ended - interpreter closed its output: {:?}", result);
+        Ok(result)
+    }
+    
+    /// Finalize a game whose interpreter went quiet: after some output, most likely at a
+    /// prompt TrekBot doesn't know, or with no output at all, most likely hung
+    async fn finish_timed_out(&mut self, timeout: ReadTimeout, output: Vec<Line>) -> Result<GameResult> {
+        if self.display_output && verbosity::shows(Verbosity::Normal) {
+            for (category, line) in self.display_filter.visible(&output, false) {
+                println!("{}", self.display_style.line(self.turn_count + 1, category, line));
+            }
+        }
+        if !output.is_empty() {
+            self.game_state.update_with(&output, |_| false)?;
+        }
+        
+        if let Err(e) = self.interpreter.terminate().await {
+            log::warn!("Failed to terminate interpreter gracefully: {}", e);
+        }
+        
+        let result = match timeout {
+            _ if self.is_game_over(&output) => self.determine_game_result(&output),
+            ReadTimeout::Partial => GameResult::NoPrompt,
+            ReadTimeout::Hang => GameResult::Hung,
+        };
+        log::info!("Game ended - interpreter timed out: {:?}", result);
+        Ok(result)
+    }
+    
+    /// Work out why the interpreter stopped: a BASIC error reported by the
+    /// interpreter, an abnormal process exit, or a normal end of program
+    fn classify_interpreter_stop(&mut self) -> GameResult {
+        if self.game_state.last_output.iter().any(|line| is_basic_error(line)) {
+            return GameResult::BasicError;
+        }
+        
+        match self.interpreter.exit_status() {
+            Some(status) if !status.success() => {
+                log::warn!("Interpreter exited abnormally: {:?}", status);
+                GameResult::InterpreterCrashed
+            }
+            _ => GameResult::InterpreterStopped,
+        }
+    }
+    
+    /// Check if the game has ended based on output
+    fn is_game_over(&self, output: &[Line]) -> bool {
+        self.game_profile.ending(output).is_some()
+    }
+    
+    /// Replace a result that doesn't say how the game went with what the parsed state
+    /// shows, when an interpreter's ending text differs from the ones is_game_over() knows
+    fn with_state_fallback(&self, result: GameResult) -> GameResult {
+        if !matches!(result, GameResult::MaxTurnsReached | GameResult::InterpreterStopped
+            | GameResult::NoPrompt | GameResult::Hung | GameResult::Unknown) {
+            return result;
+        }
+        let fallback = match self.game_state.terminal_condition() {
+            Some(TerminalCondition::KlingonsDestroyed) => GameResult::Victory,
+            Some(TerminalCondition::OutOfEnergy) => GameResult::Destroyed,
+            Some(TerminalCondition::DeadlinePassed) => GameResult::TimeUp,
+            None => return result,
+        };
+        log::info!("Game state shows {:?} rather than {:?}", fallback, result);
+        fallback
+    }
+    
+    /// Determine the game result based on output, using the game profile's endings
+    fn determine_game_result(&self, output: &[Line]) -> GameResult {
+        self.game_profile.ending(output).unwrap_or(GameResult::Unknown)
     }
     
     /// Get the current game state
@@ -183,6 +789,22 @@ impl<I: Interpreter, S: Strategy> Player<I, S> {
     pub fn get_turn_count(&self) -> usize {
         self.turn_count
     }
+    
+    /// Commands sent in the current or last game, the most recent ones with a
+    /// Retention limit
+    pub fn commands(&self) -> Vec<String> {
+        self.commands.iter().cloned().collect()
+    }
+    
+    /// Answers given at each kind of prompt in the current or last game
+    pub fn responses(&self) -> &ResponseStats {
+        &self.responses
+    }
+    
+    /// The last command the strategy sent this game and the prompt it answered
+    pub fn preceding_input(&self) -> Option<PrecedingInput> {
+        Some(PrecedingInput { prompt: self.last_prompt?, command: self.last_sent.clone()? })
+    }
 }
 
 impl<I: Interpreter, S: Strategy> Drop for Player<I, S> {
@@ -195,6 +817,121 @@ impl<I: Interpreter, S: Strategy> Drop for Player<I, S> {
     }
 }
 
+/// Why a game stopped on a broken exchange with the interpreter (in strict mode, on any
+/// anomaly), with what the bot saw at the time
+#[derive(Debug)]
+pub struct ProtocolAnomaly {
+    pub reason: String,
+    pub turn: usize,
+    pub strategy: &'static str,
+    pub last_command: Option<String>,
+    pub prompt: Option<PromptContext>,
+    /// Program line the prompt came from, when the program was indexed
+    pub origin: Option<PromptOrigin>,
+    /// Prompt table entry that would recognize an unknown prompt
+    pub suggestion: Option<String>,
+    pub output: Vec<String>,
+}
+
+impl fmt::Display for ProtocolAnomaly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Protocol anomaly: {} at turn {}", self.reason, self.turn)?;
+        writeln!(f, "  Strategy: {}", self.strategy)?;
+        writeln!(f, "  Last command: {:?}", self.last_command.as_deref().unwrap_or(""))?;
+        match &self.prompt {
+            Some(prompt) => writeln!(f, "  Prompt: {:?} ({:?})", prompt.prompt, prompt.kind)?,
+            None => writeln!(f, "  Prompt: none")?,
+        }
+        if let Some(origin) = &self.origin {
+            writeln!(f, "  Source: {}", origin)?;
+        }
+        if let Some(suggestion) = &self.suggestion {
+            writeln!(f, "  Suggested prompt rule: {}", suggestion)?;
+        }
+        write!(f, "  Output ({} lines):", self.output.len())?;
+        for line in &self.output {
+            write!(f, "\n    | {}", line)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ProtocolAnomaly {}
+
+/// Progress of one game, as shown in the live table for concurrent play
+#[derive(Debug, Clone)]
+pub struct GameProgress {
+    pub game: usize,
+    pub turn: usize,
+    pub condition: Option<String>,
+    /// Snapshot of the state after the turn, for the live charts
+    pub energy: Option<i32>,
+    pub shields: Option<i32>,
+    pub klingons: Option<i32>,
+    pub result: Option<GameResult>,
+    pub error: Option<String>,
+}
+
+/// Sends progress updates for one game to whoever is watching
+#[derive(Clone)]
+pub struct ProgressReporter {
+    game: usize,
+    sender: UnboundedSender<GameProgress>,
+}
+
+impl ProgressReporter {
+    pub fn new(game: usize, sender: UnboundedSender<GameProgress>) -> Self {
+        Self { game, sender }
+    }
+    
+    /// Report the state after a turn
+    pub fn turn(&self, turn: usize, game_state: &GameState) {
+        self.send(GameProgress {
+            game: self.game,
+            turn,
+            condition: game_state.condition.clone(),
+            energy: game_state.energy,
+            shields: game_state.shields,
+            klingons: game_state.klingons_remaining,
+            result: None,
+            error: None,
+        });
+    }
+    
+    /// Report how the game ended
+    pub fn finished(&self, turn: usize, result: GameResult) {
+        self.send(GameProgress {
+            game: self.game,
+            turn,
+            condition: None,
+            energy: None,
+            shields: None,
+            klingons: None,
+            result: Some(result),
+            error: None,
+        });
+    }
+    
+    /// Report that the game could not be played, e.g. the interpreter failed to start
+    pub fn failed(&self, error: &anyhow::Error) {
+        self.send(GameProgress {
+            game: self.game,
+            turn: 0,
+            condition: None,
+            energy: None,
+            shields: None,
+            klingons: None,
+            result: None,
+            error: Some(error.to_string()),
+        });
+    }
+    
+    fn send(&self, progress: GameProgress) {
+        // The watcher going away must never stop a game
+        let _ = self.sender.send(progress);
+    }
+}
+
 /// Result of a game session
 #[derive(Debug, Clone, PartialEq)]
 pub enum GameResult {
@@ -204,6 +941,12 @@ pub enum GameResult {
     FederationDestroyed,
     MaxTurnsReached,
     InterpreterStopped,
+    BasicError,
+    InterpreterCrashed,
+    /// Output stopped without a prompt TrekBot recognized
+    NoPrompt,
+    /// No output at all within the hang timeout
+    Hung,
     Unknown,
 }
 
@@ -220,9 +963,39 @@ impl GameResult {
             GameResult::FederationDestroyed => "Federation headquarters destroyed.",
             GameResult::MaxTurnsReached => "Game ended due to turn limit.",
             GameResult::InterpreterStopped => "Interpreter process stopped.",
+            GameResult::BasicError => "Interpreter reported a BASIC error.",
+            GameResult::InterpreterCrashed => "Interpreter process crashed.",
+            GameResult::NoPrompt => "Interpreter output stopped without a recognized prompt.",
+            GameResult::Hung => "Interpreter stopped responding.",
             GameResult::Unknown => "Game ended for unknown reasons.",
         }
     }
+    
+    /// The result with this slug()
+    pub fn from_slug(slug: &str) -> Option<Self> {
+        [
+            GameResult::Victory, GameResult::Destroyed, GameResult::TimeUp, GameResult::FederationDestroyed,
+            GameResult::MaxTurnsReached, GameResult::InterpreterStopped, GameResult::BasicError,
+            GameResult::InterpreterCrashed, GameResult::NoPrompt, GameResult::Hung, GameResult::Unknown,
+        ].into_iter().find(|result| result.slug() == slug)
+    }
+    
+    /// Short machine-readable name, e.g. "victory" or "basic_error"
+    pub fn slug(&self) -> &'static str {
+        match self {
+            GameResult::Victory => "victory",
+            GameResult::Destroyed => "destroyed",
+            GameResult::TimeUp => "time_up",
+            GameResult::FederationDestroyed => "federation_destroyed",
+            GameResult::MaxTurnsReached => "max_turns",
+            GameResult::InterpreterStopped => "interpreter_stopped",
+            GameResult::BasicError => "basic_error",
+            GameResult::InterpreterCrashed => "interpreter_crashed",
+            GameResult::NoPrompt => "no_prompt",
+            GameResult::Hung => "hung",
+            GameResult::Unknown => "unknown",
+        }
+    }
 }
 
 /// Statistics for multiple games
@@ -232,8 +1005,20 @@ pub struct GameStats {
     pub victories: usize,
     pub destroyed: usize,
     pub time_up: usize,
+    pub basic_errors: usize,
+    pub crashes: usize,
     pub other: usize,
     pub avg_turns: f64,
+    /// Fewest turns any victory took
+    pub best_win_turns: Option<usize>,
+    /// Average turns over the victories only
+    pub avg_win_turns: f64,
+    /// Games whose timing was recorded with add_timing()
+    pub timed_games: usize,
+    /// Average time from launching the interpreter to its first prompt
+    pub avg_startup_seconds: f64,
+    /// Average time spent playing after the first prompt
+    pub avg_play_seconds: f64,
 }
 
 impl GameStats {
@@ -243,18 +1028,40 @@ impl GameStats {
             victories: 0,
             destroyed: 0,
             time_up: 0,
+            basic_errors: 0,
+            crashes: 0,
             other: 0,
             avg_turns: 0.0,
+            best_win_turns: None,
+            avg_win_turns: 0.0,
+            timed_games: 0,
+            avg_startup_seconds: 0.0,
+            avg_play_seconds: 0.0,
         }
     }
     
+    /// Add one game's interpreter startup and play time
+    pub fn add_timing(&mut self, startup: Duration, play: Duration) {
+        let games = self.timed_games as f64;
+        self.avg_startup_seconds = (self.avg_startup_seconds * games + startup.as_secs_f64()) / (games + 1.0);
+        self.avg_play_seconds = (self.avg_play_seconds * games + play.as_secs_f64()) / (games + 1.0);
+        self.timed_games += 1;
+    }
+    
     pub fn add_game(&mut self, result: GameResult, turns: usize) {
         self.total_games += 1;
         
+        if result == GameResult::Victory {
+            self.best_win_turns = Some(self.best_win_turns.map_or(turns, |best| best.min(turns)));
+            self.avg_win_turns = (self.avg_win_turns * self.victories as f64 + turns as f64) / (self.victories + 1) as f64;
+        }
+        
         match result {
             GameResult::Victory => self.victories += 1,
             GameResult::Destroyed => self.destroyed += 1,
             GameResult::TimeUp => self.time_up += 1,
+            GameResult::BasicError => self.basic_errors += 1,
+            GameResult::InterpreterCrashed => self.crashes += 1,
             _ => self.other += 1,
         }
         
@@ -270,14 +1077,80 @@ impl GameStats {
         }
     }
     
+    /// Add another run's statistics to these
+    pub fn merge(&mut self, other: &GameStats) {
+        let total_games = self.total_games + other.total_games;
+        if total_games > 0 {
+            self.avg_turns = (self.avg_turns * self.total_games as f64 + other.avg_turns * other.total_games as f64)
+                / total_games as f64;
+        }
+        let victories = self.victories + other.victories;
+        if victories > 0 {
+            self.avg_win_turns = (self.avg_win_turns * self.victories as f64 + other.avg_win_turns * other.victories as f64)
+                / victories as f64;
+        }
+        let timed_games = self.timed_games + other.timed_games;
+        if timed_games > 0 {
+            let average = |a: f64, b: f64| (a * self.timed_games as f64 + b * other.timed_games as f64) / timed_games as f64;
+            self.avg_startup_seconds = average(self.avg_startup_seconds, other.avg_startup_seconds);
+            self.avg_play_seconds = average(self.avg_play_seconds, other.avg_play_seconds);
+        }
+        self.timed_games = timed_games;
+        self.best_win_turns = match (self.best_win_turns, other.best_win_turns) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        self.total_games = total_games;
+        self.victories = victories;
+        self.destroyed += other.destroyed;
+        self.time_up += other.time_up;
+        self.basic_errors += other.basic_errors;
+        self.crashes += other.crashes;
+        self.other += other.other;
+    }
+    
+    /// Fraction of games that ended in a BASIC error or an interpreter crash
+    pub fn crash_rate(&self) -> f64 {
+        if self.total_games == 0 {
+            0.0
+        } else {
+            (self.basic_errors + self.crashes) as f64 / self.total_games as f64
+        }
+    }
+    
+    pub fn to_json(&self) -> Json {
+        Json::object()
+            .with("total_games", self.total_games)
+            .with("victories", self.victories)
+            .with("destroyed", self.destroyed)
+            .with("time_up", self.time_up)
+            .with("basic_errors", self.basic_errors)
+            .with("crashes", self.crashes)
+            .with("other", self.other)
+            .with("avg_turns", self.avg_turns)
+            .with("best_win_turns", self.best_win_turns)
+            .with("avg_win_turns", self.avg_win_turns)
+            .with("timed_games", self.timed_games)
+            .with("avg_startup_seconds", self.avg_startup_seconds)
+            .with("avg_play_seconds", self.avg_play_seconds)
+    }
+    
     pub fn print_summary(&self) {
         println!("=== Game Statistics ===");
         println!("Total games: {}", self.total_games);
         println!("Victories: {} ({:.1}%)", self.victories, self.success_rate() * 100.0);
         println!("Destroyed: {} ({:.1}%)", self.destroyed, self.destroyed as f64 / self.total_games as f64 * 100.0);
         println!("Time up: {} ({:.1}%)", self.time_up, self.time_up as f64 / self.total_games as f64 * 100.0);
+        println!("BASIC errors: {} ({:.1}%)", self.basic_errors, self.basic_errors as f64 / self.total_games as f64 * 100.0);
+        println!("Interpreter crashes: {} ({:.1}%)", self.crashes, self.crashes as f64 / self.total_games as f64 * 100.0);
         println!("Other: {} ({:.1}%)", self.other, self.other as f64 / self.total_games as f64 * 100.0);
         println!("Average turns: {:.1}", self.avg_turns);
+        if let Some(best) = self.best_win_turns {
+            println!("Turns to win: best {}, average {:.1}", best, self.avg_win_turns);
+        }
+        if self.timed_games > 0 {
+            println!("Average time per game: {:.2}s startup, {:.2}s playing", self.avg_startup_seconds, self.avg_play_seconds);
+        }
     }
 }
 
@@ -285,4 +1158,168 @@ impl Default for GameStats {
     fn default() -> Self {
         Self::new()
     }
-} 
\ No newline at end of file
+}
+
+/// Statistics kept apart for each group of games, e.g. each interpreter or strategy of a
+/// mixed run, to compare them side by side in one report
+#[derive(Debug, Clone)]
+pub struct GroupedStats<K: Ord = String> {
+    groups: BTreeMap<K, GameStats>,
+}
+
+impl<K: Ord + fmt::Display> GroupedStats<K> {
+    pub fn new() -> Self {
+        Self { groups: BTreeMap::new() }
+    }
+    
+    pub fn add_game(&mut self, group: K, result: GameResult, turns: usize) {
+        self.groups.entry(group).or_default().add_game(result, turns);
+    }
+    
+    /// Add a whole run's statistics to its group
+    pub fn merge(&mut self, group: K, stats: &GameStats) {
+        self.groups.entry(group).or_default().merge(stats);
+    }
+    
+    pub fn groups(&self) -> &BTreeMap<K, GameStats> {
+        &self.groups
+    }
+    
+    /// Every group's statistics combined
+    pub fn total(&self) -> GameStats {
+        self.groups.values().fold(GameStats::new(), |mut total, stats| {
+            total.merge(stats);
+            total
+        })
+    }
+    
+    /// One row per group, in the groups' order, under a heading naming what they are
+    pub fn print_table(&self, heading: &str) {
+        println!("{:<32} {:>6} {:>13} {:>9} {:>7} {:>9} {:>8}",
+                 heading, "Games", "Victories", "Destroyed", "Crashes", "Avg turns", "Best win");
+        for (group, stats) in self.groups() {
+            let victories = format!("{} ({:.0}%)", stats.victories, stats.success_rate() * 100.0);
+            let best = stats.best_win_turns.map_or("-".to_string(), |turns| turns.to_string());
+            println!("{:<32} {:>6} {:>13} {:>9} {:>7} {:>9.1} {:>8}",
+                     group.to_string(), stats.total_games, victories, stats.destroyed,
+                     stats.basic_errors + stats.crashes, stats.avg_turns, best);
+        }
+    }
+}
+
+impl<K: Ord + fmt::Display> Default for GroupedStats<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Range of game seeds grouped together, so runs can be compared across parts of the
+/// seed space, e.g. to spot galaxies one interpreter deals badly
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SeedBucket {
+    pub first: u64,
+    pub last: u64,
+}
+
+impl SeedBucket {
+    /// The bucket of `width` seeds that `seed` falls in
+    pub fn of(seed: u64, width: u64) -> Self {
+        let width = width.max(1);
+        let first = seed / width * width;
+        Self { first, last: first.saturating_add(width - 1) }
+    }
+}
+
+impl fmt::Display for SeedBucket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "seeds {}-{}", self.first, self.last)
+    }
+}
+
+/// Statistics that several games record into at once while others read them, e.g. the
+/// workers of a concurrent run and the summary shown as they finish. Clones share the
+/// same statistics.
+#[derive(Debug, Clone, Default)]
+pub struct SharedStats {
+    inner: Arc<RwLock<GameStats>>,
+}
+
+impl SharedStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Add one game's result and timing together, so no reader sees one without the other
+    pub fn add_game(&self, result: GameResult, turns: usize, startup: Duration, play: Duration) {
+        let mut stats = self.inner.write().unwrap_or_else(PoisonError::into_inner);
+        stats.add_game(result, turns);
+        stats.add_timing(startup, play);
+    }
+    
+    /// Copy of the statistics as they are now
+    pub fn snapshot(&self) -> GameStats {
+        self.inner.read().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreter::custom::CustomInterpreter;
+    use crate::interpreter::profile::Transport;
+    use crate::interpreter::{InterpreterProfile, ReadTimeouts};
+    use crate::strategy::RandomStrategy;
+    
+    /// Play against `sh -c script`, giving up on reads after a fraction of a second
+    async fn play_stub(script: &str) -> GameResult {
+        let mut interpreter = CustomInterpreter::new(InterpreterProfile {
+            name: "stub".to_string(),
+            command: "sh".to_string(),
+            args: vec!["-c".to_string(), script.to_string()],
+            transport: Transport::Stdio,
+            quirks: Vec::new(),
+            version_args: None,
+            restart: RestartMethod::Relaunch,
+        });
+        interpreter.set_read_timeouts(ReadTimeouts { partial: Duration::from_millis(200), hang: Duration::from_millis(500) });
+        let mut player = Player::new(interpreter, RandomStrategy::with_seed(1), false);
+        player.play_game("stub.bas").await.unwrap()
+    }
+    
+    #[tokio::test]
+    async fn test_read_timeouts_end_game() {
+        // Launch reads up to the first prompt, the game loop the second
+        assert_eq!(play_stub("printf 'COMMAND?COMMAND?'; read x; printf 'ABC'; exec sleep 5").await, GameResult::NoPrompt);
+        assert_eq!(play_stub("printf 'COMMAND?COMMAND?'; read x; exec sleep 5").await, GameResult::Hung);
+    }
+    
+    #[test]
+    fn test_shared_stats() {
+        let stats = SharedStats::new();
+        let worker = stats.clone();
+        worker.add_game(GameResult::Victory, 40, Duration::from_secs(1), Duration::from_secs(3));
+        let before = stats.snapshot();
+        worker.add_game(GameResult::Destroyed, 20, Duration::from_secs(1), Duration::from_secs(1));
+        assert_eq!(before.total_games, 1);
+        
+        let after = stats.snapshot();
+        assert_eq!((after.total_games, after.victories, after.destroyed, after.timed_games), (2, 1, 1, 2));
+        assert_eq!(after.avg_play_seconds, 2.0);
+    }
+    
+    #[test]
+    fn test_grouped_stats() {
+        let mut grouped = GroupedStats::new();
+        grouped.add_game(SeedBucket::of(1500, 1000), GameResult::Victory, 30);
+        grouped.add_game(SeedBucket::of(999, 1000), GameResult::Destroyed, 10);
+        grouped.add_game(SeedBucket::of(1999, 1000), GameResult::Victory, 50);
+        let groups: Vec<(String, usize)> = grouped.groups().iter()
+            .map(|(bucket, stats)| (bucket.to_string(), stats.victories))
+            .collect();
+        assert_eq!(groups, [("seeds 0-999".to_string(), 0), ("seeds 1000-1999".to_string(), 2)]);
+        
+        let total = grouped.total();
+        assert_eq!((total.total_games, total.victories, total.best_win_turns), (3, 2, Some(30)));
+        assert_eq!(total.avg_turns, 30.0);
+    }
+}
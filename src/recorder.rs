@@ -0,0 +1,121 @@
+use crate::failures::PrecedingInput;
+use crate::metadata::RunMetadata;
+use crate::player::GameResult;
+use crate::scenario::Scenario;
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Why a game is worth keeping, in order of preference; also the file name prefix
+const CATEGORIES: [&str; 3] = ["victory", "coverage", "rare"];
+
+/// Saves interesting benchmark games into a scenario library, so the acceptance suite
+/// grows out of normal runs: victories, games that reached new BASIC lines, and games
+/// ending in a rare result. Each category holds at most `cap` scenarios, counting those
+/// already in the library from earlier runs.
+#[derive(Debug)]
+pub struct ScenarioRecorder {
+    dir: PathBuf,
+    cap: usize,
+    saved: BTreeMap<&'static str, usize>,
+}
+
+impl ScenarioRecorder {
+    pub fn new(dir: &str, cap: usize) -> Result<Self> {
+        std::fs::create_dir_all(dir).with_context(|| format!("Failed to create scenario library {}", dir))?;
+        let mut saved = BTreeMap::new();
+        for file in crate::scenario::scenario_files(dir)? {
+            let name = file.file_name().map_or_else(String::new, |name| name.to_string_lossy().to_string());
+            if let Some(category) = CATEGORIES.into_iter().find(|category| name.starts_with(&format!("{}-", category))) {
+                *saved.entry(category).or_insert(0) += 1;
+            }
+        }
+        Ok(Self { dir: PathBuf::from(dir), cap, saved })
+    }
+    
+    /// Where a game's transcript is written until it is promoted or dropped
+    pub fn pending_transcript(&self) -> PathBuf {
+        self.dir.join(format!(".pending-{}.jsonl", std::process::id()))
+    }
+    
+    /// Save the game as a scenario in the first category it qualifies for that still has
+    /// room, moving its transcript next to it. Returns the scenario's path, or None when
+    /// the game wasn't kept (its transcript is then removed).
+    pub fn promote(&mut self, scenario: &Scenario, result: &GameResult, new_lines: usize, preceding: Option<&PrecedingInput>, metadata: &RunMetadata) -> Result<Option<PathBuf>> {
+        let pending = self.pending_transcript();
+        let category = categories(result, new_lines).into_iter()
+            .find(|category| self.saved.get(category).copied().unwrap_or(0) < self.cap);
+        let Some(category) = category else {
+            std::fs::remove_file(&pending).ok();
+            return Ok(None);
+        };
+        
+        let name = (1..).map(|number| format!("{}-{:03}", category, number))
+            .find(|name| !self.dir.join(format!("{}.json", name)).exists())
+            .unwrap_or_default();
+        let path = self.dir.join(format!("{}.json", name));
+        let transcript = self.dir.join(format!("{}.jsonl", name));
+        if pending.exists() {
+            std::fs::rename(&pending, &transcript).with_context(|| format!("Failed to move transcript to {}", transcript.display()))?;
+        }
+        
+        let mut json = scenario.to_json()
+            .with("transcript", transcript.to_string_lossy().to_string());
+        if let Some(preceding) = preceding {
+            json = json.with("preceding_input", preceding.to_json());
+        }
+        let json = json.with("metadata", metadata.to_json());
+        std::fs::write(&path, format!("{}\n", json)).with_context(|| format!("Failed to write scenario {}", path.display()))?;
+        *self.saved.entry(category).or_insert(0) += 1;
+        Ok(Some(path))
+    }
+}
+
+/// Categories a game qualifies for
+fn categories(result: &GameResult, new_lines: usize) -> Vec<&'static str> {
+    let rare = matches!(result, GameResult::TimeUp | GameResult::FederationDestroyed | GameResult::BasicError
+        | GameResult::InterpreterStopped | GameResult::InterpreterCrashed | GameResult::NoPrompt | GameResult::Hung);
+    [result.is_success(), new_lines > 0, rare].into_iter()
+        .zip(CATEGORIES)
+        .filter_map(|(applies, category)| applies.then_some(category))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_promote() {
+        assert_eq!(categories(&GameResult::Victory, 3), ["victory", "coverage"]);
+        assert_eq!(categories(&GameResult::BasicError, 0), ["rare"]);
+        assert!(categories(&GameResult::MaxTurnsReached, 0).is_empty());
+        
+        let dir = std::env::temp_dir().join(format!("trekbot-recorder-{}", std::process::id()));
+        let mut recorder = ScenarioRecorder::new(&dir.to_string_lossy(), 1).unwrap();
+        let scenario = Scenario {
+            name: "game".to_string(),
+            program: "sst.bas".to_string(),
+            strategy: "random".to_string(),
+            seed: Some(7),
+            dock: false,
+            max_turns: 100,
+            allow_known_bugs: false,
+            commands: None,
+            expect_result: Some("victory".to_string()),
+            expect_lines: Vec::new(),
+        };
+        let metadata = RunMetadata::new("digest".to_string(), Vec::new());
+        let first = recorder.promote(&scenario, &GameResult::Victory, 2, None, &metadata).unwrap();
+        let second = recorder.promote(&scenario, &GameResult::Victory, 2, None, &metadata).unwrap();
+        let third = recorder.promote(&scenario, &GameResult::Victory, 2, None, &metadata).unwrap();
+        let preceding = PrecedingInput { prompt: crate::game::PromptKind::WarpFactor, command: "8".to_string() };
+        let rare = recorder.promote(&scenario, &GameResult::Hung, 0, Some(&preceding), &metadata).unwrap().unwrap();
+        let saved = std::fs::read_to_string(&rare).unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+        assert!(saved.contains(r#""preceding_input":{"prompt":"WarpFactor","command":"8"}"#));
+        assert_eq!(first, Some(dir.join("victory-001.json")));
+        assert_eq!(second, Some(dir.join("coverage-001.json")));
+        assert_eq!(third, None);
+    }
+}
@@ -0,0 +1,66 @@
+use crate::display::DisplayStyle;
+use crate::transcript::Transcript;
+use crate::turn::TurnLog;
+use anyhow::Result;
+use std::io::{BufRead, Write};
+use std::time::Duration;
+
+/// Longest pause between turns of a replay, however long the original game waited
+const MAX_PAUSE: Duration = Duration::from_secs(2);
+
+/// Replay a recorded game on the terminal without an interpreter: the opening, then
+/// each turn's command, the output that answered it and the state it left the game in.
+/// Pauses follow the recorded timing divided by `speed`; with `step` each turn waits
+/// for Enter instead ("q" stops).
+pub async fn view(transcript: &Transcript, speed: f64, step: bool) -> Result<()> {
+    let log = TurnLog::from_transcript(transcript)?;
+    let mut previous = log.opening_elapsed;
+    let style = DisplayStyle { turn_numbers: false, ..DisplayStyle::for_terminal() };
+    
+    if let Some(metadata) = transcript.metadata() {
+        println!("Recorded {} by TrekBot {} ({})", metadata.timestamp, metadata.trekbot_version,
+                 metadata.git_hash.as_deref().unwrap_or("unknown commit"));
+    }
+    
+    println!("==== Opening ({:.2}s) ====", log.opening_elapsed);
+    for line in &log.opening {
+        println!("{}", line);
+    }
+    log.opening_state.display_status();
+    
+    for turn in &log.turns {
+        if step {
+            print!("-- turn {}/{} (Enter for next, q to quit) ", turn.index, log.turns.len());
+            std::io::stdout().flush()?;
+            let mut answer = String::new();
+            std::io::stdin().lock().read_line(&mut answer)?;
+            if answer.trim().eq_ignore_ascii_case("q") {
+                break;
+            }
+        } else if speed > 0.0 {
+            let pause = Duration::from_secs_f64((turn.elapsed - previous).max(0.0) / speed);
+            tokio::time::sleep(pause.min(MAX_PAUSE)).await;
+        }
+        previous = turn.elapsed;
+        
+        println!("==== Turn {} ({:.2}s) ====", turn.index, turn.elapsed);
+        if turn.command.is_empty() {
+            println!("🤖 TrekBot sends: [ENTER]");
+        } else {
+            println!("🤖 TrekBot sends: {}", turn.command);
+        }
+        if let Some(reason) = &turn.reason {
+            println!("{}", style.decision(turn.index, reason));
+        }
+        for line in &turn.events {
+            println!("{}", line);
+        }
+        if step {
+            println!("{}", style.state_changes(turn.index, &turn.after.changes_since(&turn.before)));
+        } else {
+            turn.after.display_status();
+        }
+    }
+    println!("(end of transcript)");
+    Ok(())
+}
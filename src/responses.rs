@@ -0,0 +1,110 @@
+use crate::game::PromptKind;
+use crate::live::sparkline;
+use std::collections::BTreeMap;
+
+/// Bars in the histogram of a numeric prompt's answers
+const BINS: usize = 8;
+
+/// Answers listed for a prompt whose answers aren't numbers
+const TOP_ANSWERS: usize = 8;
+
+/// What a strategy answered at each kind of prompt, counted over one or more games.
+/// Shows whether a strategy really sends the inputs it is meant to, e.g. the spread of
+/// warp factors or how much energy goes to the shields.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ResponseStats {
+    /// Count of each answer, by prompt kind
+    answers: BTreeMap<String, BTreeMap<String, usize>>,
+}
+
+impl ResponseStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn record(&mut self, kind: PromptKind, answer: &str) {
+        let answers = self.answers.entry(format!("{:?}", kind)).or_default();
+        *answers.entry(answer.trim().to_string()).or_insert(0) += 1;
+    }
+    
+    /// Add another game's or run's answers to these
+    pub fn merge(&mut self, other: &ResponseStats) {
+        for (kind, answers) in &other.answers {
+            let merged = self.answers.entry(kind.clone()).or_default();
+            for (answer, count) in answers {
+                *merged.entry(answer.clone()).or_insert(0) += count;
+            }
+        }
+    }
+    
+    pub fn print_summary(&self) {
+        println!("=== Answers by prompt ===");
+        for (kind, answers) in &self.answers {
+            let total: usize = answers.values().sum();
+            println!("{} ({} answers)", kind, total);
+            match numeric_histogram(answers) {
+                Some(bins) => {
+                    let counts: Vec<i32> = bins.iter().map(|(_, _, count)| *count as i32).collect();
+                    println!("  {}", sparkline(&counts));
+                    for (low, high, count) in bins {
+                        println!("  {:>8.1} - {:<8.1} {:>5} ({:.1}%)", low, high, count, count as f64 * 100.0 / total as f64);
+                    }
+                }
+                None => {
+                    let mut ranked: Vec<(&String, &usize)> = answers.iter().collect();
+                    ranked.sort_by(|a, b| b.1.cmp(a.1));
+                    for (answer, count) in ranked.into_iter().take(TOP_ANSWERS) {
+                        let answer = if answer.is_empty() { "[ENTER]" } else { answer.as_str() };
+                        println!("  {:<17} {:>5} ({:.1}%)", answer, count, *count as f64 * 100.0 / total as f64);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Answer counts in BINS equal-width bins from the smallest to the largest answer, when
+/// every answer is a number: (low, high, count) for each bin
+fn numeric_histogram(answers: &BTreeMap<String, usize>) -> Option<Vec<(f64, f64, usize)>> {
+    let values: Vec<(f64, usize)> = answers.iter()
+        .map(|(answer, count)| answer.parse::<f64>().ok().map(|value| (value, *count)))
+        .collect::<Option<_>>()?;
+    if values.is_empty() {
+        return None;
+    }
+    let min = values.iter().map(|(value, _)| *value).fold(f64::INFINITY, f64::min);
+    let max = values.iter().map(|(value, _)| *value).fold(f64::NEG_INFINITY, f64::max);
+    
+    let width = ((max - min) / BINS as f64).max(f64::EPSILON);
+    let mut bins: Vec<(f64, f64, usize)> = (0..BINS)
+        .map(|bin| (min + width * bin as f64, min + width * (bin + 1) as f64, 0))
+        .collect();
+    for (value, count) in values {
+        let bin = (((value - min) / width) as usize).min(BINS - 1);
+        bins[bin].2 += count;
+    }
+    Some(bins)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_histogram() {
+        let mut responses = ResponseStats::new();
+        for warp in ["0.5", "1.0", "1.0", "8.0"] {
+            responses.record(PromptKind::WarpFactor, warp);
+        }
+        responses.record(PromptKind::Command, "NAV");
+        
+        let mut other = ResponseStats::new();
+        other.record(PromptKind::WarpFactor, "4.2");
+        responses.merge(&other);
+        
+        let bins = numeric_histogram(&responses.answers["WarpFactor"]).unwrap();
+        assert_eq!(bins.len(), BINS);
+        assert_eq!(bins.iter().map(|(_, _, count)| *count).collect::<Vec<_>>(), [3, 0, 0, 1, 0, 0, 0, 1]);
+        assert_eq!(numeric_histogram(&responses.answers["Command"]), None);
+    }
+}
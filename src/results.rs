@@ -0,0 +1,394 @@
+use crate::compress;
+use crate::json::Json;
+use crate::metadata::{self, RunMetadata};
+use crate::player::{GameStats, GroupedStats};
+use anyhow::{Context, Result};
+use std::path::Path;
+
+/// Settings that must match for two runs' statistics to be combined
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunConfig {
+    /// File name of the BASIC program (not the full path, which differs between machines)
+    pub program: String,
+    pub interpreter: String,
+    pub strategy: String,
+    pub max_turns: usize,
+    pub dock: bool,
+    pub allow_known_bugs: bool,
+}
+
+impl RunConfig {
+    /// Field names, in CSV column order
+    const FIELDS: &'static [&'static str] = &["program", "interpreter", "strategy", "max_turns", "dock", "allow_known_bugs"];
+    
+    fn values(&self) -> Vec<String> {
+        vec![
+            self.program.clone(),
+            self.interpreter.clone(),
+            self.strategy.clone(),
+            self.max_turns.to_string(),
+            self.dock.to_string(),
+            self.allow_known_bugs.to_string(),
+        ]
+    }
+    
+    fn to_json(&self) -> Json {
+        Json::object()
+            .with("program", self.program.as_str())
+            .with("interpreter", self.interpreter.as_str())
+            .with("strategy", self.strategy.as_str())
+            .with("max_turns", self.max_turns)
+            .with("dock", self.dock)
+            .with("allow_known_bugs", self.allow_known_bugs)
+    }
+    
+    fn from_json(json: &Json) -> Option<Self> {
+        Some(Self {
+            program: json.get("program")?.as_str()?.to_string(),
+            interpreter: json.get("interpreter")?.as_str()?.to_string(),
+            strategy: json.get("strategy")?.as_str()?.to_string(),
+            max_turns: json.get("max_turns")?.as_usize()?,
+            dock: json.get("dock")?.as_bool()?,
+            allow_known_bugs: json.get("allow_known_bugs")?.as_bool()?,
+        })
+    }
+    
+    /// Digest of the settings, stamped on every artifact of the run
+    pub fn digest(&self) -> String {
+        metadata::digest(&self.values().join(","))
+    }
+    
+    /// Value of the setting with this name, as in the CSV header
+    pub fn field(&self, name: &str) -> Option<String> {
+        let index = Self::FIELDS.iter().position(|field| *field == name)?;
+        self.values().into_iter().nth(index)
+    }
+    
+    /// Names of the settings that differ between two configs
+    pub fn differences(&self, other: &RunConfig) -> Vec<String> {
+        self.differences_except(other, &[])
+    }
+    
+    /// Names of the settings that differ between two configs, leaving out `ignored` ones
+    pub fn differences_except(&self, other: &RunConfig, ignored: &[&str]) -> Vec<String> {
+        Self::FIELDS.iter()
+            .zip(self.values().into_iter().zip(other.values()))
+            .filter(|(field, (a, b))| a != b && !ignored.contains(field))
+            .map(|(field, (a, b))| format!("{} ({} vs {})", field, a, b))
+            .collect()
+    }
+}
+
+/// The statistics of one benchmark run together with the settings that produced them
+#[derive(Debug, Clone)]
+pub struct RunResults {
+    pub config: RunConfig,
+    pub stats: GameStats,
+    /// Missing in files written before metadata was recorded
+    pub metadata: Option<RunMetadata>,
+    /// Labels given with --tag, e.g. "nightly" or the interpreter release under test
+    pub tags: Vec<String>,
+}
+
+/// Separates the tags in the single CSV column they share
+const TAG_SEPARATOR: char = ';';
+
+/// Check a tag can be saved in both JSON and CSV results
+pub fn parse_tag(tag: &str) -> Result<String, String> {
+    if tag.is_empty() || tag.contains(|c: char| c == ',' || c == TAG_SEPARATOR || c.is_whitespace()) {
+        return Err(format!("tags must be non-empty, without spaces, commas or {:?}", TAG_SEPARATOR));
+    }
+    Ok(tag.to_string())
+}
+
+/// Result file format, picked from the extension (ignoring any compression extension)
+fn is_csv(path: &str) -> bool {
+    let name = path.trim_end_matches(".gz").trim_end_matches(".zst").trim_end_matches(".zstd");
+    Path::new(name).extension().is_some_and(|ext| ext.eq_ignore_ascii_case("csv"))
+}
+
+const STATS_FIELDS: &[&str] = &["total_games", "victories", "destroyed", "time_up", "basic_errors", "crashes", "other", "avg_turns", "best_win_turns", "avg_win_turns", "timed_games", "avg_startup_seconds", "avg_play_seconds"];
+
+impl RunResults {
+    pub fn new(config: RunConfig, stats: GameStats) -> Self {
+        Self { config, stats, metadata: None, tags: Vec::new() }
+    }
+    
+    pub fn with_metadata(mut self, metadata: RunMetadata) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+    
+    pub fn with_tags(mut self, tags: Vec<String>) -> Self {
+        self.tags = tags;
+        self
+    }
+    
+    /// Whether the run carries every one of `tags`
+    pub fn has_tags(&self, tags: &[String]) -> bool {
+        tags.iter().all(|tag| self.tags.contains(tag))
+    }
+    
+    /// Save as JSON, or CSV for a .csv file name; .gz/.zst compresses either
+    pub fn save(&self, path: &str) -> Result<()> {
+        let contents = if is_csv(path) { self.to_csv() } else { self.to_json().to_string() + "\n" };
+        compress::write_file(path, &contents)
+    }
+    
+    pub fn load(path: &str) -> Result<Self> {
+        let contents = compress::read_file(path)?;
+        let results = if is_csv(path) { Self::from_csv(&contents) } else { Self::from_json(&Json::parse(&contents)?) };
+        results.with_context(|| format!("{} is not a TrekBot results file", path))
+    }
+    
+    pub fn to_json(&self) -> Json {
+        let json = Json::object()
+            .with("config", self.config.to_json())
+            .with("stats", self.stats.to_json())
+            .with("tags", Json::Array(self.tags.iter().map(|tag| tag.as_str().into()).collect()));
+        match &self.metadata {
+            Some(metadata) => json.with("metadata", metadata.to_json()),
+            None => json,
+        }
+    }
+    
+    fn from_json(json: &Json) -> Option<Self> {
+        let stats = json.get("stats")?;
+        let count = |field: &str| stats.get(field).and_then(Json::as_usize);
+        Some(Self {
+            config: RunConfig::from_json(json.get("config")?)?,
+            stats: GameStats {
+                total_games: count("total_games")?,
+                victories: count("victories")?,
+                destroyed: count("destroyed")?,
+                time_up: count("time_up")?,
+                basic_errors: count("basic_errors")?,
+                crashes: count("crashes")?,
+                other: count("other")?,
+                avg_turns: stats.get("avg_turns")?.as_f64()?,
+                // Not in files saved before turns to win were tracked
+                best_win_turns: count("best_win_turns"),
+                avg_win_turns: stats.get("avg_win_turns").and_then(Json::as_f64).unwrap_or(0.0),
+                // Nor before startup and play time were
+                timed_games: count("timed_games").unwrap_or(0),
+                avg_startup_seconds: stats.get("avg_startup_seconds").and_then(Json::as_f64).unwrap_or(0.0),
+                avg_play_seconds: stats.get("avg_play_seconds").and_then(Json::as_f64).unwrap_or(0.0),
+            },
+            metadata: json.get("metadata").and_then(RunMetadata::from_json),
+            // Not in files saved before runs could be tagged
+            tags: json.get("tags").and_then(Json::as_array).map_or_else(Vec::new, |tags| {
+                tags.iter().filter_map(Json::as_str).map(str::to_string).collect()
+            }),
+        })
+    }
+    
+    /// The metadata as a comment line, then one header row and one data row
+    pub fn to_csv(&self) -> String {
+        let header: Vec<&str> = RunConfig::FIELDS.iter().chain(STATS_FIELDS).chain(&["tags"]).copied().collect();
+        let stats = &self.stats;
+        let mut values = self.config.values();
+        values.extend([stats.total_games, stats.victories, stats.destroyed, stats.time_up, stats.basic_errors, stats.crashes, stats.other].iter().map(|v| v.to_string()));
+        values.push(format!("{:.3}", stats.avg_turns));
+        values.push(stats.best_win_turns.map_or(String::new(), |turns| turns.to_string()));
+        values.push(format!("{:.3}", stats.avg_win_turns));
+        values.push(stats.timed_games.to_string());
+        values.push(format!("{:.3}", stats.avg_startup_seconds));
+        values.push(format!("{:.3}", stats.avg_play_seconds));
+        values.push(self.tags.join(&TAG_SEPARATOR.to_string()));
+        let metadata = self.metadata.as_ref().map_or(String::new(), RunMetadata::csv_comment);
+        format!("{}{}\n{}\n", metadata, header.join(","), values.join(","))
+    }
+    
+    fn from_csv(text: &str) -> Option<Self> {
+        let metadata = text.lines().find_map(RunMetadata::from_csv_comment);
+        let mut lines = text.lines().filter(|line| !line.trim().is_empty() && !line.starts_with('#'));
+        let header: Vec<&str> = lines.next()?.split(',').map(str::trim).collect();
+        let row: Vec<&str> = lines.next()?.split(',').map(str::trim).collect();
+        let field = |name: &str| header.iter().position(|h| *h == name).and_then(|i| row.get(i).copied());
+        let count = |name: &str| field(name)?.parse::<usize>().ok();
+        Some(Self {
+            config: RunConfig {
+                program: field("program")?.to_string(),
+                interpreter: field("interpreter")?.to_string(),
+                strategy: field("strategy")?.to_string(),
+                max_turns: count("max_turns")?,
+                dock: field("dock")?.parse().ok()?,
+                allow_known_bugs: field("allow_known_bugs")?.parse().ok()?,
+            },
+            stats: GameStats {
+                total_games: count("total_games")?,
+                victories: count("victories")?,
+                destroyed: count("destroyed")?,
+                time_up: count("time_up")?,
+                basic_errors: count("basic_errors")?,
+                crashes: count("crashes")?,
+                other: count("other")?,
+                avg_turns: field("avg_turns")?.parse().ok()?,
+                best_win_turns: count("best_win_turns"),
+                avg_win_turns: field("avg_win_turns").and_then(|value| value.parse().ok()).unwrap_or(0.0),
+                timed_games: count("timed_games").unwrap_or(0),
+                avg_startup_seconds: field("avg_startup_seconds").and_then(|value| value.parse().ok()).unwrap_or(0.0),
+                avg_play_seconds: field("avg_play_seconds").and_then(|value| value.parse().ok()).unwrap_or(0.0),
+            },
+            metadata,
+            tags: field("tags").map_or_else(Vec::new, |tags| {
+                tags.split(TAG_SEPARATOR).filter(|tag| !tag.is_empty()).map(str::to_string).collect()
+            }),
+        })
+    }
+}
+
+/// Combine several runs into one, refusing runs whose settings differ
+pub fn merge_results(runs: &[(String, RunResults)]) -> Result<RunResults> {
+    let (first_path, first) = runs.first().context("No result files to merge")?;
+    let mut stats = GameStats::new();
+    
+    for (path, run) in runs {
+        let differences = first.config.differences(&run.config);
+        if !differences.is_empty() {
+            anyhow::bail!(
+                "{} is not compatible with {}: different {}",
+                path,
+                first_path,
+                differences.join(", ")
+            );
+        }
+        stats.merge(&run.stats);
+    }
+    
+    // The merged file gets its own metadata; keep track of every interpreter involved
+    let mut interpreters = Vec::new();
+    for identity in runs.iter().filter_map(|(_, run)| run.metadata.as_ref()).flat_map(|m| &m.interpreters) {
+        if !interpreters.contains(identity) {
+            interpreters.push(identity.clone());
+        }
+    }
+    let metadata = RunMetadata::new(first.config.digest(), interpreters);
+    let mut tags: Vec<String> = Vec::new();
+    for tag in runs.iter().flat_map(|(_, run)| &run.tags) {
+        if !tags.contains(tag) {
+            tags.push(tag.clone());
+        }
+    }
+    Ok(RunResults::new(first.config.clone(), stats).with_metadata(metadata).with_tags(tags))
+}
+
+/// Combine runs into one set of statistics for each combination of the `fields` settings,
+/// e.g. one per interpreter, labelled with their values. Runs may differ only in those.
+/// The field "tag" groups by tag instead; a run with several tags counts under each.
+pub fn group_results(runs: &[(String, RunResults)], fields: &[&str]) -> Result<GroupedStats> {
+    let (first_path, first) = runs.first().context("No result files to group")?;
+    let mut grouped = GroupedStats::new();
+    
+    for (path, run) in runs {
+        let differences = first.config.differences_except(&run.config, fields);
+        if !differences.is_empty() {
+            anyhow::bail!(
+                "{} is not compatible with {}: different {}",
+                path,
+                first_path,
+                differences.join(", ")
+            );
+        }
+        let mut groups = vec![Vec::new()];
+        for field in fields {
+            let values = match *field {
+                "tag" if run.tags.is_empty() => vec!["untagged".to_string()],
+                "tag" => run.tags.clone(),
+                _ => vec![run.config.field(field).with_context(|| format!("Unknown setting {}", field))?],
+            };
+            groups = groups.iter()
+                .flat_map(|group| values.iter().map(move |value| [group.clone(), vec![value.clone()]].concat()))
+                .collect();
+        }
+        for group in groups {
+            grouped.merge(group.join(" / "), &run.stats);
+        }
+    }
+    Ok(grouped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn sample(games: usize, victories: usize, avg_turns: f64) -> RunResults {
+        let config = RunConfig {
+            program: "superstartrek.bas".to_string(),
+            interpreter: "basic-rs".to_string(),
+            strategy: "random".to_string(),
+            max_turns: 100,
+            dock: false,
+            allow_known_bugs: false,
+        };
+        let mut stats = GameStats::new();
+        stats.total_games = games;
+        stats.victories = victories;
+        stats.other = games - victories;
+        stats.avg_turns = avg_turns;
+        stats.best_win_turns = Some(avg_turns as usize - 10);
+        stats.avg_win_turns = avg_turns - 5.0;
+        stats.add_timing(std::time::Duration::from_secs(2), std::time::Duration::from_secs_f64(avg_turns / 10.0));
+        RunResults::new(config, stats)
+    }
+    
+    #[test]
+    fn test_json_and_csv_round_trip() {
+        let run = sample(10, 2, 55.5);
+        let from_json = RunResults::from_json(&Json::parse(&run.to_json().to_string()).unwrap()).unwrap();
+        assert_eq!(from_json.config, run.config);
+        assert_eq!(from_json.stats.victories, 2);
+        let from_csv = RunResults::from_csv(&run.to_csv()).unwrap();
+        assert_eq!(from_csv.config, run.config);
+        assert_eq!(from_csv.stats.avg_turns, 55.5);
+        assert_eq!(from_csv.stats.best_win_turns, Some(45));
+        assert_eq!(from_json.stats.avg_win_turns, 50.5);
+        assert_eq!(from_csv.stats.avg_startup_seconds, 2.0);
+        assert_eq!(from_json.stats.avg_play_seconds, 5.55);
+        
+        let stamped = run.clone()
+            .with_metadata(RunMetadata::new(run.config.digest(), Vec::new()))
+            .with_tags(vec!["nightly".to_string(), "basicrs-v0.9".to_string()]);
+        let from_csv = RunResults::from_csv(&stamped.to_csv()).unwrap();
+        assert_eq!(from_csv.metadata, stamped.metadata);
+        assert_eq!(from_csv.tags, stamped.tags);
+        assert_eq!(RunResults::from_json(&stamped.to_json()).unwrap().tags, stamped.tags);
+        assert!(parse_tag("nightly run").is_err());
+        assert_eq!(from_csv.stats.victories, 2);
+    }
+    
+    #[test]
+    fn test_merge() {
+        let runs = vec![("a.json".to_string(), sample(10, 2, 50.0)), ("b.csv".to_string(), sample(30, 3, 70.0))];
+        let merged = merge_results(&runs).unwrap();
+        assert_eq!(merged.stats.total_games, 40);
+        assert_eq!(merged.stats.victories, 5);
+        assert_eq!(merged.stats.avg_turns, 65.0);
+        assert_eq!(merged.stats.best_win_turns, Some(40));
+        assert_eq!(merged.stats.avg_win_turns, 57.0);
+        assert_eq!(merged.stats.timed_games, 2);
+        assert_eq!(merged.stats.avg_play_seconds, 6.0);
+        
+        let mut other = sample(5, 1, 10.0);
+        other.config.interpreter = "trek-basic".to_string();
+        let runs = vec![("a.json".to_string(), sample(10, 2, 50.0)), ("c.json".to_string(), other)];
+        assert!(merge_results(&runs).is_err());
+        
+        // Grouping by the setting that differs keeps the runs apart instead
+        let grouped = group_results(&runs, &["interpreter"]).unwrap();
+        let groups: Vec<(&str, usize)> = grouped.groups().iter().map(|(name, stats)| (name.as_str(), stats.total_games)).collect();
+        assert_eq!(groups, [("basic-rs", 10), ("trek-basic", 5)]);
+        assert!(group_results(&runs, &["strategy"]).is_err());
+        
+        let runs = vec![
+            ("a.json".to_string(), sample(10, 2, 50.0).with_tags(vec!["nightly".to_string(), "v0.9".to_string()])),
+            ("b.json".to_string(), sample(30, 3, 70.0).with_tags(vec!["v0.9".to_string()])),
+            ("c.json".to_string(), sample(5, 1, 10.0)),
+        ];
+        assert!(runs[0].1.has_tags(&["v0.9".to_string()]) && !runs[1].1.has_tags(&["nightly".to_string()]));
+        assert_eq!(merge_results(&runs).unwrap().tags, ["nightly", "v0.9"]);
+        let grouped = group_results(&runs, &["tag"]).unwrap();
+        let groups: Vec<(&str, usize)> = grouped.groups().iter().map(|(name, stats)| (name.as_str(), stats.total_games)).collect();
+        assert_eq!(groups, [("nightly", 10), ("untagged", 5), ("v0.9", 40)]);
+    }
+}
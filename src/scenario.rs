@@ -0,0 +1,128 @@
+use crate::json::Json;
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+/// One game of an acceptance suite and what it must do, read from a JSON file:
+///
+/// ```json
+/// {"program": "superstartrek.bas", "strategy": "random", "seed": 42, "max_turns": 200,
+///  "expect_result": "max_turns", "expect_lines": [5260, 5270]}
+/// ```
+///
+/// `commands` gives an explicit script instead of a strategy. Scenarios saved by
+/// cover-target load as they are, their reached lines becoming the expected ones.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scenario {
+    pub name: String,
+    pub program: String,
+    pub strategy: String,
+    pub seed: Option<u64>,
+    pub dock: bool,
+    pub max_turns: usize,
+    pub allow_known_bugs: bool,
+    pub commands: Option<Vec<String>>,
+    /// Result slug the game must end with, e.g. "victory" or "max_turns"
+    pub expect_result: Option<String>,
+    /// BASIC lines the game must execute
+    pub expect_lines: Vec<u32>,
+}
+
+impl Scenario {
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path).with_context(|| format!("Failed to read scenario {}", path.display()))?;
+        let json = Json::parse(&text).with_context(|| format!("Invalid scenario {}", path.display()))?;
+        let name = path.file_stem().map_or_else(String::new, |stem| stem.to_string_lossy().to_string());
+        Self::from_json(name, &json).with_context(|| format!("Invalid scenario {}", path.display()))
+    }
+    
+    fn from_json(name: String, json: &Json) -> Result<Self> {
+        let strings = |key: &str| -> Option<Vec<String>> {
+            json.get(key)?.as_array()?.iter().map(|value| value.as_str().map(str::to_string)).collect()
+        };
+        let lines = json.get("expect_lines").or_else(|| json.get("reached"))
+            .and_then(Json::as_array)
+            .map_or(Ok(Vec::new()), |lines| {
+                lines.iter().map(|line| line.as_usize().map(|line| line as u32)).collect::<Option<_>>()
+                    .context("expect_lines must be line numbers")
+            })?;
+        Ok(Self {
+            name,
+            program: json.get("program").and_then(Json::as_str).context("missing \"program\"")?.to_string(),
+            strategy: json.get("strategy").and_then(Json::as_str).unwrap_or("random").to_string(),
+            seed: json.get("seed").and_then(Json::as_usize).map(|seed| seed as u64),
+            dock: json.get("dock").and_then(Json::as_bool).unwrap_or(false),
+            max_turns: json.get("max_turns").and_then(Json::as_usize).unwrap_or(100),
+            allow_known_bugs: json.get("allow_known_bugs").and_then(Json::as_bool).unwrap_or(false),
+            commands: strings("commands"),
+            expect_result: json.get("expect_result").and_then(Json::as_str).map(str::to_string),
+            expect_lines: lines,
+        })
+    }
+    
+    pub fn to_json(&self) -> Json {
+        let mut json = Json::object()
+            .with("program", self.program.as_str())
+            .with("strategy", self.strategy.as_str());
+        if let Some(seed) = self.seed {
+            json = json.with("seed", seed);
+        }
+        json = json
+            .with("dock", self.dock)
+            .with("max_turns", self.max_turns)
+            .with("allow_known_bugs", self.allow_known_bugs);
+        if let Some(commands) = &self.commands {
+            json = json.with("commands", Json::Array(commands.iter().map(|command| Json::from(command.as_str())).collect()));
+        }
+        if let Some(result) = &self.expect_result {
+            json = json.with("expect_result", result.as_str());
+        }
+        json.with("expect_lines", Json::Array(self.expect_lines.iter().map(|line| Json::from(*line as usize)).collect()))
+    }
+    
+    /// How the game fell short of the expectations; empty when it passed
+    pub fn check(&self, result: &str, covered: Option<&[u32]>) -> Vec<String> {
+        let mut failures = Vec::new();
+        if let Some(expected) = self.expect_result.as_deref().filter(|expected| *expected != result) {
+            failures.push(format!("ended with {}, expected {}", result, expected));
+        }
+        if let Some(covered) = covered {
+            let missed: Vec<String> = self.expect_lines.iter()
+                .filter(|line| !covered.contains(line))
+                .map(|line| line.to_string())
+                .collect();
+            if !missed.is_empty() {
+                failures.push(format!("never reached line{} {}", if missed.len() == 1 { "" } else { "s" }, missed.join(", ")));
+            }
+        }
+        failures
+    }
+}
+
+/// Scenario files (*.json) in a directory, in name order
+pub fn scenario_files(dir: &str) -> Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read scenario directory {}", dir))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|extension| extension == "json") && !path.to_string_lossy().ends_with(".coverage.json"))
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_scenario() {
+        let json = Json::parse(r#"{"program": "sst.bas", "commands": ["SRS", "XXX"], "expect_result": "max_turns", "reached": [5260, 5270]}"#).unwrap();
+        let scenario = Scenario::from_json("srs".to_string(), &json).unwrap();
+        assert_eq!(scenario.commands.as_deref(), Some(&["SRS".to_string(), "XXX".to_string()][..]));
+        assert_eq!((scenario.strategy.as_str(), scenario.max_turns), ("random", 100));
+        
+        assert!(scenario.check("max_turns", Some(&[5260, 5270, 5280])).is_empty());
+        assert_eq!(scenario.check("victory", Some(&[5260])), ["ended with victory, expected max_turns", "never reached line 5270"]);
+        assert!(scenario.check("max_turns", None).is_empty());
+        assert!(Scenario::from_json("bad".to_string(), &Json::parse("{}").unwrap()).is_err());
+    }
+}
@@ -0,0 +1,96 @@
+use crate::game::GameState;
+use crate::json::Json;
+
+/// Commands that make the game show the galaxy around the starting quadrant without
+/// changing anything: a long range scan, then the library computer's galactic record
+pub const SETUP_COMMANDS: &[&str] = &["LRS", "COM", "0"];
+
+/// What a game dealt before any play, as far as it shows it: the mission, where the
+/// Enterprise starts, the short range scan of its quadrant and the galactic record of
+/// the quadrants around it. Interpreters dealing different setups from the same seed
+/// generate random numbers or fill the galaxy arrays differently.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GalaxySetup {
+    /// Name and value of each thing compared, in the order they are shown
+    pub fields: Vec<(String, String)>,
+}
+
+impl GalaxySetup {
+    /// The setup as seen in the state after SETUP_COMMANDS
+    pub fn from_state(state: &GameState) -> Self {
+        let show = |value: Option<String>| value.unwrap_or_else(|| "?".to_string());
+        let mut fields = vec![
+            ("stardate".to_string(), show(state.stardate.map(|value| value.to_string()))),
+            ("deadline".to_string(), show(state.deadline.map(|value| value.to_string()))),
+            ("klingons".to_string(), show(state.klingons_remaining.map(|value| value.to_string()))),
+            ("starbases".to_string(), show(state.starbases.map(|value| value.to_string()))),
+            ("quadrant".to_string(), show(state.current_quadrant.map(|(row, column)| format!("{},{}", row, column)))),
+            ("sector".to_string(), show(state.current_sector.map(|(row, column)| format!("{},{}", row, column)))),
+        ];
+        for (i, row) in state.sector_map.iter().flatten().enumerate() {
+            let cells: Vec<&str> = row.iter().map(|cell| if cell.is_empty() { "   " } else { cell.as_str() }).collect();
+            fields.push((format!("sector row {}", i + 1), cells.join(" ")));
+        }
+        for (i, row) in state.galaxy.quadrants.iter().enumerate() {
+            let codes: Vec<&str> = row.iter().map(|code| code.as_deref().unwrap_or("***")).collect();
+            fields.push((format!("record row {}", i + 1), codes.join(" ")));
+        }
+        Self { fields }
+    }
+    
+    fn value(&self, name: &str) -> Option<&str> {
+        self.fields.iter().find(|(field, _)| field == name).map(|(_, value)| value.as_str())
+    }
+    
+    /// Each field that differs from `other`, e.g. "klingons: 17 vs 15"
+    pub fn differences(&self, other: &GalaxySetup) -> Vec<String> {
+        let mut names: Vec<&str> = self.fields.iter().map(|(name, _)| name.as_str()).collect();
+        names.extend(other.fields.iter().map(|(name, _)| name.as_str()).filter(|name| self.value(name).is_none()));
+        names.into_iter()
+            .filter(|name| self.value(name) != other.value(name))
+            .map(|name| format!("{}: {} vs {}", name, self.value(name).unwrap_or("-"), other.value(name).unwrap_or("-")))
+            .collect()
+    }
+    
+    pub fn to_json(&self) -> Json {
+        self.fields.iter().fold(Json::object(), |json, (name, value)| json.with(name, value.as_str()))
+    }
+}
+
+/// Each interpreter's setup, for saving
+pub fn to_json(setups: &[(String, GalaxySetup)]) -> Json {
+    let interpreters = setups.iter().map(|(name, setup)| {
+        Json::object()
+            .with("interpreter", name.as_str())
+            .with("setup", setup.to_json())
+    });
+    Json::Array(interpreters.collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_setup_differences() {
+        let mut state = GameState::new();
+        state.klingons_remaining = Some(17);
+        state.current_quadrant = Some((3, 5));
+        let mut sector_map = vec![vec![String::new(); 8]; 8];
+        sector_map[0][1] = "<*>".to_string();
+        state.sector_map = Some(sector_map);
+        let first = GalaxySetup::from_state(&state);
+        assert_eq!(first.value("quadrant"), Some("3,5"));
+        assert_eq!(first.value("sector row 1"), Some(format!("    <*>{}", " ".repeat(24)).as_str()));
+        assert!(first.differences(&first.clone()).is_empty());
+        
+        state.klingons_remaining = Some(15);
+        state.sector_map = None;
+        let second = GalaxySetup::from_state(&state);
+        let differences = first.differences(&second);
+        assert_eq!(differences.len(), 9);
+        assert_eq!(differences[0], "klingons: 17 vs 15");
+        assert!(differences[1].starts_with("sector row 1: "));
+        assert!(differences[1].ends_with(" vs -"));
+    }
+}
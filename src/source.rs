@@ -0,0 +1,247 @@
+use crate::coverage::reachability::parse_program;
+use crate::game::PromptKind;
+use crate::json::Json;
+use anyhow::{Context, Result};
+use std::fmt;
+use std::fs;
+
+/// A string literal printed by a PRINT statement
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrintLiteral {
+    pub line: u32,
+    pub text: String,
+}
+
+/// An INPUT statement, with the text shown before it: its own prompt string, or else the
+/// first literal printed earlier on the same line, as in `PRINT"WARP FACTOR (0-";X$;")";:INPUTW1`
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputStatement {
+    pub line: u32,
+    pub prompt: Option<String>,
+    pub variables: Vec<String>,
+}
+
+impl InputStatement {
+    /// What the statement reads, e.g. "a number into X" or "2 numbers into C1,A"
+    pub fn reads(&self) -> String {
+        let text = self.variables.iter().any(|variable| variable.ends_with('$'));
+        let what = match (self.variables.len(), text) {
+            (1, false) => "a number".to_string(),
+            (1, true) => "text".to_string(),
+            (n, false) => format!("{} numbers", n),
+            (n, true) => format!("{} values", n),
+        };
+        format!("{} into {}", what, self.variables.join(","))
+    }
+}
+
+/// Where in the program a prompt the bot saw comes from
+#[derive(Debug, Clone, PartialEq)]
+pub struct PromptOrigin {
+    pub line: u32,
+    pub input: Option<InputStatement>,
+}
+
+impl fmt::Display for PromptOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.input {
+            Some(input) => write!(f, "line {}, reading {}", self.line, input.reads()),
+            None => write!(f, "line {}", self.line),
+        }
+    }
+}
+
+/// The PRINT literals and INPUT statements of a BASIC program, for tracing what the bot
+/// sees back to the lines that printed it
+#[derive(Debug, Clone, Default)]
+pub struct ProgramIndex {
+    prints: Vec<PrintLiteral>,
+    inputs: Vec<InputStatement>,
+}
+
+impl ProgramIndex {
+    pub fn parse(source: &str) -> Self {
+        let mut index = Self::default();
+        for line in parse_program(source) {
+            let mut printed = None;
+            for statement in split_statements(&line.text) {
+                let upper = statement.trim_start().to_uppercase();
+                if upper.starts_with("REM") {
+                    break;
+                }
+                if let Some(rest) = upper.strip_prefix("PRINT") {
+                    for text in string_literals(rest) {
+                        printed.get_or_insert_with(|| text.clone());
+                        index.prints.push(PrintLiteral { line: line.number, text });
+                    }
+                } else if let Some(rest) = upper.strip_prefix("INPUT") {
+                    let literals = string_literals(rest);
+                    let variables = match rest.rfind('"') {
+                        Some(end) => &rest[end + 1..],
+                        None => rest,
+                    };
+                    index.inputs.push(InputStatement {
+                        line: line.number,
+                        prompt: literals.into_iter().next().or_else(|| printed.take()),
+                        variables: variables.trim_start_matches([';', ',', ' '])
+                            .split(',')
+                            .map(|variable| variable.trim().to_string())
+                            .filter(|variable| !variable.is_empty())
+                            .collect(),
+                    });
+                }
+            }
+        }
+        index
+    }
+    
+    pub fn load(program: &str) -> Result<Self> {
+        let source = fs::read_to_string(program)
+            .with_context(|| format!("Failed to read program {}", program))?;
+        Ok(Self::parse(&source))
+    }
+    
+    pub fn inputs(&self) -> &[InputStatement] {
+        &self.inputs
+    }
+    
+    /// The INPUT statement whose prompt starts the given prompt line, or failing that, the
+    /// line that printed it. The longest match wins, so "NUMBER OF UNITS TO SHIELDS" isn't
+    /// taken for a shorter prompt it happens to start with.
+    pub fn locate(&self, prompt: &str) -> Option<PromptOrigin> {
+        let prompt = normalize(prompt);
+        if prompt.is_empty() {
+            return None;
+        }
+        let input = self.inputs.iter()
+            .filter_map(|input| Some((normalize(input.prompt.as_ref()?), input)))
+            .filter(|(text, _)| !text.is_empty() && prompt.starts_with(text.as_str()))
+            .max_by_key(|(text, _)| text.len())
+            .map(|(_, input)| PromptOrigin { line: input.line, input: Some(input.clone()) });
+        input.or_else(|| self.printed_by(&prompt).map(|line| PromptOrigin { line, input: None }))
+    }
+    
+    /// The line whose PRINT literal is the longest one the output line starts with
+    pub fn printed_by(&self, output: &str) -> Option<u32> {
+        let output = normalize(output);
+        self.prints.iter()
+            .map(|print| (normalize(&print.text), print.line))
+            .filter(|(text, _)| text.len() >= 3 && output.starts_with(text.as_str()))
+            .max_by_key(|(text, _)| text.len())
+            .map(|(_, line)| line)
+    }
+    
+    /// An entry for the prompt table in game/prompt.rs that would recognize an unknown
+    /// prompt, with what the program expects as the answer
+    pub fn suggest_rule(&self, prompt: &str) -> Option<String> {
+        let origin = self.locate(prompt)?;
+        let input = origin.input.as_ref()?;
+        let marker = input.prompt.as_deref().map(normalize).filter(|marker| !marker.is_empty())?;
+        Some(format!("(\"{}\", PromptKind::...) for the INPUT at {}", marker, origin))
+    }
+    
+    /// Text shown before an INPUT: its own prompt, or the last string printed on the
+    /// lines before it
+    fn input_text(&self, input: &InputStatement) -> Option<String> {
+        input.prompt.as_deref()
+            .or_else(|| self.prints.iter().rev().find(|print| print.line < input.line).map(|print| print.text.as_str()))
+            .map(normalize)
+            .filter(|text| !text.is_empty())
+    }
+    
+    /// A draft of the prompt rules for this program, one per distinct INPUT prompt, with
+    /// the kind TrekBot already gives that text ("Unknown" where it's left to the author):
+    ///
+    /// ```json
+    /// {"prompts": [{"pattern": "COURSE (0-9)", "kind": "Course", "line": 2300, "reads": "a number into C1"}]}
+    /// ```
+    pub fn draft_rules(&self) -> Json {
+        let mut patterns: Vec<String> = Vec::new();
+        let mut rules = Vec::new();
+        for input in &self.inputs {
+            let Some(pattern) = self.input_text(input) else {
+                continue;
+            };
+            if patterns.contains(&pattern) {
+                continue;
+            }
+            let kind = PromptKind::classify(&pattern, &[]);
+            rules.push(Json::object()
+                .with("pattern", pattern.as_str())
+                .with("kind", format!("{:?}", kind))
+                .with("line", input.line as usize)
+                .with("reads", input.reads()));
+            patterns.push(pattern);
+        }
+        Json::object().with("prompts", Json::Array(rules))
+    }
+}
+
+/// Prompts and literals compared without case, surrounding spaces or the '?' INPUT adds
+fn normalize(text: &str) -> String {
+    text.trim().trim_end_matches('?').trim().to_uppercase()
+}
+
+/// Statements of one line, split at colons outside strings
+fn split_statements(text: &str) -> Vec<&str> {
+    let mut statements = Vec::new();
+    let mut in_string = false;
+    let mut start = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            ':' if !in_string => {
+                statements.push(&text[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    statements.push(&text[start..]);
+    statements
+}
+
+/// Text between each pair of quotes
+fn string_literals(text: &str) -> Vec<String> {
+    text.split('"').skip(1).step_by(2).map(|literal| literal.to_string()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_program_index() {
+        let index = ProgramIndex::parse(concat!(
+            "2060 INPUT\"COMMAND\";A$\n",
+            "2360 PRINT\"WARP FACTOR (0-\";X$;\")\";:INPUTW1:IFD(1)<0THEN2470\n",
+            "5560 PRINT\"ENERGY AVAILABLE =\";E+S;:INPUT\"NUMBER OF UNITS TO SHIELDS\";X\n",
+            "7540 REM INPUT\"DO YOU WANT A HARDCOPY\";A$\n",
+            "8170 PRINT\"PLEASE ENTER\":INPUT\"  INITIAL COORDINATES (X,Y)\";C1,A\n",
+            "8580 PRINT\"TORPEDO MISSED\":GOTO1990\n",
+        ));
+        assert_eq!(index.inputs().len(), 4);
+        
+        let command = index.locate("COMMAND? ").unwrap();
+        assert_eq!(command.line, 2060);
+        assert_eq!(command.to_string(), "line 2060, reading text into A$");
+        assert_eq!(index.locate("WARP FACTOR (0-8)?").unwrap().line, 2360);
+        assert_eq!(index.locate("INITIAL COORDINATES (X,Y)?").unwrap().input.unwrap().reads(), "2 numbers into C1,A");
+        assert_eq!(index.locate("TORPEDO MISSED").unwrap(), PromptOrigin { line: 8580, input: None });
+        assert_eq!(index.locate("DO YOU WANT A HARDCOPY?"), None);
+        
+        assert_eq!(
+            index.suggest_rule("NUMBER OF UNITS TO SHIELDS?").unwrap(),
+            "(\"NUMBER OF UNITS TO SHIELDS\", PromptKind::...) for the INPUT at line 5560, reading a number into X"
+        );
+        assert_eq!(index.suggest_rule("TORPEDO MISSED"), None);
+        
+        let draft = ProgramIndex::parse("100 PRINT\"YOUR ORDERS\"\n110 INPUT O$\n120 INPUT\"WARP FACTOR\";W\n130 INPUT\"WARP FACTOR\";W\n").draft_rules();
+        let rules = draft.get("prompts").unwrap().as_array().unwrap();
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].get("pattern").unwrap().as_str(), Some("YOUR ORDERS"));
+        assert_eq!(rules[0].get("kind").unwrap().as_str(), Some("Unknown"));
+        assert_eq!(rules[1].get("kind").unwrap().as_str(), Some("WarpFactor"));
+        assert_eq!(rules[1].get("line").unwrap().as_usize(), Some(120));
+    }
+}
@@ -0,0 +1,125 @@
+use crate::game::PromptKind;
+use crate::strategy::{Command, NumericRange, RandomStrategy, Strategy, TurnContext};
+use anyhow::Result;
+use std::collections::HashMap;
+
+/// Commands that lead to numeric prompts, issued in turn, with a scan so the game state
+/// (and so the valid ranges) stays current
+const COMMANDS: &[Command] = &[
+    Command::Navigation,
+    Command::Shields,
+    Command::Phasers,
+    Command::Torpedoes,
+    Command::Computer,
+    Command::ShortRangeScan,
+];
+
+/// Boundary-value strategy: answers each numeric prompt with the next of its range's
+/// boundary and out-of-range values (minimum, just inside, maximum, just past the
+/// maximum, negative, far too large, a fraction), cycling separately per prompt kind.
+/// Every game sends the same inputs, so it exercises the program's validation branches
+/// and the interpreter's number parsing deterministically.
+pub struct BoundaryStrategy {
+    next_command: usize,
+    /// Next value to try for each kind of numeric prompt
+    rotation: HashMap<PromptKind, usize>,
+    allow_known_bugs: bool,
+    fallback: RandomStrategy,
+}
+
+impl BoundaryStrategy {
+    pub fn new() -> Self {
+        Self {
+            next_command: 0,
+            rotation: HashMap::new(),
+            allow_known_bugs: false,
+            fallback: RandomStrategy::with_seed(0),
+        }
+    }
+    
+    /// Also try library computer options above 5, which crash the original program
+    pub fn set_allow_known_bugs(&mut self, allow: bool) {
+        self.allow_known_bugs = allow;
+    }
+    
+    /// The next value in the rotation for a numeric prompt
+    fn next_value(&mut self, kind: PromptKind, range: &NumericRange) -> String {
+        let mut values = range.probe_values();
+        if kind == PromptKind::ComputerCommand && !self.allow_known_bugs {
+            values.retain(|value| *value <= range.max);
+        }
+        let index = self.rotation.entry(kind).or_insert(0);
+        let value = values[*index % values.len()];
+        *index += 1;
+        range.format(value)
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl Strategy for BoundaryStrategy {
+    async fn get_command(&mut self, context: &TurnContext<'_>) -> Result<String> {
+        let TurnContext { prompt, state: game_state, .. } = *context;
+        if let Some(range) = NumericRange::for_prompt(prompt, game_state) {
+            let value = self.next_value(prompt.kind, &range);
+            return Ok(match prompt.kind {
+                PromptKind::InitialCoordinates | PromptKind::FinalCoordinates => format!("{},{}", value, value),
+                _ => value,
+            });
+        }
+        
+        match prompt.kind {
+            PromptKind::Command => {
+                let command = &COMMANDS[self.next_command % COMMANDS.len()];
+                self.next_command += 1;
+                Ok(command.to_string())
+            }
+            PromptKind::RepairAuthorization => Ok("Y".to_string()),
+            PromptKind::Aye => Ok("NO".to_string()),
+            _ => self.fallback.get_command(context).await,
+        }
+    }
+    
+    fn reset(&mut self) {
+        *self = Self { allow_known_bugs: self.allow_known_bugs, ..Self::new() };
+    }
+    
+    fn name(&self) -> &'static str {
+        "Boundary"
+    }
+}
+
+impl Default for BoundaryStrategy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{GameState, PromptContext};
+    
+    #[tokio::test]
+    async fn test_rotation() {
+        let mut strategy = BoundaryStrategy::new();
+        let state = GameState::new();
+        let course = PromptContext { kind: PromptKind::Course, ..PromptContext::default() };
+        let mut answers = Vec::new();
+        for _ in 0..10 {
+            answers.push(strategy.get_command(&TurnContext::new(&course, &state)).await.unwrap());
+        }
+        assert_eq!(answers, ["1", "2", "8", "9", "0", "10", "-9", "90", "1.5", "1"]);
+        
+        // Computer options past 5 crash the original program, so they're left out
+        let computer = PromptContext { kind: PromptKind::ComputerCommand, ..PromptContext::default() };
+        let mut answers = Vec::new();
+        for _ in 0..8 {
+            answers.push(strategy.get_command(&TurnContext::new(&computer, &state)).await.unwrap());
+        }
+        assert_eq!(answers, ["0", "1", "4", "5", "-1", "-5", "0.5", "0"]);
+        
+        let command = PromptContext { kind: PromptKind::Command, ..PromptContext::default() };
+        assert_eq!(strategy.get_command(&TurnContext::new(&command, &state)).await.unwrap(), "NAV");
+        assert_eq!(strategy.get_command(&TurnContext::new(&command, &state)).await.unwrap(), "SHE");
+    }
+}
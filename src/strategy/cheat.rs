@@ -1,30 +1,243 @@
-use crate::game::GameState;
-use crate::strategy::Strategy;
+use crate::game::{GameState, PromptKind};
+use crate::strategy::navigation::{compute_course, sector_positions, warp_for_distance, Maneuvers};
+use crate::strategy::planner::{GalaxyChart, Route};
+use crate::strategy::targeting::firing_solution;
+use crate::strategy::{Command, RandomStrategy, Strategy, TurnContext};
 use anyhow::Result;
+use std::collections::HashMap;
 
-/// Cheat strategy implementation that plays intelligently
-/// This is a stub - the full implementation will be added later
+/// Shield energy assumed for a Klingon the sensors haven't reported on, the most one
+/// starts with (S9 * 1.5 in the program)
+const KLINGON_STRENGTH: f64 = 300.0;
+
+/// Shields wanted for each Klingon in the quadrant
+const SHIELDS_PER_KLINGON: i32 = 250;
+
+/// Energy kept back so the ship can still move
+const ENERGY_RESERVE: i32 = 300;
+
+/// Resupply at a starbase below this much energy, or with no torpedoes left
+const LOW_ENERGY: i32 = 1200;
+
+/// What the strategy is waiting for after issuing a command
+#[derive(Debug, Clone, PartialEq)]
+enum Pending {
+    None,
+    /// Sent PHA, waiting for the units to fire
+    Phasers(i32),
+    /// Sent SHE, waiting for the units to put into the shields
+    Shields(i32),
+}
+
+/// Cheat strategy plays to win with everything the game will tell it. It keeps its own
+/// galactic record from long range scans, short range scans and the library computer,
+/// and hunts the Klingons quadrant by quadrant. In combat it raises the shields for the
+/// Klingons it faces, fires torpedoes along courses the short range scan shows are
+/// clear, and otherwise fires just enough phaser energy to destroy every Klingon given
+/// the energy the sensors report they have left. It docks at the nearest known starbase
+/// when energy or torpedoes run low.
 pub struct CheatStrategy {
-    // TODO: Add state tracking for intelligent play
+    pending: Pending,
+    record: GalaxyChart,
+    /// Shield energy the sensors reported left on Klingons in this quadrant, by sector
+    klingon_strength: HashMap<(i32, i32), f64>,
+    quadrant: Option<(i32, i32)>,
+    maneuvers: Maneuvers,
+    /// The game refused the weapon as damaged; tried again in the next quadrant
+    phasers_down: bool,
+    tubes_down: bool,
+    fallback: RandomStrategy,
+    /// Seed of the fallback's random choices, kept so reset() starts them over
+    seed: Option<u64>,
 }
 
 impl CheatStrategy {
     pub fn new() -> Self {
         Self {
-            // TODO: Initialize strategy state
+            pending: Pending::None,
+            record: GalaxyChart::new(),
+            klingon_strength: HashMap::new(),
+            quadrant: None,
+            maneuvers: Maneuvers::new(),
+            phasers_down: false,
+            tubes_down: false,
+            fallback: RandomStrategy::new(),
+            seed: None,
+        }
+    }
+    
+    /// Falls back on random choices that are the same every time for the same seed
+    pub fn with_seed(seed: u64) -> Self {
+        Self { fallback: RandomStrategy::with_seed(seed), seed: Some(seed), ..Self::new() }
+    }
+    
+    /// Track hits, refused weapons and scan readings from the latest output
+    fn observe(&mut self, game_state: &GameState) {
+        if game_state.current_quadrant != self.quadrant {
+            self.quadrant = game_state.current_quadrant;
+            self.klingon_strength.clear();
+            self.maneuvers.torpedo_blocked = false;
+            self.phasers_down = false;
+            self.tubes_down = false;
         }
+        
+        let mut hit = None;
+        for line in &game_state.last_output {
+            if line.contains("UNIT HIT ON KLINGON AT SECTOR") {
+                hit = parse_sector(line);
+            } else if let (Some(sector), Some(remaining)) = (hit, sensor_reading(line)) {
+                self.klingon_strength.insert(sector, remaining);
+                hit = None;
+            } else if line.contains("PHASERS INOPERATIVE") {
+                self.phasers_down = true;
+            } else if line.contains("PHOTON TUBES ARE NOT OPERATIONAL") {
+                self.tubes_down = true;
+            }
+        }
+        
+        self.maneuvers.observe(&game_state.last_output);
+        self.record.observe(game_state);
+    }
+    
+    /// Pick the next command at the COMMAND prompt
+    async fn next_command(&mut self, context: &TurnContext<'_>) -> Result<String> {
+        let game_state = context.state;
+        if self.maneuvers.take_stale_scan() {
+            return Ok(Command::ShortRangeScan.to_string());
+        }
+        let (quadrant, sector, sector_map) = match (game_state.current_quadrant, game_state.current_sector, &game_state.sector_map) {
+            (Some(quadrant), Some(sector), Some(sector_map)) => (quadrant, sector, sector_map),
+            _ => return self.fallback.get_command(context).await,
+        };
+        
+        let klingons = sector_positions(sector_map, "+K+");
+        let starbases = sector_positions(sector_map, ">!<");
+        self.record.observe_sectors(quadrant, sector_map);
+        if !klingons.is_empty() {
+            return Ok(self.fight(sector, &klingons, sector_map, game_state));
+        }
+        
+        // Resupply: fly into the starbase here, which stops the ship next to it and docks
+        let low_energy = game_state.energy.is_some_and(|energy| energy < LOW_ENERGY);
+        if (low_energy || game_state.torpedoes == Some(0)) && !game_state.is_docked() {
+            if let Some((course, distance)) = starbases.first().and_then(|&starbase| compute_course(sector, starbase)) {
+                return Ok(self.navigate(course, warp_for_distance(distance)));
+            }
+            if let Some(route) = self.record.nearest_starbase(quadrant, sector) {
+                return Ok(self.travel(route));
+            }
+        }
+        
+        if self.record.mark_scanned(quadrant) {
+            return Ok(Command::LongRangeScan.to_string());
+        }
+        // Klingons known elsewhere, or else the closest quadrant not in the record yet
+        if let Some(route) = self.record.nearest_klingons(quadrant, sector).or_else(|| self.record.nearest_uncharted(quadrant, sector)) {
+            return Ok(self.travel(route));
+        }
+        
+        // The whole record is out of date: start it again from here
+        self.record.clear();
+        self.record.mark_scanned(quadrant);
+        Ok(Command::LongRangeScan.to_string())
+    }
+    
+    /// Raise shields, then torpedo a Klingon in a clear line of fire or use phasers,
+    /// leaving the quadrant when neither weapon can be used
+    fn fight(&mut self, sector: (i32, i32), klingons: &[(i32, i32)], sector_map: &[Vec<String>], game_state: &GameState) -> String {
+        let energy = game_state.energy.unwrap_or(0);
+        let wanted = SHIELDS_PER_KLINGON * klingons.len() as i32;
+        if game_state.shields.unwrap_or(0) < wanted / 2 && energy - wanted >= ENERGY_RESERVE {
+            self.pending = Pending::Shields(wanted);
+            return Command::Shields.to_string();
+        }
+        
+        self.maneuvers.stale_scan = true;
+        let loaded = game_state.torpedoes.is_some_and(|torpedoes| torpedoes > 0);
+        if let (false, false, true, Some(solution)) = (self.tubes_down, self.maneuvers.torpedo_blocked, loaded, firing_solution(sector_map)) {
+            return self.maneuvers.fire_torpedo(solution.course);
+        }
+        if !self.phasers_down && energy > ENERGY_RESERVE {
+            self.pending = Pending::Phasers(phaser_units(sector, klingons, &self.klingon_strength));
+            return Command::Phasers.to_string();
+        }
+        
+        let away = klingons.iter()
+            .filter_map(|&klingon| compute_course(klingon, sector))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map_or(1.0, |(course, _)| course);
+        self.navigate(away, 1.0)
+    }
+    
+    fn travel(&mut self, route: Route) -> String {
+        self.navigate(route.course, route.warp)
+    }
+    
+    fn navigate(&mut self, course: f32, warp: f32) -> String {
+        self.maneuvers.navigate(course, warp)
     }
 }
 
+/// Phaser energy that should destroy every Klingon. Each gets an equal share, which
+/// reaches it divided by its distance and multiplied by 2 to 3 (lines 4450-4550), so
+/// the share has to cover the Klingon needing the most with the weakest multiplier.
+pub fn phaser_units(sector: (i32, i32), klingons: &[(i32, i32)], strength: &HashMap<(i32, i32), f64>) -> i32 {
+    let share = klingons.iter()
+        .map(|klingon| {
+            let distance = compute_course(sector, *klingon).map_or(1.0, |(_, distance)| distance as f64);
+            strength.get(klingon).copied().unwrap_or(KLINGON_STRENGTH) * distance / 2.0
+        })
+        .fold(0.0, f64::max);
+    // With ten percent to spare
+    (share * klingons.len() as f64 * 11.0 / 10.0).ceil() as i32
+}
+
+/// The sector in " 245 UNIT HIT ON KLINGON AT SECTOR 3 , 5"
+fn parse_sector(line: &str) -> Option<(i32, i32)> {
+    let (_, rest) = line.split_once("SECTOR")?;
+    let (row, column) = rest.split_once(',')?;
+    Some((row.trim().parse().ok()?, column.trim().parse().ok()?))
+}
+
+/// The energy in "   (SENSORS SHOW 55.2341 UNITS REMAINING)"
+fn sensor_reading(line: &str) -> Option<f64> {
+    let (_, rest) = line.split_once("SENSORS SHOW")?;
+    rest.split_whitespace().next()?.parse().ok()
+}
+
+#[async_trait::async_trait(?Send)]
 impl Strategy for CheatStrategy {
-    fn get_command(&mut self, _game_state: &GameState) -> Result<String> {
-        // TODO: Implement intelligent strategy
-        // For now, just return a safe command
-        Ok("SRS".to_string())
+    async fn get_command(&mut self, context: &TurnContext<'_>) -> Result<String> {
+        let TurnContext { prompt, state: game_state, .. } = *context;
+        self.observe(game_state);
+        
+        if let Some(answer) = self.maneuvers.answer(prompt.kind) {
+            return Ok(answer);
+        }
+        match (&self.pending, prompt.kind) {
+            (_, PromptKind::Command) => {
+                // Anything still pending was refused
+                self.pending = Pending::None;
+                self.maneuvers.cancel();
+                self.next_command(context).await
+            }
+            (Pending::Phasers(units), PromptKind::PhaserUnits) => {
+                let available = prompt.energy_available.or(game_state.energy).unwrap_or(*units);
+                let units = (*units).min(available - ENERGY_RESERVE).max(1);
+                self.pending = Pending::None;
+                Ok(units.to_string())
+            }
+            (Pending::Shields(units), PromptKind::ShieldUnits) => {
+                let units = prompt.energy_available.map_or(*units, |available| (*units).min(available));
+                self.pending = Pending::None;
+                Ok(units.to_string())
+            }
+            _ => self.fallback.get_command(context).await,
+        }
     }
     
     fn reset(&mut self) {
-        // TODO: Reset strategy state
+        *self = self.seed.map_or_else(Self::new, Self::with_seed);
     }
     
     fn name(&self) -> &'static str {
@@ -36,4 +249,52 @@ impl Default for CheatStrategy {
     fn default() -> Self {
         Self::new()
     }
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::PromptContext;
+    
+    fn prompt(kind: PromptKind) -> PromptContext {
+        PromptContext { kind, ..PromptContext::default() }
+    }
+    
+    #[tokio::test]
+    async fn test_combat() {
+        let mut strategy = CheatStrategy::new();
+        let mut state = GameState::new();
+        assert_eq!(strategy.get_command(&TurnContext::new(&prompt(PromptKind::Command), &state)).await.unwrap(), "SRS");
+        
+        let mut sector_map = vec![vec![String::new(); 8]; 8];
+        sector_map[3][3] = "<*>".to_string();
+        sector_map[3][5] = "*".to_string();
+        sector_map[3][6] = "+K+".to_string();
+        state.sector_map = Some(sector_map);
+        state.current_quadrant = Some((2, 2));
+        state.current_sector = Some((4, 4));
+        state.energy = Some(3000);
+        state.shields = Some(0);
+        state.torpedoes = Some(10);
+        
+        // Shields first, then phasers since a star is in the torpedo's way
+        assert_eq!(strategy.get_command(&TurnContext::new(&prompt(PromptKind::Command), &state)).await.unwrap(), "SHE");
+        assert_eq!(strategy.get_command(&TurnContext::new(&prompt(PromptKind::ShieldUnits), &state)).await.unwrap(), "250");
+        state.shields = Some(250);
+        assert_eq!(strategy.get_command(&TurnContext::new(&prompt(PromptKind::Command), &state)).await.unwrap(), "PHA");
+        assert_eq!(strategy.get_command(&TurnContext::new(&prompt(PromptKind::PhaserUnits), &state)).await.unwrap(), "495");
+        
+        // Half of that left after the hit, reported by the sensors
+        state.last_output = crate::game::share_lines(vec![
+            " 150 UNIT HIT ON KLINGON AT SECTOR 4 , 7".to_string(),
+            "   (SENSORS SHOW 150 UNITS REMAINING)".to_string(),
+        ]);
+        assert_eq!(strategy.get_command(&TurnContext::new(&prompt(PromptKind::Command), &state)).await.unwrap(), "SRS");
+        assert_eq!(phaser_units((4, 4), &[(4, 7)], &strategy.klingon_strength), 248);
+        
+        // With the star gone the torpedo has a clear shot
+        state.sector_map.as_mut().unwrap()[3][5] = String::new();
+        assert_eq!(strategy.get_command(&TurnContext::new(&prompt(PromptKind::Command), &state)).await.unwrap(), "TOR");
+        assert_eq!(strategy.get_command(&TurnContext::new(&prompt(PromptKind::TorpedoCourse), &state)).await.unwrap(), "1");
+    }
+}
@@ -0,0 +1,152 @@
+use crate::game::{PromptContext, PromptKind};
+use crate::strategy::{Command, RandomStrategy, Strategy, TurnContext};
+use anyhow::Result;
+
+/// Library computer options the original program handles (0-5)
+pub const COMPUTER_OPTIONS: std::ops::RangeInclusive<i32> = 0..=5;
+
+/// Out-of-range library computer options that hit the known crash path
+pub const INVALID_COMPUTER_OPTIONS: &[i32] = &[6, 7];
+
+/// One entry of the command menu, optionally with a library computer sub-option
+#[derive(Debug, Clone, PartialEq)]
+pub struct MenuSelection {
+    pub command: Command,
+    pub computer_option: Option<i32>,
+}
+
+impl MenuSelection {
+    /// Short label used for reports and file names, e.g. "NAV" or "COM-3"
+    pub fn label(&self) -> String {
+        match self.computer_option {
+            Some(option) => format!("{}-{}", self.command.to_string(), option),
+            None => self.command.to_string(),
+        }
+    }
+}
+
+/// Every menu selection in order: each command, then each computer option.
+/// Invalid computer options are only included when known bugs are allowed.
+pub fn menu_selections(allow_known_bugs: bool) -> Vec<MenuSelection> {
+    let commands = [
+        Command::Navigation,
+        Command::ShortRangeScan,
+        Command::LongRangeScan,
+        Command::Phasers,
+        Command::Torpedoes,
+        Command::Shields,
+        Command::DamageControl,
+    ];
+    
+    let mut selections: Vec<MenuSelection> = commands
+        .into_iter()
+        .map(|command| MenuSelection { command, computer_option: None })
+        .collect();
+    
+    let mut options: Vec<i32> = COMPUTER_OPTIONS.collect();
+    if allow_known_bugs {
+        options.extend_from_slice(INVALID_COMPUTER_OPTIONS);
+    }
+    for option in options {
+        selections.push(MenuSelection {
+            command: Command::Computer,
+            computer_option: Some(option),
+        });
+    }
+    
+    selections
+}
+
+/// Discovery strategy systematically cycles through every command and every
+/// library computer option at the COMMAND prompt, answering follow-up prompts
+/// with fixed values. This gives a deterministic coverage floor that does not
+/// depend on randomness. After the configured number of rounds it resigns.
+pub struct DiscoveryStrategy {
+    selections: Vec<MenuSelection>,
+    rounds: usize,
+    next_selection: usize,
+    pending_option: Option<i32>,
+    fallback: RandomStrategy,
+}
+
+impl DiscoveryStrategy {
+    pub fn new(selections: Vec<MenuSelection>, rounds: usize) -> Self {
+        Self {
+            selections,
+            rounds,
+            next_selection: 0,
+            pending_option: None,
+            fallback: RandomStrategy::new(),
+        }
+    }
+    
+    /// Cycle through the full menu
+    pub fn full_menu(allow_known_bugs: bool, rounds: usize) -> Self {
+        Self::new(menu_selections(allow_known_bugs), rounds)
+    }
+    
+    /// Answer prompts outside the menu with random choices that are the same every time
+    /// for the same seed
+    pub fn set_seed(&mut self, seed: u64) {
+        self.fallback = RandomStrategy::with_seed(seed);
+    }
+    
+    /// Fixed answers for the prompts that follow a menu selection
+    fn fixed_answer(&self, prompt: &PromptContext) -> Option<&'static str> {
+        match prompt.kind {
+            PromptKind::TorpedoCourse | PromptKind::Course => Some("1"),
+            PromptKind::WarpFactor => Some("0.5"),
+            PromptKind::PhaserUnits => Some("100"),
+            PromptKind::ShieldUnits => Some("200"),
+            PromptKind::InitialCoordinates => Some("1,1"),
+            PromptKind::FinalCoordinates => Some("8,8"),
+            PromptKind::RepairAuthorization => Some("Y"),
+            PromptKind::Aye => Some("NO"),
+            _ => None,
+        }
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl Strategy for DiscoveryStrategy {
+    async fn get_command(&mut self, context: &TurnContext<'_>) -> Result<String> {
+        let TurnContext { prompt, .. } = *context;
+        if prompt.kind == PromptKind::ComputerCommand {
+            let option = self.pending_option.take().unwrap_or(0);
+            return Ok(option.to_string());
+        }
+        
+        if prompt.kind == PromptKind::Command {
+            if self.selections.is_empty() || self.next_selection >= self.selections.len() * self.rounds {
+                return Ok(Command::Quit.to_string());
+            }
+            
+            let selection = &self.selections[self.next_selection % self.selections.len()];
+            self.next_selection += 1;
+            log::debug!("Discovery selection {}: {}", self.next_selection, selection.label());
+            self.pending_option = selection.computer_option;
+            return Ok(selection.command.to_string());
+        }
+        
+        if let Some(answer) = self.fixed_answer(prompt) {
+            return Ok(answer.to_string());
+        }
+        
+        self.fallback.get_command(context).await
+    }
+    
+    fn reset(&mut self) {
+        self.new_game();
+        self.fallback.reset();
+    }
+    
+    fn new_game(&mut self) {
+        self.next_selection = 0;
+        self.pending_option = None;
+        self.fallback.new_game();
+    }
+    
+    fn name(&self) -> &'static str {
+        "Discovery"
+    }
+}
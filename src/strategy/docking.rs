@@ -0,0 +1,311 @@
+use crate::game::{CourseTarget, GameState, Line, PromptContext, PromptKind};
+use crate::strategy::navigation::{compute_course, galactic_position, warp_for_distance, Maneuvers};
+use crate::strategy::{Strategy, TurnContext};
+use anyhow::Result;
+use std::collections::HashSet;
+
+/// Systems whose damage sends the Enterprise back to a starbase
+const CRITICAL_SYSTEMS: &[&str] = &[
+    "WARP ENGINES",
+    "SHORT RANGE SENSORS",
+    "PHASER CONTROL",
+    "PHOTON TUBES",
+    "SHIELD CONTROL",
+];
+
+/// Settings for the docking behavior
+#[derive(Debug, Clone)]
+pub struct DockingConfig {
+    /// Head for a starbase when total energy drops below this value
+    pub energy_threshold: i32,
+    /// Answer Y at the repair authorization prompt once docked
+    pub authorize_repairs: bool,
+    /// Give up and hand control back after this many commands without docking
+    pub max_seek_commands: usize,
+}
+
+impl Default for DockingConfig {
+    fn default() -> Self {
+        Self {
+            energy_threshold: 1000,
+            authorize_repairs: true,
+            max_seek_commands: 40,
+        }
+    }
+}
+
+/// What the docking behavior is waiting for after issuing a command
+#[derive(Debug, Clone, PartialEq)]
+enum Pending {
+    None,
+    /// Sent COM, waiting for the computer prompt to request starbase nav data
+    StarbaseNavData,
+    /// Sent DAM while docked, waiting for the repair authorization prompt
+    Repair,
+}
+
+/// Docking strategy wraps another strategy. When energy runs low or a critical
+/// system is damaged it navigates to the nearest known starbase, docks, optionally
+/// authorizes repairs and then hands control back to the wrapped strategy.
+/// This exercises the docking and repair code that random play almost never reaches.
+pub struct DockingStrategy<S: Strategy> {
+    parent: S,
+    config: DockingConfig,
+    seeking: bool,
+    pending: Pending,
+    maneuvers: Maneuvers,
+    seek_commands: usize,
+    damaged_systems: HashSet<String>,
+    starbase_quadrants: HashSet<(i32, i32)>,
+    empty_quadrants: HashSet<(i32, i32)>,
+    scanned_quadrants: HashSet<(i32, i32)>,
+    explore_index: usize,
+    /// Why the last command was chosen, see Strategy::last_decision
+    decision: Option<String>,
+}
+
+impl<S: Strategy> DockingStrategy<S> {
+    pub fn new(parent: S, config: DockingConfig) -> Self {
+        Self {
+            parent,
+            config,
+            seeking: false,
+            pending: Pending::None,
+            maneuvers: Maneuvers::new(),
+            seek_commands: 0,
+            damaged_systems: HashSet::new(),
+            starbase_quadrants: HashSet::new(),
+            empty_quadrants: HashSet::new(),
+            scanned_quadrants: HashSet::new(),
+            explore_index: 0,
+            decision: None,
+        }
+    }
+    
+    /// Track damage and starbase sightings from the latest output
+    fn observe(&mut self, game_state: &GameState) {
+        for line in &game_state.last_output {
+            for system in CRITICAL_SYSTEMS {
+                if !line.contains(system) {
+                    continue;
+                }
+                if line.contains("REPAIR COMPLETED") || line.contains("STATE OF REPAIR IMPROVED") {
+                    self.damaged_systems.remove(*system);
+                } else if line.contains("DAMAGED") {
+                    self.damaged_systems.insert(system.to_string());
+                }
+            }
+            
+            if line.contains("NO STARBASES IN THIS QUADRANT") {
+                if let Some(quadrant) = game_state.current_quadrant {
+                    self.starbase_quadrants.remove(&quadrant);
+                    self.empty_quadrants.insert(quadrant);
+                }
+            }
+        }
+        
+        let scanned = game_state.last_output.iter().any(|line| line.contains("LONG RANGE SCAN"));
+        if let (true, Some(galaxy_map), Some((q1, q2))) = (scanned, &game_state.galaxy_map, game_state.current_quadrant) {
+            self.scanned_quadrants.insert((q1, q2));
+            for (i, row) in galaxy_map.iter().enumerate() {
+                for (j, cell) in row.iter().enumerate() {
+                    let quadrant = (q1 + i as i32 - 1, q2 + j as i32 - 1);
+                    if let Ok(value) = cell.parse::<i32>() {
+                        if (value / 10) % 10 > 0 && !self.empty_quadrants.contains(&quadrant) {
+                            self.starbase_quadrants.insert(quadrant);
+                        } else {
+                            self.starbase_quadrants.remove(&quadrant);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    
+    /// Check whether the ship should head for a starbase
+    fn needs_starbase(&self, game_state: &GameState) -> bool {
+        let low_energy = game_state.energy.is_some_and(|e| e < self.config.energy_threshold);
+        low_energy || !self.damaged_systems.is_empty()
+    }
+    
+    /// Why the ship is heading for a starbase
+    fn seek_reason(&self, game_state: &GameState) -> String {
+        if self.damaged_systems.is_empty() {
+            return format!("heading for a starbase, energy {} is below {}", game_state.energy.unwrap_or(0), self.config.energy_threshold);
+        }
+        let mut damaged: Vec<&str> = self.damaged_systems.iter().map(String::as_str).collect();
+        damaged.sort();
+        format!("heading for a starbase to repair {}", damaged.join(", ").to_lowercase())
+    }
+    
+    /// Leave the command to the wrapped strategy, along with the reason for it
+    async fn delegate(&mut self, context: &TurnContext<'_>) -> Result<String> {
+        let command = self.parent.get_command(context).await;
+        self.decision = self.parent.last_decision().map(str::to_string);
+        command
+    }
+    
+    /// Pick the next command while seeking a starbase
+    fn seek_command(&mut self, game_state: &GameState) -> String {
+        self.seek_commands += 1;
+        
+        let (quadrant, sector) = match (game_state.current_quadrant, game_state.current_sector) {
+            (Some(quadrant), Some(sector)) => (quadrant, sector),
+            _ => return "SRS".to_string(),
+        };
+        
+        // Starbase nav data was just reported: fly straight at the starbase.
+        // The engines shut down next to it, which docks the ship.
+        let starbase = game_state.course_readings.iter().find(|reading| reading.target == CourseTarget::Starbase);
+        if let Some(reading) = starbase {
+            return self.navigate(reading.direction, warp_for_distance(reading.distance));
+        }
+        
+        if self.starbase_quadrants.contains(&quadrant) {
+            self.pending = Pending::StarbaseNavData;
+            return "COM".to_string();
+        }
+        
+        // Head for the closest starbase quadrant we know about
+        let here = galactic_position(quadrant, sector);
+        let nearest = self.starbase_quadrants.iter()
+            .map(|&q| galactic_position(q, (4, 4)))
+            .filter_map(|target| compute_course(here, target))
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+        if let Some((course, distance)) = nearest {
+            return self.navigate(course, warp_for_distance(distance));
+        }
+        
+        if !self.scanned_quadrants.contains(&quadrant) {
+            return "LRS".to_string();
+        }
+        
+        // Nothing known nearby: move on to an adjacent quadrant and scan again
+        const SWEEP: &[(f32, (i32, i32))] = &[(1.0, (0, 1)), (7.0, (1, 0)), (5.0, (0, -1)), (3.0, (-1, 0))];
+        for _ in 0..SWEEP.len() {
+            let (course, (dq1, dq2)) = SWEEP[self.explore_index % SWEEP.len()];
+            let target = (quadrant.0 + dq1, quadrant.1 + dq2);
+            if (1..=8).contains(&target.0) && (1..=8).contains(&target.1) && !self.scanned_quadrants.contains(&target) {
+                return self.navigate(course, 1.0);
+            }
+            self.explore_index += 1;
+        }
+        self.explore_index += 1;
+        let (course, _) = SWEEP[self.explore_index % SWEEP.len()];
+        self.navigate(course, 1.0)
+    }
+    
+    fn navigate(&mut self, course: f32, warp: f32) -> String {
+        self.maneuvers.navigate(course, warp)
+    }
+    
+    /// Answer a prompt caused by a command this behavior issued
+    fn answer_pending(&mut self, prompt: &PromptContext) -> Option<String> {
+        if let Some(answer) = self.maneuvers.answer(prompt.kind) {
+            return Some(answer);
+        }
+        // Any other prompt means a NAV sent went somewhere else
+        self.maneuvers.cancel();
+        match self.pending.clone() {
+            Pending::None => None,
+            Pending::StarbaseNavData if prompt.kind == PromptKind::ComputerCommand => {
+                self.pending = Pending::None;
+                Some("3".to_string())
+            }
+            Pending::Repair if prompt.kind == PromptKind::RepairAuthorization => {
+                self.pending = Pending::None;
+                self.damaged_systems.clear();
+                if self.config.authorize_repairs {
+                    Some("Y".to_string())
+                } else {
+                    Some("N".to_string())
+                }
+            }
+            _ => {
+                // The game went somewhere else (e.g. the command was refused)
+                self.pending = Pending::None;
+                None
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl<S: Strategy> Strategy for DockingStrategy<S> {
+    async fn get_command(&mut self, context: &TurnContext<'_>) -> Result<String> {
+        let TurnContext { prompt, state: game_state, .. } = *context;
+        self.observe(game_state);
+        self.decision = None;
+        
+        if let Some(answer) = self.answer_pending(prompt) {
+            return Ok(answer);
+        }
+        
+        if prompt.kind != PromptKind::Command {
+            return self.delegate(context).await;
+        }
+        
+        if self.seeking && game_state.is_docked() {
+            log::info!("Docked at starbase after {} commands", self.seek_commands);
+            self.seeking = false;
+            self.seek_commands = 0;
+            if !self.damaged_systems.is_empty() {
+                self.pending = Pending::Repair;
+                self.decision = Some("docked with damaged systems, asking for repairs".to_string());
+                return Ok("DAM".to_string());
+            }
+        }
+        
+        if !self.seeking && !game_state.is_docked() && self.needs_starbase(game_state) {
+            log::info!("Heading for a starbase: energy={:?}, damaged={:?}", game_state.energy, self.damaged_systems);
+            self.seeking = true;
+        }
+        
+        if self.seeking {
+            if self.seek_commands < self.config.max_seek_commands {
+                self.decision = Some(self.seek_reason(game_state));
+                return Ok(self.seek_command(game_state));
+            }
+            log::info!("No starbase reached after {} commands, resuming", self.seek_commands);
+            self.seeking = false;
+            self.seek_commands = 0;
+            self.damaged_systems.clear();
+        }
+        
+        self.delegate(context).await
+    }
+    
+    fn reset(&mut self) {
+        self.new_game();
+        self.parent.reset();
+    }
+    
+    fn new_game(&mut self) {
+        self.parent.new_game();
+        self.seeking = false;
+        self.pending = Pending::None;
+        self.maneuvers = Maneuvers::new();
+        self.seek_commands = 0;
+        self.damaged_systems.clear();
+        self.starbase_quadrants.clear();
+        self.empty_quadrants.clear();
+        self.scanned_quadrants.clear();
+        self.explore_index = 0;
+    }
+    
+    fn commands_left(&self) -> Option<usize> {
+        self.parent.commands_left()
+    }
+    
+    fn last_decision(&self) -> Option<&str> {
+        self.decision.as_deref()
+    }
+    
+    fn check_output(&mut self, output: &[Line]) -> Option<String> {
+        self.parent.check_output(output)
+    }
+    
+    fn name(&self) -> &'static str {
+        "Docking"
+    }
+}
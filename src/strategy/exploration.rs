@@ -0,0 +1,174 @@
+use crate::game::PromptKind;
+use crate::strategy::navigation::{compute_course, galactic_position, warp_for_distance, Maneuvers};
+use crate::strategy::{Command, RandomStrategy, Strategy, TurnContext};
+use anyhow::Result;
+use std::collections::{HashSet, VecDeque};
+
+/// Library computer options that print maps: the cumulative galactic record and the
+/// galaxy region name map
+const MAP_OPTIONS: &[i32] = &[0, 5];
+
+/// Moves toward one quadrant before giving up on it (e.g. stars keep blocking the way)
+const MAX_ATTEMPTS: usize = 3;
+
+/// What the exploration is waiting for after issuing a command
+#[derive(Debug, Clone, PartialEq)]
+enum Pending {
+    None,
+    /// Sent COM, waiting for the computer prompt
+    Computer { option: i32 },
+}
+
+/// Exploration strategy tries to visit every quadrant of the galaxy. In each new
+/// quadrant it runs a short and a long range scan and prints one of the computer's
+/// maps, then flies to the closest quadrant it hasn't been to. It doesn't fight, so
+/// the BASIC code it reaches is mostly galaxy setup, quadrant naming, scans and their
+/// damaged states; combine it with --coverage-file to see which lines that adds.
+pub struct ExplorationStrategy {
+    pending: Pending,
+    maneuvers: Maneuvers,
+    visited: HashSet<(i32, i32)>,
+    /// Commands still to run in the current quadrant
+    queue: VecDeque<Command>,
+    target: Option<(i32, i32)>,
+    attempts: usize,
+    maps_shown: usize,
+    fallback: RandomStrategy,
+    /// Seed of the fallback's random choices, kept so reset() starts them over
+    seed: Option<u64>,
+}
+
+impl ExplorationStrategy {
+    pub fn new() -> Self {
+        Self {
+            pending: Pending::None,
+            maneuvers: Maneuvers::new(),
+            visited: HashSet::new(),
+            queue: VecDeque::new(),
+            target: None,
+            attempts: 0,
+            maps_shown: 0,
+            fallback: RandomStrategy::new(),
+            seed: None,
+        }
+    }
+    
+    /// Falls back on random choices that are the same every time for the same seed
+    pub fn with_seed(seed: u64) -> Self {
+        Self { fallback: RandomStrategy::with_seed(seed), seed: Some(seed), ..Self::new() }
+    }
+    
+    /// Closest unvisited quadrant by warp steps, scanning rows top to bottom on ties
+    fn next_target(&self, quadrant: (i32, i32)) -> Option<(i32, i32)> {
+        (1..=8)
+            .flat_map(|row| (1..=8).map(move |column| (row, column)))
+            .filter(|target| !self.visited.contains(target))
+            .min_by_key(|target| (target.0 - quadrant.0).abs().max((target.1 - quadrant.1).abs()))
+    }
+    
+    /// Pick the next command at the COMMAND prompt
+    async fn next_command(&mut self, context: &TurnContext<'_>) -> Result<String> {
+        let game_state = context.state;
+        let (quadrant, sector) = match (game_state.current_quadrant, game_state.current_sector) {
+            (Some(quadrant), Some(sector)) => (quadrant, sector),
+            _ => return Ok(Command::ShortRangeScan.to_string()),
+        };
+        
+        if self.visited.insert(quadrant) {
+            log::debug!("Exploring quadrant {:?} ({} visited)", quadrant, self.visited.len());
+            self.queue = VecDeque::from([Command::ShortRangeScan, Command::LongRangeScan, Command::Computer]);
+        }
+        if let Some(command) = self.queue.pop_front() {
+            if command == Command::Computer {
+                let option = MAP_OPTIONS[self.maps_shown % MAP_OPTIONS.len()];
+                self.maps_shown += 1;
+                self.pending = Pending::Computer { option };
+            }
+            return Ok(command.to_string());
+        }
+        
+        // Give up on a quadrant the ship can't seem to reach
+        if self.target.is_some_and(|target| target != quadrant) && self.attempts >= MAX_ATTEMPTS {
+            self.visited.extend(self.target.take());
+        }
+        let target = match self.target.filter(|&target| !self.visited.contains(&target)) {
+            Some(target) => target,
+            None => match self.next_target(quadrant) {
+                Some(target) => {
+                    self.target = Some(target);
+                    self.attempts = 0;
+                    target
+                }
+                // Everything visited: keep scanning until the game ends
+                None => return self.fallback.get_command(context).await,
+            },
+        };
+        
+        self.attempts += 1;
+        match compute_course(galactic_position(quadrant, sector), galactic_position(target, (4, 4))) {
+            Some((course, distance)) => Ok(self.maneuvers.navigate(course, warp_for_distance(distance))),
+            None => Ok(Command::ShortRangeScan.to_string()),
+        }
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl Strategy for ExplorationStrategy {
+    async fn get_command(&mut self, context: &TurnContext<'_>) -> Result<String> {
+        let TurnContext { prompt, .. } = *context;
+        if let Some(answer) = self.maneuvers.answer(prompt.kind) {
+            return Ok(answer);
+        }
+        match (&self.pending, prompt.kind) {
+            (_, PromptKind::Command) => {
+                self.pending = Pending::None;
+                self.maneuvers.cancel();
+                self.next_command(context).await
+            }
+            (Pending::Computer { option }, PromptKind::ComputerCommand) => {
+                let option = *option;
+                self.pending = Pending::None;
+                Ok(option.to_string())
+            }
+            _ => self.fallback.get_command(context).await,
+        }
+    }
+    
+    fn reset(&mut self) {
+        *self = self.seed.map_or_else(Self::new, Self::with_seed);
+    }
+    
+    fn name(&self) -> &'static str {
+        "Exploration"
+    }
+}
+
+impl Default for ExplorationStrategy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{GameState, PromptContext};
+    
+    #[tokio::test]
+    async fn test_scans_then_moves_on() {
+        let mut strategy = ExplorationStrategy::new();
+        let mut state = GameState::new();
+        state.current_quadrant = Some((1, 1));
+        state.current_sector = Some((4, 4));
+        let prompt = |kind| PromptContext { kind, ..PromptContext::default() };
+        
+        let mut commands = Vec::new();
+        for _ in 0..4 {
+            commands.push(strategy.get_command(&TurnContext::new(&prompt(PromptKind::Command), &state)).await.unwrap());
+        }
+        assert_eq!(commands, ["SRS", "LRS", "COM", "NAV"]);
+        assert_eq!(strategy.get_command(&TurnContext::new(&prompt(PromptKind::Course), &state)).await.unwrap(), "1");
+        assert_eq!(strategy.get_command(&TurnContext::new(&prompt(PromptKind::WarpFactor), &state)).await.unwrap(), "1");
+        assert_eq!(strategy.target, Some((1, 2)));
+    }
+}
@@ -0,0 +1,167 @@
+use crate::json::Json;
+use crate::strategy::{Strategy, TurnContext};
+use anyhow::{Context, Result};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+
+/// Longest an external strategy may take to answer one turn before the game is stopped
+pub const EXTERNAL_TURN_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A strategy run as a separate program, started with `--strategy external:<command>`
+/// (run by `sh -c`, with the seed in TREKBOT_SEED). It speaks JSON lines over stdio, so
+/// it can be written in Python or anything else with a JSON library. TrekBot writes one
+/// message per line to its stdin:
+///
+/// ```json
+/// {"type": "turn", "turn": 3, "prompt": {...}, "state": {...}, "output": [...], "commands": [...]}
+/// {"type": "new_game"}
+/// {"type": "reset"}
+/// ```
+///
+/// A turn is TurnContext::to_json and is answered with one line on stdout,
+/// `{"command": "SRS", "reason": "looking for Klingons"}` (`reason` is optional).
+/// `new_game` and `reset` come before the first turn they apply to and get no answer.
+/// stdout carries nothing else; the program's logging goes to stderr, which is TrekBot's.
+pub struct ExternalStrategy {
+    command: String,
+    child: Child,
+    stdin: ChildStdin,
+    replies: Lines<BufReader<ChildStdout>>,
+    /// "reset" or "new_game", to send ahead of the next turn
+    pending: Option<&'static str>,
+    decision: Option<String>,
+}
+
+impl ExternalStrategy {
+    /// Start `command` in a shell, telling it `seed` for its random choices
+    pub fn spawn(command: &str, seed: u64) -> Result<Self> {
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .env("TREKBOT_SEED", seed.to_string())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("Failed to start external strategy '{}'", command))?;
+        let stdin = child.stdin.take().context("external strategy has no stdin")?;
+        let stdout = child.stdout.take().context("external strategy has no stdout")?;
+        log::info!("Started external strategy '{}'", command);
+        Ok(Self {
+            command: command.to_string(),
+            child,
+            stdin,
+            replies: BufReader::new(stdout).lines(),
+            pending: None,
+            decision: None,
+        })
+    }
+    
+    async fn send(&mut self, message: Json) -> Result<()> {
+        self.stdin.write_all(format!("{}\n", message).as_bytes()).await?;
+        self.stdin.flush().await?;
+        Ok(())
+    }
+    
+    /// Why the program stopped answering, with its exit status if it has exited
+    fn gone(&mut self) -> anyhow::Error {
+        match self.child.try_wait() {
+            Ok(Some(status)) => anyhow::anyhow!("External strategy '{}' exited ({})", self.command, status),
+            _ => anyhow::anyhow!("External strategy '{}' closed its output", self.command),
+        }
+    }
+}
+
+/// The command and reason in a reply line
+fn parse_reply(line: &str) -> Result<(String, Option<String>)> {
+    let reply = Json::parse(line).map_err(|e| {
+        anyhow::anyhow!("{} (stdout is only for replies, log to stderr instead)", e)
+    })?;
+    let command = reply.get("command").and_then(Json::as_str).context("no \"command\" string")?;
+    let reason = reply.get("reason").and_then(Json::as_str).map(str::to_string);
+    Ok((command.to_string(), reason))
+}
+
+#[async_trait::async_trait(?Send)]
+impl Strategy for ExternalStrategy {
+    async fn get_command(&mut self, context: &TurnContext<'_>) -> Result<String> {
+        if let Some(event) = self.pending.take() {
+            self.send(Json::object().with("type", event)).await.map_err(|_| self.gone())?;
+        }
+        let mut turn = context.to_json();
+        if let Json::Object(fields) = &mut turn {
+            fields.insert(0, ("type".to_string(), Json::from("turn")));
+        }
+        self.send(turn).await.map_err(|_| self.gone())?;
+        
+        let line = match tokio::time::timeout(EXTERNAL_TURN_TIMEOUT, self.replies.next_line()).await {
+            Ok(Ok(Some(line))) => line,
+            Ok(Ok(None)) | Ok(Err(_)) => return Err(self.gone()),
+            Err(_) => anyhow::bail!("External strategy '{}' gave no answer to the {:?} prompt in {}s",
+                                    self.command, context.prompt.kind, EXTERNAL_TURN_TIMEOUT.as_secs()),
+        };
+        let (command, reason) = parse_reply(&line)
+            .with_context(|| format!("External strategy '{}' sent an invalid reply: {}", self.command, line))?;
+        self.decision = reason;
+        Ok(command)
+    }
+    
+    fn reset(&mut self) {
+        self.pending = Some("reset");
+        self.decision = None;
+    }
+    
+    fn new_game(&mut self) {
+        // A reset still to be sent covers the new game too
+        self.pending.get_or_insert("new_game");
+        self.decision = None;
+    }
+    
+    fn last_decision(&self) -> Option<&str> {
+        self.decision.as_deref()
+    }
+    
+    fn name(&self) -> &'static str {
+        "External"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{GameState, PromptContext, PromptKind};
+    
+    /// Shields up at the command prompt, after each new game a short range scan instead,
+    /// and text that isn't JSON for any other prompt
+    const STRATEGY: &str = r#"
+        scan=""
+        while read -r line; do
+          case "$line" in
+            *'"type":"new_game"'*) scan=1 ;;
+            *'"kind":"Command"'*)
+              if [ -n "$scan" ]; then echo '{"command": "SRS"}'
+              else echo '{"command": "SHE", "reason": "seed '"$TREKBOT_SEED"'"}'; fi ;;
+            *) echo "thinking..." ;;
+          esac
+        done
+    "#;
+    
+    #[tokio::test]
+    async fn test_external_strategy() {
+        let mut strategy = ExternalStrategy::spawn(STRATEGY, 7).unwrap();
+        let command = PromptContext { kind: PromptKind::Command, ..PromptContext::default() };
+        let state = GameState::new();
+        assert_eq!(strategy.get_command(&TurnContext::new(&command, &state)).await.unwrap(), "SHE");
+        assert_eq!(strategy.last_decision(), Some("seed 7"));
+        
+        strategy.new_game();
+        assert_eq!(strategy.get_command(&TurnContext::new(&command, &state)).await.unwrap(), "SRS");
+        assert_eq!(strategy.last_decision(), None);
+        
+        let course = PromptContext { kind: PromptKind::Course, ..PromptContext::default() };
+        let error = strategy.get_command(&TurnContext::new(&course, &state)).await.unwrap_err();
+        assert!(format!("{:#}", error).contains("invalid reply: thinking..."));
+    }
+}
@@ -0,0 +1,98 @@
+use crate::game::PromptKind;
+use crate::strategy::TurnContext;
+use anyhow::Result;
+use std::collections::HashMap;
+
+/// Answers one kind of prompt for a strategy of type `S`
+pub type Handler<S> = fn(&mut S, &TurnContext<'_>) -> Result<String>;
+
+/// Which method of a strategy answers each kind of prompt. A new prompt kind is handled
+/// by registering one function, and answers every strategy gives the same way, like
+/// Enter for a message, are registered once in shared() instead of in each strategy.
+pub struct PromptHandlers<S> {
+    handlers: HashMap<PromptKind, Handler<S>>,
+}
+
+impl<S> PromptHandlers<S> {
+    /// No prompts handled yet
+    pub fn new() -> Self {
+        Self { handlers: HashMap::new() }
+    }
+    
+    /// The answers all strategies share: Enter for messages and reports waiting to go
+    /// on, and for a bare "?" the game gave no context for
+    pub fn shared() -> Self {
+        Self::new()
+            .on(PromptKind::Message, press_enter)
+            .on(PromptKind::Unknown, unknown_prompt)
+    }
+    
+    /// Answer prompts of `kind` with `handler`, in place of any handler registered before
+    pub fn on(mut self, kind: PromptKind, handler: Handler<S>) -> Self {
+        self.handlers.insert(kind, handler);
+        self
+    }
+    
+    /// Answer prompts of each of `kinds` with `handler`
+    pub fn on_each(self, kinds: &[PromptKind], handler: Handler<S>) -> Self {
+        kinds.iter().fold(self, |handlers, &kind| handlers.on(kind, handler))
+    }
+    
+    /// Answer the prompt with the handler registered for its kind
+    pub fn answer(&self, strategy: &mut S, context: &TurnContext<'_>) -> Result<String> {
+        let prompt = context.prompt;
+        match self.handlers.get(&prompt.kind) {
+            Some(handler) => handler(strategy, context),
+            None => anyhow::bail!("No answer for a {:?} prompt: '{}'", prompt.kind, prompt.prompt.trim()),
+        }
+    }
+}
+
+impl<S> Default for PromptHandlers<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Status messages and reports that just need Enter to continue
+pub fn press_enter<S>(_strategy: &mut S, _context: &TurnContext<'_>) -> Result<String> {
+    Ok(String::new())
+}
+
+/// A bare "?" is answered with Enter; any other unrecognized prompt is an error, logged
+/// with the command and output that led to it
+pub fn unknown_prompt<S>(_strategy: &mut S, context: &TurnContext<'_>) -> Result<String> {
+    let prompt = context.prompt;
+    if prompt.prompt.trim() == "?" {
+        log::warn!("Generic '?' prompt with no detectable context, sending empty response");
+        return Ok(String::new());
+    }
+    log::warn!("Unknown prompt at turn {}: '{}'", context.turn, prompt.prompt.trim());
+    log::warn!("  Last command: {:?}", context.commands.last().map_or("", |command| command.as_str()));
+    for line in context.history.iter().rev().take(5).rev() {
+        log::warn!("  | {}", line);
+    }
+    Err(anyhow::anyhow!("Unknown prompt: '{}'", prompt.prompt.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{GameState, PromptContext};
+    
+    #[test]
+    fn test_registry() {
+        let handlers: PromptHandlers<usize> = PromptHandlers::shared().on(PromptKind::Course, |count, _| {
+            *count += 1;
+            Ok(count.to_string())
+        });
+        let state = GameState::new();
+        let prompt = |kind: PromptKind, text: &str| PromptContext { kind, prompt: text.to_string(), ..PromptContext::default() };
+        let mut count = 0;
+        assert_eq!(handlers.answer(&mut count, &TurnContext::new(&prompt(PromptKind::Course, "COURSE (0-9)?"), &state)).unwrap(), "1");
+        assert_eq!(handlers.answer(&mut count, &TurnContext::new(&prompt(PromptKind::Message, "PRESS ENTER"), &state)).unwrap(), "");
+        assert_eq!(handlers.answer(&mut count, &TurnContext::new(&prompt(PromptKind::Unknown, " ? "), &state)).unwrap(), "");
+        assert!(handlers.answer(&mut count, &TurnContext::new(&prompt(PromptKind::Unknown, "HUH?"), &state)).is_err());
+        assert!(handlers.answer(&mut count, &TurnContext::new(&prompt(PromptKind::WarpFactor, "WARP FACTOR?"), &state)).is_err());
+    }
+}
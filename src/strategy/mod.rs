@@ -1,26 +1,191 @@
-use crate::game::GameState;
+use crate::game::{GameState, Line, PromptContext};
+use crate::json::Json;
 use anyhow::Result;
 
 pub mod random;
+pub mod boundary;
 pub mod cheat;
+pub mod docking;
+pub mod discovery;
+pub mod exploration;
+pub mod external;
+pub mod handlers;
+pub mod navigation;
+pub mod numeric;
+pub mod planner;
+pub mod plugin;
+pub mod risk;
+pub mod rl;
+pub mod scripted;
+pub mod scripting;
+pub mod speedrun;
+pub mod targeting;
+pub mod wasm;
+pub mod weighted;
 
 pub use random::*;
+pub use boundary::*;
 pub use cheat::*;
+pub use docking::*;
+pub use discovery::*;
+pub use exploration::*;
+pub use external::*;
+pub use handlers::*;
+pub use numeric::*;
+pub use plugin::*;
+pub use risk::*;
+pub use rl::*;
+pub use scripted::*;
+pub use scripting::*;
+pub use speedrun::*;
+pub use wasm::*;
+pub use weighted::*;
 
-/// Trait for different game playing strategies
+/// What a strategy is told when asked for a command: the prompt and state, and how the
+/// game got there
+#[derive(Debug, Clone, Copy)]
+pub struct TurnContext<'a> {
+    /// The prompt the game is waiting on
+    pub prompt: &'a PromptContext,
+    pub state: &'a GameState,
+    /// Turns played so far this game
+    pub turn: usize,
+    /// Most recent output lines across turns, oldest first, as many as the player keeps
+    pub history: &'a [Line],
+    /// Commands sent so far this game, oldest first, as many as the player keeps
+    pub commands: &'a [String],
+}
+
+impl<'a> TurnContext<'a> {
+    /// A prompt and state with no history, as at the start of a game
+    pub fn new(prompt: &'a PromptContext, state: &'a GameState) -> Self {
+        Self { prompt, state, turn: 0, history: &[], commands: &[] }
+    }
+    
+    /// What strategies outside TrekBot are told each turn: the prompt, the parsed state
+    /// and the output since the last command, e.g.
+    ///
+    /// ```json
+    /// {"turn": 3, "prompt": {"kind": "Command", "text": "COMMAND?", "energy_available": null},
+    ///  "state": {"quadrant": [4, 2], "sector": [5, 7], "energy": 2880, "shields": 0, ...},
+    ///  "output": ["...", "COMMAND?"], "commands": ["SRS", "LRS"]}
+    /// ```
+    pub fn to_json(self) -> Json {
+        Json::object()
+            .with("turn", self.turn)
+            .with("prompt", self.prompt.to_json())
+            .with("state", self.state.to_json())
+            .with("output", self.state.last_output.iter().map(|line| line.to_string()).collect::<Vec<_>>())
+            .with("commands", self.commands.to_vec())
+    }
+}
+
+/// Trait for different game playing strategies. Asking for a command is async, so a
+/// strategy can wait on an outside service such as a remote planner.
+#[async_trait::async_trait(?Send)]
 pub trait Strategy {
-    /// Get the next command to send to the game based on the current state
-    fn get_command(&mut self, game_state: &GameState) -> Result<String>;
+    /// Answer the prompt the game is waiting on, given the current state and history
+    async fn get_command(&mut self, context: &TurnContext<'_>) -> Result<String>;
     
-    /// Reset the strategy state (e.g., between games)
+    /// Start a fresh session, forgetting everything including what was learned in
+    /// earlier games
     fn reset(&mut self);
     
+    /// Get ready for another game in the same session. Adaptive strategies keep what
+    /// they learned across games and clear only what belongs to one game; the rest
+    /// have nothing to keep and start over as with reset()
+    fn new_game(&mut self) {
+        self.reset();
+    }
+    
+    /// Commands left to send, for a strategy that plays a fixed list; the game ends as at
+    /// the turn limit once there are none. None for strategies that can always answer
+    fn commands_left(&self) -> Option<usize> {
+        None
+    }
+    
+    /// Look over the output the game printed in answer to the last command, before the
+    /// game goes on. A strategy holding the game to expectations, like a script with
+    /// `expect:` lines, returns the one broken, which fails the game as strict mode does
+    fn check_output(&mut self, _output: &[Line]) -> Option<String> {
+        None
+    }
+    
+    /// Why the last command was chosen, in a few words, e.g. "2 Klingons in the quadrant,
+    /// none in a clear torpedo line". None for strategies that don't say
+    fn last_decision(&self) -> Option<&str> {
+        None
+    }
+    
     /// Get the name of this strategy
     fn name(&self) -> &'static str;
 }
 
+#[async_trait::async_trait(?Send)]
+impl<S: Strategy + ?Sized> Strategy for Box<S> {
+    async fn get_command(&mut self, context: &TurnContext<'_>) -> Result<String> {
+        (**self).get_command(context).await
+    }
+    
+    fn reset(&mut self) {
+        (**self).reset()
+    }
+    
+    fn new_game(&mut self) {
+        (**self).new_game()
+    }
+    
+    fn commands_left(&self) -> Option<usize> {
+        (**self).commands_left()
+    }
+    
+    fn last_decision(&self) -> Option<&str> {
+        (**self).last_decision()
+    }
+    
+    fn check_output(&mut self, output: &[Line]) -> Option<String> {
+        (**self).check_output(output)
+    }
+    
+    fn name(&self) -> &'static str {
+        (**self).name()
+    }
+}
+
+/// Lets a player borrow a strategy that outlives it, to play several games of one session
+#[async_trait::async_trait(?Send)]
+impl<S: Strategy + ?Sized> Strategy for &mut S {
+    async fn get_command(&mut self, context: &TurnContext<'_>) -> Result<String> {
+        (**self).get_command(context).await
+    }
+    
+    fn reset(&mut self) {
+        (**self).reset()
+    }
+    
+    fn new_game(&mut self) {
+        (**self).new_game()
+    }
+    
+    fn commands_left(&self) -> Option<usize> {
+        (**self).commands_left()
+    }
+    
+    fn last_decision(&self) -> Option<&str> {
+        (**self).last_decision()
+    }
+    
+    fn check_output(&mut self, output: &[Line]) -> Option<String> {
+        (**self).check_output(output)
+    }
+    
+    fn name(&self) -> &'static str {
+        (**self).name()
+    }
+}
+
 /// Command types that can be sent to the game
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Command {
     Navigation,
     ShortRangeScan,
@@ -34,6 +199,23 @@ pub enum Command {
 }
 
 impl Command {
+    /// Every command a strategy plays with, leaving out quitting
+    pub const PLAYABLE: [Command; 8] = [
+        Command::Navigation,
+        Command::ShortRangeScan,
+        Command::LongRangeScan,
+        Command::Phasers,
+        Command::Torpedoes,
+        Command::Shields,
+        Command::DamageControl,
+        Command::Computer,
+    ];
+    
+    /// The command typed as `name`, e.g. "NAV", ignoring case
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::PLAYABLE.iter().chain([&Command::Quit]).find(|command| command.to_string().eq_ignore_ascii_case(name.trim())).cloned()
+    }
+    
     pub fn to_string(&self) -> String {
         match self {
             Command::Navigation => "NAV".to_string(),
@@ -50,21 +232,59 @@ impl Command {
 }
 
 /// Helper function to generate random commands
-pub fn random_command() -> Command {
-    use rand::Rng;
-    let mut rng = rand::thread_rng();
-    let commands = vec![
-        Command::Navigation,
-        Command::ShortRangeScan,
-        Command::LongRangeScan,
-        Command::Phasers,
-        Command::Torpedoes,
-        Command::Shields,
-        Command::DamageControl,
-        Command::Computer,
-        // Command::Quit, // Don't include quit in random selection
-    ];
+pub fn random_command(rng: &mut impl rand::Rng) -> Command {
+    // Quit isn't playable, so it's never picked
+    let index = rng.gen_range(0..Command::PLAYABLE.len());
+    Command::PLAYABLE[index].clone()
+} 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::PromptKind;
+    
+    #[test]
+    fn test_turn_json() {
+        let prompt = PromptContext { kind: PromptKind::ShieldUnits, energy_available: Some(2500), ..PromptContext::default() };
+        let mut state = GameState::new();
+        state.current_quadrant = Some((4, 2));
+        state.damage_report.insert("WARP ENGINES".to_string(), -1.5);
+        let commands = ["SHE".to_string()];
+        let json = TurnContext { turn: 3, commands: &commands, ..TurnContext::new(&prompt, &state) }.to_json();
+        assert_eq!(json.get("turn").unwrap().as_usize(), Some(3));
+        assert_eq!(json.get("prompt").unwrap().get("kind").unwrap().as_str(), Some("ShieldUnits"));
+        assert_eq!(json.get("prompt").unwrap().get("energy_available").unwrap().as_usize(), Some(2500));
+        let state = json.get("state").unwrap();
+        assert_eq!(state.get("quadrant").unwrap().to_string(), "[4,2]");
+        assert_eq!(state.get("damage").unwrap().get("WARP ENGINES").unwrap().as_f64(), Some(-1.5));
+        assert_eq!(json.get("commands").unwrap().to_string(), "[\"SHE\"]");
+    }
     
-    let index = rng.gen_range(0..commands.len());
-    commands[index].clone()
-} 
\ No newline at end of file
+    /// Answers to rounds of prompts the strategies mostly leave to their random fallback
+    async fn answers(strategy: &mut dyn Strategy) -> Vec<String> {
+        let state = GameState::new();
+        let mut answers = Vec::new();
+        for kind in [PromptKind::Course, PromptKind::WarpFactor, PromptKind::ComputerCommand, PromptKind::Command].repeat(5) {
+            let prompt = PromptContext { kind, energy_available: Some(3000), ..PromptContext::default() };
+            answers.push(strategy.get_command(&TurnContext::new(&prompt, &state)).await.unwrap_or_default());
+        }
+        answers
+    }
+    
+    #[tokio::test]
+    async fn test_seed_survives_reset() {
+        let seeded: [fn(u64) -> Box<dyn Strategy>; 4] = [
+            |seed| Box::new(cheat::CheatStrategy::with_seed(seed)),
+            |seed| Box::new(speedrun::SpeedrunStrategy::with_seed(seed)),
+            |seed| Box::new(exploration::ExplorationStrategy::with_seed(seed)),
+            |seed| Box::new(rl::RlStrategy::with_seed(rl::Policy::new(), true, seed)),
+        ];
+        for strategy in seeded {
+            let (mut first, mut second) = (strategy(7), strategy(7));
+            // Every session starts with a reset
+            first.reset();
+            second.reset();
+            assert_eq!(answers(first.as_mut()).await, answers(second.as_mut()).await, "{}", first.name());
+        }
+    }
+}
@@ -0,0 +1,310 @@
+//! Navigation helpers shared by strategies that need to travel somewhere on purpose.
+//!
+//! Coordinates follow the BASIC program: the first component is the row (1 at the
+//! top), the second is the column. Galactic coordinates combine quadrant and sector
+//! into a single 1-64 grid so moves across quadrant boundaries can be planned directly.
+
+use crate::game::{Line, PromptKind};
+use crate::strategy::Command;
+
+/// Convert a quadrant/sector pair into galactic coordinates
+pub fn galactic_position(quadrant: (i32, i32), sector: (i32, i32)) -> (i32, i32) {
+    ((quadrant.0 - 1) * 8 + sector.0, (quadrant.1 - 1) * 8 + sector.1)
+}
+
+/// Compute the course and distance from one position to another.
+///
+/// This mirrors the library computer's direction/distance calculator (lines 8220-8460)
+/// so the result is exactly what the game itself would report.
+pub fn compute_course(from: (i32, i32), to: (i32, i32)) -> Option<(f32, f32)> {
+    let x = (to.1 - from.1) as f32;
+    let a = (from.0 - to.0) as f32;
+    
+    if x == 0.0 && a == 0.0 {
+        return None;
+    }
+    
+    let course = if x < 0.0 {
+        if a > 0.0 {
+            vertical_course(3.0, a, x)
+        } else {
+            horizontal_course(5.0, a, x)
+        }
+    } else if a < 0.0 {
+        vertical_course(7.0, a, x)
+    } else {
+        horizontal_course(1.0, a, x)
+    };
+    
+    let distance = (x * x + a * a).sqrt();
+    Some((course, distance))
+}
+
+/// Course calculation for the branches starting at line 8290
+fn horizontal_course(base: f32, a: f32, x: f32) -> f32 {
+    if a.abs() <= x.abs() {
+        base + a.abs() / x.abs()
+    } else {
+        base + ((a.abs() - x.abs()) + a.abs()) / a.abs()
+    }
+}
+
+/// Course calculation for the branches starting at line 8420
+fn vertical_course(base: f32, a: f32, x: f32) -> f32 {
+    if a.abs() >= x.abs() {
+        base + x.abs() / a.abs()
+    } else {
+        base + ((x.abs() - a.abs()) + x.abs()) / x.abs()
+    }
+}
+
+/// Row and column change per step for each whole course, C(1..9) in the program
+const COURSE_STEPS: [(f64, f64); 9] = [
+    (0.0, 1.0), (-1.0, 1.0), (-1.0, 0.0), (-1.0, -1.0), (0.0, -1.0),
+    (1.0, -1.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0),
+];
+
+/// Sectors a photon torpedo fired from `sector` on `course` passes through until it
+/// leaves the quadrant, computed as lines 4850-4960 do. The game stops the track early
+/// at whatever the torpedo hits, so its track should be a prefix of this one.
+pub fn torpedo_track(sector: (i32, i32), course: f64) -> Vec<(i32, i32)> {
+    let course = if course == 9.0 { 1.0 } else { course };
+    if !(1.0..9.0).contains(&course) {
+        return Vec::new();
+    }
+    let whole = course.floor() as usize;
+    let (from, to) = (COURSE_STEPS[whole - 1], COURSE_STEPS[whole]);
+    let fraction = course - course.floor();
+    let step = (from.0 + (to.0 - from.0) * fraction, from.1 + (to.1 - from.1) * fraction);
+    
+    let mut track = Vec::new();
+    let (mut x, mut y) = (sector.0 as f64, sector.1 as f64);
+    loop {
+        x += step.0;
+        y += step.1;
+        let (row, column) = ((x + 0.5).floor() as i32, (y + 0.5).floor() as i32);
+        if !(1..=8).contains(&row) || !(1..=8).contains(&column) {
+            return track;
+        }
+        track.push((row, column));
+    }
+}
+
+/// Warp factor that moves the given number of sectors (each warp unit is 8 sectors)
+pub fn warp_for_steps(steps: i32) -> f32 {
+    (steps as f32 / 8.0).clamp(0.125, 8.0)
+}
+
+/// Warp factor large enough to cover a straight-line distance.
+/// Rounded up to the next 1/8 so the ship never stops short.
+pub fn warp_for_distance(distance: f32) -> f32 {
+    warp_for_steps(distance.ceil() as i32)
+}
+
+/// Quadrants covered by a long range scan of the 3x3 block around `quadrant`, each with
+/// its reading (Klingons x100 + starbases x10 + stars). Cells outside the galaxy are skipped.
+pub fn scanned_quadrants(quadrant: (i32, i32), galaxy_map: &[Vec<String>]) -> Vec<((i32, i32), i32)> {
+    let mut quadrants = Vec::new();
+    for (i, row) in galaxy_map.iter().enumerate() {
+        for (j, cell) in row.iter().enumerate() {
+            if let Ok(value) = cell.parse::<i32>() {
+                quadrants.push(((quadrant.0 + i as i32 - 1, quadrant.1 + j as i32 - 1), value));
+            }
+        }
+    }
+    quadrants
+}
+
+/// Sectors of a short range scan holding the given symbol, e.g. "+K+" for Klingons
+pub fn sector_positions(sector_map: &[Vec<String>], symbol: &str) -> Vec<(i32, i32)> {
+    let mut positions = Vec::new();
+    for (i, row) in sector_map.iter().enumerate() {
+        for (j, cell) in row.iter().enumerate() {
+            if cell == symbol {
+                positions.push((i as i32 + 1, j as i32 + 1));
+            }
+        }
+    }
+    positions
+}
+
+/// Format a course or warp factor the way strategies send them to the game
+pub fn format_factor(value: f32) -> String {
+    let text = format!("{:.3}", value);
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+/// A NAV or TOR command sent, waiting for the prompts that follow it
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Maneuver {
+    /// Sent NAV, waiting for the course and warp factor prompts
+    Navigate { course: f32, warp: f32 },
+    /// Sent TOR, waiting for the torpedo course prompt
+    Torpedo { course: f32 },
+}
+
+/// NAV and TOR commands in flight for strategies that move and fire on purpose: the
+/// answers still owed to their prompts, and what their results mean for the next command
+#[derive(Debug, Clone, PartialEq)]
+pub struct Maneuvers {
+    pending: Option<Maneuver>,
+    /// The last command moved the ship or hit something, so the short range scan is out of date
+    pub stale_scan: bool,
+    /// The last torpedo missed or hit a star; use phasers until a Klingon is destroyed
+    pub torpedo_blocked: bool,
+}
+
+impl Maneuvers {
+    pub fn new() -> Self {
+        Self { pending: None, stale_scan: true, torpedo_blocked: false }
+    }
+    
+    /// Send NAV, answering its prompts with this course and warp factor. The ship
+    /// moves, so the scan is out of date and a torpedo may have a clear line again.
+    pub fn navigate(&mut self, course: f32, warp: f32) -> String {
+        self.pending = Some(Maneuver::Navigate { course, warp });
+        self.stale_scan = true;
+        self.torpedo_blocked = false;
+        Command::Navigation.to_string()
+    }
+    
+    /// Send TOR, answering its prompt with this course
+    pub fn fire_torpedo(&mut self, course: f32) -> String {
+        self.pending = Some(Maneuver::Torpedo { course });
+        self.stale_scan = true;
+        Command::Torpedoes.to_string()
+    }
+    
+    /// The answer to a prompt that follows the NAV or TOR sent, or None for any other prompt
+    pub fn answer(&mut self, kind: PromptKind) -> Option<String> {
+        let (value, last) = match (self.pending?, kind) {
+            (Maneuver::Navigate { course, .. }, PromptKind::Course) => (course, false),
+            (Maneuver::Navigate { warp, .. }, PromptKind::WarpFactor) => (warp, true),
+            (Maneuver::Torpedo { course }, PromptKind::TorpedoCourse) => (course, true),
+            _ => return None,
+        };
+        if last {
+            self.pending = None;
+        }
+        Some(format_factor(value))
+    }
+    
+    /// Forget the command sent, e.g. when the game refused it
+    pub fn cancel(&mut self) {
+        self.pending = None;
+    }
+    
+    /// Whether the short range scan needs redoing before the next decision; it is taken
+    /// to be redone after this says so
+    pub fn take_stale_scan(&mut self) -> bool {
+        std::mem::take(&mut self.stale_scan)
+    }
+    
+    /// Note whether torpedoes in the latest output missed or destroyed a Klingon
+    pub fn observe(&mut self, output: &[Line]) {
+        for line in output {
+            if line.contains("TORPEDO MISSED") || line.contains("ABSORBED TORPEDO ENERGY") {
+                self.torpedo_blocked = true;
+            } else if line.contains("KLINGON DESTROYED") {
+                self.torpedo_blocked = false;
+            }
+        }
+    }
+}
+
+impl Default for Maneuvers {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    /// Number of warp steps needed to cover a distance, as the game moves one sector
+    /// along the major axis per step.
+    fn steps_between(from: (i32, i32), to: (i32, i32)) -> i32 {
+        (to.0 - from.0).abs().max((to.1 - from.1).abs())
+    }
+    
+    #[test]
+    fn test_maneuvers() {
+        let mut maneuvers = Maneuvers::new();
+        assert!(maneuvers.take_stale_scan());
+        assert!(!maneuvers.take_stale_scan());
+        assert_eq!(maneuvers.answer(PromptKind::Course), None);
+        
+        maneuvers.torpedo_blocked = true;
+        assert_eq!(maneuvers.navigate(2.5, 0.125), "NAV");
+        assert!(!maneuvers.torpedo_blocked);
+        assert_eq!(maneuvers.answer(PromptKind::TorpedoCourse), None);
+        assert_eq!(maneuvers.answer(PromptKind::Course).as_deref(), Some("2.5"));
+        assert_eq!(maneuvers.answer(PromptKind::WarpFactor).as_deref(), Some("0.125"));
+        assert_eq!(maneuvers.answer(PromptKind::WarpFactor), None);
+        
+        assert_eq!(maneuvers.fire_torpedo(7.0), "TOR");
+        maneuvers.cancel();
+        assert_eq!(maneuvers.answer(PromptKind::TorpedoCourse), None);
+        assert!(maneuvers.take_stale_scan());
+        
+        let output: Vec<Line> = vec!["TORPEDO MISSED".into(), "***KLINGON DESTROYED***".into(), "STAR AT 3 , 4 ABSORBED TORPEDO ENERGY.".into()];
+        maneuvers.observe(&output[..1]);
+        assert!(maneuvers.torpedo_blocked);
+        maneuvers.observe(&output[1..2]);
+        assert!(!maneuvers.torpedo_blocked);
+        maneuvers.observe(&output);
+        assert!(maneuvers.torpedo_blocked);
+    }
+    
+    #[test]
+    fn test_torpedo_track() {
+        assert_eq!(torpedo_track((4, 5), 1.0), vec![(4, 6), (4, 7), (4, 8)]);
+        assert_eq!(torpedo_track((4, 5), 9.0), torpedo_track((4, 5), 1.0));
+        assert_eq!(torpedo_track((2, 2), 4.0), vec![(1, 1)]);
+        assert_eq!(torpedo_track((8, 1), 1.5), vec![(8, 2), (7, 3), (7, 4), (6, 5), (6, 6), (5, 7), (5, 8)]);
+        assert_eq!(torpedo_track((4, 5), 10.0), vec![]);
+    }
+    
+    #[test]
+    fn test_cardinal_courses() {
+        assert_eq!(compute_course((4, 4), (4, 6)).map(|c| c.0), Some(1.0));
+        assert_eq!(compute_course((4, 4), (2, 4)).map(|c| c.0), Some(3.0));
+        assert_eq!(compute_course((4, 4), (4, 1)).map(|c| c.0), Some(5.0));
+        assert_eq!(compute_course((4, 4), (7, 4)).map(|c| c.0), Some(7.0));
+        assert_eq!(compute_course((4, 4), (4, 4)), None);
+    }
+    
+    #[test]
+    fn test_diagonal_courses() {
+        assert_eq!(compute_course((4, 4), (3, 5)).map(|c| c.0), Some(2.0));
+        assert_eq!(compute_course((4, 4), (3, 3)).map(|c| c.0), Some(4.0));
+        assert_eq!(compute_course((4, 4), (5, 3)).map(|c| c.0), Some(6.0));
+        assert_eq!(compute_course((4, 4), (5, 5)).map(|c| c.0), Some(8.0));
+    }
+    
+    #[test]
+    fn test_galactic_position_and_warp() {
+        assert_eq!(galactic_position((1, 1), (1, 1)), (1, 1));
+        assert_eq!(galactic_position((2, 3), (4, 5)), (12, 21));
+        assert_eq!(steps_between((12, 21), (4, 29)), 8);
+        assert_eq!(warp_for_steps(8), 1.0);
+        assert_eq!(warp_for_distance(2.5), 0.375);
+        assert_eq!(format_factor(1.0), "1");
+        assert_eq!(format_factor(0.375), "0.375");
+    }
+    
+    #[test]
+    fn test_scan_readings() {
+        let row = |cells: &[&str]| cells.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+        let galaxy_map = vec![row(&["***", "***", "***"]), row(&["105", "002", "011"]), row(&["200", "000", "007"])];
+        let quadrants = scanned_quadrants((1, 4), &galaxy_map);
+        assert_eq!(quadrants.len(), 6);
+        assert_eq!(quadrants[0], ((1, 3), 105));
+        assert_eq!(quadrants[3], ((2, 3), 200));
+        
+        let sector_map = vec![row(&["", "+K+"]), row(&["<*>", "+K+"])];
+        assert_eq!(sector_positions(&sector_map, "+K+"), vec![(1, 2), (2, 2)]);
+        assert_eq!(sector_positions(&sector_map, "<*>"), vec![(2, 1)]);
+    }
+}
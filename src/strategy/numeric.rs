@@ -0,0 +1,153 @@
+use crate::game::{GameState, PromptContext, PromptKind};
+use rand::Rng;
+
+/// The values a numeric prompt accepts, written with `decimals` decimal places
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NumericRange {
+    pub min: f64,
+    pub max: f64,
+    pub decimals: usize,
+}
+
+impl NumericRange {
+    pub fn integer(min: i32, max: i32) -> Self {
+        Self { min: min as f64, max: max as f64, decimals: 0 }
+    }
+    
+    pub fn decimal(min: f64, max: f64, decimals: usize) -> Self {
+        Self { min, max, decimals }
+    }
+    
+    /// The range the game accepts at a prompt, when it has one
+    pub fn for_prompt(prompt: &PromptContext, game_state: &GameState) -> Option<Self> {
+        let energy = prompt.energy_available.or(game_state.energy);
+        match prompt.kind {
+            PromptKind::Course | PromptKind::TorpedoCourse => Some(Self::integer(1, 9)),
+            PromptKind::WarpFactor => {
+                let (min, max) = prompt.warp_range.unwrap_or((0.0, 8.0));
+                Some(Self::decimal(min as f64, max as f64, if max <= 0.2 { 2 } else { 1 }))
+            }
+            PromptKind::PhaserUnits | PromptKind::EnergyUnits => energy.map(|energy| Self::integer(1, energy.max(1))),
+            PromptKind::ShieldUnits => energy.map(|energy| Self::integer(0, energy)),
+            PromptKind::InitialCoordinates | PromptKind::FinalCoordinates => Some(Self::integer(1, 8)),
+            PromptKind::ComputerCommand => Some(Self::integer(0, 5)),
+            _ => None,
+        }
+    }
+    
+    /// Smallest difference between two values as written
+    pub fn step(&self) -> f64 {
+        10f64.powi(-(self.decimals as i32))
+    }
+    
+    pub fn contains(&self, value: f64) -> bool {
+        let steps = value / self.step();
+        value >= self.min && value <= self.max && (steps - steps.round()).abs() < 1e-6
+    }
+    
+    /// A value as typed at the prompt; fractions of integer ranges keep their decimals
+    pub fn format(&self, value: f64) -> String {
+        if self.decimals == 0 && value.fract() != 0.0 {
+            value.to_string()
+        } else {
+            format!("{:.*}", self.decimals, value)
+        }
+    }
+    
+    /// The edges of the range and the values just inside them
+    pub fn boundary_values(&self) -> Vec<f64> {
+        let mut values = vec![self.min, self.min + self.step(), self.max - self.step(), self.max];
+        values.retain(|value| self.contains(*value));
+        values.dedup();
+        values
+    }
+    
+    /// Values the game should refuse: just outside either edge, negative, far too large,
+    /// and for integer prompts a fraction
+    pub fn out_of_range_values(&self) -> Vec<f64> {
+        let mut values = vec![self.min - self.step(), self.max + self.step(), -self.max.abs().max(1.0), self.max.abs().max(1.0) * 10.0];
+        if self.decimals == 0 {
+            values.push(self.min + 0.5);
+        }
+        values.retain(|value| !self.contains(*value));
+        values.dedup();
+        values
+    }
+    
+    /// Boundary values, then out-of-range ones: the values worth trying one after another
+    pub fn probe_values(&self) -> Vec<f64> {
+        let mut values = self.boundary_values();
+        values.extend(self.out_of_range_values());
+        values
+    }
+    
+    /// A uniformly random accepted value
+    pub fn random_value(&self, rng: &mut impl Rng) -> f64 {
+        if self.max <= self.min {
+            self.min
+        } else if self.decimals == 0 {
+            rng.gen_range(self.min as i64..=self.max as i64) as f64
+        } else {
+            rng.gen_range(self.min..=self.max)
+        }
+    }
+}
+
+/// How often generated numbers stray from ordinary in-range values, as fractions of all
+/// numeric answers. The default only ever answers in range.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct NumericPolicy {
+    /// Answers at or just inside the edges of the range
+    pub boundary: f64,
+    /// Answers the game should refuse
+    pub out_of_range: f64,
+}
+
+impl NumericPolicy {
+    /// An answer for a prompt accepting `range`, drawn according to the policy
+    pub fn generate(&self, range: &NumericRange, rng: &mut impl Rng) -> String {
+        let roll: f64 = if self.boundary > 0.0 || self.out_of_range > 0.0 { rng.gen() } else { 1.0 };
+        let values = if roll < self.out_of_range {
+            range.out_of_range_values()
+        } else if roll < self.out_of_range + self.boundary {
+            range.boundary_values()
+        } else {
+            Vec::new()
+        };
+        let value = match values.len() {
+            0 => range.random_value(rng),
+            count => values[rng.gen_range(0..count)],
+        };
+        range.format(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    
+    #[test]
+    fn test_range_values() {
+        let course = NumericRange::integer(1, 9);
+        assert_eq!(course.boundary_values(), vec![1.0, 2.0, 8.0, 9.0]);
+        assert_eq!(course.out_of_range_values(), vec![0.0, 10.0, -9.0, 90.0, 1.5]);
+        assert_eq!(course.format(1.5), "1.5");
+        
+        let warp = NumericRange::decimal(0.0, 0.2, 2);
+        assert_eq!(warp.boundary_values().iter().map(|v| warp.format(*v)).collect::<Vec<_>>(), ["0.00", "0.01", "0.19", "0.20"]);
+        assert!(warp.out_of_range_values().iter().all(|value| !warp.contains(*value)));
+    }
+    
+    #[test]
+    fn test_policy() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let range = NumericRange::integer(1, 500);
+        let legal = NumericPolicy::default();
+        assert!((0..100).all(|_| range.contains(legal.generate(&range, &mut rng).parse().unwrap())));
+        
+        let fuzz = NumericPolicy { boundary: 0.0, out_of_range: 1.0 };
+        assert!((0..100).all(|_| !range.contains(fuzz.generate(&range, &mut rng).parse().unwrap())));
+    }
+}
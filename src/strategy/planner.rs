@@ -0,0 +1,143 @@
+use crate::game::GameState;
+use crate::strategy::navigation::{compute_course, galactic_position, scanned_quadrants, sector_positions, warp_for_distance};
+use std::collections::{HashMap, HashSet};
+
+/// A warp that takes the ship from where it is to the middle of another quadrant
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Route {
+    pub target: (i32, i32),
+    pub course: f32,
+    pub warp: f32,
+    /// In sectors
+    pub distance: f32,
+}
+
+/// Route from a sector of one quadrant to the middle of `target`, None if already there
+pub fn route_to(quadrant: (i32, i32), sector: (i32, i32), target: (i32, i32)) -> Option<Route> {
+    let (course, distance) = compute_course(galactic_position(quadrant, sector), galactic_position(target, (4, 4)))?;
+    Some(Route { target, course, warp: warp_for_distance(distance), distance })
+}
+
+/// Klingons in a quadrant reading
+pub fn klingons(reading: i32) -> i32 {
+    reading / 100
+}
+
+/// Starbases in a quadrant reading
+pub fn starbases(reading: i32) -> i32 {
+    (reading / 10) % 10
+}
+
+/// The galaxy as far as a strategy has seen it: the latest reading of each quadrant
+/// (Klingons x100 + starbases x10 + stars) from long and short range scans and the
+/// library computer's galactic record, and the quadrants long range scans were taken
+/// from. Routes to the quadrants worth going to come with the NAV course and warp
+/// factor that get there.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GalaxyChart {
+    readings: HashMap<(i32, i32), i32>,
+    scanned_from: HashSet<(i32, i32)>,
+}
+
+impl GalaxyChart {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Chart the readings of any long range scan or galactic record in the latest output
+    pub fn observe(&mut self, game_state: &GameState) {
+        let scanned = game_state.last_output.iter().any(|line| line.contains("LONG RANGE SCAN"));
+        if let (true, Some(galaxy_map), Some(quadrant)) = (scanned, &game_state.galaxy_map, game_state.current_quadrant) {
+            self.readings.extend(scanned_quadrants(quadrant, galaxy_map));
+        }
+        if game_state.last_output.iter().any(|line| line.contains("COMPUTER RECORD OF GALAXY")) {
+            for (i, row) in game_state.galaxy.quadrants.iter().enumerate() {
+                for (j, code) in row.iter().enumerate() {
+                    if let Some(reading) = code.as_deref().and_then(|code| code.parse().ok()) {
+                        self.readings.insert((i as i32 + 1, j as i32 + 1), reading);
+                    }
+                }
+            }
+        }
+    }
+    
+    /// Chart the quadrant the ship is in from its short range scan
+    pub fn observe_sectors(&mut self, quadrant: (i32, i32), sector_map: &[Vec<String>]) {
+        let count = |symbol| sector_positions(sector_map, symbol).len() as i32;
+        self.readings.insert(quadrant, count("+K+") * 100 + count(">!<") * 10 + count("*"));
+    }
+    
+    /// Note a long range scan is being taken from `quadrant`, telling whether it is the first
+    pub fn mark_scanned(&mut self, quadrant: (i32, i32)) -> bool {
+        self.scanned_from.insert(quadrant)
+    }
+    
+    pub fn is_scanned_from(&self, quadrant: (i32, i32)) -> bool {
+        self.scanned_from.contains(&quadrant)
+    }
+    
+    /// Route to the closest other charted quadrant whose reading passes `wanted`
+    pub fn nearest(&self, quadrant: (i32, i32), sector: (i32, i32), wanted: impl Fn(i32) -> bool) -> Option<Route> {
+        self.readings.iter()
+            .filter(|(&target, &reading)| target != quadrant && wanted(reading))
+            .filter_map(|(&target, _)| route_to(quadrant, sector, target))
+            .min_by(|a, b| a.distance.total_cmp(&b.distance))
+    }
+    
+    /// Route to the closest quadrant known to hold Klingons
+    pub fn nearest_klingons(&self, quadrant: (i32, i32), sector: (i32, i32)) -> Option<Route> {
+        self.nearest(quadrant, sector, |reading| klingons(reading) > 0)
+    }
+    
+    /// Route to the closest quadrant known to hold a starbase
+    pub fn nearest_starbase(&self, quadrant: (i32, i32), sector: (i32, i32)) -> Option<Route> {
+        self.nearest(quadrant, sector, |reading| starbases(reading) > 0)
+    }
+    
+    /// Route to the closest quadrant with no reading yet
+    pub fn nearest_uncharted(&self, quadrant: (i32, i32), sector: (i32, i32)) -> Option<Route> {
+        (1..=8)
+            .flat_map(|row| (1..=8).map(move |column| (row, column)))
+            .filter(|target| !self.readings.contains_key(target))
+            .filter_map(|target| route_to(quadrant, sector, target))
+            .min_by(|a, b| a.distance.total_cmp(&b.distance))
+    }
+    
+    /// Forget everything, e.g. once the chart is too old to trust
+    pub fn clear(&mut self) {
+        self.readings.clear();
+        self.scanned_from.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_chart_routes() {
+        let mut chart = GalaxyChart::new();
+        let mut state = GameState::new();
+        state.current_quadrant = Some((1, 1));
+        state.galaxy_map = Some(vec![
+            vec!["***".to_string(), "***".to_string(), "***".to_string()],
+            vec!["***".to_string(), "005".to_string(), "207".to_string()],
+            vec!["***".to_string(), "013".to_string(), "000".to_string()],
+        ]);
+        state.last_output = crate::game::share_lines(vec!["LONG RANGE SCAN FOR QUADRANT 1 , 1".to_string()]);
+        chart.observe(&state);
+        assert!(chart.mark_scanned((1, 1)) && !chart.mark_scanned((1, 1)));
+        
+        // Klingons one quadrant east: course 1, a whole warp
+        let route = chart.nearest_klingons((1, 1), (4, 4)).unwrap();
+        assert_eq!((route.target, route.course, route.warp), ((1, 2), 1.0, 1.0));
+        assert_eq!(chart.nearest_starbase((1, 1), (4, 4)).unwrap().target, (2, 1));
+        assert!(chart.nearest_uncharted((1, 1), (4, 4)).is_some_and(|route| route.target == (1, 3)));
+        
+        let mut sector_map = vec![vec![String::new(); 8]; 8];
+        sector_map[0][0] = "<*>".to_string();
+        chart.observe_sectors((1, 2), &sector_map);
+        assert_eq!(chart.nearest_klingons((1, 1), (4, 4)), None);
+        assert_eq!(route_to((1, 1), (4, 4), (1, 1)), None);
+    }
+}
@@ -0,0 +1,371 @@
+use crate::strategy::{Strategy, TurnContext};
+use anyhow::{Context, Result};
+use std::ffi::{c_char, c_void, CStr, CString};
+
+/// Version of the C interface below; a plugin built for another version is refused
+pub const PLUGIN_ABI_VERSION: u32 = 1;
+
+/// A strategy compiled as a shared library, loaded with `--strategy plugin:<path>`. The
+/// library exports these functions (the `trekbot_plugin_` prefix left out):
+///
+/// ```c
+/// uint32_t abi_version(void);                        // PLUGIN_ABI_VERSION
+/// void *new(uint64_t seed);                          // a strategy instance
+/// char *get_command(void *strategy, const char *turn);
+/// void free_string(char *text);                      // frees what get_command returned
+/// void reset(void *strategy);                        // forget the game, keep the seed going
+/// void free(void *strategy);
+/// // Optional
+/// void new_game(void *strategy);                     // reset() if missing
+/// const char *name(void *strategy);
+/// const char *last_decision(void *strategy);         // NULL if it has no reason to give
+/// ```
+///
+/// `turn` is the JSON of TurnContext::to_json. get_command returns the answer as a
+/// string the plugin allocated, or NULL if it can't answer, which stops the game.
+/// Between games of a session TrekBot calls new_game, and for a fresh session it frees
+/// the instance and creates another with the same seed, so its random choices start over.
+pub struct PluginStrategy {
+    /// Kept open until the instance has been freed
+    _library: Library,
+    api: PluginApi,
+    instance: *mut c_void,
+    seed: u64,
+    path: String,
+}
+
+type AbiVersionFn = unsafe extern "C" fn() -> u32;
+type NewFn = unsafe extern "C" fn(u64) -> *mut c_void;
+type GetCommandFn = unsafe extern "C" fn(*mut c_void, *const c_char) -> *mut c_char;
+type FreeStringFn = unsafe extern "C" fn(*mut c_char);
+type InstanceFn = unsafe extern "C" fn(*mut c_void);
+type InstanceStrFn = unsafe extern "C" fn(*mut c_void) -> *const c_char;
+
+/// The functions a plugin exports
+struct PluginApi {
+    new: NewFn,
+    get_command: GetCommandFn,
+    free_string: FreeStringFn,
+    reset: InstanceFn,
+    free: InstanceFn,
+    new_game: Option<InstanceFn>,
+    name: Option<InstanceStrFn>,
+    last_decision: Option<InstanceStrFn>,
+}
+
+impl PluginStrategy {
+    /// Load the plugin at `path` and create its strategy, seeding its random choices
+    pub fn load(path: &str, seed: u64) -> Result<Self> {
+        let library = Library::open(path)?;
+        // SAFETY: the symbols are declared with the signatures the interface documents;
+        // a plugin of another interface version is refused before any other is called
+        unsafe {
+            let abi_version = std::mem::transmute::<*mut c_void, AbiVersionFn>(library.symbol("abi_version")?);
+            let version = abi_version();
+            if version != PLUGIN_ABI_VERSION {
+                anyhow::bail!("Plugin {} implements interface version {}, TrekBot needs {}", path, version, PLUGIN_ABI_VERSION);
+            }
+            let api = PluginApi {
+                new: std::mem::transmute::<*mut c_void, NewFn>(library.symbol("new")?),
+                get_command: std::mem::transmute::<*mut c_void, GetCommandFn>(library.symbol("get_command")?),
+                free_string: std::mem::transmute::<*mut c_void, FreeStringFn>(library.symbol("free_string")?),
+                reset: std::mem::transmute::<*mut c_void, InstanceFn>(library.symbol("reset")?),
+                free: std::mem::transmute::<*mut c_void, InstanceFn>(library.symbol("free")?),
+                new_game: library.optional_symbol("new_game").map(|symbol| std::mem::transmute::<*mut c_void, InstanceFn>(symbol)),
+                name: library.optional_symbol("name").map(|symbol| std::mem::transmute::<*mut c_void, InstanceStrFn>(symbol)),
+                last_decision: library.optional_symbol("last_decision").map(|symbol| std::mem::transmute::<*mut c_void, InstanceStrFn>(symbol)),
+            };
+            let instance = (api.new)(seed);
+            if instance.is_null() {
+                anyhow::bail!("Plugin {} failed to create its strategy", path);
+            }
+            let strategy = Self { _library: library, api, instance, seed, path: path.to_string() };
+            log::info!("Loaded strategy plugin {} ({})", strategy.plugin_name().unwrap_or("unnamed"), path);
+            Ok(strategy)
+        }
+    }
+    
+    /// The name the plugin gives itself, if it exports one
+    pub fn plugin_name(&self) -> Option<&str> {
+        // SAFETY: the plugin returns a string that lives as long as the instance
+        self.api.name.and_then(|name| unsafe { borrowed_str(name(self.instance)) })
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl Strategy for PluginStrategy {
+    async fn get_command(&mut self, context: &TurnContext<'_>) -> Result<String> {
+        let turn = CString::new(context.to_json().to_string()).context("Turn to send to the plugin contains a NUL")?;
+        // SAFETY: the instance is live until drop, and the answer is freed by the plugin
+        // that allocated it, after it has been copied
+        unsafe {
+            let answer = (self.api.get_command)(self.instance, turn.as_ptr());
+            if answer.is_null() {
+                anyhow::bail!("Plugin {} gave no answer to the {:?} prompt", self.path, context.prompt.kind);
+            }
+            let command = CStr::from_ptr(answer).to_string_lossy().into_owned();
+            (self.api.free_string)(answer);
+            Ok(command)
+        }
+    }
+    
+    fn reset(&mut self) {
+        // SAFETY: the old instance is freed once and replaced before any other call
+        unsafe {
+            let instance = (self.api.new)(self.seed);
+            if instance.is_null() {
+                log::warn!("Plugin {} failed to create a fresh strategy, keeping the old one", self.path);
+                (self.api.reset)(self.instance);
+                return;
+            }
+            (self.api.free)(self.instance);
+            self.instance = instance;
+        }
+    }
+    
+    fn new_game(&mut self) {
+        // SAFETY: the instance is live until drop
+        unsafe { (self.api.new_game.unwrap_or(self.api.reset))(self.instance) }
+    }
+    
+    fn last_decision(&self) -> Option<&str> {
+        // SAFETY: the plugin returns a string that lives until its next call
+        self.api.last_decision.and_then(|last_decision| unsafe { borrowed_str(last_decision(self.instance)) })
+    }
+    
+    fn name(&self) -> &'static str {
+        "Plugin"
+    }
+}
+
+impl Drop for PluginStrategy {
+    fn drop(&mut self) {
+        // SAFETY: the instance came from this plugin's new() and is freed once, before
+        // the library is closed as the fields are dropped
+        unsafe { (self.api.free)(self.instance) };
+    }
+}
+
+/// A string owned by the plugin, None for NULL or text that isn't UTF-8
+unsafe fn borrowed_str<'a>(text: *const c_char) -> Option<&'a str> {
+    if text.is_null() {
+        return None;
+    }
+    CStr::from_ptr(text).to_str().ok()
+}
+
+/// A shared library opened with dlopen
+struct Library {
+    handle: *mut c_void,
+    path: String,
+}
+
+#[cfg(unix)]
+mod dl {
+    use std::ffi::{c_char, c_int, c_void};
+    
+    /// Resolve every symbol on load, so a broken plugin fails at once rather than mid-game
+    pub const RTLD_NOW: c_int = 2;
+    
+    #[cfg_attr(target_os = "linux", link(name = "dl"))]
+    extern "C" {
+        pub fn dlopen(filename: *const c_char, flags: c_int) -> *mut c_void;
+        pub fn dlsym(handle: *mut c_void, symbol: *const c_char) -> *mut c_void;
+        pub fn dlclose(handle: *mut c_void) -> c_int;
+        pub fn dlerror() -> *mut c_char;
+    }
+}
+
+#[cfg(unix)]
+impl Library {
+    fn open(path: &str) -> Result<Self> {
+        // A bare file name would be searched for on the library path rather than here
+        let full_path = std::fs::canonicalize(path).with_context(|| format!("Strategy plugin {} not found", path))?;
+        let c_path = CString::new(full_path.to_string_lossy().as_bytes()).context("Plugin path contains a NUL")?;
+        // SAFETY: dlopen takes a NUL-terminated path; loading runs the library's initializers
+        let handle = unsafe { dl::dlopen(c_path.as_ptr(), dl::RTLD_NOW) };
+        if handle.is_null() {
+            anyhow::bail!("Failed to load strategy plugin {}: {}", path, last_error());
+        }
+        Ok(Self { handle, path: path.to_string() })
+    }
+    
+    /// Address of `trekbot_plugin_<name>`, None if the library doesn't export it
+    fn optional_symbol(&self, name: &str) -> Option<*mut c_void> {
+        let symbol = CString::new(format!("trekbot_plugin_{}", name)).ok()?;
+        // SAFETY: the handle is open until close()
+        let address = unsafe { dl::dlsym(self.handle, symbol.as_ptr()) };
+        (!address.is_null()).then_some(address)
+    }
+    
+    fn symbol(&self, name: &str) -> Result<*mut c_void> {
+        self.optional_symbol(name)
+            .with_context(|| format!("Strategy plugin {} doesn't export trekbot_plugin_{}", self.path, name))
+    }
+}
+
+#[cfg(unix)]
+impl Drop for Library {
+    fn drop(&mut self) {
+        // SAFETY: nothing from the library is used after this
+        unsafe { dl::dlclose(self.handle) };
+    }
+}
+
+/// What dlopen last complained about
+#[cfg(unix)]
+fn last_error() -> String {
+    // SAFETY: dlerror returns NULL or a NUL-terminated message
+    unsafe { borrowed_str(dl::dlerror()) }.unwrap_or("unknown error").to_string()
+}
+
+#[cfg(not(unix))]
+impl Library {
+    fn open(path: &str) -> Result<Self> {
+        anyhow::bail!("Strategy plugins are only supported on Unix, can't load {}", path)
+    }
+    
+    fn optional_symbol(&self, _name: &str) -> Option<*mut c_void> {
+        None
+    }
+    
+    fn symbol(&self, name: &str) -> Result<*mut c_void> {
+        anyhow::bail!("Strategy plugin {} doesn't export trekbot_plugin_{}", self.path, name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{GameState, PromptContext, PromptKind};
+    use std::path::PathBuf;
+    
+    /// A plugin built for the tests: warp factors from a seeded generator at the command
+    /// prompt, no answer to any other, and a count of the answers freed. Built with
+    /// `--cfg old_abi` it claims interface version 0.
+    const FIXTURE: &str = r#"
+        use std::ffi::{c_char, c_void, CStr, CString};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        
+        static FREED: AtomicUsize = AtomicUsize::new(0);
+        
+        struct Bot { state: u64, decision: CString }
+        
+        #[no_mangle]
+        pub extern "C" fn trekbot_plugin_abi_version() -> u32 {
+            if cfg!(old_abi) { 0 } else { 1 }
+        }
+        
+        #[no_mangle]
+        pub extern "C" fn trekbot_plugin_new(seed: u64) -> *mut c_void {
+            Box::into_raw(Box::new(Bot { state: seed, decision: CString::default() })) as *mut c_void
+        }
+        
+        #[no_mangle]
+        pub unsafe extern "C" fn trekbot_plugin_get_command(bot: *mut c_void, turn: *const c_char) -> *mut c_char {
+            let bot = &mut *(bot as *mut Bot);
+            if !CStr::from_ptr(turn).to_string_lossy().contains("\"kind\":\"Command\"") {
+                return std::ptr::null_mut();
+            }
+            bot.state = bot.state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            let warp = (bot.state >> 33) % 8 + 1;
+            bot.decision = CString::new(format!("rolled {}", warp)).unwrap();
+            CString::new(format!("WARP {}", warp)).unwrap().into_raw()
+        }
+        
+        #[no_mangle]
+        pub unsafe extern "C" fn trekbot_plugin_free_string(text: *mut c_char) {
+            drop(CString::from_raw(text));
+            FREED.fetch_add(1, Ordering::SeqCst);
+        }
+        
+        #[no_mangle]
+        pub unsafe extern "C" fn trekbot_plugin_reset(bot: *mut c_void) {
+            (*(bot as *mut Bot)).decision = CString::default();
+        }
+        
+        #[no_mangle]
+        pub unsafe extern "C" fn trekbot_plugin_free(bot: *mut c_void) {
+            drop(Box::from_raw(bot as *mut Bot));
+        }
+        
+        #[no_mangle]
+        pub unsafe extern "C" fn trekbot_plugin_last_decision(bot: *mut c_void) -> *const c_char {
+            let bot = &*(bot as *const Bot);
+            if bot.decision.as_bytes().is_empty() { std::ptr::null() } else { bot.decision.as_ptr() }
+        }
+        
+        #[no_mangle]
+        pub extern "C" fn trekbot_plugin_freed() -> usize {
+            FREED.load(Ordering::SeqCst)
+        }
+    "#;
+    
+    /// Compile FIXTURE as a shared library named `name`, with `cfg` set if given
+    fn build_fixture(name: &str, cfg: Option<&str>) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("trekbot-plugins-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let source = dir.join(format!("{}.rs", name));
+        std::fs::write(&source, FIXTURE).unwrap();
+        let library = dir.join(format!("lib{}.so", name));
+        let mut rustc = std::process::Command::new("rustc");
+        rustc.args(["--crate-type", "cdylib", "--edition", "2021", "-o"]).arg(&library).arg(&source);
+        if let Some(cfg) = cfg {
+            rustc.args(["--cfg", cfg]);
+        }
+        assert!(rustc.status().unwrap().success(), "failed to build the {} plugin", name);
+        library
+    }
+    
+    /// The answers to eight turns at the command prompt
+    async fn answers(strategy: &mut PluginStrategy) -> Vec<String> {
+        let (prompt, state) = (PromptContext { kind: PromptKind::Command, ..PromptContext::default() }, GameState::new());
+        let mut answers = Vec::new();
+        for _ in 0..8 {
+            answers.push(strategy.get_command(&TurnContext::new(&prompt, &state)).await.unwrap());
+        }
+        answers
+    }
+    
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_plugin_strategy() {
+        let path = build_fixture("fixture", None);
+        let mut strategy = PluginStrategy::load(&path.to_string_lossy(), 5).unwrap();
+        assert_eq!(strategy.plugin_name(), None);
+        
+        let first = answers(&mut strategy).await;
+        assert!(first[7].starts_with("WARP "));
+        assert_eq!(strategy.last_decision(), Some(first[7].replace("WARP", "rolled").as_str()));
+        // SAFETY: the fixture exports this with the signature given
+        let freed = unsafe { std::mem::transmute::<*mut c_void, extern "C" fn() -> usize>(strategy._library.symbol("freed").unwrap()) };
+        assert_eq!(freed(), 8);
+        
+        // Without new_game the plugin's reset starts the next game, carrying on its numbers
+        strategy.new_game();
+        assert_eq!(strategy.last_decision(), None);
+        assert_ne!(answers(&mut strategy).await, first);
+        // A fresh session starts them over
+        strategy.reset();
+        assert_eq!(answers(&mut strategy).await, first);
+        
+        let course = PromptContext { kind: PromptKind::Course, ..PromptContext::default() };
+        let error = strategy.get_command(&TurnContext::new(&course, &GameState::new())).await.unwrap_err();
+        assert!(error.to_string().contains("gave no answer to the Course prompt"));
+        assert_eq!(freed(), 24);
+    }
+    
+    #[cfg(unix)]
+    #[test]
+    fn test_plugin_interface_version() {
+        let path = build_fixture("old-fixture", Some("old_abi"));
+        let error = PluginStrategy::load(&path.to_string_lossy(), 1).err().unwrap();
+        assert!(error.to_string().contains("implements interface version 0, TrekBot needs 1"));
+    }
+    
+    #[test]
+    fn test_missing_plugin() {
+        let error = PluginStrategy::load("no-such-plugin.so", 1).err().unwrap();
+        assert!(format!("{:#}", error).contains("not found"));
+    }
+}
@@ -1,23 +1,113 @@
-use crate::game::{GameState, parse_energy_available, parse_warp_factor_range};
-use crate::strategy::{Strategy, random_command};
+use crate::game::{GameState, PromptContext, PromptKind};
+use crate::strategy::{NumericPolicy, NumericRange, PromptHandlers, Strategy, TurnContext, random_command};
+use crate::tune::{Param, ParamValues};
 use anyhow::Result;
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::sync::OnceLock;
+
+/// Chances and energy fractions the random strategy plays by. The defaults are the
+/// values it has always used; `tune` searches for better ones.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RandomParams {
+    /// Chance of raising the shields when under attack
+    pub danger_shields: f64,
+    /// Chance of firing phasers when under attack and not raising the shields
+    pub danger_phasers: f64,
+    /// Least fraction of the available energy put into the shields after the first time
+    pub shield_min: f64,
+    /// Most fraction of the available energy put into the shields after the first time
+    pub shield_max: f64,
+    /// Most units of energy fired with phasers
+    pub phaser_max: i32,
+}
+
+impl RandomParams {
+    /// Settings `tune` can vary, with the ranges it searches and their defaults
+    pub const TUNABLE: &'static [Param] = &[
+        Param { name: "danger_shields", min: 0.0, max: 1.0, default: 0.5 },
+        Param { name: "danger_phasers", min: 0.0, max: 1.0, default: 0.3 },
+        Param { name: "shield_min", min: 0.0, max: 1.0, default: 0.3 },
+        Param { name: "shield_max", min: 0.0, max: 1.0, default: 0.7 },
+        Param { name: "phaser_max", min: 1.0, max: 3000.0, default: 499.0 },
+    ];
+    
+    /// Settings from tuned values, with the default for any not given
+    pub fn from_values(values: &ParamValues) -> Self {
+        let value = |name: &str| {
+            let param = Self::TUNABLE.iter().find(|param| param.name == name).expect("tunable parameter");
+            values.get(name).copied().unwrap_or(param.default).clamp(param.min, param.max)
+        };
+        let (shield_min, shield_max) = (value("shield_min"), value("shield_max"));
+        Self {
+            danger_shields: value("danger_shields"),
+            danger_phasers: value("danger_phasers"),
+            shield_min: shield_min.min(shield_max),
+            shield_max: shield_min.max(shield_max),
+            phaser_max: (value("phaser_max").round() as i32).max(1),
+        }
+    }
+}
+
+impl Default for RandomParams {
+    fn default() -> Self {
+        Self::from_values(&ParamValues::new())
+    }
+}
 
 /// Random strategy implementation that plays the game randomly
 /// This is similar to the original Python RandomStrategy but designed to be legal ~90% of the time
 pub struct RandomStrategy {
-    rng: rand::rngs::ThreadRng,
+    rng: StdRng,
     first_turn: bool,
+    allow_known_bugs: bool,
+    numeric: NumericPolicy,
+    params: RandomParams,
+    /// Why the last command was chosen, see Strategy::last_decision
+    decision: Option<String>,
 }
 
 impl RandomStrategy {
     pub fn new() -> Self {
+        Self::with_rng(StdRng::from_entropy())
+    }
+    
+    /// Random strategy that makes the same choices every time for the same seed
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_rng(StdRng::seed_from_u64(seed))
+    }
+    
+    fn with_rng(rng: StdRng) -> Self {
         Self {
-            rng: rand::thread_rng(),
+            rng,
             first_turn: true,
+            allow_known_bugs: false,
+            numeric: NumericPolicy::default(),
+            params: RandomParams::default(),
+            decision: None,
         }
     }
     
+    /// Play by these chances and energy fractions instead of the defaults
+    pub fn set_params(&mut self, params: RandomParams) {
+        self.params = params;
+    }
+    
+    /// How often numeric answers are boundary or out-of-range values (never by default)
+    pub fn set_numeric_policy(&mut self, policy: NumericPolicy) {
+        self.numeric = policy;
+    }
+    
+    /// A number for a prompt accepting `range`, following the numeric policy
+    fn number(&mut self, range: NumericRange) -> String {
+        self.numeric.generate(&range, &mut self.rng)
+    }
+    
+    /// Allow inputs that trigger known bugs in the original program (e.g. COM options above 5)
+    pub fn set_allow_known_bugs(&mut self, allow: bool) {
+        self.allow_known_bugs = allow;
+    }
+    
     /// Handle the main command prompt
     fn handle_command_prompt(&mut self, game_state: &GameState) -> Result<String> {
         // // If this is the first turn, set shields to a random value between 0-1000
@@ -36,47 +126,50 @@ impl RandomStrategy {
         
         // If we're in danger and shields are low, prioritize shield commands
         if is_dangerous {
-            // 50% chance to use shields when in danger
-            if self.rng.gen_bool(0.5) {
+            // 50% chance to use shields when in danger by default
+            if self.rng.gen_bool(self.params.danger_shields) {
+                self.decision = Some("under attack, raising shields".to_string());
                 return Ok("SHE".to_string()); // Shield command
             }
-            // 30% chance to use phasers when in danger
-            if self.rng.gen_bool(0.3) {
+            // 30% chance to use phasers when in danger by default
+            if self.rng.gen_bool(self.params.danger_phasers) {
+                self.decision = Some("under attack, firing phasers".to_string());
                 return Ok("PHA".to_string()); // Phaser command
             }
         }
         
         // Otherwise use random command
-        Ok(random_command().to_string())
+        self.decision = Some(if is_dangerous { "under attack, but picked at random" } else { "picked at random" }.to_string());
+        Ok(random_command(&mut self.rng).to_string())
     }
     
     /// Handle torpedo course prompt
-    fn handle_torpedo_course(&mut self, _game_state: &GameState) -> Result<String> {
-        let course = self.rng.gen_range(1..10);
-        Ok(course.to_string())
+    fn handle_torpedo_course(&mut self) -> Result<String> {
+        Ok(self.number(NumericRange::integer(1, 9)))
     }
     
     /// Handle computer command prompt
-    fn handle_computer_command(&mut self, _game_state: &GameState) -> Result<String> {
-        // Super star trek has a bug - anything larger than 5 crashes
-        let command = self.rng.gen_range(0..6);
-        Ok(command.to_string())
+    fn handle_computer_command(&mut self, prompt: &PromptContext) -> Result<String> {
+        // After an invalid option the game lists the valid ones; pick from those
+        let listed = prompt.menu_choices();
+        if !listed.is_empty() && !self.allow_known_bugs {
+            return Ok(listed[self.rng.gen_range(0..listed.len())].to_string());
+        }
+        
+        // Super star trek has a bug - anything larger than 5 crashes.
+        // Only pick those options when robustness testing asks for them.
+        let last = if self.allow_known_bugs { 7 } else { 5 };
+        Ok(self.number(NumericRange::integer(0, last)))
     }
     
     /// Handle course selection prompt
-    fn handle_course_prompt(&mut self, _game_state: &GameState) -> Result<String> {
-        let course = self.rng.gen_range(1..10);
-        Ok(course.to_string())
+    fn handle_course_prompt(&mut self) -> Result<String> {
+        Ok(self.number(NumericRange::integer(1, 9)))
     }
     
     /// Handle shield units prompt
-    fn handle_shield_units(&mut self, game_state: &GameState) -> Result<String> {
-        // Try to extract energy from the last output
-        let energy = if let Some(last_output) = game_state.last_output.last() {
-            parse_energy_available(last_output).unwrap_or(3000)
-        } else {
-            3000
-        };
+    fn handle_shield_units(&mut self, prompt: &PromptContext, game_state: &GameState) -> Result<String> {
+        let energy = prompt.energy_available.unwrap_or(3000);
         
         // Check if this looks like the initial shield setting (current shields are 0)
         let current_shields = game_state.shields.unwrap_or(0);
@@ -84,51 +177,43 @@ impl RandomStrategy {
         if current_shields == 0 {
             // Initial shield setting - use random value between 0-1000, but don't exceed available energy
             let max_initial_shields = std::cmp::min(1000, energy);
-            let units = self.rng.gen_range(0..=max_initial_shields);
-            return Ok(units.to_string());
+            return Ok(self.number(NumericRange::integer(0, max_initial_shields)));
         }
         
         // Subsequent shield adjustments - be more defensive
-        // Use 30-70% of available energy for shields
-        let min_shields = (energy as f32 * 0.3) as i32;
-        let max_shields = (energy as f32 * 0.7) as i32;
-        let units = self.rng.gen_range(min_shields..=max_shields);
-        
-        Ok(units.to_string())
+        // Use 30-70% of available energy for shields by default
+        let min_shields = (energy as f32 * self.params.shield_min as f32) as i32;
+        let max_shields = (energy as f32 * self.params.shield_max as f32) as i32;
+        Ok(self.number(NumericRange::integer(min_shields, max_shields)))
     }
     
     /// Handle warp factor prompt
-    fn handle_warp_factor(&mut self, game_state: &GameState) -> Result<String> {
-        if let Some(last_output) = game_state.last_output.last() {
-            if let Some((_min, max)) = parse_warp_factor_range(last_output) {
-                if max <= 0.2 {
-                    // Damaged warp engines
-                    let factor = self.rng.gen::<f32>() / 4.0;
-                    return Ok(format!("{:.2}", factor));
-                }
+    fn handle_warp_factor(&mut self, prompt: &PromptContext) -> Result<String> {
+        if let Some((_min, max)) = prompt.warp_range {
+            if max <= 0.2 {
+                // Damaged warp engines
+                return Ok(self.number(NumericRange::decimal(0.0, 0.25, 2)));
             }
         }
         
         // Default range - warp factors are typically 0.1 to 8.0
-        let factor = self.rng.gen_range(0.1..8.0);
-        Ok(format!("{:.1}", factor))
+        Ok(self.number(NumericRange::decimal(0.1, 8.0, 1)))
     }
     
     /// Handle coordinates prompt
-    fn handle_coordinates(&mut self, _game_state: &GameState) -> Result<String> {
-        let x = self.rng.gen_range(1..9);
-        let y = self.rng.gen_range(1..9);
+    fn handle_coordinates(&mut self) -> Result<String> {
+        let x = self.number(NumericRange::integer(1, 8));
+        let y = self.number(NumericRange::integer(1, 8));
         Ok(format!("{},{}", x, y))
     }
     
     /// Handle phaser units prompt
-    fn handle_phaser_units(&mut self, _game_state: &GameState) -> Result<String> {
-        let units = self.rng.gen_range(1..500);
-        Ok(units.to_string())
+    fn handle_phaser_units(&mut self) -> Result<String> {
+        Ok(self.number(NumericRange::integer(1, self.params.phaser_max)))
     }
     
     /// Handle AYE prompt
-    fn handle_aye_prompt(&mut self, _game_state: &GameState) -> Result<String> {
+    fn handle_aye_prompt(&mut self) -> Result<String> {
         if self.rng.gen_bool(0.1) {
             Ok("aye".to_string())
         } else {
@@ -137,7 +222,7 @@ impl RandomStrategy {
     }
     
     /// Handle repair authorization prompt
-    fn handle_repair_prompt(&mut self, _game_state: &GameState) -> Result<String> {
+    fn handle_repair_prompt(&mut self) -> Result<String> {
         if self.rng.gen_bool(0.5) {
             Ok("Y".to_string())
         } else {
@@ -147,244 +232,39 @@ impl RandomStrategy {
     
     /// Handle energy available prompt
     fn handle_energy_prompt(&mut self, energy_value: i32) -> Result<String> {
-        let units = self.rng.gen_range(1..=energy_value);
-        Ok(units.to_string())
+        Ok(self.number(NumericRange::integer(1, energy_value)))
     }
 }
 
+/// The random strategy's answer to each kind of prompt, built once
+fn handlers() -> &'static PromptHandlers<RandomStrategy> {
+    static HANDLERS: OnceLock<PromptHandlers<RandomStrategy>> = OnceLock::new();
+    HANDLERS.get_or_init(|| {
+        PromptHandlers::<RandomStrategy>::shared()
+            .on(PromptKind::Command, |strategy, context| strategy.handle_command_prompt(context.state))
+            .on(PromptKind::Course, |strategy, _| strategy.handle_course_prompt())
+            .on(PromptKind::WarpFactor, |strategy, context| strategy.handle_warp_factor(context.prompt))
+            .on(PromptKind::TorpedoCourse, |strategy, _| strategy.handle_torpedo_course())
+            .on(PromptKind::PhaserUnits, |strategy, _| strategy.handle_phaser_units())
+            .on(PromptKind::ShieldUnits, |strategy, context| strategy.handle_shield_units(context.prompt, context.state))
+            .on(PromptKind::EnergyUnits, |strategy, context| match context.prompt.energy_available {
+                Some(energy) => strategy.handle_energy_prompt(energy),
+                None => Err(anyhow::anyhow!("Could not parse energy value from: {}", context.prompt.prompt)),
+            })
+            .on(PromptKind::ComputerCommand, |strategy, context| strategy.handle_computer_command(context.prompt))
+            .on_each(&[PromptKind::InitialCoordinates, PromptKind::FinalCoordinates], |strategy, _| strategy.handle_coordinates())
+            .on(PromptKind::RepairAuthorization, |strategy, _| strategy.handle_repair_prompt())
+            .on(PromptKind::Aye, |strategy, _| strategy.handle_aye_prompt())
+    })
+}
+
+#[async_trait::async_trait(?Send)]
 impl Strategy for RandomStrategy {
-    fn get_command(&mut self, game_state: &GameState) -> Result<String> {
-        let prompt = game_state.get_current_prompt().unwrap_or("").trim();
-        
-        log::debug!("Random strategy handling prompt: '{}'", prompt);
-        
-        // If the prompt is just "?", look at the full context to understand what's being asked
-        let effective_prompt = if prompt == "?" {
-            // Look at the last few lines to find the context
-            let recent_lines = game_state.last_output.iter()
-                .rev()
-                .take(3)
-                .collect::<Vec<_>>();
-            
-            let mut context_prompt = prompt;
-            for line in recent_lines {
-                if line.contains("WARP FACTOR") {
-                    context_prompt = "WARP FACTOR";
-                    break;
-                }
-                if line.contains("COURSE (0-9)") {
-                    context_prompt = "COURSE (0-9)";
-                    break;
-                }
-                if line.contains("PHOTON TORPEDO COURSE") {
-                    context_prompt = "PHOTON TORPEDO COURSE";
-                    break;
-                }
-                if line.contains("NUMBER OF UNITS TO SHIELDS") {
-                    context_prompt = "NUMBER OF UNITS TO SHIELDS";
-                    break;
-                }
-                if line.contains("NUMBER OF UNITS TO FIRE") {
-                    context_prompt = "NUMBER OF UNITS TO FIRE";
-                    break;
-                }
-                if line.contains("INITIAL COORDINATES (X,Y)") {
-                    context_prompt = "INITIAL COORDINATES (X,Y)";
-                    break;
-                }
-                if line.contains("FINAL COORDINATES (X,Y)") {
-                    context_prompt = "FINAL COORDINATES (X,Y)";
-                    break;
-                }
-                if line.contains("COMPUTER ACTIVE AND AWAITING COMMAND") {
-                    context_prompt = "COMPUTER ACTIVE AND AWAITING COMMAND";
-                    break;
-                }
-            }
-            context_prompt
-        } else {
-            prompt
-        };
-        
-        log::debug!("Effective prompt after context detection: '{}'", effective_prompt);
-        
-        match effective_prompt {
-            // Main command prompt
-            "COMMAND" | "COMMAND?" => self.handle_command_prompt(game_state),
-            "ENTER ONE OF THE FOLLOWING:" => {
-                // This is just a menu header, not a command prompt
-                // The game will show the menu and then prompt for COMMAND?
-                Ok("".to_string())
-            },
-            "PLEASE ENTER" => {
-                // This is just a print statement before the actual INPUT prompt
-                // The game will show this and then prompt for coordinates
-                Ok("".to_string())
-            },
-            
-            // Navigation prompts
-            p if p.contains("COURSE (0-9)") => self.handle_course_prompt(game_state),
-            p if p.contains("WARP FACTOR") => self.handle_warp_factor(game_state),
-            
-            // Weapon prompts
-            p if p.contains("PHOTON TORPEDO COURSE") => self.handle_torpedo_course(game_state),
-            p if p.contains("NUMBER OF UNITS TO FIRE") => self.handle_phaser_units(game_state),
-            p if p.contains("PHASERS LOCKED ON TARGET") && p.contains("ENERGY AVAILABLE") => {
-                // Handle phaser targeting prompt like "PHASERS LOCKED ON TARGET; ENERGY AVAILABLE = 3000 UNITS"
-                if let Some(energy) = parse_energy_available(p) {
-                    self.handle_energy_prompt(energy)
-                } else {
-                    Err(anyhow::anyhow!("Could not parse energy value from: {}", p))
-                }
-            }
-            
-            // Shield and energy prompts
-            p if p.contains("NUMBER OF UNITS TO SHIELDS") => self.handle_shield_units(game_state),
-            p if p.starts_with("ENERGY AVAILABLE = ") => {
-                if let Some(energy) = parse_energy_available(p) {
-                    self.handle_energy_prompt(energy)
-                } else {
-                    Err(anyhow::anyhow!("Could not parse energy value from: {}", p))
-                }
-            }
-            
-            // Computer prompts
-            p if p.contains("COMPUTER ACTIVE AND AWAITING COMMAND") => self.handle_computer_command(game_state),
-            p if p.contains("INITIAL COORDINATES (X,Y)") => self.handle_coordinates(game_state),
-            p if p.contains("FINAL COORDINATES (X,Y)") => self.handle_coordinates(game_state),
-            
-            // Repair and maintenance prompts
-            p if p.contains("WILL YOU AUTHORIZE THE REPAIR ORDER") => self.handle_repair_prompt(game_state),
-            p if p.contains("SHIELD CONTROL INOPERABLE") => {
-                // Pick a different command since shields are broken
-                self.handle_command_prompt(game_state)
-            }
-            
-            // Mission control prompts
-            p if p.contains("LET HIM STEP FORWARD AND ENTER 'AYE'") => self.handle_aye_prompt(game_state),
-            
-            // Status messages and reports that just need Enter to continue
-            p if p.contains("LT. UHURA REPORTS MESSAGE") => {
-                Ok("".to_string())
-            }
-            p if p.contains("SHIELDS NOW AT") && p.contains("UNITS PER YOUR COMMAND") => {
-                // Status message after shield changes - just continue
-                Ok("".to_string())
-            }
-            p if p.contains("DEFLECTOR CONTROL ROOM REPORT") => {
-                // Status message - just continue
-                Ok("".to_string())
-            }
-            p if p.contains("DAMAGE CONTROL REPORT") => {
-                // Status message - just continue
-                Ok("".to_string())
-            }
-            p if p.contains("ENGINEERING REPORTS") => {
-                // Status message - just continue
-                Ok("".to_string())
-            }
-            p if p.contains("CHIEF ENGINEER SCOTT REPORTS") => {
-                // Status message - just continue
-                Ok("".to_string())
-            }
-            p if p.contains("STARBASE SHIELDS PROTECT") => {
-                // Status message - just continue
-                Ok("".to_string())
-            }
-            p if p.contains("SENSORS SHOW NO DAMAGE") => {
-                // Status message - just continue
-                Ok("".to_string())
-            }
-            p if p.contains("UNIT HIT ON") => {
-                // Status message - just continue
-                Ok("".to_string())
-            }
-            p if p.contains("KLINGON DESTROYED") => {
-                // Status message - just continue
-                Ok("".to_string())
-            }
-            p if p.contains("TORPEDO TRACK") => {
-                // Status message - just continue
-                Ok("".to_string())
-            }
-            p if p.contains("STAR AT") && p.contains("ABSORBED TORPEDO") => {
-                // Status message - just continue
-                Ok("".to_string())
-            }
-            p if p.contains("STARBASE DESTROYED") => {
-                // Status message - just continue
-                Ok("".to_string())
-            }
-            p if p.contains("TORPEDO MISSED") => {
-                // Status message - just continue
-                Ok("".to_string())
-            }
-            p if p.contains("SHIELDS UNCHANGED") => {
-                // Status message - just continue
-                Ok("".to_string())
-            }
-            p if p.contains("CONDITION RED") => {
-                // Status message - just continue
-                Ok("".to_string())
-            }
-            p if p.contains("WARP ENGINES SHUT DOWN") => {
-                // Status message - just continue
-                Ok("".to_string())
-            }
-            p if p.contains("PERMISSION TO ATTEMPT CROSSING") => {
-                // Status message - just continue
-                Ok("".to_string())
-            }
-            p if p.contains("NOW ENTERING") && p.contains("QUADRANT") => {
-                // Status message when entering new quadrant - just continue
-                Ok("".to_string())
-            }
-            
-            // Help menu lines - these are just informational, not prompts
-            p if p.contains("NAV  (TO SET COURSE)") => {
-                Ok("".to_string())
-            }
-            p if p.contains("SRS  (FOR SHORT RANGE SENSOR SCAN)") => {
-                Ok("".to_string())
-            }
-            p if p.contains("LRS  (FOR LONG RANGE SENSOR SCAN)") => {
-                Ok("".to_string())
-            }
-            p if p.contains("PHA  (TO FIRE PHASERS)") => {
-                Ok("".to_string())
-            }
-            p if p.contains("TOR  (TO FIRE PHOTON TORPEDOES)") => {
-                Ok("".to_string())
-            }
-            p if p.contains("SHE  (TO RAISE OR LOWER SHIELDS)") => {
-                Ok("".to_string())
-            }
-            p if p.contains("DAM  (FOR DAMAGE CONTROL REPORTS)") => {
-                Ok("".to_string())
-            }
-            p if p.contains("COM  (TO CALL ON LIBRARY-COMPUTER)") => {
-                Ok("".to_string())
-            }
-            p if p.contains("XXX  (TO RESIGN YOUR COMMAND)") => {
-                Ok("".to_string())
-            }
-            
-            // Header lines that precede the actual prompts - wait for real prompt
-            p if p.contains("PLEASE ENTER") => {
-                Ok("".to_string())
-            }
-            
-            // Generic "?" prompt - couldn't determine context, just send Enter
-            "?" => {
-                log::warn!("Generic '?' prompt with no detectable context, sending empty response");
-                Ok("".to_string())
-            }
-            
-            _ => {
-                log::warn!("Unknown prompt in random strategy: '{}'", prompt);
-                Err(anyhow::anyhow!("Unknown prompt: '{}'", prompt))
-            }
-        }
+    async fn get_command(&mut self, context: &TurnContext<'_>) -> Result<String> {
+        let prompt = context.prompt;
+        log::debug!("Random strategy handling {:?} prompt at turn {}: '{}'", prompt.kind, context.turn, prompt.prompt.trim());
+        self.decision = None;
+        handlers().answer(self, context)
     }
     
     fn reset(&mut self) {
@@ -392,6 +272,10 @@ impl Strategy for RandomStrategy {
         self.first_turn = true;
     }
     
+    fn last_decision(&self) -> Option<&str> {
+        self.decision.as_deref()
+    }
+    
     fn name(&self) -> &'static str {
         "Random"
     }
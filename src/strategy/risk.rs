@@ -0,0 +1,269 @@
+use crate::game::{GameState, PromptKind};
+use crate::strategy::navigation::{compute_course, format_factor, sector_positions};
+use crate::strategy::{Command, SpeedrunStrategy, Strategy, TurnContext};
+use crate::tune::Param;
+use anyhow::Result;
+
+/// Hit strength assumed until the Enterprise has been hit
+const DEFAULT_HIT: f64 = 200.0;
+
+/// Shields are raised to cover the expected damage plus this many standard deviations
+const SHIELD_MARGIN: f64 = 3.0;
+
+/// Energy kept back from the shields so the ship can still move and fire
+const ENERGY_RESERVE: i32 = 200;
+
+/// What the risk model decided to do at a COMMAND prompt
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RiskAction {
+    Fight,
+    /// Raise the shields to this many units
+    ShieldUp(i32),
+    Flee,
+}
+
+/// What the strategy is waiting for after a defensive command
+#[derive(Debug, Clone, PartialEq)]
+enum Pending {
+    None,
+    Shields(i32),
+    Flee { course: f32 },
+}
+
+/// Risk strategy weighs the chance of being destroyed before the next command against
+/// a risk aversion between 0 (fight unless destruction is more likely than not) and 1
+/// (never stay under fire). Each Klingon in the quadrant is assumed to fire once, with
+/// hits spread evenly up to twice the average seen so far this session; when the chance that they
+/// get through the shields is too high it raises the shields if there is the energy
+/// for it and otherwise flees the quadrant. Fighting and getting around are left to
+/// the speedrun strategy.
+pub struct RiskStrategy {
+    aversion: f64,
+    hits: Vec<i32>,
+    pending: Pending,
+    parent: SpeedrunStrategy,
+    /// Why the last command was chosen, see Strategy::last_decision
+    decision: Option<String>,
+}
+
+impl RiskStrategy {
+    /// Settings `tune` can vary, with the ranges it searches and their defaults
+    pub const TUNABLE: &'static [Param] = &[Param { name: "risk_aversion", min: 0.0, max: 1.0, default: 0.5 }];
+    
+    pub fn new(aversion: f64) -> Self {
+        Self {
+            aversion: aversion.clamp(0.0, 1.0),
+            hits: Vec::new(),
+            pending: Pending::None,
+            parent: SpeedrunStrategy::new(),
+            decision: None,
+        }
+    }
+    
+    /// Plays the same way every time for the same seed and aversion
+    pub fn with_seed(aversion: f64, seed: u64) -> Self {
+        Self { parent: SpeedrunStrategy::with_seed(seed), ..Self::new(aversion) }
+    }
+    
+    /// Record the hits taken since the last command
+    fn observe(&mut self, game_state: &GameState) {
+        for line in &game_state.last_output {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            if let Some(index) = words.windows(2).position(|pair| pair == ["UNIT", "HIT"]) {
+                if let Some(hit) = index.checked_sub(1).and_then(|i| words[i].parse().ok()) {
+                    self.hits.push(hit);
+                }
+            }
+        }
+    }
+    
+    fn average_hit(&self) -> f64 {
+        if self.hits.is_empty() {
+            DEFAULT_HIT
+        } else {
+            self.hits.iter().sum::<i32>() as f64 / self.hits.len() as f64
+        }
+    }
+    
+    /// Mean and standard deviation of the damage `klingons` Klingons deal in one round
+    fn damage(&self, klingons: usize) -> (f64, f64) {
+        let hit = self.average_hit();
+        // Each hit is uniform on [0, 2 * hit], with variance (2 * hit)^2 / 12
+        (klingons as f64 * hit, (klingons as f64 * hit * hit / 3.0).sqrt())
+    }
+    
+    /// Chance that `klingons` Klingons knock down shields of the given strength this turn
+    pub fn destruction_probability(&self, klingons: usize, shields: i32) -> f64 {
+        if klingons == 0 {
+            return 0.0;
+        }
+        let (mean, deviation) = self.damage(klingons);
+        1.0 - normal_cdf((shields as f64 - mean) / deviation)
+    }
+    
+    /// Klingons in the quadrant and the chance they knock down the shields this turn
+    fn danger(&self, game_state: &GameState) -> (usize, f64) {
+        let klingons = game_state.sector_map.as_deref().map_or(0, |map| sector_positions(map, "+K+").len());
+        (klingons, self.destruction_probability(klingons, game_state.shields.unwrap_or(0)))
+    }
+    
+    /// Fight, raise shields or flee, given the state at a COMMAND prompt
+    pub fn decide(&self, game_state: &GameState) -> RiskAction {
+        let (klingons, risk) = self.danger(game_state);
+        let shields = game_state.shields.unwrap_or(0);
+        let tolerated = 0.5 * (1.0 - self.aversion);
+        if risk <= tolerated {
+            return RiskAction::Fight;
+        }
+        
+        let (mean, deviation) = self.damage(klingons);
+        let needed = (mean + SHIELD_MARGIN * deviation).ceil() as i32;
+        let available = game_state.energy.unwrap_or(0) - ENERGY_RESERVE;
+        log::debug!("Risk {:.3} with {} Klingons and shields {} (tolerating {:.3})", risk, klingons, shields, tolerated);
+        if shields < needed && needed <= available {
+            RiskAction::ShieldUp(needed)
+        } else {
+            RiskAction::Flee
+        }
+    }
+    
+    /// Leave the command to the speedrun strategy, along with the reason for it
+    async fn delegate(&mut self, context: &TurnContext<'_>) -> Result<String> {
+        let command = self.parent.get_command(context).await;
+        self.decision = self.parent.last_decision().map(str::to_string);
+        command
+    }
+    
+    /// Course directly away from the closest Klingon
+    pub fn escape_course(game_state: &GameState) -> f32 {
+        let (Some(sector), Some(map)) = (game_state.current_sector, &game_state.sector_map) else {
+            return 1.0;
+        };
+        sector_positions(map, "+K+")
+            .into_iter()
+            .filter_map(|klingon| compute_course(klingon, sector))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map_or(1.0, |(course, _)| course)
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl Strategy for RiskStrategy {
+    async fn get_command(&mut self, context: &TurnContext<'_>) -> Result<String> {
+        let TurnContext { prompt, state: game_state, .. } = *context;
+        self.observe(game_state);
+        self.decision = None;
+        
+        match (self.pending.clone(), prompt.kind) {
+            (_, PromptKind::Command) => self.pending = Pending::None,
+            (Pending::Shields(units), PromptKind::ShieldUnits) => {
+                self.pending = Pending::None;
+                return Ok(units.to_string());
+            }
+            (Pending::Flee { course }, PromptKind::Course) => return Ok(format_factor(course)),
+            (Pending::Flee { .. }, PromptKind::WarpFactor) => {
+                self.pending = Pending::None;
+                return Ok("1".to_string());
+            }
+            _ => {}
+        }
+        if prompt.kind != PromptKind::Command {
+            return self.delegate(context).await;
+        }
+        
+        let (klingons, risk) = self.danger(game_state);
+        match self.decide(game_state) {
+            RiskAction::Fight => self.delegate(context).await,
+            RiskAction::ShieldUp(units) => {
+                self.pending = Pending::Shields(units);
+                self.decision = Some(format!("{:.0}% chance {} Klingons get through the shields, raising them to {}", risk * 100.0, klingons, units));
+                Ok(Command::Shields.to_string())
+            }
+            RiskAction::Flee => {
+                self.pending = Pending::Flee { course: Self::escape_course(game_state) };
+                self.decision = Some(format!("{:.0}% chance {} Klingons get through the shields, more than they can be raised against, fleeing", risk * 100.0, klingons));
+                Ok(Command::Navigation.to_string())
+            }
+        }
+    }
+    
+    fn last_decision(&self) -> Option<&str> {
+        self.decision.as_deref()
+    }
+    
+    fn reset(&mut self) {
+        self.hits.clear();
+        self.pending = Pending::None;
+        self.parent.reset();
+    }
+    
+    /// The hits seen are kept: Klingons hit as hard in the next game
+    fn new_game(&mut self) {
+        self.pending = Pending::None;
+        self.parent.new_game();
+    }
+    
+    fn name(&self) -> &'static str {
+        "Risk"
+    }
+}
+
+/// Standard normal cumulative distribution, using the Abramowitz and Stegun
+/// approximation of erf (error below 1.5e-7)
+fn normal_cdf(x: f64) -> f64 {
+    let z = x.abs() / std::f64::consts::SQRT_2;
+    let t = 1.0 / (1.0 + 0.3275911 * z);
+    let poly = t * (0.254829592 + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let erf = 1.0 - poly * (-z * z).exp();
+    if x >= 0.0 { 0.5 * (1.0 + erf) } else { 0.5 * (1.0 - erf) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn under_fire(klingons: usize, shields: i32, energy: i32) -> GameState {
+        let mut state = GameState::new();
+        let mut sector_map = vec![vec![String::new(); 8]; 8];
+        sector_map[0][0] = "<*>".to_string();
+        for sector in sector_map[7].iter_mut().take(klingons) {
+            *sector = "+K+".to_string();
+        }
+        state.sector_map = Some(sector_map);
+        state.current_sector = Some((1, 1));
+        state.shields = Some(shields);
+        state.energy = Some(energy);
+        state
+    }
+    
+    #[test]
+    fn test_decisions_follow_aversion() {
+        assert!((normal_cdf(0.0) - 0.5).abs() < 1e-6);
+        assert!((normal_cdf(1.96) - 0.975).abs() < 1e-3);
+        
+        let bold = RiskStrategy::new(0.0);
+        let careful = RiskStrategy::new(0.9);
+        assert_eq!(bold.destruction_probability(0, 0), 0.0);
+        assert_eq!(careful.decide(&under_fire(0, 0, 3000)), RiskAction::Fight);
+        
+        // Two Klingons against 450 units of shields: about a 38% chance of going down
+        let state = under_fire(2, 450, 3000);
+        assert_eq!(bold.decide(&state), RiskAction::Fight);
+        assert!(matches!(careful.decide(&state), RiskAction::ShieldUp(units) if units > 450));
+        assert_eq!(careful.decide(&under_fire(2, 450, 500)), RiskAction::Flee);
+    }
+    
+    #[test]
+    fn test_hits_kept_between_games() {
+        let mut strategy = RiskStrategy::new(0.5);
+        let mut state = GameState::new();
+        state.last_output = crate::game::share_lines(vec!["  300 UNIT HIT ON ENTERPRISE FROM SECTOR 8 , 1".to_string()]);
+        strategy.observe(&state);
+        assert_eq!(strategy.average_hit(), 300.0);
+        
+        strategy.new_game();
+        assert_eq!(strategy.average_hit(), 300.0);
+        strategy.reset();
+        assert_eq!(strategy.average_hit(), DEFAULT_HIT);
+    }
+}
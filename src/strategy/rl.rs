@@ -0,0 +1,315 @@
+use crate::compress;
+use crate::game::{GameState, PromptKind};
+use crate::json::Json;
+use crate::strategy::navigation::{format_factor, sector_positions};
+use crate::strategy::{Command, RiskStrategy, SpeedrunStrategy, Strategy, TurnContext};
+use anyhow::{Context, Result};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// How far each update moves a value towards what was just seen
+const LEARNING_RATE: f64 = 0.2;
+
+/// Weight of the value of where an action led against the reward it earned at once
+const DISCOUNT: f64 = 0.9;
+
+/// Chance of trying a random action instead of the best known one while training
+const EXPLORATION: f64 = 0.1;
+
+/// Reward for each Klingon destroyed
+const KLINGON_REWARD: f64 = 1.0;
+
+/// Reward per unit of energy and shields gained, a penalty when spent or lost to hits
+const ENERGY_REWARD: f64 = 0.001;
+
+/// Reward at the end of a game won
+const VICTORY_REWARD: f64 = 10.0;
+
+/// Penalty at the end of a game lost in any way
+const DEFEAT_PENALTY: f64 = 10.0;
+
+/// Shield strength the Shields action aims for, as far as the energy allows
+const SHIELD_UNITS: i32 = 600;
+
+/// Energy kept back from the shields so the ship can still move and fire
+const ENERGY_RESERVE: i32 = 200;
+
+/// What the strategy can choose at a COMMAND prompt
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RlAction {
+    /// Leave the command to the speedrun strategy: fire or move on to the next Klingons
+    Hunt,
+    Shields,
+    Flee,
+}
+
+const ACTIONS: [RlAction; 3] = [RlAction::Hunt, RlAction::Shields, RlAction::Flee];
+
+/// What the strategy is waiting for after a command of its own
+#[derive(Debug, Clone, PartialEq)]
+enum Pending {
+    None,
+    Shields(i32),
+    Flee { course: f32 },
+}
+
+/// Value of each action in each discretized game state: a Q-table, built up over many
+/// games and saved between runs
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Policy {
+    values: BTreeMap<String, [f64; 3]>,
+    /// Games the policy has been trained on
+    pub games: usize,
+}
+
+impl Policy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Load the policy saved at `path`, or start an untrained one if there is none yet
+    pub fn load_or_new(path: &str) -> Result<Self> {
+        if !Path::new(path).exists() {
+            return Ok(Self::default());
+        }
+        let json = Json::parse(&compress::read_file(path)?)?;
+        Self::from_json(&json).with_context(|| format!("{} is not a TrekBot policy file", path))
+    }
+    
+    pub fn save(&self, path: &str) -> Result<()> {
+        compress::write_file(path, &(self.to_json().to_string() + "\n"))
+    }
+    
+    fn to_json(&self) -> Json {
+        let states = self.values.iter().map(|(state, values)| {
+            Json::object()
+                .with("state", state.as_str())
+                .with("values", Json::Array(values.iter().map(|&value| value.into()).collect()))
+        });
+        Json::object()
+            .with("games", self.games)
+            .with("states", Json::Array(states.collect()))
+    }
+    
+    fn from_json(json: &Json) -> Option<Self> {
+        let mut values = BTreeMap::new();
+        for entry in json.get("states")?.as_array()? {
+            let numbers: Vec<f64> = entry.get("values")?.as_array()?.iter().map(Json::as_f64).collect::<Option<_>>()?;
+            values.insert(entry.get("state")?.as_str()?.to_string(), numbers.try_into().ok()?);
+        }
+        Some(Self { values, games: json.get("games")?.as_usize()? })
+    }
+    
+    fn values(&self, state: &str) -> [f64; 3] {
+        self.values.get(state).copied().unwrap_or_default()
+    }
+    
+    /// Index of the action worth most in `state`, the first on a tie
+    fn best(&self, state: &str) -> usize {
+        let values = self.values(state);
+        (0..ACTIONS.len()).fold(0, |best, action| if values[action] > values[best] { action } else { best })
+    }
+    
+    /// Move the value of `action` in `state` towards the reward it earned plus, unless the
+    /// game is over, the discounted value of the best action in the state it led to
+    fn update(&mut self, state: &str, action: usize, reward: f64, next: Option<&str>) {
+        let future = next.map_or(0.0, |next| {
+            let values = self.values(next);
+            DISCOUNT * values.iter().copied().fold(f64::NEG_INFINITY, f64::max)
+        });
+        let value = &mut self.values.entry(state.to_string()).or_default()[action];
+        *value += LEARNING_RATE * (reward + future - *value);
+    }
+}
+
+/// The game state as the policy sees it: Klingons in the quadrant and rough bands of
+/// shields and energy, e.g. "k2-s1-e2"
+pub fn state_key(game_state: &GameState) -> String {
+    let klingons = game_state.sector_map.as_deref().map_or(0, |map| sector_positions(map, "+K+").len());
+    let band = |value: Option<i32>, limits: &[i32]| limits.iter().filter(|&&limit| value.unwrap_or(0) >= limit).count();
+    format!("k{}-s{}-e{}", klingons.min(3), band(game_state.shields, &[100, 300, 600]), band(game_state.energy, &[500, 1500]))
+}
+
+/// How well the game is going, to reward actions by how much they improved it
+fn standing(game_state: &GameState) -> f64 {
+    let energy = game_state.energy.unwrap_or(0) + game_state.shields.unwrap_or(0);
+    energy as f64 * ENERGY_REWARD - game_state.klingons_remaining.unwrap_or(0) as f64 * KLINGON_REWARD
+}
+
+/// Reinforcement learning strategy chooses, at each COMMAND prompt, between hunting
+/// Klingons the speedrun way, raising the shields and fleeing the quadrant, by the
+/// values its policy has learned for the state of the game. While training it
+/// sometimes tries another action at random and updates the policy with Q-learning:
+/// each action is rewarded for Klingons destroyed and energy gained or lost until the
+/// next decision, and the last one of a game for winning or losing it. A policy file
+/// is loaded when the strategy is created and, while training, saved again when it is
+/// dropped, so learning carries over from one game and run to the next.
+pub struct RlStrategy {
+    /// The policy the session started with, restored by reset()
+    initial: Policy,
+    policy: Policy,
+    policy_file: Option<String>,
+    learning: bool,
+    rng: StdRng,
+    /// Seed of the exploration choices, kept so reset() starts them over
+    seed: Option<u64>,
+    /// State and action of the last decision, with the standing at that point
+    last: Option<(String, usize, f64)>,
+    pending: Pending,
+    parent: SpeedrunStrategy,
+}
+
+impl RlStrategy {
+    pub fn new(policy: Policy, learning: bool) -> Self {
+        Self::with_optional_seed(policy, learning, None)
+    }
+    
+    /// Strategy that explores the same way every time for the same seed
+    pub fn with_seed(policy: Policy, learning: bool, seed: u64) -> Self {
+        Self::with_optional_seed(policy, learning, Some(seed))
+    }
+    
+    fn with_optional_seed(policy: Policy, learning: bool, seed: Option<u64>) -> Self {
+        Self {
+            initial: policy.clone(),
+            policy,
+            policy_file: None,
+            learning,
+            rng: seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64),
+            seed,
+            last: None,
+            pending: Pending::None,
+            parent: seed.map_or_else(SpeedrunStrategy::new, SpeedrunStrategy::with_seed),
+        }
+    }
+    
+    /// Save the policy here when the strategy is dropped, if it is learning
+    pub fn set_policy_file(&mut self, path: String) {
+        self.policy_file = Some(path);
+    }
+    
+    /// Pick an action for `state`, learning from how the last one turned out
+    fn decide(&mut self, state: String, game_state: &GameState) -> RlAction {
+        let now = standing(game_state);
+        if let (true, Some((last_state, action, then))) = (self.learning, self.last.take()) {
+            self.policy.update(&last_state, action, now - then, Some(&state));
+        }
+        let action = if self.learning && self.rng.gen_bool(EXPLORATION) {
+            self.rng.gen_range(0..ACTIONS.len())
+        } else {
+            self.policy.best(&state)
+        };
+        self.last = Some((state, action, now));
+        ACTIONS[action]
+    }
+    
+    /// Reward the last decision of the game for how the game ended
+    fn finish(&mut self, game_state: &GameState) {
+        let Some((state, action, then)) = self.last.take() else { return };
+        if !self.learning {
+            return;
+        }
+        let won = game_state.klingons_remaining == Some(0)
+            || game_state.last_output.iter().any(|line| line.contains("CONGRATULATION"));
+        let outcome = if won { VICTORY_REWARD } else { -DEFEAT_PENALTY };
+        self.policy.update(&state, action, standing(game_state) - then + outcome, None);
+        self.policy.games += 1;
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl Strategy for RlStrategy {
+    async fn get_command(&mut self, context: &TurnContext<'_>) -> Result<String> {
+        let TurnContext { prompt, state: game_state, .. } = *context;
+        match (self.pending.clone(), prompt.kind) {
+            (_, PromptKind::Command) => self.pending = Pending::None,
+            (_, PromptKind::Aye) => self.finish(game_state),
+            (Pending::Shields(units), PromptKind::ShieldUnits) => {
+                self.pending = Pending::None;
+                return Ok(units.to_string());
+            }
+            (Pending::Flee { course }, PromptKind::Course) => return Ok(format_factor(course)),
+            (Pending::Flee { .. }, PromptKind::WarpFactor) => {
+                self.pending = Pending::None;
+                return Ok("1".to_string());
+            }
+            _ => {}
+        }
+        if prompt.kind != PromptKind::Command {
+            return self.parent.get_command(context).await;
+        }
+        
+        match self.decide(state_key(game_state), game_state) {
+            RlAction::Hunt => self.parent.get_command(context).await,
+            RlAction::Shields => {
+                let total = game_state.energy.unwrap_or(0) + game_state.shields.unwrap_or(0);
+                self.pending = Pending::Shields(SHIELD_UNITS.min(total - ENERGY_RESERVE).max(0));
+                Ok(Command::Shields.to_string())
+            }
+            RlAction::Flee => {
+                self.pending = Pending::Flee { course: RiskStrategy::escape_course(game_state) };
+                Ok(Command::Navigation.to_string())
+            }
+        }
+    }
+    
+    fn reset(&mut self) {
+        self.policy = self.initial.clone();
+        if let Some(seed) = self.seed {
+            self.rng = StdRng::seed_from_u64(seed);
+        }
+        self.new_game();
+        self.parent.reset();
+    }
+    
+    /// The policy is kept: that is what the strategy learns from game to game
+    fn new_game(&mut self) {
+        self.last = None;
+        self.pending = Pending::None;
+        self.parent.new_game();
+    }
+    
+    fn name(&self) -> &'static str {
+        "RL"
+    }
+}
+
+impl Drop for RlStrategy {
+    fn drop(&mut self) {
+        if let (true, Some(path)) = (self.learning, &self.policy_file) {
+            if let Err(e) = self.policy.save(path) {
+                log::warn!("Failed to save policy to {}: {}", path, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_policy_learns_and_round_trips() {
+        let mut state = GameState::new();
+        let mut sector_map = vec![vec![String::new(); 8]; 8];
+        sector_map[0][0] = "<*>".to_string();
+        sector_map[7][7] = "+K+".to_string();
+        state.sector_map = Some(sector_map);
+        state.shields = Some(350);
+        state.energy = Some(2000);
+        assert_eq!(state_key(&state), "k1-s2-e2");
+        
+        let mut policy = Policy::new();
+        assert_eq!(policy.best("k1-s2-e2"), 0);
+        policy.update("k1-s2-e2", 2, 5.0, None);
+        policy.update("k0-s0-e0", 0, 0.0, Some("k1-s2-e2"));
+        assert_eq!(policy.best("k1-s2-e2"), 2);
+        assert_eq!(policy.values("k1-s2-e2")[2], LEARNING_RATE * 5.0);
+        assert!(policy.values("k0-s0-e0")[0] > 0.0);
+        
+        policy.games = 3;
+        assert_eq!(Policy::from_json(&Json::parse(&policy.to_json().to_string()).unwrap()), Some(policy));
+    }
+}
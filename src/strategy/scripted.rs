@@ -0,0 +1,255 @@
+use crate::game::{Line, PromptKind};
+use crate::strategy::{Strategy, TurnContext};
+use crate::transcript::{Direction, Transcript};
+use anyhow::{Context, Result};
+
+/// Written in a script file for an empty answer, as answer reports show it
+const ENTER: &str = "[ENTER]";
+
+/// One answer of a script, and the kind of prompt it is for if the script names one
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScriptStep {
+    pub prompt: Option<PromptKind>,
+    pub answer: String,
+}
+
+/// Text the game must print in answer to a script's previous answer, from a line like
+/// `expect: "KLINGON DESTROYED" within 3 lines`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expectation {
+    /// Matched anywhere in a line, ignoring case
+    pub text: String,
+    /// Only the first this many lines of the output count; all of it if None
+    pub within: Option<usize>,
+    /// Answers before it in the script; 0 checks the output before the first prompt is answered
+    pub after: usize,
+    /// Line of the script it was written on
+    pub line: usize,
+}
+
+impl Expectation {
+    /// Parse what follows `expect:`, a quoted text optionally followed by `within N lines`
+    fn parse(spec: &str, after: usize, line: usize) -> Result<Self> {
+        let invalid = || anyhow::anyhow!("Script line {}: expected `expect: \"TEXT\"`, optionally followed by `within N lines`", line);
+        let (text, rest) = spec.trim().strip_prefix('"').and_then(|spec| spec.split_once('"')).ok_or_else(invalid)?;
+        let words: Vec<&str> = rest.split_whitespace().collect();
+        let within = match words[..] {
+            [] => None,
+            ["within", count, "line" | "lines"] => Some(count.parse().map_err(|_| invalid())?),
+            _ => return Err(invalid()),
+        };
+        Ok(Self { text: text.to_string(), within, after, line })
+    }
+    
+    /// Whether the output meets the expectation
+    pub fn met_by(&self, output: &[Line]) -> bool {
+        let text = self.text.to_uppercase();
+        output.iter().take(self.within.unwrap_or(usize::MAX)).any(|line| line.to_uppercase().contains(&text))
+    }
+}
+
+/// The answers of a script and what the game is expected to print in between
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Script {
+    pub steps: Vec<ScriptStep>,
+    pub expectations: Vec<Expectation>,
+}
+
+/// Parse a script: one answer per line, `[ENTER]` for an empty one, optionally after
+/// the kind of prompt it answers and a colon, e.g. `WarpFactor: 2`. A line starting
+/// with `expect:` is instead text the previous answer must make the game print, see
+/// Expectation. Blank lines and lines starting with # are skipped.
+pub fn parse_script(text: &str) -> Result<Script> {
+    let mut script = Script::default();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(spec) = line.strip_prefix("expect:") {
+            script.expectations.push(Expectation::parse(spec, script.steps.len(), index + 1)?);
+            continue;
+        }
+        let (prompt, answer) = match line.split_once(':') {
+            Some((kind, answer)) if PromptKind::from_name(kind).is_some() => (PromptKind::from_name(kind), answer.trim()),
+            _ => (None, line),
+        };
+        let answer = if answer == ENTER { "" } else { answer };
+        script.steps.push(ScriptStep { prompt, answer: answer.to_string() });
+    }
+    Ok(script)
+}
+
+/// Sends a fixed list of answers in order, e.g. the commands of a recorded game to play
+/// it again on another interpreter build, or a script file for a regression test. An
+/// answer without a prompt kind goes to whatever prompt comes next. One with a kind
+/// waits for a prompt of that kind; with a default the prompts before it get the
+/// default, otherwise the game stops with an error. Once the script is used up the
+/// default answers everything, or without one the game stops. Expectations are checked
+/// against the output of the answer before them, and the first one not met fails the game.
+pub struct ScriptedStrategy {
+    steps: Vec<ScriptStep>,
+    next: usize,
+    default: Option<String>,
+    expectations: Vec<Expectation>,
+    /// No output has been checked since the last answer from the script
+    unchecked: bool,
+}
+
+impl ScriptedStrategy {
+    pub fn new(commands: Vec<String>) -> Self {
+        Self::from_steps(commands.into_iter().map(|answer| ScriptStep { prompt: None, answer }).collect())
+    }
+    
+    pub fn from_steps(steps: Vec<ScriptStep>) -> Self {
+        Self::from_script(Script { steps, expectations: Vec::new() })
+    }
+    
+    pub fn from_script(script: Script) -> Self {
+        Self { steps: script.steps, next: 0, default: None, expectations: script.expectations, unchecked: true }
+    }
+    
+    /// Script read from a file in the format of parse_script()
+    pub fn load(path: &str) -> Result<Self> {
+        let text = std::fs::read_to_string(path).with_context(|| format!("Failed to read script {}", path))?;
+        Ok(Self::from_script(parse_script(&text).with_context(|| format!("Invalid script {}", path))?))
+    }
+    
+    /// Every input of a recorded game, exactly as it was sent, to reproduce the game
+    pub fn from_transcript(transcript: &Transcript) -> Self {
+        let inputs = transcript.entries().iter()
+            .filter(|entry| entry.direction == Direction::Input)
+            .map(|entry| entry.text.clone())
+            .collect();
+        Self::new(inputs)
+    }
+    
+    /// Answer to send when the script has nothing for a prompt
+    pub fn set_default(&mut self, default: Option<String>) {
+        self.default = default;
+    }
+    
+    fn default_or(&self, error: impl FnOnce() -> anyhow::Error) -> Result<String> {
+        self.default.clone().ok_or_else(error)
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl Strategy for ScriptedStrategy {
+    async fn get_command(&mut self, context: &TurnContext<'_>) -> Result<String> {
+        let TurnContext { prompt, .. } = *context;
+        match self.steps.get(self.next) {
+            Some(step) if step.prompt.is_none_or(|kind| kind == prompt.kind) => {
+                self.next += 1;
+                self.unchecked = true;
+                Ok(step.answer.clone())
+            }
+            Some(step) => self.default_or(|| {
+                anyhow::anyhow!("Script answer {} is for a {:?} prompt, not {:?}", self.next + 1, step.prompt.unwrap_or_default(), prompt.kind)
+            }),
+            None => self.default_or(|| anyhow::anyhow!("Script ran out after {} commands", self.steps.len())),
+        }
+    }
+    
+    fn reset(&mut self) {
+        self.next = 0;
+        self.unchecked = true;
+    }
+    
+    fn check_output(&mut self, output: &[Line]) -> Option<String> {
+        if !std::mem::take(&mut self.unchecked) {
+            return None;
+        }
+        let answered = self.next.checked_sub(1).map(|index| self.steps[index].answer.as_str());
+        let broken = self.expectations.iter().find(|expectation| expectation.after == self.next && !expectation.met_by(output))?;
+        let within = broken.within.map_or(String::new(), |lines| format!(" within {} lines", lines));
+        Some(match answered {
+            Some(answer) => format!("script line {} expected {:?}{} after the answer {:?}", broken.line, broken.text, within, answer),
+            None => format!("script line {} expected {:?}{} before the first answer", broken.line, broken.text, within),
+        })
+    }
+    
+    /// Without a default the game ends with the script
+    fn commands_left(&self) -> Option<usize> {
+        self.default.is_none().then(|| self.steps.len().saturating_sub(self.next))
+    }
+    
+    fn name(&self) -> &'static str {
+        "Scripted"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{GameState, PromptContext};
+    
+    fn prompt(kind: PromptKind) -> PromptContext {
+        PromptContext { kind, ..PromptContext::default() }
+    }
+    
+    #[tokio::test]
+    async fn test_script_file() {
+        let steps = parse_script("# Warp one quadrant east\nNAV\ncourse: 1\n\nWarpFactor: 1\n[ENTER]\nCOM\n").unwrap().steps;
+        assert_eq!(steps.len(), 5);
+        assert_eq!(steps[1], ScriptStep { prompt: Some(PromptKind::Course), answer: "1".to_string() });
+        assert_eq!(steps[3].answer, "");
+        
+        let state = GameState::new();
+        let mut strategy = ScriptedStrategy::from_steps(steps.clone());
+        assert_eq!(strategy.get_command(&TurnContext::new(&prompt(PromptKind::Command), &state)).await.unwrap(), "NAV");
+        assert!(strategy.get_command(&TurnContext::new(&prompt(PromptKind::Message), &state)).await.is_err());
+        
+        // A default answers the prompts in between and anything after the script
+        let mut strategy = ScriptedStrategy::from_steps(steps);
+        strategy.set_default(Some("SRS".to_string()));
+        let mut answers = Vec::new();
+        for kind in [PromptKind::Command, PromptKind::Message, PromptKind::Course, PromptKind::WarpFactor] {
+            answers.push(strategy.get_command(&TurnContext::new(&prompt(kind), &state)).await.unwrap());
+        }
+        assert_eq!(answers, ["NAV", "SRS", "1", "1"]);
+        strategy.get_command(&TurnContext::new(&prompt(PromptKind::Message), &state)).await.unwrap();
+        strategy.get_command(&TurnContext::new(&prompt(PromptKind::Command), &state)).await.unwrap();
+        assert_eq!(strategy.get_command(&TurnContext::new(&prompt(PromptKind::Command), &state)).await.unwrap(), "SRS");
+        assert_eq!(strategy.commands_left(), None);
+    }
+    
+    #[tokio::test]
+    async fn test_expectations() {
+        let script = parse_script("expect: \"orders\"\nTOR\n1\nexpect: \"KLINGON DESTROYED\" within 2 lines\nSRS\n").unwrap();
+        assert_eq!(script.steps.len(), 3);
+        assert_eq!(script.expectations[1], Expectation { text: "KLINGON DESTROYED".to_string(), within: Some(2), after: 2, line: 4 });
+        assert!(parse_script("expect: KLINGON DESTROYED").is_err());
+        assert!(parse_script("expect: \"KLINGON\" within three lines").is_err());
+        
+        let lines = |lines: &[&str]| crate::game::share_lines(lines.iter().map(|line| line.to_string()).collect());
+        let state = GameState::new();
+        let mut strategy = ScriptedStrategy::from_script(script);
+        assert_eq!(strategy.check_output(&lines(&["YOUR ORDERS ARE AS FOLLOWS:", "COMMAND?"])), None);
+        // Output is only checked once per answer
+        assert_eq!(strategy.check_output(&lines(&["COMMAND?"])), None);
+        strategy.get_command(&TurnContext::new(&prompt(PromptKind::Command), &state)).await.unwrap();
+        strategy.get_command(&TurnContext::new(&prompt(PromptKind::TorpedoCourse), &state)).await.unwrap();
+        let broken = strategy.check_output(&lines(&["TORPEDO TRACK:", "  4 , 5", "*** KLINGON DESTROYED ***", "COMMAND?"])).unwrap();
+        assert_eq!(broken, "script line 4 expected \"KLINGON DESTROYED\" within 2 lines after the answer \"1\"");
+        
+        strategy.reset();
+        assert!(strategy.check_output(&lines(&["COMMAND?"])).is_some());
+    }
+    
+    #[tokio::test]
+    async fn test_replay_transcript() {
+        let mut transcript = Transcript::new();
+        transcript.record_output(&["COMMAND?".to_string()]);
+        transcript.record_input("NAV");
+        transcript.record_output(&["COURSE (0-9)?".to_string()]);
+        transcript.record_input(" 1.50 ");
+        let mut strategy = ScriptedStrategy::from_transcript(&transcript);
+        assert_eq!(strategy.commands_left(), Some(2));
+        
+        let state = GameState::new();
+        strategy.get_command(&TurnContext::new(&prompt(PromptKind::Command), &state)).await.unwrap();
+        assert_eq!(strategy.get_command(&TurnContext::new(&prompt(PromptKind::Course), &state)).await.unwrap(), " 1.50 ");
+        assert_eq!(strategy.commands_left(), Some(0));
+    }
+}
@@ -0,0 +1,181 @@
+use crate::strategy::{Strategy, TurnContext};
+use anyhow::{Context, Result};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rhai::{CallFnOptions, Dynamic, Engine, EvalAltResult, Map, Scope, AST};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Operations a script may run for one answer before it is stopped
+pub const SCRIPT_OPERATIONS_PER_TURN: u64 = 10_000_000;
+
+/// Deepest a script's function calls may nest
+pub const SCRIPT_MAX_CALL_LEVELS: usize = 64;
+
+/// Longest string, and most elements of an array or map, a script may build
+pub const SCRIPT_MAX_STRING_SIZE: usize = 1 << 20;
+pub const SCRIPT_MAX_COLLECTION_SIZE: usize = 100_000;
+
+/// A strategy written as a Rhai script, loaded with `--strategy script:<path>`. The script
+/// defines `get_command(turn)`, which gets the turn as a map in the shape of
+/// TurnContext::to_json and returns the answer:
+///
+/// ```rhai
+/// fn get_command(turn) {
+///     if turn.prompt.kind != "Command" { return "1"; }
+///     this.commands += 1;
+///     if turn.state.shields == 0 { "SHE" } else { ["NAV", "SRS", "PHA"][random(3)] }
+/// }
+/// ```
+///
+/// `this` is a map the script can keep what it likes in for the rest of the game, and
+/// `this.decision`, if the script sets it for an answer, is the reason given for it.
+/// `random(n)` picks a number below n, the same ones every time for the same seed, and
+/// `print` goes to the log. Like a WASM strategy, an answer is cut off after
+/// SCRIPT_OPERATIONS_PER_TURN operations, and strings, arrays and call depth are capped.
+pub struct RhaiStrategy {
+    engine: Engine,
+    ast: AST,
+    /// `this` for the script, cleared for each game
+    memory: Dynamic,
+    rng: Rc<RefCell<StdRng>>,
+    seed: Option<u64>,
+    decision: Option<String>,
+    path: String,
+}
+
+impl RhaiStrategy {
+    /// Compile the script at `path`, with its random numbers fixed by `seed` if given
+    pub fn load(path: &str, seed: Option<u64>) -> Result<Self> {
+        let source = std::fs::read_to_string(path).with_context(|| format!("Failed to read strategy script {}", path))?;
+        let rng = Rc::new(RefCell::new(new_rng(seed)));
+        let mut engine = Engine::new();
+        engine.set_max_operations(SCRIPT_OPERATIONS_PER_TURN)
+            .set_max_call_levels(SCRIPT_MAX_CALL_LEVELS)
+            .set_max_string_size(SCRIPT_MAX_STRING_SIZE)
+            .set_max_array_size(SCRIPT_MAX_COLLECTION_SIZE)
+            .set_max_map_size(SCRIPT_MAX_COLLECTION_SIZE);
+        let script_rng = rng.clone();
+        engine.register_fn("random", move |below: i64| -> i64 { script_rng.borrow_mut().gen_range(0..below.max(1)) });
+        engine.on_print(|text| log::info!("{}", text));
+        let ast = engine.compile(&source).map_err(|e| anyhow::anyhow!("Invalid strategy script {}: {}", path, e))?;
+        if !ast.iter_functions().any(|function| function.name == "get_command" && function.params.len() == 1) {
+            anyhow::bail!("Strategy script {} doesn't define get_command(turn)", path);
+        }
+        Ok(Self { engine, ast, memory: Map::new().into(), rng, seed, decision: None, path: path.to_string() })
+    }
+}
+
+/// Seeded random numbers, or ones from entropy without a seed
+fn new_rng(seed: Option<u64>) -> StdRng {
+    seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64)
+}
+
+#[async_trait::async_trait(?Send)]
+impl Strategy for RhaiStrategy {
+    async fn get_command(&mut self, context: &TurnContext<'_>) -> Result<String> {
+        let turn = self.engine.parse_json(context.to_json().to_string(), true)
+            .map_err(|e| anyhow::anyhow!("Failed to hand the turn to {}: {}", self.path, e))?;
+        if let Some(mut memory) = self.memory.write_lock::<Map>() {
+            memory.remove("decision");
+        }
+        let options = CallFnOptions::new().eval_ast(false).bind_this_ptr(&mut self.memory);
+        let answer = self.engine
+            .call_fn_with_options::<Dynamic>(options, &mut Scope::new(), &self.ast, "get_command", (turn,))
+            .map_err(|e| match *e {
+                EvalAltResult::ErrorTooManyOperations(_) => anyhow::anyhow!(
+                    "Strategy script {} ran out of operations at turn {} (more than {})", self.path, context.turn, SCRIPT_OPERATIONS_PER_TURN),
+                _ => anyhow::anyhow!("Strategy script {} failed at turn {}: {}", self.path, context.turn, e),
+            })?;
+        self.decision = self.memory.read_lock::<Map>()
+            .and_then(|memory| memory.get("decision").map(|decision| decision.to_string()));
+        match answer.into_string() {
+            Ok(command) => Ok(command),
+            Err(type_name) => anyhow::bail!("Strategy script {} answered with a {} instead of a string", self.path, type_name),
+        }
+    }
+    
+    fn reset(&mut self) {
+        self.memory = Map::new().into();
+        self.decision = None;
+        *self.rng.borrow_mut() = new_rng(self.seed);
+    }
+    
+    fn new_game(&mut self) {
+        // The random numbers carry on through the session rather than repeat each game
+        self.memory = Map::new().into();
+        self.decision = None;
+    }
+    
+    fn last_decision(&self) -> Option<&str> {
+        self.decision.as_deref()
+    }
+    
+    fn name(&self) -> &'static str {
+        "Script"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{GameState, PromptContext, PromptKind};
+    
+    #[tokio::test]
+    async fn test_script_strategy() {
+        let path = std::env::temp_dir().join(format!("trekbot-strategy-{}.rhai", std::process::id()));
+        std::fs::write(&path, r#"
+            fn get_command(turn) {
+                if turn.prompt.kind != "Command" { return "1"; }
+                this.decision = "shields at " + turn.state.shields;
+                if turn.state.shields == 0 { "SHE" } else { ["NAV", "SRS"][random(2)] }
+            }
+        "#).unwrap();
+        let mut strategy = RhaiStrategy::load(&path.to_string_lossy(), Some(3)).unwrap();
+        let command = PromptContext { kind: PromptKind::Command, ..PromptContext::default() };
+        let mut state = GameState::new();
+        state.shields = Some(0);
+        assert_eq!(strategy.get_command(&TurnContext::new(&command, &state)).await.unwrap(), "SHE");
+        assert_eq!(strategy.last_decision(), Some("shields at 0"));
+        let course = PromptContext { kind: PromptKind::Course, ..PromptContext::default() };
+        assert_eq!(strategy.get_command(&TurnContext::new(&course, &state)).await.unwrap(), "1");
+        assert_eq!(strategy.last_decision(), None);
+        
+        std::fs::write(&path, "fn answer(turn) { \"SRS\" }").unwrap();
+        assert!(RhaiStrategy::load(&path.to_string_lossy(), None).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }    
+    #[tokio::test]
+    async fn test_script_limits_and_random() {
+        let path = std::env::temp_dir().join(format!("trekbot-random-{}.rhai", std::process::id()));
+        std::fs::write(&path, r#"
+            fn get_command(turn) {
+                if turn.prompt.kind == "Course" { loop {} }
+                "" + random(10) + " "
+            }
+        "#).unwrap();
+        let mut strategy = RhaiStrategy::load(&path.to_string_lossy(), Some(3)).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        
+        // The random numbers carry on into the next game and start over in a new session
+        let first = numbers(&mut strategy).await;
+        strategy.new_game();
+        assert_ne!(numbers(&mut strategy).await, first);
+        strategy.reset();
+        assert_eq!(numbers(&mut strategy).await, first);
+        
+        let course = PromptContext { kind: PromptKind::Course, ..PromptContext::default() };
+        let error = strategy.get_command(&TurnContext::new(&course, &GameState::new())).await.unwrap_err();
+        assert!(error.to_string().contains("ran out of operations"));
+    }
+    
+    /// The answers to eight turns of a game
+    async fn numbers(strategy: &mut RhaiStrategy) -> String {
+        let (prompt, state) = (PromptContext::default(), GameState::new());
+        let mut numbers = String::new();
+        for _ in 0..8 {
+            numbers += &strategy.get_command(&TurnContext::new(&prompt, &state)).await.unwrap();
+        }
+        numbers
+    }
+}
@@ -0,0 +1,214 @@
+use crate::game::{GameState, PromptKind};
+use crate::strategy::navigation::{sector_positions, Maneuvers};
+use crate::strategy::planner::GalaxyChart;
+use crate::strategy::targeting::firing_solution;
+use crate::strategy::{Command, RandomStrategy, Strategy, TurnContext};
+use anyhow::Result;
+
+/// Adjacent quadrants to try when no Klingons are known: course and quadrant offset
+const SWEEP: &[(f32, (i32, i32))] = &[(1.0, (0, 1)), (7.0, (1, 0)), (5.0, (0, -1)), (3.0, (-1, 0))];
+
+/// What the speedrun is waiting for after issuing a command
+#[derive(Debug, Clone, PartialEq)]
+enum Pending {
+    None,
+    /// Sent PHA, waiting for the units to fire
+    Phasers,
+}
+
+/// Speedrun strategy goes for victory in as few turns as it can. It fires torpedoes at
+/// the closest Klingon in the quadrant that a course clear of stars and starbases
+/// reaches (phasers when none does or the tubes are empty), then flies straight to the nearest quadrant a long range scan showed Klingons in.
+/// It never raises shields or docks, so nearly every command goes through the
+/// navigation and combat code.
+pub struct SpeedrunStrategy {
+    pending: Pending,
+    maneuvers: Maneuvers,
+    chart: GalaxyChart,
+    explore_index: usize,
+    fallback: RandomStrategy,
+    /// Seed of the fallback's random choices, kept so reset() starts them over
+    seed: Option<u64>,
+    /// Why the last command was chosen, see Strategy::last_decision
+    decision: Option<String>,
+}
+
+impl SpeedrunStrategy {
+    pub fn new() -> Self {
+        Self {
+            pending: Pending::None,
+            maneuvers: Maneuvers::new(),
+            chart: GalaxyChart::new(),
+            explore_index: 0,
+            fallback: RandomStrategy::new(),
+            seed: None,
+            decision: None,
+        }
+    }
+    
+    /// Falls back on random choices that are the same every time for the same seed
+    pub fn with_seed(seed: u64) -> Self {
+        Self { fallback: RandomStrategy::with_seed(seed), seed: Some(seed), ..Self::new() }
+    }
+    
+    /// Track torpedo results and long range scan readings from the latest output
+    fn observe(&mut self, game_state: &GameState) {
+        self.maneuvers.observe(&game_state.last_output);
+        self.chart.observe(game_state);
+    }
+    
+    fn decide(&mut self, reason: String) {
+        self.decision = Some(reason);
+    }
+    
+    /// Answer as the random strategy would, and for its reasons
+    async fn fall_back(&mut self, context: &TurnContext<'_>) -> Result<String> {
+        let command = self.fallback.get_command(context).await;
+        self.decision = self.fallback.last_decision().map(str::to_string);
+        command
+    }
+    
+    /// Pick the next command at the COMMAND prompt
+    async fn next_command(&mut self, context: &TurnContext<'_>) -> Result<String> {
+        let game_state = context.state;
+        if self.maneuvers.take_stale_scan() {
+            self.decide("the short range scan is out of date".to_string());
+            return Ok(Command::ShortRangeScan.to_string());
+        }
+        let (quadrant, sector, sector_map) = match (game_state.current_quadrant, game_state.current_sector, &game_state.sector_map) {
+            (Some(quadrant), Some(sector), Some(sector_map)) => (quadrant, sector, sector_map),
+            _ => return self.fall_back(context).await,
+        };
+        
+        // Klingons here: shoot the closest one in the clear
+        self.chart.observe_sectors(quadrant, sector_map);
+        let klingons = sector_positions(sector_map, "+K+").len();
+        if klingons > 0 {
+            self.maneuvers.stale_scan = true;
+            let loaded = game_state.torpedoes.is_none_or(|torpedoes| torpedoes > 0);
+            if let (false, true, Some(solution)) = (self.maneuvers.torpedo_blocked, loaded, firing_solution(sector_map)) {
+                self.decide(format!("{} Klingons in the quadrant, the closest in a clear torpedo line", klingons));
+                return Ok(self.maneuvers.fire_torpedo(solution.course));
+            }
+            let why_not = if !loaded {
+                "no torpedoes left"
+            } else if self.maneuvers.torpedo_blocked {
+                "the last torpedo missed"
+            } else {
+                "none in a clear torpedo line"
+            };
+            self.pending = Pending::Phasers;
+            self.decide(format!("{} Klingons in the quadrant, {}", klingons, why_not));
+            return Ok(Command::Phasers.to_string());
+        }
+        
+        if self.chart.mark_scanned(quadrant) {
+            self.decide("no Klingons here and the quadrants around not scanned yet".to_string());
+            return Ok(Command::LongRangeScan.to_string());
+        }
+        
+        // Fly to the closest quadrant known to hold Klingons
+        if let Some(route) = self.chart.nearest_klingons(quadrant, sector) {
+            self.decide(format!("closest Klingons known are in quadrant {},{}", route.target.0, route.target.1));
+            return Ok(self.navigate(route.course, route.warp));
+        }
+        
+        // None known: move on to an adjacent quadrant that hasn't been scanned
+        for _ in 0..SWEEP.len() {
+            let (course, (dq1, dq2)) = SWEEP[self.explore_index % SWEEP.len()];
+            let next = (quadrant.0 + dq1, quadrant.1 + dq2);
+            if (1..=8).contains(&next.0) && (1..=8).contains(&next.1) && !self.chart.is_scanned_from(next) {
+                self.decide(format!("no Klingons known, exploring quadrant {},{}", next.0, next.1));
+                return Ok(self.navigate(course, 1.0));
+            }
+            self.explore_index += 1;
+        }
+        self.explore_index += 1;
+        self.decide("no Klingons known and the quadrants around all scanned, moving on".to_string());
+        let (course, _) = SWEEP[self.explore_index % SWEEP.len()];
+        Ok(self.navigate(course, 1.0))
+    }
+    
+    fn navigate(&mut self, course: f32, warp: f32) -> String {
+        self.maneuvers.navigate(course, warp)
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl Strategy for SpeedrunStrategy {
+    async fn get_command(&mut self, context: &TurnContext<'_>) -> Result<String> {
+        let TurnContext { prompt, state: game_state, .. } = *context;
+        self.observe(game_state);
+        self.decision = None;
+        
+        if let Some(answer) = self.maneuvers.answer(prompt.kind) {
+            return Ok(answer);
+        }
+        match (&self.pending, prompt.kind) {
+            (_, PromptKind::Command) => {
+                // Anything still pending was refused (e.g. a damaged system)
+                self.pending = Pending::None;
+                self.maneuvers.cancel();
+                self.next_command(context).await
+            }
+            (Pending::Phasers, PromptKind::PhaserUnits) => {
+                self.pending = Pending::None;
+                let energy = prompt.energy_available.or(game_state.energy).unwrap_or(200);
+                let units = (energy / 2).max(1);
+                self.decide(format!("firing {} phaser units, half of the {} available", units, energy));
+                Ok(units.to_string())
+            }
+            _ => self.fall_back(context).await,
+        }
+    }
+    
+    fn reset(&mut self) {
+        *self = self.seed.map_or_else(Self::new, Self::with_seed);
+    }
+    
+    fn last_decision(&self) -> Option<&str> {
+        self.decision.as_deref()
+    }
+    
+    fn name(&self) -> &'static str {
+        "Speedrun"
+    }
+}
+
+impl Default for SpeedrunStrategy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::PromptContext;
+    
+    fn command_prompt() -> PromptContext {
+        PromptContext { kind: PromptKind::Command, ..PromptContext::default() }
+    }
+    
+    #[tokio::test]
+    async fn test_shoots_nearest_klingon() {
+        let mut strategy = SpeedrunStrategy::new();
+        let mut state = GameState::new();
+        assert_eq!(strategy.get_command(&TurnContext::new(&command_prompt(), &state)).await.unwrap(), "SRS");
+        
+        let mut sector_map = vec![vec![String::new(); 8]; 8];
+        sector_map[3][3] = "<*>".to_string();
+        sector_map[3][6] = "+K+".to_string();
+        sector_map[0][0] = "+K+".to_string();
+        state.sector_map = Some(sector_map);
+        state.current_quadrant = Some((2, 2));
+        state.current_sector = Some((4, 4));
+        state.torpedoes = Some(10);
+        
+        assert_eq!(strategy.get_command(&TurnContext::new(&command_prompt(), &state)).await.unwrap(), "TOR");
+        assert_eq!(strategy.last_decision(), Some("2 Klingons in the quadrant, the closest in a clear torpedo line"));
+        let course = PromptContext { kind: PromptKind::TorpedoCourse, ..PromptContext::default() };
+        assert_eq!(strategy.get_command(&TurnContext::new(&course, &state)).await.unwrap(), "1");
+        assert_eq!(strategy.get_command(&TurnContext::new(&command_prompt(), &state)).await.unwrap(), "SRS");
+    }
+}
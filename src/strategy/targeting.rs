@@ -0,0 +1,99 @@
+use crate::strategy::navigation::{compute_course, sector_positions, torpedo_track};
+
+/// How far either side of the direct course to look for a way past something in the way
+const MAX_DEFLECTION: f32 = 0.5;
+
+/// Steps between the courses tried, the finest the game's course arithmetic tells apart
+/// at short range
+const DEFLECTION_STEP: f32 = 0.01;
+
+/// A photon torpedo course that hits a Klingon before passing through anything else
+#[derive(Debug, Clone, PartialEq)]
+pub struct FiringSolution {
+    /// Sector of the Klingon
+    pub target: (i32, i32),
+    /// Course to enter at the torpedo course prompt, 1 up to but not including 9
+    pub course: f32,
+    pub distance: f32,
+}
+
+/// Firing solutions for every Klingon on a short range scan that a torpedo from the
+/// Enterprise can reach, closest first. Each uses the direct course when it is clear,
+/// otherwise the nearest course up to half a point either side whose track still gets
+/// there first. Klingons behind stars, starbases or other Klingons on every such course
+/// are left out.
+pub fn firing_solutions(sector_map: &[Vec<String>]) -> Vec<FiringSolution> {
+    let Some(&enterprise) = sector_positions(sector_map, "<*>").first() else { return Vec::new() };
+    let mut solutions: Vec<FiringSolution> = sector_positions(sector_map, "+K+")
+        .into_iter()
+        .filter_map(|target| {
+            let (direct, distance) = compute_course(enterprise, target)?;
+            let course = deflections().map(|offset| wrap(direct + offset)).find(|&course| hits(sector_map, enterprise, target, course))?;
+            Some(FiringSolution { target, course, distance })
+        })
+        .collect();
+    solutions.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+    solutions
+}
+
+/// The closest Klingon a torpedo can hit, if any
+pub fn firing_solution(sector_map: &[Vec<String>]) -> Option<FiringSolution> {
+    firing_solutions(sector_map).into_iter().next()
+}
+
+/// 0, then alternately further to each side: 0.01, -0.01, 0.02, ...
+fn deflections() -> impl Iterator<Item = f32> {
+    let steps = (MAX_DEFLECTION / DEFLECTION_STEP).round() as i32;
+    std::iter::once(0.0).chain((1..=steps).flat_map(|step| {
+        let offset = step as f32 * DEFLECTION_STEP;
+        [offset, -offset]
+    }))
+}
+
+/// Bring a course back into 1 up to but not including 9
+fn wrap(course: f32) -> f32 {
+    let course = (course - 1.0).rem_euclid(8.0) + 1.0;
+    // Rounded as format_factor would send it, so the game flies exactly this course
+    (course * 1000.0).round() / 1000.0
+}
+
+/// Whether a torpedo on `course` reaches `target` through empty sectors only
+fn hits(sector_map: &[Vec<String>], from: (i32, i32), target: (i32, i32), course: f32) -> bool {
+    let track = torpedo_track(from, course as f64);
+    let Some(hit) = track.iter().position(|&passed| passed == target) else { return false };
+    track[..hit].iter().all(|&(row, column)| sector_map[row as usize - 1][column as usize - 1].is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn sector_map(objects: &[((i32, i32), &str)]) -> Vec<Vec<String>> {
+        let mut map = vec![vec![String::new(); 8]; 8];
+        for &((row, column), symbol) in objects {
+            map[row as usize - 1][column as usize - 1] = symbol.to_string();
+        }
+        map
+    }
+    
+    #[test]
+    fn test_firing_solutions() {
+        // Straight along the row, and nothing to aim from without the Enterprise
+        let open = sector_map(&[((4, 2), "<*>"), ((4, 7), "+K+")]);
+        assert_eq!(firing_solution(&open), Some(FiringSolution { target: (4, 7), course: 1.0, distance: 5.0 }));
+        assert!(firing_solutions(&sector_map(&[((4, 7), "+K+")])).is_empty());
+        
+        // A star on the direct course to the far Klingon: one a little off it gets past
+        let blocked = sector_map(&[((8, 1), "<*>"), ((4, 3), "*"), ((1, 4), "+K+"), ((8, 8), "+K+")]);
+        let solutions = firing_solutions(&blocked);
+        assert_eq!(solutions.len(), 2);
+        assert_eq!((solutions[0].target, solutions[0].course), ((8, 8), 1.0));
+        assert_eq!(solutions[1].target, (1, 4));
+        assert!(!hits(&blocked, (8, 1), (1, 4), compute_course((8, 1), (1, 4)).unwrap().0));
+        assert!(hits(&blocked, (8, 1), (1, 4), solutions[1].course));
+        
+        // Boxed in behind a starbase: no course reaches it
+        let boxed = sector_map(&[((1, 1), "<*>"), ((1, 2), ">!<"), ((1, 3), "+K+")]);
+        assert_eq!(firing_solution(&boxed), None);
+    }
+}
@@ -0,0 +1,344 @@
+use crate::strategy::{Strategy, TurnContext};
+use anyhow::{Context, Result};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use wasmtime::{Caller, Config, Engine, Linker, Module, Store, StoreLimits, StoreLimitsBuilder, Trap, TypedFunc};
+
+/// Instructions, roughly, a WASM strategy may run for one answer before it is stopped
+pub const WASM_FUEL_PER_TURN: u64 = 50_000_000;
+
+/// Most memory a WASM strategy may grow to
+pub const WASM_MEMORY_LIMIT: usize = 64 << 20;
+
+/// Reads one number of the turn
+type NumberFn = fn(&TurnContext) -> Option<i32>;
+
+/// Numbers a WASM strategy can ask for, each an import of the `trekbot` module taking
+/// nothing and returning an i32, -1 when unknown
+const NUMBERS: &[(&str, NumberFn)] = &[
+    ("turn", |context| i32::try_from(context.turn).ok()),
+    ("energy", |context| context.state.energy),
+    ("shields", |context| context.state.shields),
+    ("torpedoes", |context| context.state.torpedoes),
+    ("klingons", |context| context.state.klingons_remaining),
+    ("starbases", |context| context.state.starbases),
+    ("stardate", |context| context.state.stardate),
+    ("time_remaining", |context| context.state.time_remaining),
+    ("quadrant_row", |context| context.state.current_quadrant.map(|(row, _)| row)),
+    ("quadrant_column", |context| context.state.current_quadrant.map(|(_, column)| column)),
+    ("sector_row", |context| context.state.current_sector.map(|(row, _)| row)),
+    ("sector_column", |context| context.state.current_sector.map(|(_, column)| column)),
+    ("docked", |context| Some(context.state.is_docked() as i32)),
+    ("energy_available", |context| context.prompt.energy_available),
+];
+
+/// A strategy compiled to WebAssembly, loaded with `--strategy wasm:<path>` (a .wasm or
+/// .wat file). It runs sandboxed: it gets no WASI, so no files, network or clock, only
+/// what the `trekbot` import module gives it, and each answer is cut off after
+/// WASM_FUEL_PER_TURN instructions and WASM_MEMORY_LIMIT bytes. That makes it safe to
+/// run strategies contributed by people a benchmark farm doesn't know.
+///
+/// The module exports its `memory` and `get_command()`, which answers the prompt the
+/// game is waiting on by calling the imports:
+///
+/// ```text
+/// energy() shields() torpedoes() klingons() ...  -> i32    // NUMBERS, -1 when unknown
+/// prompt_kind(ptr, capacity) -> i32                        // e.g. "Command", copied to ptr
+/// prompt_text(ptr, capacity) -> i32                        // the prompt line
+/// turn_json(ptr, capacity) -> i32                          // TurnContext::to_json
+/// random(below) -> i32                                     // same ones for the same seed
+/// answer(ptr, len)                                         // the command to send
+/// explain(ptr, len)                                        // optional reason for it
+/// log(ptr, len)
+/// ```
+///
+/// The text functions copy at most `capacity` bytes and return the full length, so a
+/// strategy can call again with a bigger buffer. `reset()` and `new_game()` exports are
+/// optional: `new_game()` is called between games of a session, `reset()` in its place
+/// if it's missing. A fresh session gets a fresh instance, with new memory and globals
+/// and `random()` started over.
+pub struct WasmStrategy {
+    module: Module,
+    linker: Linker<Host>,
+    instance: Loaded,
+    seed: u64,
+    path: String,
+}
+
+/// An instance of the module with the exports TrekBot calls
+struct Loaded {
+    store: Store<Host>,
+    get_command: TypedFunc<(), ()>,
+    reset: Option<TypedFunc<(), ()>>,
+    new_game: Option<TypedFunc<(), ()>>,
+}
+
+/// What the strategy's imports read and write during one call
+struct Host {
+    limits: StoreLimits,
+    numbers: Vec<i32>,
+    prompt_kind: String,
+    prompt_text: String,
+    turn_json: String,
+    answer: Option<String>,
+    decision: Option<String>,
+    rng: StdRng,
+}
+
+impl WasmStrategy {
+    /// Compile the module at `path` and instantiate it, seeding its random numbers
+    pub fn load(path: &str, seed: u64) -> Result<Self> {
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config).map_err(|e| anyhow::anyhow!("Failed to start the WASM engine: {}", e))?;
+        let module = Module::from_file(&engine, path).map_err(|e| anyhow::anyhow!("Invalid WASM strategy {}: {}", path, e))?;
+        
+        let mut linker = Linker::new(&engine);
+        define_imports(&mut linker).map_err(|e| anyhow::anyhow!("Failed to set up the WASM host API: {}", e))?;
+        let instance = instantiate(&linker, &module, path, seed)?;
+        log::info!("Loaded WASM strategy {}", path);
+        Ok(Self { module, linker, instance, seed, path: path.to_string() })
+    }
+    
+    /// Run one of the module's functions on a fresh fuel allowance
+    fn call(&mut self, function: TypedFunc<(), ()>, name: &str) -> Result<()> {
+        self.instance.store.set_fuel(WASM_FUEL_PER_TURN).map_err(|e| anyhow::anyhow!("{}", e))?;
+        function.call(&mut self.instance.store, ()).map_err(|e| match e.downcast_ref::<Trap>() {
+            Some(Trap::OutOfFuel) => anyhow::anyhow!("WASM strategy {} ran out of fuel in {}() (more than {} instructions)", self.path, name, WASM_FUEL_PER_TURN),
+            _ => anyhow::anyhow!("WASM strategy {} failed in {}(): {}", self.path, name, e),
+        })
+    }
+}
+
+/// A new instance of the module, with its own memory and random numbers from `seed`
+fn instantiate(linker: &Linker<Host>, module: &Module, path: &str, seed: u64) -> Result<Loaded> {
+    let host = Host {
+        limits: StoreLimitsBuilder::new().memory_size(WASM_MEMORY_LIMIT).instances(1).build(),
+        numbers: Vec::new(),
+        prompt_kind: String::new(),
+        prompt_text: String::new(),
+        turn_json: String::new(),
+        answer: None,
+        decision: None,
+        rng: StdRng::seed_from_u64(seed),
+    };
+    let mut store = Store::new(module.engine(), host);
+    store.limiter(|host| &mut host.limits);
+    // Start functions run on the same allowance as a turn
+    store.set_fuel(WASM_FUEL_PER_TURN).map_err(|e| anyhow::anyhow!("{}", e))?;
+    let instance = linker.instantiate(&mut store, module)
+        .map_err(|e| anyhow::anyhow!("Failed to instantiate WASM strategy {}: {}", path, e))?;
+    if instance.get_memory(&mut store, "memory").is_none() {
+        anyhow::bail!("WASM strategy {} doesn't export its memory", path);
+    }
+    let get_command = instance.get_typed_func::<(), ()>(&mut store, "get_command")
+        .map_err(|e| anyhow::anyhow!("WASM strategy {} doesn't export get_command(): {}", path, e))?;
+    let reset = instance.get_typed_func::<(), ()>(&mut store, "reset").ok();
+    let new_game = instance.get_typed_func::<(), ()>(&mut store, "new_game").ok();
+    Ok(Loaded { store, get_command, reset, new_game })
+}
+
+/// The `trekbot` import module
+fn define_imports(linker: &mut Linker<Host>) -> wasmtime::Result<()> {
+    for (index, (name, _)) in NUMBERS.iter().enumerate() {
+        linker.func_wrap("trekbot", name, move |caller: Caller<'_, Host>| -> i32 { caller.data().numbers[index] })?;
+    }
+    linker.func_wrap("trekbot", "prompt_kind", |mut caller: Caller<'_, Host>, ptr: u32, capacity: u32| -> wasmtime::Result<i32> {
+        let text = caller.data().prompt_kind.clone();
+        copy_out(&mut caller, &text, ptr, capacity)
+    })?;
+    linker.func_wrap("trekbot", "prompt_text", |mut caller: Caller<'_, Host>, ptr: u32, capacity: u32| -> wasmtime::Result<i32> {
+        let text = caller.data().prompt_text.clone();
+        copy_out(&mut caller, &text, ptr, capacity)
+    })?;
+    linker.func_wrap("trekbot", "turn_json", |mut caller: Caller<'_, Host>, ptr: u32, capacity: u32| -> wasmtime::Result<i32> {
+        let text = caller.data().turn_json.clone();
+        copy_out(&mut caller, &text, ptr, capacity)
+    })?;
+    linker.func_wrap("trekbot", "random", |mut caller: Caller<'_, Host>, below: i32| -> i32 {
+        caller.data_mut().rng.gen_range(0..below.max(1))
+    })?;
+    linker.func_wrap("trekbot", "answer", |mut caller: Caller<'_, Host>, ptr: u32, len: u32| -> wasmtime::Result<()> {
+        let text = copy_in(&mut caller, ptr, len)?;
+        caller.data_mut().answer = Some(text);
+        Ok(())
+    })?;
+    linker.func_wrap("trekbot", "explain", |mut caller: Caller<'_, Host>, ptr: u32, len: u32| -> wasmtime::Result<()> {
+        let text = copy_in(&mut caller, ptr, len)?;
+        caller.data_mut().decision = Some(text);
+        Ok(())
+    })?;
+    linker.func_wrap("trekbot", "log", |mut caller: Caller<'_, Host>, ptr: u32, len: u32| -> wasmtime::Result<()> {
+        log::info!("{}", copy_in(&mut caller, ptr, len)?);
+        Ok(())
+    })?;
+    Ok(())
+}
+
+/// Copy `text` into the strategy's memory at `ptr`, as much as fits in `capacity`,
+/// returning its full length
+fn copy_out(caller: &mut Caller<'_, Host>, text: &str, ptr: u32, capacity: u32) -> wasmtime::Result<i32> {
+    let memory = caller.get_export("memory").and_then(|export| export.into_memory())
+        .ok_or_else(|| wasmtime::Error::msg("no exported memory"))?;
+    let bytes = &text.as_bytes()[..text.len().min(capacity as usize)];
+    memory.write(&mut *caller, ptr as usize, bytes)?;
+    Ok(text.len() as i32)
+}
+
+/// Read `len` bytes of text from the strategy's memory at `ptr`
+fn copy_in(caller: &mut Caller<'_, Host>, ptr: u32, len: u32) -> wasmtime::Result<String> {
+    let memory = caller.get_export("memory").and_then(|export| export.into_memory())
+        .ok_or_else(|| wasmtime::Error::msg("no exported memory"))?;
+    let mut bytes = vec![0; len as usize];
+    memory.read(&*caller, ptr as usize, &mut bytes)?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+#[async_trait::async_trait(?Send)]
+impl Strategy for WasmStrategy {
+    async fn get_command(&mut self, context: &TurnContext<'_>) -> Result<String> {
+        let host = self.instance.store.data_mut();
+        host.numbers = NUMBERS.iter().map(|(_, number)| number(context).unwrap_or(-1)).collect();
+        host.prompt_kind = format!("{:?}", context.prompt.kind);
+        host.prompt_text = context.prompt.prompt.trim().to_string();
+        host.turn_json = context.to_json().to_string();
+        host.answer = None;
+        host.decision = None;
+        self.call(self.instance.get_command.clone(), "get_command")?;
+        self.instance.store.data_mut().answer.take()
+            .with_context(|| format!("WASM strategy {} gave no answer to the {:?} prompt", self.path, context.prompt.kind))
+    }
+    
+    fn reset(&mut self) {
+        // The module compiled once; only the instance starts over
+        match instantiate(&self.linker, &self.module, &self.path, self.seed) {
+            Ok(instance) => self.instance = instance,
+            Err(e) => log::warn!("{:#}", e),
+        }
+    }
+    
+    fn new_game(&mut self) {
+        // The instance is kept, so games of a session don't repeat the same random numbers
+        self.instance.store.data_mut().decision = None;
+        let function = self.instance.new_game.clone().map(|new_game| (new_game, "new_game"))
+            .or_else(|| self.instance.reset.clone().map(|reset| (reset, "reset")));
+        if let Some((function, name)) = function {
+            if let Err(e) = self.call(function, name) {
+                log::warn!("{:#}", e);
+            }
+        }
+    }
+    
+    fn last_decision(&self) -> Option<&str> {
+        self.instance.store.data().decision.as_deref()
+    }
+    
+    fn name(&self) -> &'static str {
+        "WASM"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{GameState, PromptContext, PromptKind};
+    
+    /// Shields up when they are down, otherwise a short range scan, and a loop that
+    /// never ends for any other prompt
+    const STRATEGY: &str = r#"
+        (module
+          (import "trekbot" "shields" (func $shields (result i32)))
+          (import "trekbot" "prompt_kind" (func $prompt_kind (param i32 i32) (result i32)))
+          (import "trekbot" "answer" (func $answer (param i32 i32)))
+          (import "trekbot" "explain" (func $explain (param i32 i32)))
+          (memory (export "memory") 1)
+          (data (i32.const 0) "SHESRSshields are down")
+          (func (export "get_command")
+            (if (i32.ne (call $prompt_kind (i32.const 100) (i32.const 16)) (i32.const 7))
+              (then (loop $forever (br $forever))))
+            (if (i32.eqz (call $shields))
+              (then (call $answer (i32.const 0) (i32.const 3))
+                    (call $explain (i32.const 6) (i32.const 16)))
+              (else (call $answer (i32.const 3) (i32.const 3))))))
+    "#;
+    
+    #[tokio::test]
+    async fn test_wasm_strategy() {
+        let path = std::env::temp_dir().join(format!("trekbot-strategy-{}.wat", std::process::id()));
+        std::fs::write(&path, STRATEGY).unwrap();
+        let mut strategy = WasmStrategy::load(&path.to_string_lossy(), 1).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        
+        let command = PromptContext { kind: PromptKind::Command, ..PromptContext::default() };
+        let mut state = GameState::new();
+        state.shields = Some(0);
+        assert_eq!(strategy.get_command(&TurnContext::new(&command, &state)).await.unwrap(), "SHE");
+        assert_eq!(strategy.last_decision(), Some("shields are down"));
+        state.shields = Some(500);
+        assert_eq!(strategy.get_command(&TurnContext::new(&command, &state)).await.unwrap(), "SRS");
+        assert_eq!(strategy.last_decision(), None);
+        
+        let course = PromptContext { kind: PromptKind::Course, ..PromptContext::default() };
+        let error = strategy.get_command(&TurnContext::new(&course, &state)).await.unwrap_err();
+        assert!(error.to_string().contains("ran out of fuel"));
+    }
+    
+    /// A random letter for every prompt
+    const RANDOM_LETTERS: &str = r#"
+        (module
+          (import "trekbot" "random" (func $random (param i32) (result i32)))
+          (import "trekbot" "answer" (func $answer (param i32 i32)))
+          (memory (export "memory") 1)
+          (func (export "get_command")
+            (i32.store8 (i32.const 0) (i32.add (i32.const 65) (call $random (i32.const 26))))
+            (call $answer (i32.const 0) (i32.const 1))))
+    "#;
+    
+    #[tokio::test]
+    async fn test_random_numbers_across_games() {
+        let path = std::env::temp_dir().join(format!("trekbot-random-{}.wat", std::process::id()));
+        std::fs::write(&path, RANDOM_LETTERS).unwrap();
+        let mut strategy = WasmStrategy::load(&path.to_string_lossy(), 1).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        
+        let first = letters(&mut strategy).await;
+        strategy.new_game();
+        assert_ne!(letters(&mut strategy).await, first);
+        strategy.reset();
+        assert_eq!(letters(&mut strategy).await, first);
+    }
+    
+    /// The answers to eight turns of a game
+    async fn letters(strategy: &mut WasmStrategy) -> String {
+        let (prompt, state) = (PromptContext::default(), GameState::new());
+        let mut letters = String::new();
+        for _ in 0..8 {
+            letters += &strategy.get_command(&TurnContext::new(&prompt, &state)).await.unwrap();
+        }
+        letters
+    }    
+    /// Counts its answers in memory, "A", "B", ..., and has no reset() export
+    const COUNTING: &str = r#"
+        (module
+          (import "trekbot" "answer" (func $answer (param i32 i32)))
+          (memory (export "memory") 1)
+          (func (export "get_command")
+            (i32.store8 (i32.const 0) (i32.add (i32.const 65) (i32.load8_u (i32.const 8))))
+            (i32.store8 (i32.const 8) (i32.add (i32.load8_u (i32.const 8)) (i32.const 1)))
+            (call $answer (i32.const 0) (i32.const 1))))
+    "#;
+    
+    #[tokio::test]
+    async fn test_reset_forgets_memory() {
+        let path = std::env::temp_dir().join(format!("trekbot-counting-{}.wat", std::process::id()));
+        std::fs::write(&path, COUNTING).unwrap();
+        let mut strategy = WasmStrategy::load(&path.to_string_lossy(), 1).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        
+        assert_eq!(letters(&mut strategy).await, "ABCDEFGH");
+        strategy.new_game();
+        assert_eq!(letters(&mut strategy).await, "IJKLMNOP");
+        strategy.reset();
+        assert_eq!(letters(&mut strategy).await, "ABCDEFGH");
+    }
+}
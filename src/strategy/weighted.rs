@@ -0,0 +1,165 @@
+use crate::compress;
+use crate::game::PromptKind;
+use crate::json::Json;
+use crate::strategy::{Command, RandomStrategy, Strategy, TurnContext};
+use anyhow::{Context, Result};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// How likely the weighted random strategy is to pick each command, relative to the
+/// others. Commands start equally likely, as the random strategy picks them; a weight
+/// of 0 never sends one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandWeights {
+    weights: Vec<(Command, f64)>,
+}
+
+impl CommandWeights {
+    pub fn new() -> Self {
+        Self { weights: Command::PLAYABLE.iter().map(|command| (command.clone(), 1.0)).collect() }
+    }
+    
+    /// Weights from a JSON file naming commands as they are typed, e.g. {"NAV": 3, "PHA": 1}
+    pub fn load(path: &str) -> Result<Self> {
+        let json = Json::parse(&compress::read_file(path)?)?;
+        let Json::Object(fields) = &json else {
+            anyhow::bail!("{} is not a command weights file: expected an object like {{\"NAV\": 3}}", path);
+        };
+        let mut weights = Self::new();
+        for (name, weight) in fields {
+            let weight = weight.as_f64().with_context(|| format!("Weight of {} in {} is not a number", name, path))?;
+            weights.set(name, weight)?;
+        }
+        Ok(weights)
+    }
+    
+    /// Set a weight from NAME=WEIGHT, e.g. "TOR=2"
+    pub fn add(&mut self, spec: &str) -> Result<()> {
+        let (name, weight) = spec.split_once('=').with_context(|| format!("Expected COMMAND=WEIGHT, got {}", spec))?;
+        let weight = weight.trim().parse::<f64>().with_context(|| format!("Weight of {} is not a number: {}", name, weight))?;
+        self.set(name, weight)
+    }
+    
+    fn set(&mut self, name: &str, weight: f64) -> Result<()> {
+        if !(weight >= 0.0 && weight.is_finite()) {
+            anyhow::bail!("Weight of {} must be zero or more, not {}", name, weight);
+        }
+        let command = Command::from_name(name).with_context(|| {
+            let names: Vec<String> = Command::PLAYABLE.iter().map(Command::to_string).collect();
+            format!("Unknown command {}; expected one of {}", name, names.join(", "))
+        })?;
+        let others: f64 = self.weights.iter().filter(|(known, _)| *known != command).map(|(_, weight)| weight).sum();
+        if others + weight <= 0.0 {
+            anyhow::bail!("At least one command needs a weight above zero");
+        }
+        match self.weights.iter_mut().find(|(known, _)| *known == command) {
+            Some(entry) => entry.1 = weight,
+            None => self.weights.push((command, weight)),
+        }
+        Ok(())
+    }
+    
+    fn total(&self) -> f64 {
+        self.weights.iter().map(|(_, weight)| weight).sum()
+    }
+    
+    /// A command drawn with the chances the weights give
+    pub fn choose(&self, rng: &mut impl Rng) -> Command {
+        let mut draw = rng.gen_range(0.0..self.total());
+        for (command, weight) in &self.weights {
+            if draw < *weight {
+                return command.clone();
+            }
+            draw -= weight;
+        }
+        // Rounding can leave the draw just past the last weight
+        self.weights.iter().rev().find(|(_, weight)| *weight > 0.0).map_or(Command::ShortRangeScan, |(command, _)| command.clone())
+    }
+}
+
+impl Default for CommandWeights {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Random play with the command at each COMMAND prompt drawn from configurable weights,
+/// to bias the mix of commands without recompiling. Everything else, from courses to
+/// shield units, is answered as the random strategy answers it.
+pub struct WeightedRandomStrategy {
+    weights: CommandWeights,
+    rng: StdRng,
+    parent: RandomStrategy,
+    decision: Option<String>,
+}
+
+impl WeightedRandomStrategy {
+    pub fn new(weights: CommandWeights) -> Self {
+        Self { weights, rng: StdRng::from_entropy(), parent: RandomStrategy::new(), decision: None }
+    }
+    
+    /// Strategy that makes the same choices every time for the same seed
+    pub fn with_seed(weights: CommandWeights, seed: u64) -> Self {
+        Self { weights, rng: StdRng::seed_from_u64(seed), parent: RandomStrategy::with_seed(seed), decision: None }
+    }
+    
+    /// The random strategy answering everything but commands, to configure
+    pub fn parent_mut(&mut self) -> &mut RandomStrategy {
+        &mut self.parent
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl Strategy for WeightedRandomStrategy {
+    async fn get_command(&mut self, context: &TurnContext<'_>) -> Result<String> {
+        let TurnContext { prompt, .. } = *context;
+        match prompt.kind {
+            PromptKind::Command => {
+                self.decision = Some("drawn by the command weights".to_string());
+                Ok(self.weights.choose(&mut self.rng).to_string())
+            }
+            _ => {
+                let command = self.parent.get_command(context).await;
+                self.decision = self.parent.last_decision().map(str::to_string);
+                command
+            }
+        }
+    }
+    
+    fn reset(&mut self) {
+        self.parent.reset();
+    }
+    
+    fn last_decision(&self) -> Option<&str> {
+        self.decision.as_deref()
+    }
+    
+    fn name(&self) -> &'static str {
+        "WeightedRandom"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{GameState, PromptContext};
+    
+    #[tokio::test]
+    async fn test_weighted_commands() {
+        let mut weights = CommandWeights::new();
+        weights.add("tor=2").unwrap();
+        for command in Command::PLAYABLE.iter().filter(|&command| *command != Command::Torpedoes) {
+            weights.add(&format!("{}=0", command.to_string())).unwrap();
+        }
+        assert!(weights.add("WARP=1").is_err());
+        assert!(weights.add("TOR=-1").is_err());
+        assert!(weights.add("TOR=0").is_err());
+        
+        let mut rng = StdRng::seed_from_u64(3);
+        assert!((0..20).all(|_| weights.choose(&mut rng) == Command::Torpedoes));
+        
+        let mut strategy = WeightedRandomStrategy::with_seed(weights, 3);
+        let prompt = PromptContext { kind: PromptKind::Command, ..PromptContext::default() };
+        assert_eq!(strategy.get_command(&TurnContext::new(&prompt, &GameState::new())).await.unwrap(), "TOR");
+    }
+}
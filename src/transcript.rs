@@ -0,0 +1,223 @@
+use crate::compress;
+use crate::json::Json;
+use crate::metadata::RunMetadata;
+use anyhow::{Context, Result};
+use std::collections::VecDeque;
+use std::time::Instant;
+
+/// Which side of the conversation a transcript entry came from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    /// Printed by the interpreter
+    Output,
+    /// Sent by TrekBot
+    Input,
+}
+
+impl Direction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Direction::Output => "out",
+            Direction::Input => "in",
+        }
+    }
+    
+    fn parse(text: &str) -> Option<Self> {
+        match text {
+            "out" => Some(Direction::Output),
+            "in" => Some(Direction::Input),
+            _ => None,
+        }
+    }
+}
+
+/// One line of interpreter I/O with the time it happened
+#[derive(Debug, Clone)]
+pub struct TranscriptEntry {
+    /// Seconds since the transcript started
+    pub elapsed: f64,
+    pub direction: Direction,
+    pub text: String,
+    /// Why the strategy sent a command, if it said
+    pub reason: Option<String>,
+}
+
+/// Raw interpreter I/O for one game, saved as JSON lines
+/// (`{"t":0.52,"dir":"out","text":"COMMAND?"}`, with `"why"` on a command the strategy
+/// explained) after an optional `{"metadata":{...}}` line, compressed if the file name
+/// ends in .gz or .zst
+#[derive(Debug, Clone)]
+pub struct Transcript {
+    start: Instant,
+    entries: VecDeque<TranscriptEntry>,
+    /// Most recent entries kept; all of them if None
+    limit: Option<usize>,
+    /// Entries dropped to stay within the limit
+    dropped: usize,
+    metadata: Option<RunMetadata>,
+}
+
+impl Transcript {
+    pub fn new() -> Self {
+        Self::with_limit(None)
+    }
+    
+    /// A transcript keeping only the most recent `limit` entries, for soak runs too long
+    /// to hold every line in memory. One that dropped entries can't be replayed.
+    pub fn with_limit(limit: Option<usize>) -> Self {
+        Self {
+            start: Instant::now(),
+            entries: VecDeque::new(),
+            limit,
+            dropped: 0,
+            metadata: None,
+        }
+    }
+    
+    /// Provenance written as the first line
+    pub fn set_metadata(&mut self, metadata: RunMetadata) {
+        self.metadata = Some(metadata);
+    }
+    
+    /// Record lines printed by the interpreter, before any normalization
+    pub fn record_output(&mut self, lines: &[String]) {
+        for line in lines {
+            self.push(Direction::Output, line);
+        }
+    }
+    
+    /// Record a command sent to the interpreter
+    pub fn record_input(&mut self, command: &str) {
+        self.push(Direction::Input, command);
+    }
+    
+    /// Record a command along with the strategy's reason for it
+    pub fn record_decision(&mut self, command: &str, reason: Option<&str>) {
+        self.push(Direction::Input, command);
+        if let Some(entry) = self.entries.back_mut() {
+            entry.reason = reason.map(str::to_string);
+        }
+    }
+    
+    fn push(&mut self, direction: Direction, text: &str) {
+        if self.limit.is_some_and(|limit| self.entries.len() >= limit) {
+            self.dropped += 1;
+            if self.entries.pop_front().is_none() {
+                return;
+            }
+        }
+        self.entries.push_back(TranscriptEntry {
+            elapsed: self.start.elapsed().as_secs_f64(),
+            direction,
+            text: text.to_string(),
+            reason: None,
+        });
+    }
+    
+    pub fn to_jsonl(&self) -> String {
+        let mut jsonl = String::new();
+        if let Some(metadata) = &self.metadata {
+            jsonl.push_str(&Json::object().with("metadata", metadata.to_json()).to_string());
+            jsonl.push('\n');
+        }
+        for entry in &self.entries {
+            let mut line = Json::object()
+                .with("t", (entry.elapsed * 1000.0).round() / 1000.0)
+                .with("dir", entry.direction.as_str())
+                .with("text", entry.text.as_str());
+            if let Some(reason) = &entry.reason {
+                line = line.with("why", reason.as_str());
+            }
+            jsonl.push_str(&line.to_string());
+            jsonl.push('\n');
+        }
+        jsonl
+    }
+    
+    pub fn save(&self, path: &str) -> Result<()> {
+        if self.dropped > 0 {
+            log::warn!("Transcript {} keeps only the last {} entries, {} earlier ones dropped", path, self.entries.len(), self.dropped);
+        }
+        compress::write_file(path, &self.to_jsonl())
+    }
+    
+    /// Read a transcript written by save()
+    pub fn load(path: &str) -> Result<Self> {
+        let text = compress::read_file(path)?;
+        Self::from_jsonl(&text).with_context(|| format!("{} is not a TrekBot transcript", path))
+    }
+    
+    pub fn from_jsonl(text: &str) -> Result<Self> {
+        let mut transcript = Self::new();
+        for (number, line) in text.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+            let json = Json::parse(line).with_context(|| format!("line {}", number + 1))?;
+            if let Some(metadata) = json.get("metadata") {
+                transcript.metadata = RunMetadata::from_json(metadata);
+                continue;
+            }
+            let entry = (|| {
+                Some(TranscriptEntry {
+                    elapsed: json.get("t")?.as_f64()?,
+                    direction: Direction::parse(json.get("dir")?.as_str()?)?,
+                    text: json.get("text")?.as_str()?.to_string(),
+                    reason: json.get("why").and_then(Json::as_str).map(str::to_string),
+                })
+            })();
+            transcript.entries.push_back(entry.with_context(|| format!("line {}: not a transcript entry", number + 1))?);
+        }
+        Ok(transcript)
+    }
+    
+    pub fn entries(&self) -> &VecDeque<TranscriptEntry> {
+        &self.entries
+    }
+    
+    pub fn metadata(&self) -> Option<&RunMetadata> {
+        self.metadata.as_ref()
+    }
+}
+
+impl Default for Transcript {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_jsonl_round_trip() {
+        let mut transcript = Transcript::new();
+        transcript.record_output(&["COMMAND?".to_string()]);
+        transcript.record_input("SRS");
+        transcript.record_decision("PHA", Some("Klingon in range"));
+        
+        let loaded = Transcript::from_jsonl(&transcript.to_jsonl()).unwrap();
+        assert_eq!(loaded.entries().len(), 3);
+        assert_eq!(loaded.entries()[1].direction, Direction::Input);
+        assert_eq!(loaded.entries()[1].text, "SRS");
+        assert_eq!(loaded.entries()[1].reason, None);
+        assert_eq!(loaded.entries()[2].reason.as_deref(), Some("Klingon in range"));
+        assert!(Transcript::from_jsonl("{\"t\":1}").is_err());
+    }
+    
+    #[test]
+    fn test_limit_keeps_latest_entries() {
+        let mut transcript = Transcript::with_limit(Some(2));
+        transcript.record_output(&["COMMAND?".to_string()]);
+        transcript.record_input("SRS");
+        transcript.record_decision("PHA", Some("Klingon in range"));
+        
+        assert_eq!(transcript.dropped, 1);
+        assert_eq!(transcript.entries().len(), 2);
+        assert_eq!(transcript.entries()[0].text, "SRS");
+        assert_eq!(transcript.entries()[1].reason.as_deref(), Some("Klingon in range"));
+        
+        let mut empty = Transcript::with_limit(Some(0));
+        empty.record_input("SRS");
+        assert!(empty.entries().is_empty());
+        assert_eq!(empty.dropped, 1);
+    }
+}
@@ -0,0 +1,169 @@
+use crate::compress;
+use crate::json::Json;
+use crate::player::GameStats;
+use anyhow::{Context, Result};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::BTreeMap;
+
+/// Fraction of each generation kept as it is and bred from
+const SURVIVORS: f64 = 0.5;
+
+/// Chance of each setting of a new candidate being changed at random
+const MUTATION_RATE: f64 = 0.3;
+
+/// Largest random change to a setting, as a fraction of its range
+const MUTATION_SIZE: f64 = 0.2;
+
+/// A strategy setting `tune` can vary, and the range it searches
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Param {
+    pub name: &'static str,
+    pub min: f64,
+    pub max: f64,
+    pub default: f64,
+}
+
+/// Value of each tuned setting by name
+pub type ParamValues = BTreeMap<String, f64>;
+
+/// The default value of every setting
+pub fn defaults(params: &[Param]) -> ParamValues {
+    params.iter().map(|param| (param.name.to_string(), param.default)).collect()
+}
+
+/// Settings saved by `tune --output`
+pub fn load(path: &str) -> Result<ParamValues> {
+    let json = Json::parse(&compress::read_file(path)?)?;
+    let values = match &json {
+        Json::Object(fields) => fields.iter().map(|(name, value)| Some((name.clone(), value.as_f64()?))).collect(),
+        _ => None,
+    };
+    values.with_context(|| format!("{} is not a TrekBot parameter file", path))
+}
+
+pub fn save(values: &ParamValues, path: &str) -> Result<()> {
+    let json = values.iter().fold(Json::object(), |json, (name, &value)| json.with(name, value));
+    compress::write_file(path, &(json.to_string() + "\n"))
+}
+
+/// The settings on one line, e.g. "danger_shields=0.50 phaser_max=499.00"
+pub fn describe(values: &ParamValues) -> String {
+    let values: Vec<String> = values.iter().map(|(name, value)| format!("{}={:.2}", name, value)).collect();
+    values.join(" ")
+}
+
+/// How well a set of settings played: a victory counts 1 and surviving until time runs
+/// out half that, so settings that keep the ship alive beat ones that lose it even
+/// before any of them win
+pub fn score(stats: &GameStats) -> f64 {
+    if stats.total_games == 0 {
+        return 0.0;
+    }
+    (stats.victories as f64 + 0.5 * stats.time_up as f64) / stats.total_games as f64
+}
+
+/// The defaults with the setting `name` moved in `steps` even steps across its range
+pub fn sweep(params: &[Param], name: &str, steps: usize) -> Result<Vec<ParamValues>> {
+    let param = params.iter().find(|param| param.name == name).with_context(|| {
+        let names: Vec<&str> = params.iter().map(|param| param.name).collect();
+        format!("No setting {} to sweep; the strategy has {}", name, names.join(", "))
+    })?;
+    let steps = steps.max(2);
+    Ok((0..steps)
+        .map(|step| {
+            let mut values = defaults(params);
+            values.insert(name.to_string(), param.min + (param.max - param.min) * step as f64 / (steps - 1) as f64);
+            values
+        })
+        .collect())
+}
+
+/// Genetic search: each generation keeps the best scoring half and fills the rest with
+/// children mixing the settings of two of them, some changed a little at random
+pub struct Evolution {
+    params: Vec<Param>,
+    size: usize,
+    rng: StdRng,
+}
+
+impl Evolution {
+    pub fn new(params: &[Param], size: usize, seed: Option<u64>) -> Self {
+        let rng = seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64);
+        Self { params: params.to_vec(), size: size.max(2), rng }
+    }
+    
+    /// The defaults and random settings to start from
+    pub fn first_generation(&mut self) -> Vec<ParamValues> {
+        let mut generation = vec![defaults(&self.params)];
+        while generation.len() < self.size {
+            let values = self.params.iter()
+                .map(|param| (param.name.to_string(), self.rng.gen_range(param.min..=param.max)))
+                .collect();
+            generation.push(values);
+        }
+        generation
+    }
+    
+    /// The generation bred from `scored`, the last one with each candidate's score
+    pub fn next_generation(&mut self, scored: &[(ParamValues, f64)]) -> Vec<ParamValues> {
+        let mut ranked: Vec<&(ParamValues, f64)> = scored.iter().collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+        let survivors = ((self.size as f64 * SURVIVORS).ceil() as usize).clamp(1, ranked.len().max(1));
+        let parents: Vec<&ParamValues> = ranked.iter().take(survivors).map(|(values, _)| values).collect();
+        
+        let mut generation: Vec<ParamValues> = parents.iter().map(|&values| values.clone()).collect();
+        while generation.len() < self.size && !parents.is_empty() {
+            let mother = parents[self.rng.gen_range(0..parents.len())];
+            let father = parents[self.rng.gen_range(0..parents.len())];
+            generation.push(self.child(mother, father));
+        }
+        generation
+    }
+    
+    fn child(&mut self, mother: &ParamValues, father: &ParamValues) -> ParamValues {
+        let mut child = ParamValues::new();
+        for param in &self.params {
+            let parent = if self.rng.gen_bool(0.5) { mother } else { father };
+            let mut value = parent.get(param.name).copied().unwrap_or(param.default);
+            if self.rng.gen_bool(MUTATION_RATE) {
+                let size = (param.max - param.min) * MUTATION_SIZE;
+                value += self.rng.gen_range(-size..=size);
+            }
+            child.insert(param.name.to_string(), value.clamp(param.min, param.max));
+        }
+        child
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    const PARAMS: &[Param] = &[
+        Param { name: "chance", min: 0.0, max: 1.0, default: 0.5 },
+        Param { name: "units", min: 100.0, max: 500.0, default: 200.0 },
+    ];
+    
+    #[test]
+    fn test_sweep_and_evolve() {
+        let sweep = sweep(PARAMS, "units", 5).unwrap();
+        let units: Vec<f64> = sweep.iter().map(|values| values["units"]).collect();
+        assert_eq!(units, [100.0, 200.0, 300.0, 400.0, 500.0]);
+        assert!(sweep.iter().all(|values| values["chance"] == 0.5));
+        assert!(super::sweep(PARAMS, "warp", 5).is_err());
+        
+        let mut evolution = Evolution::new(PARAMS, 4, Some(7));
+        let first = evolution.first_generation();
+        assert_eq!(first.len(), 4);
+        assert_eq!(first[0], defaults(PARAMS));
+        
+        let scored: Vec<(ParamValues, f64)> = first.iter().cloned().zip([0.1, 0.4, 0.0, 0.2]).collect();
+        let next = evolution.next_generation(&scored);
+        assert_eq!(next.len(), 4);
+        assert_eq!(next[0], first[1]);
+        assert_eq!(next[1], first[3]);
+        assert!(next.iter().all(|values| (100.0..=500.0).contains(&values["units"])));
+        assert_eq!(describe(&next[0]).split(' ').count(), 2);
+    }
+}
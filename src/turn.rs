@@ -0,0 +1,146 @@
+use crate::game::{GameState, PromptContext};
+use crate::interpreter::split_prompt_lines;
+use crate::json::Json;
+use crate::transcript::{Direction, Transcript};
+use anyhow::Result;
+
+/// One turn of a game, the unit replays, exports and analysis work in: the prompt the
+/// game was waiting on and the state it was in, the command that answered it, and what
+/// the game printed in reply up to its next prompt, with the state that left it in
+#[derive(Debug, Clone)]
+pub struct Turn {
+    /// Counted from 1
+    pub index: usize,
+    pub prompt: PromptContext,
+    pub before: GameState,
+    pub command: String,
+    /// Why the strategy sent the command, if it said
+    pub reason: Option<String>,
+    /// Output printed in reply to the command, ending at the next prompt
+    pub events: Vec<String>,
+    pub after: GameState,
+    /// Seconds into the game when the command was sent
+    pub elapsed: f64,
+    /// Seconds from the prompt arriving to the command being sent
+    pub think_time: f64,
+    /// Seconds from the command to the last line of its reply
+    pub response_time: f64,
+}
+
+impl Turn {
+    /// The turn as a JSON line of a turn log, e.g.
+    ///
+    /// ```json
+    /// {"turn": 12, "elapsed": 4.18, "think_time": 0.002, "response_time": 0.031,
+    ///  "prompt": {"kind": "Command", ...}, "before": {"energy": 2880, ...}, "command": "PHA",
+    ///  "reason": null, "events": ["...", "COMMAND?"], "after": {"energy": 2380, ...}}
+    /// ```
+    pub fn to_json(&self) -> Json {
+        let seconds = |seconds: f64| (seconds * 1000.0).round() / 1000.0;
+        Json::object()
+            .with("turn", self.index)
+            .with("elapsed", seconds(self.elapsed))
+            .with("think_time", seconds(self.think_time))
+            .with("response_time", seconds(self.response_time))
+            .with("prompt", self.prompt.to_json())
+            .with("before", self.before.to_json())
+            .with("command", self.command.as_str())
+            .with("reason", self.reason.clone())
+            .with("events", self.events.clone())
+            .with("after", self.after.to_json())
+    }
+}
+
+/// A recorded game as turns, after the output it opened with
+#[derive(Debug, Clone)]
+pub struct TurnLog {
+    /// Output before the first command: the orders and the first prompt
+    pub opening: Vec<String>,
+    /// Seconds into the game when the first prompt arrived
+    pub opening_elapsed: f64,
+    pub opening_state: GameState,
+    pub turns: Vec<Turn>,
+}
+
+impl TurnLog {
+    /// Rebuild the turns of a transcript, parsing the game state from the output alone
+    pub fn from_transcript(transcript: &Transcript) -> Result<Self> {
+        let mut entries = transcript.entries().iter().peekable();
+        let mut opening = Vec::new();
+        let mut last_output = 0.0;
+        while let Some(entry) = entries.next_if(|entry| entry.direction == Direction::Output) {
+            opening.push(entry.text.clone());
+            last_output = entry.elapsed;
+        }
+        let mut state = GameState::new();
+        state.update(&split_prompt_lines(opening.clone()))?;
+        let mut log = Self { opening, opening_elapsed: last_output, opening_state: state.clone(), turns: Vec::new() };
+        
+        while let Some(input) = entries.next() {
+            let mut events = Vec::new();
+            let mut replied = input.elapsed;
+            while let Some(entry) = entries.next_if(|entry| entry.direction == Direction::Output) {
+                events.push(entry.text.clone());
+                replied = entry.elapsed;
+            }
+            let before = state.clone();
+            state.update(&split_prompt_lines(events.clone()))?;
+            log.turns.push(Turn {
+                index: log.turns.len() + 1,
+                prompt: before.prompt_context.clone().unwrap_or_default(),
+                before,
+                command: input.text.clone(),
+                reason: input.reason.clone(),
+                events,
+                after: state.clone(),
+                elapsed: input.elapsed,
+                think_time: (input.elapsed - last_output).max(0.0),
+                response_time: replied - input.elapsed,
+            });
+            last_output = replied;
+        }
+        Ok(log)
+    }
+    
+    /// Every command sent, in order
+    pub fn commands(&self) -> Vec<String> {
+        self.turns.iter().map(|turn| turn.command.clone()).collect()
+    }
+    
+    /// The turns as JSON lines, one turn per line
+    pub fn to_jsonl(&self) -> String {
+        self.turns.iter().map(|turn| format!("{}\n", turn.to_json())).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::PromptKind;
+    
+    #[test]
+    fn test_turns_from_transcript() {
+        let mut transcript = Transcript::new();
+        transcript.record_output(&["YOUR ORDERS ARE AS FOLLOWS:".to_string(), "COMMAND?".to_string()]);
+        transcript.record_decision("SHE", Some("shields down"));
+        transcript.record_output(&["ENERGY AVAILABLE = 3000 NUMBER OF UNITS TO SHIELDS?".to_string()]);
+        transcript.record_input("500");
+        transcript.record_output(&["DEFLECTOR CONTROL ROOM REPORT:".to_string(), "  'SHIELDS NOW AT 500 UNITS PER YOUR COMMAND.'".to_string()]);
+        
+        let log = TurnLog::from_transcript(&transcript).unwrap();
+        assert_eq!(log.opening.len(), 2);
+        assert_eq!(log.commands(), ["SHE", "500"]);
+        let first = &log.turns[0];
+        assert_eq!((first.index, first.prompt.kind), (1, PromptKind::Command));
+        assert_eq!(first.reason.as_deref(), Some("shields down"));
+        assert_eq!(first.events.len(), 1);
+        let second = &log.turns[1];
+        assert_eq!(second.prompt.kind, PromptKind::ShieldUnits);
+        assert_eq!((second.before.shields, second.after.shields), (None, Some(500)));
+        assert!(second.think_time >= 0.0 && second.response_time >= 0.0);
+        
+        let line = log.to_jsonl().lines().nth(1).map(|line| Json::parse(line).unwrap()).unwrap();
+        assert_eq!(line.get("command").unwrap().as_str(), Some("500"));
+        assert_eq!(line.get("after").unwrap().get("shields").unwrap().as_usize(), Some(500));
+    }
+}
@@ -0,0 +1,60 @@
+use crate::interpreter::Tolerance;
+use crate::transcript::{Direction, Transcript};
+
+/// Where the type-ahead game's output first differed from the lockstep game's
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mismatch {
+    /// Counted from 1, over the non-blank output lines
+    pub line: usize,
+    pub expected: String,
+    /// None past the end of the type-ahead output
+    pub actual: Option<String>,
+}
+
+/// Everything the interpreter printed, without blank lines or trailing spaces. Reads
+/// are split differently when the answers are already waiting, so turns aren't compared.
+pub fn output_lines(transcript: &Transcript) -> Vec<String> {
+    transcript.entries().iter()
+        .filter(|entry| entry.direction == Direction::Output)
+        .map(|entry| entry.text.trim_end().to_string())
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+/// Compare the output of the same commands sent one prompt at a time and all at once,
+/// line by line within `tolerance`. Extra output after the lockstep game ends is fine,
+/// e.g. when it stopped at --max-turns.
+pub fn first_mismatch(lockstep: &[String], type_ahead: &[String], tolerance: &Tolerance) -> Option<Mismatch> {
+    lockstep.iter().enumerate().find_map(|(index, expected)| {
+        let actual = type_ahead.get(index);
+        (!actual.is_some_and(|actual| tolerance.lines_match(expected, actual))).then(|| Mismatch {
+            line: index + 1,
+            expected: expected.clone(),
+            actual: actual.cloned(),
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_first_mismatch() {
+        let mut lockstep = Transcript::new();
+        lockstep.record_output(&["COMMAND?".to_string(), "".to_string()]);
+        lockstep.record_input("SRS");
+        lockstep.record_output(&["ENERGY 3000 ".to_string(), "COMMAND?".to_string()]);
+        let lockstep = output_lines(&lockstep);
+        assert_eq!(lockstep, ["COMMAND?", "ENERGY 3000", "COMMAND?"]);
+        
+        let exact = Tolerance::default();
+        let lines = |lines: &[&str]| lines.iter().map(|line| line.to_string()).collect::<Vec<_>>();
+        assert_eq!(first_mismatch(&lockstep, &lines(&["COMMAND?", "ENERGY 3000", "COMMAND?", "QUIT"]), &exact), None);
+        assert_eq!(
+            first_mismatch(&lockstep, &lines(&["COMMAND?", "?REDO FROM START"]), &exact),
+            Some(Mismatch { line: 2, expected: "ENERGY 3000".to_string(), actual: Some("?REDO FROM START".to_string()) })
+        );
+        assert_eq!(first_mismatch(&lockstep, &lines(&["COMMAND?"]), &exact).map(|mismatch| mismatch.actual), Some(None));
+    }
+}
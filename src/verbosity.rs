@@ -0,0 +1,89 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// How much TrekBot prints, chosen once with -q/-v. The same level decides what the
+/// progress lines, the status line, --display and the logs show, so they can't
+/// disagree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    /// Only results and errors
+    Quiet,
+    /// Progress, results and warnings
+    Normal,
+    /// Also info logs
+    Verbose,
+    /// Also debug logs: every command sent, prompt decisions
+    Debug,
+    /// Also trace logs
+    Trace,
+}
+
+static LEVEL: AtomicU8 = AtomicU8::new(Verbosity::Normal as u8);
+
+impl Verbosity {
+    /// Level from the number of -q and -v flags given
+    pub fn from_flags(quiet: u8, verbose: u8) -> Self {
+        match verbose as i32 - quiet as i32 {
+            i32::MIN..=-1 => Verbosity::Quiet,
+            0 => Verbosity::Normal,
+            1 => Verbosity::Verbose,
+            2 => Verbosity::Debug,
+            _ => Verbosity::Trace,
+        }
+    }
+    
+    /// TrekBot's own log records at this level; other crates stay at warnings
+    pub fn log_level(self) -> log::LevelFilter {
+        match self {
+            Verbosity::Quiet => log::LevelFilter::Error,
+            Verbosity::Normal => log::LevelFilter::Warn,
+            Verbosity::Verbose => log::LevelFilter::Info,
+            Verbosity::Debug => log::LevelFilter::Debug,
+            Verbosity::Trace => log::LevelFilter::Trace,
+        }
+    }
+    
+    /// Make this the level for the whole run and set up logging to match. RUST_LOG,
+    /// if set, still picks log levels per module (e.g. RUST_LOG=trekbot::player=debug).
+    pub fn install(self) {
+        LEVEL.store(self as u8, Ordering::Relaxed);
+        let mut builder = env_logger::Builder::new();
+        builder.filter_level(log::LevelFilter::Warn);
+        builder.filter_module("trekbot", self.log_level());
+        if let Ok(filters) = std::env::var("RUST_LOG") {
+            builder.parse_filters(&filters);
+        }
+        builder.init();
+    }
+}
+
+/// The level chosen for this run
+pub fn current() -> Verbosity {
+    match LEVEL.load(Ordering::Relaxed) {
+        0 => Verbosity::Quiet,
+        1 => Verbosity::Normal,
+        2 => Verbosity::Verbose,
+        3 => Verbosity::Debug,
+        _ => Verbosity::Trace,
+    }
+}
+
+/// Whether messages meant for `level` should be printed
+pub fn shows(level: Verbosity) -> bool {
+    current() >= level
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_flags() {
+        assert_eq!(Verbosity::from_flags(0, 0), Verbosity::Normal);
+        assert_eq!(Verbosity::from_flags(1, 0), Verbosity::Quiet);
+        assert_eq!(Verbosity::from_flags(0, 2), Verbosity::Debug);
+        assert_eq!(Verbosity::from_flags(0, 7), Verbosity::Trace);
+        assert_eq!(Verbosity::from_flags(1, 1), Verbosity::Normal);
+        assert!(Verbosity::Verbose > Verbosity::Normal);
+        assert_eq!(Verbosity::Quiet.log_level(), log::LevelFilter::Error);
+    }
+}
@@ -0,0 +1,94 @@
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Where the files of a run go while it is in progress: one working directory per game,
+/// `<root>/game-0001/`, `<root>/game-0002/`, ... Each interpreter runs inside its game's
+/// directory, so whatever it writes (e.g. the coverage.json BasicRS always records)
+/// can't clobber another game's files. A temporary root is removed when the run ends.
+pub struct RunLayout {
+    root: PathBuf,
+    keep: bool,
+}
+
+impl RunLayout {
+    /// Lay the run out under `dir`, keeping its files, or in a new temporary directory
+    pub fn new(dir: Option<&str>) -> Result<Self> {
+        let (root, keep) = match dir {
+            Some(dir) => (PathBuf::from(absolute(dir)), true),
+            None => {
+                let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_nanos());
+                let name = format!("trekbot-{}-{}", std::process::id(), nanos);
+                (std::env::temp_dir().join(name), false)
+            }
+        };
+        std::fs::create_dir_all(&root)
+            .with_context(|| format!("Failed to create run directory {}", root.display()))?;
+        Ok(Self { root, keep })
+    }
+    
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+    
+    /// Create the working directory of a game; games are numbered from 0 like
+    /// everywhere else, but directories from 1
+    pub fn game_dir(&self, game: usize) -> Result<PathBuf> {
+        let dir = self.root.join(format!("game-{:04}", game + 1));
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create game directory {}", dir.display()))?;
+        Ok(dir)
+    }
+}
+
+impl Drop for RunLayout {
+    fn drop(&mut self) {
+        if !self.keep {
+            if let Err(e) = std::fs::remove_dir_all(&self.root) {
+                log::warn!("Failed to remove run directory {}: {}", self.root.display(), e);
+            }
+        }
+    }
+}
+
+/// Resolve a path against the current directory, so it still points to the same file
+/// from inside a game's working directory
+pub fn absolute(path: &str) -> String {
+    let path = Path::new(path);
+    if path.is_absolute() {
+        return path.to_string_lossy().to_string();
+    }
+    match std::env::current_dir() {
+        Ok(dir) => dir.join(path).to_string_lossy().to_string(),
+        Err(_) => path.to_string_lossy().to_string(),
+    }
+}
+
+/// Like absolute(), but a bare command name such as "python3" is left for PATH lookup
+pub fn absolute_command(command: &str) -> String {
+    if command.contains(std::path::MAIN_SEPARATOR) || command.contains('/') {
+        absolute(command)
+    } else {
+        command.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_game_dirs_removed_with_temporary_layout() {
+        let layout = RunLayout::new(None).unwrap();
+        let root = layout.root().to_path_buf();
+        let first = layout.game_dir(0).unwrap();
+        let second = layout.game_dir(1).unwrap();
+        assert!(first.ends_with("game-0001") && second.ends_with("game-0002"));
+        assert!(first.is_dir() && second.is_dir());
+        
+        drop(layout);
+        assert!(!root.exists());
+        assert_eq!(absolute_command("python3"), "python3");
+        assert!(Path::new(&absolute("superstartrek.bas")).is_absolute());
+    }
+}
@@ -0,0 +1,29 @@
+//! `play --porcelain` run the way a script runs it, against a stub interpreter
+
+use std::process::Command;
+
+#[test]
+fn test_porcelain_prints_one_line() {
+    let dir = std::env::temp_dir().join(format!("trekbot-porcelain-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let config = dir.join("trekbot.json");
+    std::fs::write(&config, r#"{"interpreters": [{"name": "stub", "command": "sh",
+        "args": ["-c", "echo 'COMMAND? '; read x; echo 'THE ENTERPRISE HAS BEEN DESTROYED'"]}]}"#).unwrap();
+    let program = dir.join("stub.bas");
+    std::fs::write(&program, "10 END\n").unwrap();
+    
+    let output = Command::new(env!("CARGO_BIN_EXE_trekbot"))
+        .args(["play", "--interpreter", "stub", "--porcelain"])
+        .arg("--config").arg(&config)
+        .arg("--program").arg(&program)
+        .arg("--transcript").arg(dir.join("game.jsonl"))
+        .output()
+        .unwrap();
+    std::fs::remove_dir_all(&dir).ok();
+    
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(stdout.lines().count(), 1, "{}", stdout);
+    assert!(stdout.starts_with("result="));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Transcript saved to"));
+}